@@ -1,21 +1,34 @@
 use std::fs::create_dir_all;
-use std::{fs::read_dir, path::PathBuf, sync::Arc};
+use std::{
+    collections::HashMap,
+    fs::read_dir,
+    path::{Path, PathBuf},
+    sync::{Arc, Weak},
+    time::SystemTime,
+};
 
 use iced::widget::{
-    column as col, container, horizontal_space, radio, row, text, text_input, vertical_space,
+    button, checkbox, column as col, container, horizontal_space, pick_list, radio, row,
+    scrollable, slider, text, text_input, tooltip, vertical_space,
 };
-use iced::{Alignment, Command, Element, Length, Point, Renderer, Size};
+use iced::{Alignment, Color, Command, Element, Length, Point, Renderer, Size};
 use iced_native::image::Handle;
+use serde::{Deserialize, Serialize};
 
 use crate::image::convert::image_arc_to_handle;
+use crate::image::{image_filter, open_image, parse_extensions, DEFAULT_EXTENSIONS};
 use crate::naming_convention::NamingConvention;
 use crate::persistence::{Persistence, PersistentKey, PersistentValue};
 use crate::status_bar::StatusBar;
 use crate::style::{Layout, Style};
 use crate::{
-    image::{convert::image_to_handle, GrayscaleImage, ImageFormat, RgbaImage},
+    image::{
+        convert::{color_to_hex, hex_to_color, image_to_handle},
+        dpi::MM_PER_INCH,
+        GrayscaleImage, ImageFormat, Rgba16Image, RgbaImage,
+    },
     style::Theme,
-    widgets::Browser,
+    widgets::{Browser, RecentColors},
     workspace::WorkspaceTemplate,
 };
 
@@ -31,14 +44,78 @@ pub struct ProgramData {
     pub cache: Persistence,
     /// Collection of frames loaded into the program
     pub available_frames: Vec<FrameImage>,
+    /// Images loaded from drive, used to avoid decoding the same file multiple times
+    pub images: ImageCache,
+    /// Names and latest results of all open workspaces, allows modifiers to use results of other workspaces
+    pub workspace_results: Vec<WorkspaceResult>,
+    /// Identifier given to the next workspace that gets created
+    next_workspace_id: u64,
+    /// Colors most recently chosen in color pickers
+    pub recent_colors: RecentColors,
     /// Intended export path, meant to be combined with individual names from workspaces
     output: PathBuf,
+    /// Export paths used for specific workspace templates, they take precedence over the general export path
+    template_output: HashMap<WorkspaceTemplate, PathBuf>,
     /// Currently used color scheme for the UI
     theme: Theme,
+    /// Whatever focused text inputs are outlined with a thick ring
+    focus_rings: bool,
     /// Determines which layout the workspaces should be displayed with
     layout: Layout,
     /// Which template new workspaces should use
     new_workspace_template: WorkspaceTemplate,
+    /// Whatever exported images should carry over color profile of their source image
+    keep_color_profile: bool,
+    /// Whatever an SVG outline of the visible area should be exported alongside the images
+    export_outline: bool,
+    /// Whatever fully transparent margins are cut off from exported images
+    trim_export: bool,
+    /// Transparent pixels left around the visible part of trimmed exports
+    trim_padding: f32,
+    /// Whatever a JSON file with workspace settings should be exported alongside the images
+    export_metadata: bool,
+    /// Whatever 16 bit source images should keep their precision in PNG exports
+    keep_high_precision: bool,
+    /// Whatever new workspaces require choosing the export format before they can be exported
+    ask_format: bool,
+    /// Whatever export skips workspaces which didn't change since they were last exported
+    export_changed_only: bool,
+    /// Whatever token workspaces should size their export to a square fitting the source image
+    token_auto_square: bool,
+    /// Whatever portrait workspaces should start with the image mirrored horizontally
+    portrait_mirror: bool,
+    /// Whatever a workspace should be created from an image address or path found in the clipboard when the program starts
+    clipboard_link_on_start: bool,
+    /// Whatever the workspace previews should show a checkerboard pattern behind transparent areas
+    checkerboard: bool,
+    /// Size of a single check in the preview checkerboard pattern
+    checkerboard_size: f32,
+    /// Number of tasks rendering is split into, zero means it is decided automatically
+    render_workers: u32,
+    /// List of file extensions recognized as images, as typed in by the user
+    image_extensions: String,
+    /// How much the zoom changes with a single step of the scroll wheel
+    zoom_step: f32,
+    /// Whatever scrolling up should zoom out instead of in
+    invert_scroll: bool,
+    /// Whatever open workspaces should be periodically saved for recovery after a crash
+    autosave: bool,
+    /// Minutes between autosaves of open workspaces
+    autosave_interval: f32,
+    /// Folders outside of the program data the frames are also loaded from
+    frame_folders: Vec<PathBuf>,
+    /// Width of the modifier properties panel in workspaces
+    properties_width: f32,
+    /// Frame category selected for renaming in settings
+    rename_category: Option<String>,
+    /// New name for the frame category selected for renaming
+    rename_category_name: String,
+    /// Whatever settings show the raw values stored in the cache in place of the regular settings
+    show_stored_values: bool,
+    /// Id and key of the stored value waiting for confirmation to be cleared
+    clear_stored_value: Option<(String, String)>,
+    /// Id and key of the stored value being edited along with its new value as typed in
+    edit_stored_value: Option<(String, String, String)>,
 }
 
 /// Messages for customizing the program settings
@@ -46,9 +123,92 @@ pub struct ProgramData {
 pub enum ProgramDataMessage {
     /// Sets a new theme
     SetTheme(Theme),
+    /// Sets whatever focused text inputs are outlined with a thick ring
+    SetFocusRings(bool),
     SetLayout(Layout),
     SetNamingConvention(WorkspaceTemplate, String),
     SetProjectName(String),
+    /// Selects frame category to be renamed
+    SelectFrameCategory(String),
+    /// Sets the new name for selected frame category
+    SetFrameCategoryName(String),
+    /// Renames selected frame category
+    RenameFrameCategory,
+    /// Sets whatever color profiles should be carried over to exported images
+    SetKeepColorProfile(bool),
+    /// Sets whatever SVG outlines should be exported with the images
+    SetExportOutline(bool),
+    /// Sets whatever transparent margins are trimmed from exported images
+    SetTrimExport(bool),
+    /// Sets how many transparent pixels are kept around trimmed exports
+    SetTrimPadding(f32),
+    /// Sets whatever JSON metadata should be exported with the images
+    SetExportMetadata(bool),
+    /// Sets whatever PNG exports should keep 16 bit precision of source images
+    SetKeepHighPrecision(bool),
+    /// Sets whatever export format of new workspaces has to be chosen before the first export
+    SetAskFormat(bool),
+    /// Sets whatever export should skip workspaces unchanged since their last export
+    SetExportChangedOnly(bool),
+    /// Sets whatever token workspaces should size their export to a square based on the source image
+    SetTokenAutoSquare(bool),
+    /// Sets whatever portrait workspaces should start with the image mirrored
+    SetPortraitMirror(bool),
+    /// Sets whatever an image address or path found in the clipboard should be opened when the program starts
+    SetClipboardLinkOnStart(bool),
+    /// Toggles checkerboard pattern behind transparent areas in previews
+    SetCheckerboard(bool),
+    /// Sets the size of checks in the preview checkerboard pattern
+    SetCheckerboardSize(f32),
+    /// Sets how many tasks rendering is split into, zero decides automatically
+    SetRenderWorkers(u32),
+    /// Sets the list of file extensions recognized as images
+    SetImageExtensions(String),
+    /// Sets how much a single step of the scroll wheel zooms images
+    SetZoomStep(f32),
+    /// Sets whatever the scroll wheel zooms in the opposite direction
+    SetInvertScroll(bool),
+    /// Sets whatever open workspaces should be periodically saved for recovery
+    SetAutosave(bool),
+    /// Sets how many minutes pass between autosaves
+    SetAutosaveInterval(f32),
+    /// Requests a folder to be chosen for loading frames from, settings don't handle it since it requires the file browser
+    BrowseFrameFolder,
+    /// Stops loading frames from the folder on the index
+    RemoveFrameFolder(usize),
+    /// Switches between regular settings and the list of raw values stored in the cache
+    ShowStoredValues(bool),
+    /// Asks for confirmation of clearing the stored value with the id and key
+    ClearStoredValue(String, String),
+    /// Clears the stored value waiting for confirmation
+    ConfirmClearStoredValue,
+    /// Keeps the stored value waiting for confirmation
+    CancelClearStoredValue,
+    /// Starts editing the stored value with the id and key
+    EditStoredValue(String, String),
+    /// Sets text of the stored value being edited
+    SetStoredValue(String),
+    /// Replaces the stored value being edited with the typed in one
+    ConfirmEditStoredValue,
+    /// Stops editing the stored value without changing it
+    CancelEditStoredValue,
+}
+
+impl ProgramDataMessage {
+    /// Tests whatever the message changes names or content of exported files
+    pub fn changes_export(&self) -> bool {
+        matches!(
+            self,
+            ProgramDataMessage::SetNamingConvention(..)
+                | ProgramDataMessage::SetProjectName(_)
+                | ProgramDataMessage::SetKeepColorProfile(_)
+                | ProgramDataMessage::SetExportOutline(_)
+                | ProgramDataMessage::SetTrimExport(_)
+                | ProgramDataMessage::SetTrimPadding(_)
+                | ProgramDataMessage::SetExportMetadata(_)
+                | ProgramDataMessage::SetKeepHighPrecision(_)
+        )
+    }
 }
 
 impl ProgramData {
@@ -65,15 +225,21 @@ impl ProgramData {
             Some(t) => t.to_theme(),
             None => Theme::default(),
         };
+        let focus_rings = cache
+            .get(PersistentData::SettingsID, PersistentData::FocusRings)
+            .and_then(|x| x.check_bool())
+            .unwrap_or(false);
         let layout = match cache.get_copy(PersistentData::SettingsID, PersistentData::Layout) {
             Some(l) => l.to_layout(),
             None => Layout::default(),
         };
+        let output_override = output_override();
         let output = match cache.get_copy(PersistentData::SettingsID, PersistentData::Output) {
-            Some(o) => o.to_string(),
+            Some(o) => o.into_string(),
             None => String::new(),
         }
         .into();
+        let output = output_override.clone().unwrap_or(output);
         let naming = NamingConvention::new(&cache);
 
         let new_workspace_template = cache
@@ -90,20 +256,171 @@ impl ProgramData {
             })
             .unwrap_or_default();
 
+        let keep_color_profile = cache
+            .get(PersistentData::SettingsID, PersistentData::KeepColorProfile)
+            .and_then(|x| x.check_bool())
+            .unwrap_or(false);
+        let trim_export = cache
+            .get(PersistentData::SettingsID, PersistentData::TrimExport)
+            .and_then(|x| x.check_bool())
+            .unwrap_or(false);
+        let trim_padding = cache
+            .get(PersistentData::SettingsID, PersistentData::TrimPadding)
+            .and_then(|x| x.check_float())
+            .unwrap_or(0.0);
+        let export_outline = cache
+            .get(PersistentData::SettingsID, PersistentData::ExportOutline)
+            .and_then(|x| x.check_bool())
+            .unwrap_or(false);
+        let export_metadata = cache
+            .get(PersistentData::SettingsID, PersistentData::ExportMetadata)
+            .and_then(|x| x.check_bool())
+            .unwrap_or(false);
+        let ask_format = cache
+            .get(PersistentData::SettingsID, PersistentData::AskFormat)
+            .and_then(|x| x.check_bool())
+            .unwrap_or(false);
+        let export_changed_only = cache
+            .get(
+                PersistentData::SettingsID,
+                PersistentData::ExportChangedOnly,
+            )
+            .and_then(|x| x.check_bool())
+            .unwrap_or(false);
+        let keep_high_precision = cache
+            .get(
+                PersistentData::SettingsID,
+                PersistentData::KeepHighPrecision,
+            )
+            .and_then(|x| x.check_bool())
+            .unwrap_or(false);
+        let clipboard_link_on_start = cache
+            .get(
+                PersistentData::SettingsID,
+                PersistentData::ClipboardLinkOnStart,
+            )
+            .and_then(|x| x.check_bool())
+            .unwrap_or(false);
+        let token_auto_square = cache
+            .get(PersistentData::SettingsID, PersistentData::TokenAutoSquare)
+            .and_then(|x| x.check_bool())
+            .unwrap_or(false);
+        let portrait_mirror = cache
+            .get(PersistentData::SettingsID, PersistentData::PortraitMirror)
+            .and_then(|x| x.check_bool())
+            .unwrap_or(false);
+        let checkerboard = cache
+            .get(PersistentData::SettingsID, PersistentData::Checkerboard)
+            .and_then(|x| x.check_bool())
+            .unwrap_or(true);
+        let checkerboard_size = cache
+            .get(PersistentData::SettingsID, PersistentData::CheckerboardSize)
+            .and_then(|x| x.check_float())
+            .unwrap_or(8.0);
+        let render_workers = cache
+            .get(PersistentData::SettingsID, PersistentData::RenderWorkers)
+            .and_then(|x| x.check_integer())
+            .unwrap_or(0);
+        let image_extensions = cache
+            .get(PersistentData::SettingsID, PersistentData::ImageExtensions)
+            .and_then(|x| x.check_string())
+            .unwrap_or(DEFAULT_EXTENSIONS)
+            .to_string();
+        let zoom_step = cache
+            .get(PersistentData::SettingsID, PersistentData::ZoomStep)
+            .and_then(|x| x.check_float())
+            .unwrap_or(0.1);
+        let invert_scroll = cache
+            .get(PersistentData::SettingsID, PersistentData::InvertScroll)
+            .and_then(|x| x.check_bool())
+            .unwrap_or(false);
+        let autosave = cache
+            .get(PersistentData::SettingsID, PersistentData::Autosave)
+            .and_then(|x| x.check_bool())
+            .unwrap_or(true);
+        let autosave_interval = cache
+            .get(PersistentData::SettingsID, PersistentData::AutosaveInterval)
+            .and_then(|x| x.check_float())
+            .unwrap_or(2.0);
+
+        let properties_width = cache
+            .get(PersistentData::SettingsID, PersistentData::PropertiesWidth)
+            .and_then(|x| x.check_float())
+            .unwrap_or(DEFAULT_PROPERTIES_WIDTH);
+
+        let frame_folders = cache
+            .get(PersistentData::SettingsID, PersistentData::FrameFolders)
+            .and_then(|x| x.check_string())
+            .map(|x| x.lines().map(PathBuf::from).collect())
+            .unwrap_or_default();
+
+        let recent_colors = cache
+            .get(PersistentData::SettingsID, PersistentData::RecentColors)
+            .and_then(|x| x.check_string())
+            .map(|x| x.split_whitespace().filter_map(hex_to_color).collect())
+            .unwrap_or_default();
+
+        // overridden output folder is used for all templates
+        let template_output = WorkspaceTemplate::ALL
+            .iter()
+            .filter(|_| output_override.is_none())
+            .filter_map(|t| {
+                cache
+                    .get(PersistentData::OutputID, *t)
+                    .and_then(|x| x.check_string())
+                    .map(|x| (*t, PathBuf::from(x)))
+            })
+            .collect();
+
         Self {
             file,
             output,
+            template_output,
             available_frames: Vec::new(),
+            images: ImageCache::default(),
             status: StatusBar::new(),
             theme,
+            focus_rings,
             layout,
             naming,
             cache,
             new_workspace_template,
+            keep_color_profile,
+            export_outline,
+            trim_export,
+            trim_padding,
+            export_metadata,
+            keep_high_precision,
+            ask_format,
+            export_changed_only,
+            token_auto_square,
+            portrait_mirror,
+            clipboard_link_on_start,
+            checkerboard,
+            checkerboard_size,
+            render_workers,
+            image_extensions,
+            zoom_step,
+            invert_scroll,
+            autosave,
+            autosave_interval,
+            frame_folders,
+            properties_width,
+            rename_category: None,
+            rename_category_name: String::new(),
+            show_stored_values: false,
+            clear_stored_value: None,
+            edit_stored_value: None,
+            workspace_results: Vec::new(),
+            next_workspace_id: 0,
+            recent_colors: RecentColors::new(recent_colors),
         }
     }
     /// Draws UI for customizing program settings
     pub fn view(&self) -> Element<ProgramDataMessage, Renderer> {
+        if self.show_stored_values {
+            return self.stored_values_view();
+        }
         let theme = row![
             text("Theme: "),
             radio("Light", Theme::Light, Some(self.theme), |x| {
@@ -112,6 +429,18 @@ impl ProgramData {
             radio("Dark", Theme::Dark, Some(self.theme), |x| {
                 ProgramDataMessage::SetTheme(x)
             }),
+            radio(
+                "High Contrast",
+                Theme::HighContrast,
+                Some(self.theme),
+                ProgramDataMessage::SetTheme
+            ),
+            horizontal_space(10),
+            checkbox(
+                "Outline focused text fields",
+                self.focus_rings,
+                ProgramDataMessage::SetFocusRings
+            ),
         ]
         .padding(20)
         .spacing(5)
@@ -142,6 +471,7 @@ impl ProgramData {
                         self.naming.check(&WorkspaceTemplate::None),
                         |x| ProgramDataMessage::SetNamingConvention(WorkspaceTemplate::None, x)
                     )
+                    .style(self.input_style())
                     .width(Length::FillPortion(5)),
                 ]
                 .align_items(Alignment::Center),
@@ -152,6 +482,7 @@ impl ProgramData {
                         self.naming.check(&WorkspaceTemplate::Token),
                         |x| ProgramDataMessage::SetNamingConvention(WorkspaceTemplate::Token, x)
                     )
+                    .style(self.input_style())
                     .width(Length::FillPortion(5)),
                 ]
                 .align_items(Alignment::Center),
@@ -162,6 +493,7 @@ impl ProgramData {
                         self.naming.check(&WorkspaceTemplate::Portrait),
                         |x| ProgramDataMessage::SetNamingConvention(WorkspaceTemplate::Portrait, x)
                     )
+                    .style(self.input_style())
                     .width(Length::FillPortion(5)),
                 ]
                 .align_items(Alignment::Center)
@@ -171,15 +503,326 @@ impl ProgramData {
         .padding(20)
         .spacing(5);
 
+        let categories = self
+            .available_frames
+            .iter()
+            .fold(Vec::new(), |mut v: Vec<String>, f| {
+                if !v.contains(f.category()) {
+                    v.push(f.category().clone());
+                }
+                v
+            });
+        let rename = if self.rename_category.is_some() && !self.rename_category_name.is_empty() {
+            button("Rename").on_press(ProgramDataMessage::RenameFrameCategory)
+        } else {
+            button("Rename")
+        };
+        let frame_categories = row![
+            text("Frame Categories: "),
+            pick_list(
+                categories,
+                self.rename_category.clone(),
+                ProgramDataMessage::SelectFrameCategory
+            )
+            .placeholder("Category"),
+            text_input("New Name", &self.rename_category_name, |x| {
+                ProgramDataMessage::SetFrameCategoryName(x)
+            })
+            .style(self.input_style()),
+            rename,
+        ]
+        .align_items(Alignment::Center)
+        .padding(20)
+        .spacing(5);
+
+        let templates = row![
+            text("Templates: "),
+            col![
+                checkbox(
+                    "Size tokens to a square fitting the source image",
+                    self.token_auto_square,
+                    ProgramDataMessage::SetTokenAutoSquare
+                ),
+                checkbox(
+                    "Mirror portraits so they face the other way",
+                    self.portrait_mirror,
+                    ProgramDataMessage::SetPortraitMirror
+                ),
+            ]
+            .spacing(5),
+        ]
+        .align_items(Alignment::Center)
+        .padding(20)
+        .spacing(5);
+
+        let startup = row![
+            text("Startup: "),
+            checkbox(
+                "Create a workspace from an image path or address in the clipboard",
+                self.clipboard_link_on_start,
+                ProgramDataMessage::SetClipboardLinkOnStart
+            ),
+        ]
+        .align_items(Alignment::Center)
+        .padding(20)
+        .spacing(5);
+
+        let preview = row![
+            text("Preview: "),
+            checkbox(
+                "Checkerboard behind transparent areas",
+                self.checkerboard,
+                ProgramDataMessage::SetCheckerboard
+            ),
+            text(format!("Check size: {}", self.checkerboard_size)),
+            slider(
+                2.0..=32.0,
+                self.checkerboard_size,
+                ProgramDataMessage::SetCheckerboardSize
+            )
+            .step(1.0)
+            .width(150),
+        ]
+        .align_items(Alignment::Center)
+        .padding(20)
+        .spacing(5);
+
+        let scrolling = row![
+            text("Scrolling: "),
+            checkbox(
+                "Invert zoom direction",
+                self.invert_scroll,
+                ProgramDataMessage::SetInvertScroll
+            ),
+            tooltip(
+                text(format!("Zoom step: {:.2}", self.zoom_step)),
+                "How much a single step of the scroll wheel zooms images, holding Shift makes it ten times smaller",
+                tooltip::Position::Bottom
+            )
+            .style(Style::Frame),
+            slider(0.01..=0.5, self.zoom_step, ProgramDataMessage::SetZoomStep)
+                .step(0.01)
+                .width(150),
+        ]
+        .align_items(Alignment::Center)
+        .padding(20)
+        .spacing(5);
+
+        let autosave = row![
+            text("Autosave: "),
+            checkbox(
+                "Save open workspaces for recovery after a crash",
+                self.autosave,
+                ProgramDataMessage::SetAutosave
+            ),
+            text(format!("Every {} min", self.autosave_interval)),
+            slider(
+                1.0..=30.0,
+                self.autosave_interval,
+                ProgramDataMessage::SetAutosaveInterval
+            )
+            .step(1.0)
+            .width(150),
+        ]
+        .align_items(Alignment::Center)
+        .padding(20)
+        .spacing(5);
+
+        let frame_folders = self.frame_folders.iter().enumerate().fold(
+            col![row![
+                text("Frame Folders: "),
+                tooltip(
+                    button("Add Folder").on_press(ProgramDataMessage::BrowseFrameFolder),
+                    "Frames are also loaded from the chosen folder, categories are prefixed with the folder name",
+                    tooltip::Position::Bottom
+                )
+                .style(Style::Frame),
+            ]
+            .align_items(Alignment::Center)
+            .spacing(5)]
+            .padding(20)
+            .spacing(5),
+            |c, (i, f)| {
+                c.push(
+                    row![
+                        text(f.to_string_lossy()).width(Length::Fill),
+                        button("Remove").on_press(ProgramDataMessage::RemoveFrameFolder(i)),
+                    ]
+                    .align_items(Alignment::Center)
+                    .spacing(5),
+                )
+            },
+        );
+
+        let export = col![
+            checkbox(
+                "Keep color profile of source images when exporting",
+                self.keep_color_profile,
+                ProgramDataMessage::SetKeepColorProfile
+            ),
+            checkbox(
+                "Export SVG outline of visible area alongside images",
+                self.export_outline,
+                ProgramDataMessage::SetExportOutline
+            ),
+            row![
+                checkbox(
+                    "Trim fully transparent margins from exported images",
+                    self.trim_export,
+                    ProgramDataMessage::SetTrimExport
+                ),
+                tooltip(
+                    text(format!("Padding: {} px", self.trim_padding)),
+                    "Transparent pixels kept around the visible part of the image, the image never grows past its export size",
+                    tooltip::Position::Bottom
+                )
+                .style(Style::Frame),
+                slider(
+                    0.0..=64.0,
+                    self.trim_padding,
+                    ProgramDataMessage::SetTrimPadding
+                )
+                .step(1.0)
+                .width(150),
+            ]
+            .align_items(Alignment::Center)
+            .spacing(5),
+            checkbox(
+                "Export JSON metadata with workspace settings alongside images",
+                self.export_metadata,
+                ProgramDataMessage::SetExportMetadata
+            ),
+            checkbox(
+                "Keep 16 bit precision of source images in PNG exports",
+                self.keep_high_precision,
+                ProgramDataMessage::SetKeepHighPrecision
+            ),
+            checkbox(
+                "Ask for export format on first export of new workspaces",
+                self.ask_format,
+                ProgramDataMessage::SetAskFormat
+            ),
+            checkbox(
+                "Only export workspaces which changed since their last export",
+                self.export_changed_only,
+                ProgramDataMessage::SetExportChangedOnly
+            ),
+        ]
+        .padding(20)
+        .spacing(5);
+
+        let workers = if self.render_workers == 0 {
+            String::from("Auto")
+        } else {
+            self.render_workers.to_string()
+        };
+        let performance = row![
+            text("Performance: "),
+            tooltip(
+                text(format!("Render workers: {workers}")),
+                "How many tasks rendering of the image is split into, Auto uses the number of processor threads",
+                tooltip::Position::Bottom
+            )
+            .style(Style::Frame),
+            slider(
+                0..=64,
+                self.render_workers,
+                ProgramDataMessage::SetRenderWorkers
+            )
+            .step(1)
+            .width(150),
+        ]
+        .align_items(Alignment::Center)
+        .padding(20)
+        .spacing(5);
+
+        let (supported, unsupported) = parse_extensions(&self.image_extensions);
+        let recognized = if supported.is_empty() {
+            String::from("No files are recognized as images")
+        } else {
+            format!("Recognized as images: .{}", supported.join(" ."))
+        };
+        let mut image_files = col![
+            row![
+                text("Image Files: "),
+                tooltip(
+                    text_input(
+                        DEFAULT_EXTENSIONS,
+                        &self.image_extensions,
+                        ProgramDataMessage::SetImageExtensions
+                    )
+                    .style(self.input_style())
+                    .width(Length::Fill),
+                    "Extensions of files shown when opening images and importing frames, separated by commas",
+                    tooltip::Position::Bottom
+                )
+                .style(Style::Frame),
+                button("Reset").on_press(ProgramDataMessage::SetImageExtensions(
+                    DEFAULT_EXTENSIONS.to_string()
+                )),
+            ]
+            .align_items(Alignment::Center)
+            .spacing(5),
+            text(recognized),
+        ]
+        .padding(20)
+        .spacing(5);
+        if !unsupported.is_empty() {
+            image_files = image_files.push(
+                container(text(format!(
+                    "Ignored, the program can't open these: .{}",
+                    unsupported.join(" .")
+                )))
+                .padding(4)
+                .width(Length::Fill)
+                .style(Style::Danger),
+            );
+        }
+
+        let stored_values = row![
+            text("Stored Values: "),
+            tooltip(
+                button("Show").on_press(ProgramDataMessage::ShowStoredValues(true)),
+                "Lists all values the program remembers between runs and allows clearing them",
+                tooltip::Position::Bottom
+            )
+            .style(Style::Frame),
+        ]
+        .align_items(Alignment::Center)
+        .padding(20)
+        .spacing(5);
+
         let theme = container(theme).style(Style::Frame);
         let workspace_layout = container(workspace_layout).style(Style::Frame);
+        let preview = container(preview).style(Style::Frame);
+        let scrolling = container(scrolling).style(Style::Frame);
+        let performance = container(performance).style(Style::Frame);
+        let autosave = container(autosave).style(Style::Frame);
         let naming_convention = container(naming_convention).style(Style::Frame);
+        let templates = container(templates).style(Style::Frame);
+        let startup = container(startup).style(Style::Frame);
+        let frame_categories = container(frame_categories).style(Style::Frame);
+        let frame_folders = container(frame_folders).style(Style::Frame);
+        let export = container(export).style(Style::Frame);
+        let image_files = container(image_files).style(Style::Frame);
+        let stored_values = container(stored_values).style(Style::Frame);
 
         let ui = col![
             vertical_space(Length::Fill),
             theme,
             workspace_layout,
+            preview,
+            scrolling,
+            performance,
+            autosave,
             naming_convention,
+            templates,
+            startup,
+            frame_categories,
+            frame_folders,
+            image_files,
+            export,
+            stored_values,
             vertical_space(Length::Fill),
         ]
         .align_items(Alignment::Center)
@@ -196,6 +839,98 @@ impl ProgramData {
         container(ui).style(Style::Margins).into()
     }
 
+    /// Draws the list of raw values stored in the cache
+    fn stored_values_view(&self) -> Element<'_, ProgramDataMessage, Renderer> {
+        let header = row![
+            text("Stored Values").width(Length::Fill),
+            button("Back").on_press(ProgramDataMessage::ShowStoredValues(false)),
+        ]
+        .align_items(Alignment::Center)
+        .spacing(5);
+        let note = text(
+            "Clearing a value makes the program fall back to its default. \
+            Edited values have to keep the form they are shown in. \
+            Settings are read at start, so clearing or editing them takes effect after restart.",
+        );
+
+        let entries = self.cache.entries().into_iter().fold(
+            col![].spacing(4).padding(5),
+            |c, (id, key, value)| {
+                let pending = self
+                    .clear_stored_value
+                    .as_ref()
+                    .is_some_and(|(i, k)| i == id && k == key);
+                let editing = self
+                    .edit_stored_value
+                    .as_ref()
+                    .filter(|(i, k, _)| i == id && k == key);
+                let value: Element<'_, ProgramDataMessage, Renderer> = match editing {
+                    Some((_, _, text)) => {
+                        text_input("Value", text, ProgramDataMessage::SetStoredValue)
+                            .on_submit(ProgramDataMessage::ConfirmEditStoredValue)
+                            .style(self.input_style())
+                            .width(Length::FillPortion(3))
+                            .into()
+                    }
+                    None => text(value.to_string()).width(Length::FillPortion(3)).into(),
+                };
+                let actions = if editing.is_some() {
+                    row![
+                        button("Save").on_press(ProgramDataMessage::ConfirmEditStoredValue),
+                        button("Cancel").on_press(ProgramDataMessage::CancelEditStoredValue),
+                    ]
+                } else if pending {
+                    row![
+                        text("Clear?"),
+                        button("Clear")
+                            .on_press(ProgramDataMessage::ConfirmClearStoredValue)
+                            .style(Style::Danger.into()),
+                        button("Cancel").on_press(ProgramDataMessage::CancelClearStoredValue),
+                    ]
+                } else {
+                    row![
+                        button("Edit").on_press(ProgramDataMessage::EditStoredValue(
+                            id.to_string(),
+                            key.to_string()
+                        )),
+                        button("Clear").on_press(ProgramDataMessage::ClearStoredValue(
+                            id.to_string(),
+                            key.to_string()
+                        ))
+                    ]
+                };
+                c.push(
+                    container(
+                        row![
+                            text(id).width(Length::FillPortion(2)),
+                            text(key).width(Length::FillPortion(2)),
+                            value,
+                            actions.align_items(Alignment::Center).spacing(5),
+                        ]
+                        .align_items(Alignment::Center)
+                        .spacing(5),
+                    )
+                    .padding(5)
+                    .style(if pending { Style::Danger } else { Style::Frame }),
+                )
+            },
+        );
+
+        let ui = col![header, note, scrollable(entries).height(Length::Fill)]
+            .padding(20)
+            .spacing(10)
+            .width(Length::FillPortion(3))
+            .height(Length::Fill);
+
+        let ui = row![
+            horizontal_space(Length::Fill),
+            ui,
+            horizontal_space(Length::Fill),
+        ];
+
+        container(ui).style(Style::Margins).into()
+    }
+
     /// Updates settings according to the message
     pub fn update(&mut self, message: ProgramDataMessage) -> Command<ProgramDataMessage> {
         match message {
@@ -208,6 +943,15 @@ impl ProgramData {
                 );
                 Command::none()
             }
+            ProgramDataMessage::SetFocusRings(enabled) => {
+                self.focus_rings = enabled;
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::FocusRings,
+                    enabled,
+                );
+                Command::none()
+            }
             ProgramDataMessage::SetLayout(l) => {
                 self.set_layout(l);
                 Command::none()
@@ -228,9 +972,419 @@ impl ProgramData {
                 self.naming.project_name = sanitize_file_name(n);
                 Command::none()
             }
+            ProgramDataMessage::SelectFrameCategory(c) => {
+                self.rename_category_name = c.clone();
+                self.rename_category = Some(c);
+                Command::none()
+            }
+            ProgramDataMessage::SetFrameCategoryName(n) => {
+                if has_invalid_characters(&n) {
+                    self.status
+                        .warning("Removed invalid characters from the category name");
+                }
+                self.rename_category_name = sanitize_file_name_allow_path(n);
+                Command::none()
+            }
+            ProgramDataMessage::RenameFrameCategory => {
+                let Some(from) = self.rename_category.take() else {
+                    return Command::none();
+                };
+                let to = sanitize_file_name_ends(&self.rename_category_name);
+                match rename_frame_category(&mut self.available_frames, &from, &to) {
+                    Ok(_) => {
+                        self.status
+                            .log(&format!("Renamed category {} to {}", from, to));
+                        self.rename_category_name.clear();
+                    }
+                    Err(e) => {
+                        self.status
+                            .error(&format!("Error: Couldn't rename category {}: {}", from, e));
+                        self.rename_category = Some(from);
+                    }
+                }
+                Command::none()
+            }
+            ProgramDataMessage::SetKeepColorProfile(keep) => {
+                self.keep_color_profile = keep;
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::KeepColorProfile,
+                    keep,
+                );
+                Command::none()
+            }
+            ProgramDataMessage::SetTrimExport(trim) => {
+                self.trim_export = trim;
+                self.cache
+                    .set(PersistentData::SettingsID, PersistentData::TrimExport, trim);
+                Command::none()
+            }
+            ProgramDataMessage::SetTrimPadding(padding) => {
+                self.trim_padding = padding;
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::TrimPadding,
+                    padding,
+                );
+                Command::none()
+            }
+            ProgramDataMessage::SetExportOutline(export) => {
+                self.export_outline = export;
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::ExportOutline,
+                    export,
+                );
+                Command::none()
+            }
+            ProgramDataMessage::SetExportMetadata(export) => {
+                self.export_metadata = export;
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::ExportMetadata,
+                    export,
+                );
+                Command::none()
+            }
+            ProgramDataMessage::SetKeepHighPrecision(keep) => {
+                self.keep_high_precision = keep;
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::KeepHighPrecision,
+                    keep,
+                );
+                Command::none()
+            }
+            ProgramDataMessage::SetAskFormat(ask) => {
+                self.ask_format = ask;
+                self.cache
+                    .set(PersistentData::SettingsID, PersistentData::AskFormat, ask);
+                Command::none()
+            }
+            ProgramDataMessage::SetExportChangedOnly(changed) => {
+                self.export_changed_only = changed;
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::ExportChangedOnly,
+                    changed,
+                );
+                Command::none()
+            }
+            ProgramDataMessage::SetClipboardLinkOnStart(open) => {
+                self.clipboard_link_on_start = open;
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::ClipboardLinkOnStart,
+                    open,
+                );
+                Command::none()
+            }
+            ProgramDataMessage::SetTokenAutoSquare(square) => {
+                self.token_auto_square = square;
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::TokenAutoSquare,
+                    square,
+                );
+                Command::none()
+            }
+            ProgramDataMessage::SetPortraitMirror(mirror) => {
+                self.portrait_mirror = mirror;
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::PortraitMirror,
+                    mirror,
+                );
+                Command::none()
+            }
+            ProgramDataMessage::SetCheckerboard(checkerboard) => {
+                self.checkerboard = checkerboard;
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::Checkerboard,
+                    checkerboard,
+                );
+                Command::none()
+            }
+            ProgramDataMessage::SetCheckerboardSize(size) => {
+                self.checkerboard_size = size;
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::CheckerboardSize,
+                    size,
+                );
+                Command::none()
+            }
+            ProgramDataMessage::SetRenderWorkers(count) => {
+                self.render_workers = count;
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::RenderWorkers,
+                    count,
+                );
+                Command::none()
+            }
+            ProgramDataMessage::SetImageExtensions(extensions) => {
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::ImageExtensions,
+                    extensions.clone(),
+                );
+                self.image_extensions = extensions;
+                Command::none()
+            }
+            ProgramDataMessage::SetZoomStep(step) => {
+                self.zoom_step = step;
+                self.cache
+                    .set(PersistentData::SettingsID, PersistentData::ZoomStep, step);
+                Command::none()
+            }
+            ProgramDataMessage::SetInvertScroll(invert) => {
+                self.invert_scroll = invert;
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::InvertScroll,
+                    invert,
+                );
+                Command::none()
+            }
+            ProgramDataMessage::SetAutosave(autosave) => {
+                self.autosave = autosave;
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::Autosave,
+                    autosave,
+                );
+                Command::none()
+            }
+            ProgramDataMessage::SetAutosaveInterval(interval) => {
+                self.autosave_interval = interval;
+                self.cache.set(
+                    PersistentData::SettingsID,
+                    PersistentData::AutosaveInterval,
+                    interval,
+                );
+                Command::none()
+            }
+            ProgramDataMessage::BrowseFrameFolder => Command::none(),
+            ProgramDataMessage::RemoveFrameFolder(index) => {
+                if index < self.frame_folders.len() {
+                    self.frame_folders.remove(index);
+                    self.store_frame_folders();
+                }
+                Command::none()
+            }
+            ProgramDataMessage::ShowStoredValues(show) => {
+                self.show_stored_values = show;
+                self.clear_stored_value = None;
+                self.edit_stored_value = None;
+                Command::none()
+            }
+            ProgramDataMessage::ClearStoredValue(id, key) => {
+                self.clear_stored_value = Some((id, key));
+                self.edit_stored_value = None;
+                Command::none()
+            }
+            ProgramDataMessage::ConfirmClearStoredValue => {
+                if let Some((id, key)) = self.clear_stored_value.take() {
+                    if self.cache.remove(id.clone(), key.clone()).is_some() {
+                        self.status
+                            .log(&format!("Cleared stored value {} of {}", key, id));
+                    }
+                }
+                Command::none()
+            }
+            ProgramDataMessage::CancelClearStoredValue => {
+                self.clear_stored_value = None;
+                Command::none()
+            }
+            ProgramDataMessage::EditStoredValue(id, key) => {
+                if let Some(value) = self.cache.get(id.clone(), key.clone()) {
+                    self.edit_stored_value = Some((id, key, value.to_string()));
+                    self.clear_stored_value = None;
+                }
+                Command::none()
+            }
+            ProgramDataMessage::SetStoredValue(text) => {
+                if let Some((_, _, value)) = &mut self.edit_stored_value {
+                    *value = text;
+                }
+                Command::none()
+            }
+            ProgramDataMessage::ConfirmEditStoredValue => {
+                let Some((id, key, text)) = &self.edit_stored_value else {
+                    return Command::none();
+                };
+                let Some(old) = self.cache.get(id.clone(), key.clone()) else {
+                    self.edit_stored_value = None;
+                    return Command::none();
+                };
+                match old.parse_like(text) {
+                    Some(value) => {
+                        self.status
+                            .log(&format!("Changed stored value {} of {}", key, id));
+                        self.cache.set(id.clone(), key.clone(), value);
+                        self.edit_stored_value = None;
+                    }
+                    None => self.status.error(&format!(
+                        "\"{}\" is not a valid value for {} of {}",
+                        text, key, id
+                    )),
+                }
+                Command::none()
+            }
+            ProgramDataMessage::CancelEditStoredValue => {
+                self.edit_stored_value = None;
+                Command::none()
+            }
+        }
+    }
+
+    /// Returns folders outside of the program data the frames are also loaded from
+    pub fn frame_folders(&self) -> &[PathBuf] {
+        &self.frame_folders
+    }
+
+    /// Adds a folder to load frames from, returns false if the folder was already added
+    pub fn add_frame_folder(&mut self, folder: PathBuf) -> bool {
+        if self.frame_folders.contains(&folder) {
+            return false;
+        }
+        self.frame_folders.push(folder);
+        self.store_frame_folders();
+        true
+    }
+
+    /// Saves the list of frame folders in persistent data
+    fn store_frame_folders(&mut self) {
+        let folders = self
+            .frame_folders
+            .iter()
+            .map(|x| x.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.cache.set(
+            PersistentData::SettingsID,
+            PersistentData::FrameFolders,
+            folders,
+        );
+    }
+
+    /// Returns width of the modifier properties panel in workspaces
+    pub fn properties_width(&self) -> f32 {
+        self.properties_width
+    }
+
+    /// Sets width of the modifier properties panel, the width is kept within reasonable limits and remembered between runs
+    pub fn set_properties_width(&mut self, width: f32) {
+        self.properties_width = width.clamp(MIN_PROPERTIES_WIDTH, MAX_PROPERTIES_WIDTH);
+        self.cache.set(
+            PersistentData::SettingsID,
+            PersistentData::PropertiesWidth,
+            self.properties_width,
+        );
+    }
+
+    /// Returns size of checks in the pattern drawn behind previews, or none if the pattern is disabled
+    pub fn checkerboard(&self) -> Option<f32> {
+        if self.checkerboard {
+            Some(self.checkerboard_size)
+        } else {
+            None
         }
     }
 
+    /// Returns how much a single step of the scroll wheel changes the zoom, negative when the direction is inverted
+    pub fn scroll_zoom_step(&self) -> f32 {
+        if self.invert_scroll {
+            -self.zoom_step
+        } else {
+            self.zoom_step
+        }
+    }
+
+    /// Returns minutes between autosaves of open workspaces, or none if autosave is disabled
+    pub fn autosave_interval(&self) -> Option<f32> {
+        if self.autosave {
+            Some(self.autosave_interval)
+        } else {
+            None
+        }
+    }
+
+    /// Tests whatever token workspaces should size their export to a square fitting the source image
+    pub fn token_auto_square(&self) -> bool {
+        self.token_auto_square
+    }
+
+    /// Tests whatever portrait workspaces should start with the image mirrored horizontally
+    pub fn portrait_mirror(&self) -> bool {
+        self.portrait_mirror
+    }
+
+    /// Tests whatever SVG outlines should be exported alongside images
+    pub fn export_outline(&self) -> bool {
+        self.export_outline
+    }
+
+    /// Returns how many transparent pixels are kept around exported images if their transparent margins should be trimmed
+    pub fn trim_export(&self) -> Option<u32> {
+        self.trim_export.then_some(self.trim_padding as u32)
+    }
+
+    /// Tests whatever JSON metadata should be exported alongside images
+    pub fn export_metadata(&self) -> bool {
+        self.export_metadata
+    }
+
+    /// Tests whatever new workspaces need their export format chosen before exporting
+    pub fn ask_format(&self) -> bool {
+        self.ask_format
+    }
+
+    /// Tests whatever workspaces unchanged since their last export should be skipped when exporting
+    pub fn export_changed_only(&self) -> bool {
+        self.export_changed_only
+    }
+
+    /// Tests whatever an image address or path found in the clipboard should be opened when the program starts
+    pub fn clipboard_link_on_start(&self) -> bool {
+        self.clipboard_link_on_start
+    }
+
+    /// Number of tasks rendering is split into, zero means it is decided based on the processor
+    pub fn render_workers(&self) -> usize {
+        self.render_workers as usize
+    }
+
+    /// Hands out an identifier for a new workspace, identifiers are never reused while the program runs
+    pub fn next_workspace_id(&mut self) -> u64 {
+        let id = self.next_workspace_id;
+        self.next_workspace_id += 1;
+        id
+    }
+
+    /// Extensions of files recognized as images, lowercase and without the leading dot
+    pub fn recognized_extensions(&self) -> Vec<String> {
+        parse_extensions(&self.image_extensions).0
+    }
+
+    /// Filter for the file browser accepting files with extensions recognized as images
+    pub fn image_filter(&self) -> impl Fn(&PathBuf) -> bool + 'static {
+        let extensions = self.recognized_extensions();
+        move |path| image_filter(path, &extensions)
+    }
+
+    /// Tests whatever 16 bit source images should be loaded to keep their precision in exports
+    pub fn keep_high_precision(&self) -> bool {
+        self.keep_high_precision
+    }
+
+    /// Tests whatever exported images should carry over color profile of their source
+    pub fn keep_color_profile(&self) -> bool {
+        self.keep_color_profile
+    }
+
     pub fn get_workspace_template(&self) -> WorkspaceTemplate {
         self.new_workspace_template
     }
@@ -244,11 +1398,16 @@ impl ProgramData {
         self.new_workspace_template = template;
     }
 
-    pub fn get_output_folder(&self) -> &PathBuf {
-        &self.output
+    /// Returns the export path for the workspace template, or the general export path if the template doesn't have one set
+    pub fn get_output_folder(&self, template: WorkspaceTemplate) -> &PathBuf {
+        self.template_output.get(&template).unwrap_or(&self.output)
     }
 
-    pub fn set_output_folder(&mut self, output: PathBuf) {
+    /// Sets the export path for the workspace template, it also becomes the general export path for templates which don't have one set
+    pub fn set_output_folder(&mut self, output: PathBuf, template: WorkspaceTemplate) {
+        self.cache
+            .set(PersistentData::OutputID, template, output.clone());
+        self.template_output.insert(template, output.clone());
         self.output = output;
         self.cache.set(
             PersistentData::SettingsID,
@@ -257,6 +1416,29 @@ impl ProgramData {
         );
     }
 
+    /// Puts colors recently chosen in color pickers into the cache
+    pub fn store_recent_colors(&mut self) {
+        let colors = self
+            .recent_colors
+            .colors()
+            .into_iter()
+            .map(color_to_hex)
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.cache.set(
+            PersistentData::SettingsID,
+            PersistentData::RecentColors,
+            colors,
+        );
+    }
+
+    /// Style for text inputs following the focus ring setting
+    pub fn input_style(&self) -> Style {
+        Style::Input {
+            focus_ring: self.focus_rings,
+        }
+    }
+
     pub fn get_theme(&self) -> Theme {
         self.theme
     }
@@ -285,6 +1467,7 @@ impl Drop for ProgramData {
             PersistentData::Folder,
             PersistentValue::String(path),
         );
+        self.store_recent_colors();
     }
 }
 
@@ -304,6 +1487,8 @@ pub struct WorkspaceData {
     pub output: String,
     /// Format the exported image will have, the value is private to ensure it will be properly cached
     format: ImageFormat,
+    /// Whatever the format was chosen by the user, only false while the program is set to ask for it
+    format_chosen: bool,
 
     /// Flag used to signal to the workspace and its modifiers what is the intended output to better adjust default values
     pub template: WorkspaceTemplate,
@@ -311,13 +1496,68 @@ pub struct WorkspaceData {
     pub offset: Point,
     /// Zoom applied to the source image for rendering
     pub zoom: f32,
+    /// Whatever the source image is flipped horizontally for rendering
+    pub mirror: bool,
     /// Denotes whatever the workspace needs to be rerendered
     pub dirty: bool,
+    /// ICC color profile embedded in the source image, if it had any
+    pub color_profile: Option<Arc<Vec<u8>>>,
+    /// Dominant colors extracted from the image for use in modifiers
+    pub palette: Vec<Color>,
+    /// Whatever a copy of the image without the frame should be exported alongside the result
+    pub export_art: bool,
+    /// Path or address of the file the source image was loaded from, if known
+    pub source_path: Option<String>,
+    /// Source image with 16 bits per channel, used in place of the 8 bit source where modifiers left the image untouched
+    pub precise_source: Option<Arc<Rgba16Image>>,
+    /// Physical size the image is meant to be printed at, export size is calculated from it when set
+    pub print_size: Option<PrintSize>,
+    /// Free text notes about what the workspace is for, they are never used in file names
+    pub notes: String,
+    /// Identifier of the workspace, it stays the same when other workspaces are closed
+    pub id: u64,
+}
+
+/// Latest result of a workspace shared with modifiers of other workspaces
+#[derive(Debug, Clone)]
+pub struct WorkspaceResult {
+    /// Identifier of the workspace the result came from
+    pub id: u64,
+    pub name: String,
+    pub image: Handle,
+}
+
+/// Physical width of the printed image and resolution it is printed with
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PrintSize {
+    pub width_mm: f32,
+    pub dpi: f32,
+}
+
+impl PrintSize {
+    /// Calculates how many pixels wide the image needs to be to print at this size
+    pub fn pixels(&self) -> u32 {
+        ((self.width_mm / MM_PER_INCH * self.dpi).round() as u32).max(1)
+    }
+}
+
+/// Shrinks the size proportionally so it fits within the limit, sizes already within the limit are returned unchanged
+pub fn fit_size(size: Size<u32>, limit: Size<u32>) -> Size<u32> {
+    let scale = (limit.width as f32 / size.width.max(1) as f32)
+        .min(limit.height as f32 / size.height.max(1) as f32);
+    if scale >= 1.0 {
+        return size;
+    }
+    Size {
+        width: ((size.width as f32 * scale).round() as u32).clamp(1, limit.width.max(1)),
+        height: ((size.height as f32 * scale).round() as u32).clamp(1, limit.height.max(1)),
+    }
 }
 
 impl WorkspaceData {
-    pub fn new(image: Arc<RgbaImage>, name: String, pdata: &ProgramData) -> Self {
+    pub fn new(id: u64, image: Arc<RgbaImage>, name: String, pdata: &ProgramData) -> Self {
         Self {
+            id,
             export_size: match pdata.get_workspace_template() {
                 WorkspaceTemplate::Portrait => Size {
                     width: image.width(),
@@ -332,7 +1572,16 @@ impl WorkspaceData {
             output: name,
             offset: Point::ORIGIN,
             zoom: 1.0,
+            mirror: pdata.get_workspace_template() == WorkspaceTemplate::Portrait
+                && pdata.portrait_mirror(),
             dirty: true,
+            color_profile: None,
+            palette: Vec::new(),
+            export_art: false,
+            source_path: None,
+            precise_source: None,
+            print_size: None,
+            notes: String::new(),
             format: pdata
                 .cache
                 .get_copy(PersistentData::WorkspaceID, PersistentData::Format)
@@ -344,6 +1593,7 @@ impl WorkspaceData {
                     }
                 })
                 .unwrap_or(ImageFormat::WebP),
+            format_chosen: !pdata.ask_format(),
             template: pdata.get_workspace_template(),
             source_preview: image_arc_to_handle(&image),
             image_result: image_arc_to_handle(&image),
@@ -351,24 +1601,97 @@ impl WorkspaceData {
         }
     }
 
+    /// Point of the source image placed at the center of the result
+    ///
+    /// Horizontal offset is reversed for mirrored images so the image still follows the cursor when dragged
+    pub fn focus_point(&self) -> Point {
+        let x = if self.mirror {
+            self.offset.x
+        } else {
+            -self.offset.x
+        };
+        Point {
+            x: self.source.width() as f32 * 0.5 + x,
+            y: self.source.height() as f32 * 0.5 - self.offset.y,
+        }
+    }
+
     /// Retrieves the format this workspace will export the image to
     pub fn get_export_format(&self) -> ImageFormat {
         self.format
     }
 
+    /// Tests whatever the export format was chosen, workspaces only wait for it while the program is set to ask for the format
+    pub fn is_format_chosen(&self) -> bool {
+        self.format_chosen
+    }
+
     /// Sets the format this workspace will export the image to
     pub fn set_export_format(&mut self, format: ImageFormat, pdata: &mut ProgramData) {
         self.format = format;
+        self.format_chosen = true;
         pdata
             .cache
             .set(PersistentData::WorkspaceID, PersistentData::Format, format);
     }
 }
 
+/// Cache of images loaded from drive, allows sharing decoded images between workspaces and modifiers
+///
+/// Images are held weakly, they are released once nothing uses them anymore.
+/// Files modified since they were cached are loaded again.
+#[derive(Default)]
+pub struct ImageCache {
+    images: HashMap<PathBuf, (Option<SystemTime>, Weak<RgbaImage>)>,
+}
+
+impl ImageCache {
+    /// Returns the cached image if it is still in use and the file didn't change since it was loaded
+    pub fn get(&self, path: &Path) -> Option<Arc<RgbaImage>> {
+        let (modified, image) = self.images.get(path)?;
+        if *modified != Self::modified(path) {
+            return None;
+        }
+        image.upgrade()
+    }
+
+    /// Stores the image in cache
+    pub fn insert(&mut self, path: PathBuf, image: &Arc<RgbaImage>) {
+        self.images.retain(|_, (_, x)| x.strong_count() > 0);
+        let modified = Self::modified(&path);
+        self.images.insert(path, (modified, Arc::downgrade(image)));
+    }
+
+    /// Returns the image from cache or loads it from drive if it isn't there
+    pub fn load(&mut self, path: &Path) -> image::ImageResult<Arc<RgbaImage>> {
+        if let Some(image) = self.get(path) {
+            return Ok(image);
+        }
+        let image = Arc::new(open_image(path)?);
+        self.insert(path.to_path_buf(), &image);
+        Ok(image)
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|x| x.modified()).ok()
+    }
+}
+
 pub const PROJECT_NAME: &str = "token-maker";
 pub const PROJECT_DATA_FOLDER: &str = "data";
 pub const PROJECT_FRAMES_FOLDER: &str = "frames";
 
+/// Width of the modifier properties panel before the user resizes it
+const DEFAULT_PROPERTIES_WIDTH: f32 = 360.0;
+/// Limits of the modifier properties panel width
+const MIN_PROPERTIES_WIDTH: f32 = 200.0;
+const MAX_PROPERTIES_WIDTH: f32 = 1200.0;
+
+/// Current working directory, used in place of user directories the system doesn't provide
+pub fn working_dir() -> PathBuf {
+    std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+}
+
 /// This is the primary data path intended for use in saving content to drive
 ///
 /// This leads to the same folder as the executable is on windows and in debug build
@@ -377,12 +1700,12 @@ macro_rules! save_data_path {
     () => {{
             if cfg!(windows) || cfg!(debug_assertions) {
                 // if we're on windows or in debug build then we're expected to use the same directory as the binary is in
-                let mut d = std::env::current_dir().unwrap();
+                let mut d = $crate::data::working_dir();
                 d.push(PROJECT_DATA_FOLDER);
                 d
             } else {
-                // On unix we grab the path from user data location
-                let mut d = dirs::data_local_dir().unwrap();
+                // On unix we grab the path from user data location, falling back to the working directory if there is none
+                let mut d = dirs::data_local_dir().unwrap_or_else($crate::data::working_dir);
                 d.push(PROJECT_NAME);
                 d
             }
@@ -414,7 +1737,7 @@ macro_rules! load_data_path {
         [
             #[cfg(any(windows, debug_assertions))]
             {
-                let mut d = std::env::current_dir().unwrap();
+                let mut d = $crate::data::working_dir();
                 d.push(PROJECT_DATA_FOLDER);
                 $(
                     d.push($paths);
@@ -423,7 +1746,7 @@ macro_rules! load_data_path {
             },
             #[cfg(not(debug_assertions))]
             {
-                let mut d = dirs::data_local_dir().unwrap();
+                let mut d = dirs::data_local_dir().unwrap_or_else($crate::data::working_dir);
                 d.push(PROJECT_NAME);
                 $(
                     d.push($paths);
@@ -515,6 +1838,17 @@ pub fn sanitize_dir_name(name: String) -> String {
         .collect()
 }
 
+/// Tests whatever a file can be written at the path, either by overwriting it or by creating it in its folder
+pub fn is_writable(path: &Path) -> bool {
+    match std::fs::metadata(path) {
+        Ok(meta) => meta.is_file() && !meta.permissions().readonly(),
+        Err(_) => path
+            .parent()
+            .and_then(|x| std::fs::metadata(x).ok())
+            .is_some_and(|x| x.is_dir() && !x.permissions().readonly()),
+    }
+}
+
 /// Holds images prepared to be used as frames for tokens
 #[derive(Debug, Clone)]
 pub struct FrameImage {
@@ -570,11 +1904,22 @@ impl FrameImage {
         self.display.clone()
     }
 
+    /// Gets the name of the frame, it is also the name of its file
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     /// Gets the category under which the image lays
     pub fn category(&self) -> &String {
         &self.category
     }
 
+    /// Moves the frame to a different category, updating its identifier
+    pub fn set_category(&mut self, category: String) {
+        self.category = sanitize_file_name_ends(&category);
+        self.id = format!("{}/{}", self.category, self.name);
+    }
+
     /// Clones the pointer to the frame image
     pub fn image(&self) -> Arc<RgbaImage> {
         self.frame.clone()
@@ -586,10 +1931,10 @@ impl FrameImage {
     }
 
     /// Saves the frame using its name for path location
-    pub fn save_frame(&self) {
+    pub fn save_frame(&self) -> Result<(), String> {
         let mut location = save_frames_path!(&self.category);
         if location.exists() == false {
-            create_dir_all(&location).unwrap();
+            create_dir_all(&location).map_err(|e| format!("{:?}: {}", location, e))?;
         }
         location.push(format!("{}.webp", &self.name));
 
@@ -600,10 +1945,12 @@ impl FrameImage {
             self.frame.height(),
             image::ColorType::Rgba8,
         )
-        .unwrap();
+        .map_err(|e| format!("{:?}: {}", location, e))?;
 
+        let Some(mask) = self.mask.as_ref() else {
+            return Ok(());
+        };
         location.set_file_name(format!("{}-mask.webp", &self.name));
-        let mask = self.mask.as_ref().unwrap();
         let pix = mask.as_raw();
         let width = mask.width() as usize;
         let mask = RgbaImage::from_fn(mask.width(), mask.height(), |x, y| {
@@ -613,23 +1960,250 @@ impl FrameImage {
         });
 
         image::save_buffer(
-            location,
+            &location,
             &mask,
             mask.width(),
             mask.height(),
             image::ColorType::Rgba8,
         )
-        .unwrap();
+        .map_err(|e| format!("{:?}: {}", location, e))
+    }
+}
+
+/// Renames a frame category, moving its folder in the frames save path and updating all affected frames
+///
+/// Frames of the category found in the other frame folders of the program are moved as well so the rename holds after restart.
+/// Subcategories of the renamed category are moved together with it.
+pub fn rename_frame_category(
+    frames: &mut [FrameImage],
+    from: &str,
+    to: &str,
+) -> std::io::Result<()> {
+    let mut roots = vec![save_frames_path!()];
+    for root in load_frames_path!() {
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    rename_category_in(&roots, frames, from, to)
+}
+
+/// Renames the category folder in each of the frame roots that contains it and updates the frames
+///
+/// Folders already moved are put back if any of them fails to move.
+fn rename_category_in(
+    roots: &[PathBuf],
+    frames: &mut [FrameImage],
+    from: &str,
+    to: &str,
+) -> std::io::Result<()> {
+    if to.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "category name can't be empty",
+        ));
+    }
+    let moves: Vec<(PathBuf, PathBuf)> = roots
+        .iter()
+        .map(|root| (root.join(from), root.join(to)))
+        .filter(|(source, _)| source.is_dir())
+        .collect();
+    if moves.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "category folder doesn't exist",
+        ));
+    }
+    if moves.iter().any(|(_, target)| target.exists()) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            "category with that name already exists",
+        ));
+    }
+
+    for (ix, (source, target)) in moves.iter().enumerate() {
+        let moved = target
+            .parent()
+            .map_or(Ok(()), create_dir_all)
+            .and_then(|_| std::fs::rename(source, target));
+        if let Err(e) = moved {
+            for (source, target) in moves[..ix].iter() {
+                let _ = std::fs::rename(target, source);
+            }
+            return Err(e);
+        }
+    }
+
+    let prefix = format!("{}/", from);
+    frames.iter_mut().for_each(|f| {
+        if f.category == from {
+            f.set_category(to.to_string());
+        } else if let Some(sub) = f.category.strip_prefix(&prefix) {
+            let category = format!("{}/{}", to, sub);
+            f.set_category(category);
+        }
+    });
+    Ok(())
+}
+
+/// Copies frames with their masks from the folder into the frames save path under the category
+///
+/// Pairs are expected to follow the same naming as saved frames, `{name}.{ext}` for the frame and `{name}-mask.{ext}` for its mask.
+/// Returns names of imported frames and descriptions of files that were skipped.
+pub async fn import_frames(
+    folder: PathBuf,
+    category: String,
+    extensions: Vec<String>,
+) -> std::io::Result<(Vec<String>, Vec<String>)> {
+    let target = save_frames_path!(&category);
+    let mut imported = Vec::new();
+    let mut skipped = Vec::new();
+
+    let mut files: Vec<PathBuf> = read_dir(&folder)?
+        .filter_map(|x| x.ok())
+        .map(|x| x.path())
+        .filter(|x| x.is_file() && image_filter(x, &extensions))
+        .collect();
+    files.sort();
+    let stem = |path: &PathBuf| {
+        path.file_stem()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+
+    for path in files.iter() {
+        let name = stem(path);
+        if let Some(frame) = name.strip_suffix("-mask") {
+            if !files.iter().any(|x| stem(x) == frame) {
+                skipped.push(format!("{}: mask without a frame", name));
+            }
+            continue;
+        }
+        let mask_name = format!("{}-mask", name);
+        let Some(mask_path) = files.iter().find(|x| stem(x) == mask_name) else {
+            skipped.push(format!("{}: missing mask", name));
+            continue;
+        };
+        let (Ok(frame), Ok(mask)) = (open_image(path), open_image(mask_path)) else {
+            skipped.push(format!("{}: image can't be read", name));
+            continue;
+        };
+        if frame.dimensions() != mask.dimensions() {
+            skipped.push(format!("{}: mask size doesn't match the frame", name));
+            continue;
+        }
+        let name = sanitize_file_name_ends(&name);
+        if name.is_empty() {
+            skipped.push(format!("{}: invalid name", stem(path)));
+            continue;
+        }
+        let location = target.join(format!("{}.webp", name));
+        if location.exists() {
+            skipped.push(format!("{}: frame already exists in the category", name));
+            continue;
+        }
+
+        create_dir_all(&target)?;
+        let saved = frame
+            .save(&location)
+            .and_then(|_| mask.save(target.join(format!("{}-mask.webp", name))));
+        match saved {
+            Ok(_) => imported.push(name),
+            Err(e) => skipped.push(format!("{}: {}", name, e)),
+        }
+    }
+    Ok((imported, skipped))
+}
+
+/// Name of the environment variable which overrides the export folder
+const OUTPUT_ENV: &str = "TOKEN_MAKER_OUTPUT";
+/// Command line flag which overrides the export folder, it takes precedence over the environment variable
+const OUTPUT_FLAG: &str = "--output";
+
+/// Reads export folder given to the program at startup, either with the command line flag or the environment variable
+///
+/// The flag accepts the path both as the following argument and after equals sign
+fn output_override() -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == OUTPUT_FLAG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg
+            .strip_prefix(OUTPUT_FLAG)
+            .and_then(|x| x.strip_prefix('='))
+        {
+            return Some(PathBuf::from(path));
+        }
     }
+    std::env::var_os(OUTPUT_ENV)
+        .filter(|x| !x.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Frames built into the program as category, name, frame image and mask image
+const STARTER_FRAMES: [(&str, &str, &[u8], &[u8]); 3] = [
+    (
+        "ring",
+        "ring-thin",
+        include_bytes!("../data/frames/ring/ring-thin.webp"),
+        include_bytes!("../data/frames/ring/ring-thin-mask.webp"),
+    ),
+    (
+        "ring",
+        "ring-thick",
+        include_bytes!("../data/frames/ring/ring-thick.webp"),
+        include_bytes!("../data/frames/ring/ring-thick-mask.webp"),
+    ),
+    (
+        "hexagon",
+        "hexagon-upright-thin",
+        include_bytes!("../data/frames/hexagon/hexagon-upright-thin.webp"),
+        include_bytes!("../data/frames/hexagon/hexagon-upright-thin-mask.webp"),
+    ),
+];
+
+/// Writes frames built into the program into the frames folder, meant for when the program can't find any frames
+pub fn write_starter_frames() -> Result<(), String> {
+    for (category, name, frame, mask) in STARTER_FRAMES {
+        let frame = image::load_from_memory(frame)
+            .map_err(|e| e.to_string())?
+            .into_rgba8();
+        let mask = image::load_from_memory(mask)
+            .map_err(|e| e.to_string())?
+            .into_luma8();
+        FrameImage::new(name.to_string(), category.to_string(), frame, Some(mask)).save_frame()?;
+    }
+    Ok(())
 }
 
 /// Function crawls through frames folder and gathers all images for frames and their masks
-pub async fn load_frames() -> std::io::Result<Vec<FrameImage>> {
+///
+/// Frames from the additional folders have their categories prefixed with the name of the folder so they don't collide with frames of the program
+pub async fn load_frames(folders: Vec<PathBuf>) -> std::io::Result<Vec<FrameImage>> {
     let mut res = vec![];
-    let mut dirs = Vec::from(load_frames_path!());
+    let mut dirs: Vec<(Option<(String, PathBuf)>, PathBuf)> =
+        load_frames_path!().into_iter().map(|x| (None, x)).collect();
+    let mut prefixes: Vec<String> = Vec::new();
+    for folder in folders {
+        let name = folder
+            .file_name()
+            .map(|x| sanitize_file_name_ends(&x.to_string_lossy().to_string()))
+            .filter(|x| !x.is_empty())
+            .unwrap_or_else(|| String::from("folder"));
+        // folders sharing the same name still need distinct categories
+        let mut prefix = name.clone();
+        let mut count = 1;
+        while prefixes.contains(&prefix) {
+            count += 1;
+            prefix = format!("{}-{}", name, count);
+        }
+        prefixes.push(prefix.clone());
+        dirs.push((Some((prefix, folder.clone())), folder));
+    }
 
     // loads all the images from the frames folder and its subfolders
-    while let Some(p) = dirs.pop() {
+    while let Some((root, p)) = dirs.pop() {
         // read directory or skip if that failed
         let Ok(dir) = read_dir(p) else {
             continue;
@@ -644,7 +2218,7 @@ pub async fn load_frames() -> std::io::Result<Vec<FrameImage>> {
 
             // recurse into subdirectories
             if path.is_dir() {
-                dirs.push(path.clone());
+                dirs.push((root.clone(), path.clone()));
                 continue;
             }
 
@@ -665,7 +2239,19 @@ pub async fn load_frames() -> std::io::Result<Vec<FrameImage>> {
             let img = img.into_rgba8();
 
             // Constructing the category for the image
-            let category = {
+            let category = if let Some((prefix, root)) = &root {
+                let mut image_folder = path.clone();
+                image_folder.pop();
+                image_folder
+                    .strip_prefix(root)
+                    .unwrap_or(Path::new(""))
+                    .iter()
+                    .fold(prefix.clone(), |mut s, i| {
+                        s.push('/');
+                        s.push_str(&i.to_string_lossy());
+                        s
+                    })
+            } else {
                 let mut image_folder = path.clone();
                 image_folder.pop();
                 let mut found = false;
@@ -715,13 +2301,37 @@ pub async fn load_frames() -> std::io::Result<Vec<FrameImage>> {
 enum PersistentData {
     SettingsID,
     FileBrowserID,
+    OutputID,
     WorkspaceID,
     Format,
     Theme,
+    FocusRings,
     Layout,
     Output,
     Folder,
     WorkspaceTemplate,
+    KeepColorProfile,
+    ExportOutline,
+    TrimExport,
+    TrimPadding,
+    ExportMetadata,
+    KeepHighPrecision,
+    AskFormat,
+    ExportChangedOnly,
+    TokenAutoSquare,
+    PortraitMirror,
+    ClipboardLinkOnStart,
+    Checkerboard,
+    CheckerboardSize,
+    RenderWorkers,
+    ImageExtensions,
+    ZoomStep,
+    InvertScroll,
+    Autosave,
+    AutosaveInterval,
+    RecentColors,
+    FrameFolders,
+    PropertiesWidth,
 }
 
 impl PersistentKey for PersistentData {
@@ -729,13 +2339,81 @@ impl PersistentKey for PersistentData {
         match self {
             PersistentData::SettingsID => "settings",
             PersistentData::FileBrowserID => "file-browser",
+            PersistentData::OutputID => "output-folders",
             PersistentData::Theme => "theme",
+            PersistentData::FocusRings => "focus-rings",
             PersistentData::Layout => "layout",
             PersistentData::Output => "output",
             PersistentData::Folder => "folder",
             PersistentData::WorkspaceID => "workspace",
             PersistentData::Format => "format",
             PersistentData::WorkspaceTemplate => "template",
+            PersistentData::KeepColorProfile => "keep-color-profile",
+            PersistentData::ExportOutline => "export-outline",
+            PersistentData::TrimExport => "trim-export",
+            PersistentData::TrimPadding => "trim-padding",
+            PersistentData::ExportMetadata => "export-metadata",
+            PersistentData::KeepHighPrecision => "keep-high-precision",
+            PersistentData::AskFormat => "ask-format",
+            PersistentData::ExportChangedOnly => "export-changed-only",
+            PersistentData::TokenAutoSquare => "token-auto-square",
+            PersistentData::PortraitMirror => "portrait-mirror",
+            PersistentData::ClipboardLinkOnStart => "clipboard-link-on-start",
+            PersistentData::Checkerboard => "checkerboard",
+            PersistentData::CheckerboardSize => "checkerboard-size",
+            PersistentData::RenderWorkers => "render-workers",
+            PersistentData::ImageExtensions => "image-extensions",
+            PersistentData::ZoomStep => "zoom-step",
+            PersistentData::InvertScroll => "invert-scroll",
+            PersistentData::Autosave => "autosave",
+            PersistentData::AutosaveInterval => "autosave-interval",
+            PersistentData::RecentColors => "recent-colors",
+            PersistentData::FrameFolders => "frame-folders",
+            PersistentData::PropertiesWidth => "properties-width",
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rename_category_in_all_roots() {
+        let base = std::env::temp_dir().join("token-maker-rename-category");
+        let _ = std::fs::remove_dir_all(&base);
+        let roots = vec![base.join("save"), base.join("load"), base.join("empty")];
+        create_dir_all(roots[0].join("old/sub")).unwrap();
+        create_dir_all(roots[1].join("old")).unwrap();
+        create_dir_all(&roots[2]).unwrap();
+
+        let frame = || RgbaImage::new(1, 1);
+        let mut frames = vec![
+            FrameImage::new("a".into(), "old".into(), frame(), None),
+            FrameImage::new("b".into(), "old/sub".into(), frame(), None),
+            FrameImage::new("c".into(), "older".into(), frame(), None),
+        ];
+        rename_category_in(&roots, &mut frames, "old", "new/name").unwrap();
+
+        assert!(roots[0].join("new/name/sub").is_dir());
+        assert!(roots[1].join("new/name").is_dir());
+        assert!(!roots[0].join("old").exists());
+        assert!(!roots[1].join("old").exists());
+        assert!(!roots[2].join("new").exists());
+        let ids: Vec<&str> = frames.iter().map(|x| x.id()).collect();
+        assert_eq!(ids, ["new/name/a", "new/name/sub/b", "older/c"]);
+        assert_eq!(frames[1].category(), "new/name/sub");
+
+        // an existing target in any of the roots leaves everything as it was
+        create_dir_all(roots[1].join("taken")).unwrap();
+        let result = rename_category_in(&roots, &mut frames, "new/name", "taken");
+        assert_eq!(
+            result.unwrap_err().kind(),
+            std::io::ErrorKind::AlreadyExists
+        );
+        assert!(roots[0].join("new/name").is_dir());
+        assert_eq!(frames[0].id(), "new/name/a");
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
@@ -1,5 +1,7 @@
 use iced::{
-    widget::{column as col, container, row, text, text_input},
+    widget::{
+        button, column as col, container, row, slider, text, text_input, tooltip, tooltip::Position,
+    },
     Alignment, Command, Element, Length, Renderer, Vector,
 };
 use iced_native::image::Handle;
@@ -15,6 +17,11 @@ use crate::{
     widgets::PixelSampler,
 };
 
+/// Fraction of the frame below which the mask is considered suspiciously small
+const MIN_MASK_COVERAGE: f32 = 0.05;
+/// Fraction of the frame above which the mask is considered to have leaked outside of the frame
+const MAX_MASK_COVERAGE: f32 = 0.95;
+
 /// Editor for creating new frames for use in the program
 pub struct FrameMaker {
     /// Name for the new frame image
@@ -27,6 +34,12 @@ pub struct FrameMaker {
     frame: RgbaImage,
     /// The grayscale mask image this editor is meant to help create
     mask: Option<GrayscaleImage>,
+    /// Mask and preview from before the latest mask change, used for undoing it
+    previous: Option<(Option<GrayscaleImage>, Handle)>,
+    /// Pixel the mask was spread from
+    seed: Option<Vector<u32>>,
+    /// Pixels with alpha below this value are considered to be inside of the frame
+    alpha_threshold: u8,
     /// Flag that marks whatever the editor is awaiting rendering result
     rendering: bool,
 }
@@ -36,12 +49,18 @@ pub enum FrameMakerMessage {
     /// Result of user clicking the PixelSampler
     /// The vector is pixel location the user clicked
     SelectedPixel(Vector<u32>),
-    /// Result of generating the frame, gives preview image and the mask
-    GeneratedMask(Handle, GrayscaleImage),
+    /// Result of generating the frame, gives preview image, the mask and the alpha threshold used to create it
+    GeneratedMask(Handle, GrayscaleImage, u8),
+    /// Sets the alpha value below which pixels are considered inside of the frame
+    SetAlphaThreshold(f32),
     /// Sets the name for the frame
     SetName(String),
     /// Sets the category for the frame
     SetCategory(String),
+    /// Restores the mask from before the latest change
+    UndoMask,
+    /// Removes the mask
+    ClearMask,
 }
 
 impl FrameMaker {
@@ -53,6 +72,9 @@ impl FrameMaker {
             name: String::from("frame"),
             category: String::from("frame"),
             mask: None,
+            previous: None,
+            seed: None,
+            alpha_threshold: u8::MAX,
             preview: image_to_handle(image.clone()),
             frame: image,
             rendering: false,
@@ -69,6 +91,8 @@ impl FrameMaker {
         self.name = String::from("new-frame");
         self.category = String::from("frame");
         self.mask = None;
+        self.previous = None;
+        self.seed = None;
         self.preview = image_to_handle(frame.clone());
         self.frame = frame;
     }
@@ -100,15 +124,54 @@ impl FrameMaker {
         true
     }
 
+    /// Returns which fraction of the frame is covered by the mask
+    fn mask_coverage(&self) -> Option<f32> {
+        let mask = self.mask.as_ref()?;
+        let covered = mask.pixels().filter(|x| x[0] > 127).count();
+        Some(covered as f32 / mask.pixels().len().max(1) as f32)
+    }
+
+    /// Returns a warning if the mask covers an area too small or too big to be a likely result of picking the right spot
+    fn mask_warning(&self) -> Option<&'static str> {
+        let coverage = self.mask_coverage()?;
+        if coverage < MIN_MASK_COVERAGE {
+            Some("Mask covers very little of the frame, consider clicking inside the opening of the frame instead")
+        } else if coverage > MAX_MASK_COVERAGE {
+            Some("Mask covers almost the whole image, the frame may have a gap letting the mask leak outside")
+        } else {
+            None
+        }
+    }
+
+    /// Tests if there is a mask change that can be undone
+    pub fn can_undo(&self) -> bool {
+        self.previous.is_some() && !self.rendering
+    }
+
+    /// Remembers current mask and preview so the next change to them can be undone
+    fn store_previous(&mut self) {
+        self.previous = Some((self.mask.clone(), self.preview.clone()));
+    }
+
+    /// Creates a task spreading the mask from the seed pixel with current alpha threshold
+    fn regenerate_mask(&self, seed: Vector<u32>) -> Command<FrameMakerMessage> {
+        let threshold = self.alpha_threshold;
+        Command::perform(
+            create_mask(self.frame.clone(), seed, threshold),
+            move |(h, g)| FrameMakerMessage::GeneratedMask(h, g, threshold),
+        )
+    }
+
     /// Constructs UI for the editor
-    pub fn view(&self, _pdata: &ProgramData) -> Element<FrameMakerMessage, Renderer> {
+    pub fn view(&self, pdata: &ProgramData) -> Element<'_, FrameMakerMessage, Renderer> {
         let name = row![
             text("Name: "),
             text_input(
                 "New Frame Name",
                 &self.name,
                 |x| FrameMakerMessage::SetName(x)
-            ),
+            )
+            .style(pdata.input_style()),
         ]
         .spacing(5)
         .padding(5)
@@ -119,7 +182,57 @@ impl FrameMaker {
             text("Category: "),
             text_input("Category for new frame", &self.category, |x| {
                 FrameMakerMessage::SetCategory(x)
-            }),
+            })
+            .style(pdata.input_style()),
+        ]
+        .spacing(5)
+        .padding(5)
+        .align_items(Alignment::Center)
+        .height(Length::Shrink);
+
+        let undo = if self.can_undo() {
+            button("Undo").on_press(FrameMakerMessage::UndoMask)
+        } else {
+            button("Undo")
+        };
+        let clear = if self.mask.is_some() && !self.rendering {
+            button("Clear Mask").on_press(FrameMakerMessage::ClearMask)
+        } else {
+            button("Clear Mask")
+        };
+        let coverage = match self.mask_coverage() {
+            Some(coverage) => format!("Covers {:.1}% of the frame", coverage * 100.0),
+            None => String::from("Click inside the frame to create it"),
+        };
+        let mask = row![
+            text("Mask: "),
+            tooltip(
+                undo,
+                "Restores the mask from before the last change",
+                Position::Bottom
+            )
+            .style(Style::Frame),
+            clear,
+            text(coverage),
+        ]
+        .spacing(5)
+        .padding(5)
+        .align_items(Alignment::Center)
+        .height(Length::Shrink);
+
+        let threshold = row![
+            tooltip(
+                text(format!("Alpha threshold: {}", self.alpha_threshold)),
+                "Pixels less opaque than this value are considered inside of the frame",
+                Position::Bottom
+            )
+            .style(Style::Frame),
+            slider(
+                1.0..=255.0,
+                self.alpha_threshold as f32,
+                FrameMakerMessage::SetAlphaThreshold
+            )
+            .step(1.0),
         ]
         .spacing(5)
         .padding(5)
@@ -128,6 +241,7 @@ impl FrameMaker {
 
         let name = container(name).style(Style::Frame);
         let category = container(category).style(Style::Frame);
+        let mask = container(col![mask, threshold]).style(Style::Frame);
 
         let preview = container(PixelSampler::new(self.preview.clone(), |x| {
             FrameMakerMessage::SelectedPixel(x)
@@ -138,7 +252,17 @@ impl FrameMaker {
         .width(Length::Fill)
         .height(Length::Fill);
 
-        let ui = col![name, category, preview,].spacing(2).padding(2);
+        let ui = col![name, category, mask].spacing(2).padding(2);
+        let ui = match self.mask_warning() {
+            Some(warning) => ui.push(
+                container(text(warning))
+                    .padding(5)
+                    .width(Length::Fill)
+                    .style(Style::Danger),
+            ),
+            None => ui,
+        };
+        let ui = ui.push(preview);
 
         container(ui).style(Style::Margins).into()
     }
@@ -151,15 +275,45 @@ impl FrameMaker {
     ) -> Command<FrameMakerMessage> {
         match message {
             FrameMakerMessage::SelectedPixel(p) => {
+                self.seed = Some(p);
                 self.rendering = true;
-                Command::perform(create_mask(self.frame.clone(), p), |(h, g)| {
-                    FrameMakerMessage::GeneratedMask(h, g)
-                })
+                self.regenerate_mask(p)
+            }
+            FrameMakerMessage::SetAlphaThreshold(threshold) => {
+                self.alpha_threshold = threshold as u8;
+                match self.seed {
+                    Some(seed) => {
+                        self.rendering = true;
+                        self.regenerate_mask(seed)
+                    }
+                    None => Command::none(),
+                }
             }
-            FrameMakerMessage::GeneratedMask(image, mask) => {
+            FrameMakerMessage::GeneratedMask(image, mask, threshold) => {
+                // threshold changed while the mask was generating, newer mask is on its way
+                if threshold != self.alpha_threshold {
+                    return Command::none();
+                }
                 self.rendering = false;
+                self.store_previous();
                 self.mask = Some(mask);
                 self.preview = image;
+                if let Some(warning) = self.mask_warning() {
+                    pdata.status.warning(warning);
+                }
+                Command::none()
+            }
+            FrameMakerMessage::UndoMask => {
+                if let Some((mask, preview)) = self.previous.take() {
+                    self.mask = mask;
+                    self.preview = preview;
+                }
+                Command::none()
+            }
+            FrameMakerMessage::ClearMask => {
+                self.store_previous();
+                self.mask = None;
+                self.preview = image_to_handle(self.frame.clone());
                 Command::none()
             }
             FrameMakerMessage::SetName(n) => {
@@ -185,9 +339,15 @@ impl FrameMaker {
 }
 
 /// Creates a mask out of the image by flood spreading the mask pixel by pixel from the source position using alpha channel of the image.
-async fn create_mask(image: RgbaImage, flood_source: Vector<u32>) -> (Handle, GrayscaleImage) {
+///
+/// Pixels with alpha lower than the threshold are considered inside of the frame
+async fn create_mask(
+    image: RgbaImage,
+    flood_source: Vector<u32>,
+    alpha_threshold: u8,
+) -> (Handle, GrayscaleImage) {
     let mask = flood_fill_mask(&image, flood_source, 0, |s| {
-        if s[3] < 255 {
+        if s[3] < alpha_threshold {
             Some(255)
         } else {
             None
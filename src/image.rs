@@ -1,16 +1,37 @@
 pub mod convert;
+pub mod dpi;
+pub mod icc;
 pub mod operations;
+pub mod outline;
+pub mod palette;
+pub mod psd;
+pub mod script;
+pub mod text;
 
-use std::{fmt::Display, path::PathBuf, sync::Arc};
+use std::{
+    fmt::Display,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
 
 use iced::{Color, Point, Size};
-use image::{ImageBuffer, Luma, Rgba};
+use image::{imageops, ImageBuffer, Luma, Rgba};
 use serde::{Deserialize, Serialize};
 
-use self::operations::*;
+use self::{convert::color_to_hex, operations::*, text::TextAlignment};
 
 pub type RgbaImage = ImageBuffer<Rgba<u8>, Vec<u8>>;
 pub type GrayscaleImage = ImageBuffer<Luma<u8>, Vec<u8>>;
+pub type Rgba16Image = ImageBuffer<Rgba<u16>, Vec<u16>>;
+
+/// Operation defined outside of the built in ones, like a user script, which transforms each pixel on its own
+pub trait PixelOperation: Display + Send + Sync {
+    /// Calculates the new color of the pixel at the position within the image of given size, color channels are in 0 to 1 range
+    fn transform(&self, color: [f32; 4], x: u32, y: u32, size: Size<u32>) -> [f32; 4];
+}
 
 /// Operation markers, they hold data and denote which operation should be performed on the image
 pub enum ImageOperation {
@@ -20,6 +41,8 @@ pub enum ImageOperation {
         resolution: Size<u32>,
         focus_point: Point,
         size: f32,
+        /// Whatever the source image is flipped horizontally
+        mirror: bool,
     },
 
     /// Uses the mask image to hide parts of the rendered image, dark parts of the mask hide pixels in the result
@@ -32,6 +55,8 @@ pub enum ImageOperation {
         mask: Arc<GrayscaleImage>,
         center: Point,
         size: f32,
+        /// Whatever the mask is flipped horizontally along with the source image
+        mirror: bool,
     },
 
     /// Masks color from the image, turning matching pixels within the range transparent
@@ -53,31 +78,124 @@ pub enum ImageOperation {
     ///
     /// This operation expects the both images to be the same resolution
     BackgroundImage(Arc<RgbaImage>),
+
+    /// Stretches the tonal range of the image so the black point turns black and the white point turns white
+    ///
+    /// Each of the red, green and blue channels is then stretched between its own points and the result is compressed into the output range
+    Levels {
+        black: f32,
+        white: f32,
+        channels: [(f32, f32); 3],
+        output: (f32, f32),
+    },
+
+    /// Shifts brightness of the color channels and scales their contrast around middle gray, both values are in -1 to 1 range
+    BrightnessContrast { brightness: f32, contrast: f32 },
+
+    /// Shifts hue of the colors by degrees and adds to their saturation and value, the latter two are in -1 to 1 range
+    HsvAdjust {
+        hue: f32,
+        saturation: f32,
+        value: f32,
+    },
+
+    /// Recolors semi-transparent edges with colors pulled inward from opaque pixels, up to the width in pixels
+    Defringe { width: u32 },
+
+    /// Adds grain to color channels of the image, the same seed always produces the same grain
+    Noise {
+        intensity: f32,
+        monochrome: bool,
+        seed: u64,
+    },
+
+    /// Adds a blurred dark ellipse under the image, positions and sizes are fractions of the image size
+    GroundShadow {
+        offset: f32,
+        width: f32,
+        height: f32,
+        blur: f32,
+        opacity: f32,
+    },
+
+    /// Adds a blurred silhouette of the image under it in the color, offset is a fraction of the image size
+    /// and blur a fraction of its smaller side
+    DropShadow {
+        offset: Point,
+        blur: f32,
+        color: Color,
+    },
+
+    /// Draws an outline of the thickness in pixels around visible parts of the image
+    Outline { thickness: u32, color: Color },
+
+    /// Mirrors a wedge of the image around its center into symmetric segments, rotation is in degrees
+    Kaleidoscope { segments: u32, rotation: f32 },
+
+    /// Rotates the image clockwise by the number of quarter turns, keeping its size
+    Rotate90 { turns: u32 },
+
+    /// Rotates the image clockwise around its center by the angle in degrees, keeping its size
+    Rotate { angle: f32 },
+
+    /// Mirrors the image from left to right
+    FlipHorizontal,
+
+    /// Mirrors the image from top to bottom
+    FlipVertical,
+
+    /// Blends a gradient between two colors over the image, angle is in degrees and only used by linear gradients
+    GradientOverlay {
+        shape: GradientShape,
+        start: Color,
+        end: Color,
+        angle: f32,
+        mode: BlendMode,
+        opacity: f32,
+    },
+
+    /// Transforms every pixel of the image with the operation, resulting colors are clamped to the valid range
+    Custom(Arc<dyn PixelOperation>),
+
+    /// Draws a line of text over the image, offset from the center of the image and size are fractions of the image size
+    DrawText {
+        text: String,
+        offset: Point,
+        size: f32,
+        color: Color,
+        alignment: TextAlignment,
+    },
 }
 
 impl ImageOperation {
-    /// Creates a starting image in rendering process
-    pub async fn begin(self) -> RgbaImage {
+    /// Creates a starting image in rendering process, split into the number of worker tasks
+    pub async fn begin(self, workers: usize) -> RgbaImage {
         match self {
             ImageOperation::Begin {
                 image,
                 resolution,
                 focus_point,
                 size,
-            } => resample_image(image, resolution, focus_point, size).await,
+                mirror,
+            } => resample_image(image, resolution, focus_point, size, mirror, workers).await,
             _ => panic!("Image processing began on a wrong operation"),
         }
     }
     /// Performs the operation on the image, returning the result
-    pub async fn perform(self, image: RgbaImage) -> RgbaImage {
+    ///
+    /// Operations going over every pixel split the work into the number of worker tasks, zero picks it based on the processor
+    pub async fn perform(self, image: RgbaImage, workers: usize) -> RgbaImage {
         match self {
             ImageOperation::Begin { .. } => {
                 panic!("Tried to call Begin operation as not a first operation!")
             }
             ImageOperation::Mask { mask } => mask_image(image, mask.as_ref()),
-            ImageOperation::MaskWithOffset { mask, center, size } => {
-                mask_image_with_offset(image, mask, center, size).await
-            }
+            ImageOperation::MaskWithOffset {
+                mask,
+                center,
+                size,
+                mirror,
+            } => mask_image_with_offset(image, mask, center, size, mirror, workers).await,
             ImageOperation::MaskColor {
                 color,
                 range,
@@ -86,6 +204,249 @@ impl ImageOperation {
             ImageOperation::Blend { overlay } => blend_images(image, overlay.as_ref()),
             ImageOperation::BackgroundColor(color) => underlay_color(image, color),
             ImageOperation::BackgroundImage(under) => underlay_image(image, under),
+            ImageOperation::Levels {
+                black,
+                white,
+                channels,
+                output,
+            } => levels(image, black, white, channels, output),
+            ImageOperation::BrightnessContrast {
+                brightness,
+                contrast,
+            } => brightness_contrast(image, brightness, contrast, workers).await,
+            ImageOperation::HsvAdjust {
+                hue,
+                saturation,
+                value,
+            } => hsv_adjust(image, hue, saturation, value, workers).await,
+            ImageOperation::Defringe { width } => defringe(image, width),
+            ImageOperation::Noise {
+                intensity,
+                monochrome,
+                seed,
+            } => add_noise(image, intensity, monochrome, seed),
+            ImageOperation::GroundShadow {
+                offset,
+                width,
+                height,
+                blur,
+                opacity,
+            } => ground_shadow(image, offset, width, height, blur, opacity),
+            ImageOperation::DropShadow {
+                offset,
+                blur,
+                color,
+            } => drop_shadow(image, offset, blur, color),
+            ImageOperation::Outline { thickness, color } => {
+                outline_silhouette(image, thickness, color)
+            }
+            ImageOperation::Kaleidoscope { segments, rotation } => {
+                kaleidoscope(image, segments, rotation)
+            }
+            ImageOperation::Rotate90 { turns } => rotate_quarters(image, turns),
+            ImageOperation::Rotate { angle } => rotate_free(image, angle, workers).await,
+            ImageOperation::FlipHorizontal => imageops::flip_horizontal(&image),
+            ImageOperation::FlipVertical => imageops::flip_vertical(&image),
+            ImageOperation::GradientOverlay {
+                shape,
+                start,
+                end,
+                angle,
+                mode,
+                opacity,
+            } => gradient_overlay(image, shape, start, end, angle, mode, opacity),
+            ImageOperation::Custom(operation) => custom_operation(image, operation, workers).await,
+            ImageOperation::DrawText {
+                text,
+                offset,
+                size,
+                color,
+                alignment,
+            } => draw_label(image, &text, offset, size, color, alignment),
+        }
+    }
+}
+
+/// Describes the operation with its parameters, images are only described by their size
+impl Display for ImageOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageOperation::Begin {
+                image,
+                resolution,
+                focus_point,
+                size,
+                mirror,
+            } => write!(
+                f,
+                "Begin: {}x{} source, {}x{} result, focus ({:.1}, {:.1}), zoom {:.2}{}",
+                image.width(),
+                image.height(),
+                resolution.width,
+                resolution.height,
+                focus_point.x,
+                focus_point.y,
+                size,
+                if *mirror { ", mirrored" } else { "" }
+            ),
+            ImageOperation::Mask { mask } => {
+                write!(f, "Mask: {}x{} mask", mask.width(), mask.height())
+            }
+            ImageOperation::MaskWithOffset {
+                mask,
+                center,
+                size,
+                mirror,
+            } => write!(
+                f,
+                "Mask with offset: {}x{} mask, center ({:.1}, {:.1}), size {:.2}{}",
+                mask.width(),
+                mask.height(),
+                center.x,
+                center.y,
+                size,
+                if *mirror { ", mirrored" } else { "" }
+            ),
+            ImageOperation::MaskColor {
+                color,
+                range,
+                soft_border,
+            } => write!(
+                f,
+                "Mask color: {}, range {:.3}, soft border {:.3}",
+                color_to_hex(*color),
+                range,
+                soft_border
+            ),
+            ImageOperation::Blend { overlay } => {
+                write!(f, "Blend: {}x{} overlay", overlay.width(), overlay.height())
+            }
+            ImageOperation::BackgroundColor(color) => {
+                write!(f, "Background color: {}", color_to_hex(*color))
+            }
+            ImageOperation::BackgroundImage(under) => write!(
+                f,
+                "Background image: {}x{} image",
+                under.width(),
+                under.height()
+            ),
+            ImageOperation::Levels {
+                black,
+                white,
+                channels,
+                output,
+            } => {
+                write!(
+                    f,
+                    "Levels: black {:.3}, white {:.3}, output {:.3} to {:.3}",
+                    black, white, output.0, output.1
+                )?;
+                for (name, (black, white)) in ["red", "green", "blue"].iter().zip(channels) {
+                    write!(f, ", {} {:.3} to {:.3}", name, black, white)?;
+                }
+                Ok(())
+            }
+            ImageOperation::BrightnessContrast {
+                brightness,
+                contrast,
+            } => write!(
+                f,
+                "Brightness and contrast: brightness {:.2}, contrast {:.2}",
+                brightness, contrast
+            ),
+            ImageOperation::HsvAdjust {
+                hue,
+                saturation,
+                value,
+            } => write!(
+                f,
+                "HSV adjust: hue {:.0}, saturation {:.2}, value {:.2}",
+                hue, saturation, value
+            ),
+            ImageOperation::Defringe { width } => write!(f, "Defringe: width {}", width),
+            ImageOperation::Noise {
+                intensity,
+                monochrome,
+                seed,
+            } => write!(
+                f,
+                "Noise: intensity {:.2}, monochrome {}, seed {}",
+                intensity, monochrome, seed
+            ),
+            ImageOperation::GroundShadow {
+                offset,
+                width,
+                height,
+                blur,
+                opacity,
+            } => write!(
+                f,
+                "Ground shadow: offset {:.2}, size {:.2}x{:.2}, blur {:.3}, opacity {:.2}",
+                offset, width, height, blur, opacity
+            ),
+            ImageOperation::DropShadow {
+                offset,
+                blur,
+                color,
+            } => write!(
+                f,
+                "Drop shadow: offset ({:.2}, {:.2}), blur {:.3}, color {}",
+                offset.x,
+                offset.y,
+                blur,
+                color_to_hex(*color)
+            ),
+            ImageOperation::Outline { thickness, color } => write!(
+                f,
+                "Outline: thickness {}, color {}",
+                thickness,
+                color_to_hex(*color)
+            ),
+            ImageOperation::Kaleidoscope { segments, rotation } => write!(
+                f,
+                "Kaleidoscope: {} segments, rotation {:.0}",
+                segments, rotation
+            ),
+            ImageOperation::Rotate90 { turns } => {
+                write!(f, "Rotate: {} quarter turns", turns)
+            }
+            ImageOperation::Rotate { angle } => write!(f, "Rotate: {:.1} degrees", angle),
+            ImageOperation::FlipHorizontal => write!(f, "Flip horizontal"),
+            ImageOperation::FlipVertical => write!(f, "Flip vertical"),
+            ImageOperation::GradientOverlay {
+                shape,
+                start,
+                end,
+                angle,
+                mode,
+                opacity,
+            } => write!(
+                f,
+                "Gradient overlay: {} {} to {}, angle {:.0}, {} blending, opacity {:.2}",
+                shape,
+                color_to_hex(*start),
+                color_to_hex(*end),
+                angle,
+                mode,
+                opacity
+            ),
+            ImageOperation::Custom(operation) => write!(f, "Custom: {}", operation),
+            ImageOperation::DrawText {
+                text,
+                offset,
+                size,
+                color,
+                alignment,
+            } => write!(
+                f,
+                "Draw text: {:?}, offset ({:.2}, {:.2}), size {:.2}, {}, {} aligned",
+                text,
+                offset.x,
+                offset.y,
+                size,
+                color_to_hex(*color),
+                alignment
+            ),
         }
     }
 }
@@ -115,27 +476,164 @@ impl Display for ImageFormat {
     }
 }
 
-pub fn image_filter(path: &PathBuf) -> bool {
+/// Extensions of all files the program is able to open as images
+pub const SUPPORTED_EXTENSIONS: [&str; 12] = [
+    "png", "webp", "jpg", "jpeg", "psd", "bmp", "gif", "tga", "tif", "tiff", "ico", "qoi",
+];
+
+/// Extensions recognized as images unless they are changed in settings
+pub const DEFAULT_EXTENSIONS: &str = "png, webp, jpg, jpeg, psd";
+
+/// Splits the list of extensions separated by commas or spaces
+///
+/// Returns extensions the program can open, followed by those it can't
+pub fn parse_extensions(list: &str) -> (Vec<String>, Vec<String>) {
+    let mut supported = Vec::new();
+    let mut unsupported = Vec::new();
+    for ext in list.split(|c: char| c == ',' || c.is_whitespace()) {
+        let ext = ext.trim_start_matches('.').to_lowercase();
+        if ext.is_empty() || supported.contains(&ext) || unsupported.contains(&ext) {
+            continue;
+        }
+        if SUPPORTED_EXTENSIONS.contains(&ext.as_str()) {
+            supported.push(ext);
+        } else {
+            unsupported.push(ext);
+        }
+    }
+    (supported, unsupported)
+}
+
+/// Tests whatever the file has one of the extensions, which are expected lowercase and without the leading dot
+pub fn image_filter(path: &Path, extensions: &[String]) -> bool {
     let Some(ext) = path.extension().and_then(|x| Some(x.to_string_lossy().to_lowercase())) else {
         return false;
     };
 
-    match ext.as_str() {
-        "png" | "webp" | "jpg" | "jpeg" => true,
-        _ => false,
+    extensions.contains(&ext)
+}
+
+/// Opens the image file, Photoshop documents are flattened
+pub fn open_image(path: &Path) -> image::ImageResult<RgbaImage> {
+    if psd::is_psd(path) {
+        return psd::open_psd(path).map(|x| x.composite).map_err(|e| {
+            image::ImageError::IoError(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        });
+    }
+    Ok(image::open(path)?.into_rgba8())
+}
+
+/// Opens the image with 16 bits per channel, returns none if the file doesn't hold more than 8 bits per channel
+pub fn open_image_16(path: &Path) -> Option<Rgba16Image> {
+    if psd::is_psd(path) {
+        return None;
+    }
+    let image = image::open(path).ok()?;
+    match image.color() {
+        image::ColorType::L16
+        | image::ColorType::La16
+        | image::ColorType::Rgb16
+        | image::ColorType::Rgba16
+        | image::ColorType::Rgb32F
+        | image::ColorType::Rgba32F => Some(image.into_rgba16()),
+        _ => None,
     }
 }
 
 pub async fn download_image(url: String) -> Result<RgbaImage, String> {
-    let Ok(res) = reqwest::get(url).await else {
-        return Err("Error: Clipboard doesn't contain a valid URL".to_string());
+    download_image_resumable(url, Arc::new(Download::default())).await
+}
+
+/// Progress of an image download, shared between the downloading task and the interface
+///
+/// Data received before the download failed is kept so retrying it can continue where it stopped
+#[derive(Debug, Default)]
+pub struct Download {
+    data: Mutex<Vec<u8>>,
+    received: AtomicU64,
+    /// Size of the whole file, zero when the server didn't tell it
+    total: AtomicU64,
+}
+
+impl Download {
+    /// Returns how many bytes were received and the size of the whole file if it is known
+    pub fn progress(&self) -> (u64, Option<u64>) {
+        let total = self.total.load(Ordering::Relaxed);
+        (
+            self.received.load(Ordering::Relaxed),
+            if total > 0 { Some(total) } else { None },
+        )
+    }
+}
+
+/// Downloads the image while reporting progress, data already held by the download is continued from if the server supports it
+pub async fn download_image_resumable(
+    url: String,
+    download: Arc<Download>,
+) -> Result<RgbaImage, String> {
+    let offset = download.data.lock().unwrap().len() as u64;
+    let mut request = reqwest::Client::new().get(url);
+    if offset > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+    }
+    let mut res = match request.send().await {
+        Ok(res) => res,
+        Err(e) if e.is_builder() => {
+            return Err("Error: Clipboard doesn't contain a valid URL".to_string())
+        }
+        Err(_) => return Err("Error: Couldn't connect to the server".to_string()),
     };
-    let Ok(btes) = res.bytes().await else {
-        return Err("Error: Couldn't download image".to_string());
+    if !res.status().is_success() {
+        return Err(format!("Error: Server responded with {}", res.status()));
+    }
+
+    // servers without support for partial downloads send the whole file again
+    let resumed = res.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    let start = if resumed {
+        offset
+    } else {
+        download.data.lock().unwrap().clear();
+        0
     };
-    let Ok(img) = image::load_from_memory(&btes) else {
+    let total = res.content_length().map_or(0, |x| x + start);
+    download.total.store(total, Ordering::Relaxed);
+    download.received.store(start, Ordering::Relaxed);
+
+    loop {
+        match res.chunk().await {
+            Ok(Some(chunk)) => {
+                let mut data = download.data.lock().unwrap();
+                data.extend_from_slice(&chunk);
+                download
+                    .received
+                    .store(data.len() as u64, Ordering::Relaxed);
+            }
+            Ok(None) => break,
+            Err(_) => return Err("Error: Download was interrupted".to_string()),
+        }
+    }
+
+    let data = std::mem::take(&mut *download.data.lock().unwrap());
+    let Ok(img) = image::load_from_memory(&data) else {
         return Err("Error: URL doesn't point to a valid image".to_string());
     };
     let img = img.into_rgba8();
     Ok(img)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_filter_uses_given_extensions() {
+        let (extensions, unsupported) = parse_extensions(".PNG, jpg  webp,exe");
+        assert_eq!(extensions, ["png", "jpg", "webp"]);
+        assert_eq!(unsupported, ["exe"]);
+        assert!(image_filter(Path::new("/tmp/orc.png"), &extensions));
+        assert!(image_filter(Path::new("/tmp/orc.JPG"), &extensions));
+        assert!(!image_filter(Path::new("/tmp/orc.psd"), &extensions));
+        assert!(!image_filter(Path::new("/tmp/png"), &extensions));
+        assert!(!image_filter(Path::new("/tmp/orc.png"), &[]));
+    }
+}
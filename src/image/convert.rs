@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use iced::Color;
-use iced_native::image::Handle;
+use iced_native::image::{Data, Handle};
 use image::Rgba;
 
 use super::{GrayscaleImage, RgbaImage};
@@ -32,12 +32,65 @@ pub fn grayscale_to_handle(mask: &GrayscaleImage) -> Handle {
     image_to_handle(i)
 }
 
+/// Converts iced image back into an image buffer, returns none if the handle doesn't hold raw pixels
+pub fn handle_to_image(handle: &Handle) -> Option<RgbaImage> {
+    let Data::Rgba {
+        width,
+        height,
+        pixels,
+    } = handle.data()
+    else {
+        return None;
+    };
+    RgbaImage::from_raw(*width, *height, pixels.to_vec())
+}
+
+/// Extracts transparency of the iced image as a grayscale image, returns none if the handle doesn't hold raw pixels
+pub fn handle_to_alpha(handle: &Handle) -> Option<GrayscaleImage> {
+    let Data::Rgba {
+        width,
+        height,
+        pixels,
+    } = handle.data()
+    else {
+        return None;
+    };
+    GrayscaleImage::from_raw(*width, *height, pixels.chunks(4).map(|x| x[3]).collect())
+}
+
+/// Formats the color as a hex string in #rrggbbaa notation
+pub fn color_to_hex(color: Color) -> String {
+    let [r, g, b, a] = color.into_rgba8();
+    format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+}
+
+/// Parses a hex color in #rrggbb or #rrggbbaa notation, the leading # is optional
+pub fn hex_to_color(hex: &str) -> Option<Color> {
+    let hex = hex.trim().trim_start_matches('#');
+    if !hex.is_ascii() || (hex.len() != 6 && hex.len() != 8) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { u8::MAX };
+    Some(Color::from_rgba8(
+        channel(0)?,
+        channel(2)?,
+        channel(4)?,
+        alpha as f32 / u8::MAX as f32,
+    ))
+}
+
 /// Turns hsv color into iced rgb color. Valid value ranges are 0.0..=1.0
 pub fn hsv_to_color(hue: f32, saturation: f32, value: f32) -> Color {
+    let [r, g, b] = hsv_to_rgb(hue, saturation, value);
+    Color::from_rgb(r, g, b)
+}
+
+/// Turns hsv color into red, green and blue channels, works the same as `hsv_to_color` without going through iced color
+pub fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> [f32; 3] {
     // if there's no saturation then we have pure grayscale, which means, only value matters
     if saturation <= 0.0 {
-        let v = value;
-        return Color::from_rgb(v, v, v);
+        return [value; 3];
     }
 
     let hue = hue * 360.0;
@@ -60,13 +113,19 @@ pub fn hsv_to_color(hue: f32, saturation: f32, value: f32) -> Color {
         _ => unreachable!(),
     };
 
-    Color::from_rgb(r, g, b)
+    [r, g, b]
 }
 
 /// Turns the color into a hue, saturation and value components in that order
 pub fn color_to_hsv(color: Color) -> (f32, f32, f32) {
-    let min = color.r.min(color.b.min(color.g));
-    let max = color.r.max(color.b.max(color.g));
+    rgb_to_hsv([color.r, color.g, color.b])
+}
+
+/// Turns red, green and blue channels into hue, saturation and value, works the same as `color_to_hsv` without going through iced color
+pub fn rgb_to_hsv(color: [f32; 3]) -> (f32, f32, f32) {
+    let [r, g, b] = color;
+    let min = r.min(b.min(g));
+    let max = r.max(b.max(g));
     let delta = max - min;
 
     // min and max are similar, which means it's grayscale
@@ -76,18 +135,38 @@ pub fn color_to_hsv(color: Color) -> (f32, f32, f32) {
 
     let value = max;
     let saturation = delta / max;
-    let hue = if color.r == max {
-        (color.g - color.b) / delta
-    } else if color.g == max {
-        2.0 + (color.b - color.r).abs() / delta
+    let hue = if r == max {
+        (g - b) / delta
+    } else if g == max {
+        2.0 + (b - r) / delta
     } else {
-        4.0 + (color.r - color.g).abs() / delta
+        4.0 + (r - g) / delta
     } * 60.0
         / 360.0;
+    // reds leaning towards magenta come out negative
+    let hue = hue.rem_euclid(1.0);
 
     (hue, saturation, value)
 }
 
+/// Calculates relative luminance of the color as defined by WCAG, transparency is ignored
+pub fn relative_luminance(color: Color) -> f32 {
+    let linear = |c: f32| {
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(color.r) + 0.7152 * linear(color.g) + 0.0722 * linear(color.b)
+}
+
+/// Calculates WCAG contrast ratio between two colors, ranging from 1 for the same colors to 21 for black and white
+pub fn contrast_ratio(a: Color, b: Color) -> f32 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
 pub fn pixel_to_color(pixel: &Rgba<u8>) -> Color {
     Color {
         r: pixel[0] as f32 / 255.0,
@@ -96,3 +175,39 @@ pub fn pixel_to_color(pixel: &Rgba<u8>) -> Color {
         a: pixel[3] as f32 / 255.0,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hsv_round_trip() {
+        // each primary leaning towards both of its neighbors, the old conversion mirrored some of them
+        let colors = [
+            ([1.0, 0.3, 0.0], 0.3 / 6.0),
+            ([1.0, 0.0, 0.3], 5.7 / 6.0),
+            ([0.3, 1.0, 0.0], 1.7 / 6.0),
+            ([0.0, 1.0, 0.3], 2.3 / 6.0),
+            ([0.0, 0.3, 1.0], 3.7 / 6.0),
+            ([0.3, 0.0, 1.0], 4.3 / 6.0),
+        ];
+        for (color, expected_hue) in colors {
+            let (hue, saturation, value) = rgb_to_hsv(color);
+            assert!((hue - expected_hue).abs() < 1e-5, "{color:?} has hue {hue}");
+            let back = hsv_to_rgb(hue, saturation, value);
+            for (a, b) in color.iter().zip(back) {
+                assert!((a - b).abs() < 1e-5, "{color:?} came back as {back:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn hsv_round_trip_color() {
+        let color = Color::from_rgb(0.2, 0.4, 0.9);
+        let (hue, saturation, value) = color_to_hsv(color);
+        let back = hsv_to_color(hue, saturation, value);
+        assert!((color.r - back.r).abs() < 1e-5);
+        assert!((color.g - back.g).abs() < 1e-5);
+        assert!((color.b - back.b).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,69 @@
+use super::{icc::crc32, ImageFormat};
+
+/// Millimeters in an inch
+pub const MM_PER_INCH: f32 = 25.4;
+
+/// Writes resolution of the image into already encoded image data so it prints at the intended physical size
+///
+/// Only PNG and JPEG formats support this, other formats return an error
+pub fn embed_dpi(data: &[u8], format: ImageFormat, dpi: f32) -> Result<Vec<u8>, String> {
+    if dpi <= 0.0 {
+        return Err("Resolution has to be positive".to_string());
+    }
+    match format {
+        ImageFormat::Png => embed_png(data, dpi),
+        ImageFormat::Jpeg => embed_jpeg(data, dpi),
+        ImageFormat::WebP => Err("WebP files don't support resolution metadata".to_string()),
+    }
+}
+
+/// Inserts pHYs chunk right after the header chunk
+fn embed_png(data: &[u8], dpi: f32) -> Result<Vec<u8>, String> {
+    // 8 bytes of signature followed by header chunk with 13 bytes of data, 12 bytes of length, type and crc
+    const HEADER_END: usize = 8 + 12 + 13;
+    if data.len() < HEADER_END || &data[12..16] != b"IHDR" {
+        return Err("Invalid PNG data".to_string());
+    }
+
+    // PNG stores the resolution in pixels per meter
+    let density = (dpi * 1000.0 / MM_PER_INCH).round() as u32;
+    let mut chunk = b"pHYs".to_vec();
+    chunk.extend_from_slice(&density.to_be_bytes());
+    chunk.extend_from_slice(&density.to_be_bytes());
+    // unit is meter
+    chunk.push(1);
+
+    let mut result = Vec::with_capacity(data.len() + chunk.len() + 8);
+    result.extend_from_slice(&data[..HEADER_END]);
+    result.extend_from_slice(&((chunk.len() - 4) as u32).to_be_bytes());
+    result.extend_from_slice(&chunk);
+    result.extend_from_slice(&crc32(&chunk).to_be_bytes());
+    result.extend_from_slice(&data[HEADER_END..]);
+    Ok(result)
+}
+
+/// Sets the density in JFIF segment, the segment is added if the data doesn't have it
+fn embed_jpeg(data: &[u8], dpi: f32) -> Result<Vec<u8>, String> {
+    if data.len() < 4 || data[0..2] != [0xFF, 0xD8] {
+        return Err("Invalid JPEG data".to_string());
+    }
+    let density = (dpi.round() as u32).min(u16::MAX as u32) as u16;
+
+    let mut result = data.to_vec();
+    if data.len() >= 18 && data[2..4] == [0xFF, 0xE0] && &data[6..11] == b"JFIF\0" {
+        // version is followed by units, horizontal and vertical density
+        result[13] = 1;
+        result[14..16].copy_from_slice(&density.to_be_bytes());
+        result[16..18].copy_from_slice(&density.to_be_bytes());
+    } else {
+        let mut segment = vec![0xFF, 0xE0, 0x00, 0x10];
+        segment.extend_from_slice(b"JFIF\0");
+        segment.extend_from_slice(&[1, 1, 1]);
+        segment.extend_from_slice(&density.to_be_bytes());
+        segment.extend_from_slice(&density.to_be_bytes());
+        // no thumbnail
+        segment.extend_from_slice(&[0, 0]);
+        result.splice(2..2, segment);
+    }
+    Ok(result)
+}
@@ -0,0 +1,204 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+use image::{
+    codecs::{jpeg::JpegDecoder, png::PngDecoder, webp::WebPDecoder},
+    ImageDecoder,
+};
+
+use super::ImageFormat;
+
+/// Reads embedded ICC color profile from the image file, returns none if the file doesn't have one or it can't be read
+pub fn read_profile(path: &Path) -> Option<Vec<u8>> {
+    let format = image::ImageFormat::from_path(path).ok()?;
+    let file = BufReader::new(File::open(path).ok()?);
+    match format {
+        image::ImageFormat::Png => PngDecoder::new(file).ok()?.icc_profile(),
+        image::ImageFormat::Jpeg => JpegDecoder::new(file).ok()?.icc_profile(),
+        image::ImageFormat::WebP => WebPDecoder::new(file).ok()?.icc_profile(),
+        _ => None,
+    }
+}
+
+/// Embeds the ICC color profile into already encoded image data
+///
+/// Only PNG and JPEG formats support this, other formats return an error
+pub fn embed_profile(data: &[u8], format: ImageFormat, profile: &[u8]) -> Result<Vec<u8>, String> {
+    if profile.is_empty() {
+        return Err("Color profile is empty".to_string());
+    }
+    match format {
+        ImageFormat::Png => embed_png(data, profile),
+        ImageFormat::Jpeg => embed_jpeg(data, profile),
+        ImageFormat::WebP => Err("WebP files don't support color profiles".to_string()),
+    }
+}
+
+/// Inserts iCCP chunk right after the header chunk
+fn embed_png(data: &[u8], profile: &[u8]) -> Result<Vec<u8>, String> {
+    // 8 bytes of signature followed by header chunk with 13 bytes of data, 12 bytes of length, type and crc
+    const HEADER_END: usize = 8 + 12 + 13;
+    if data.len() < HEADER_END || &data[12..16] != b"IHDR" {
+        return Err("Invalid PNG data".to_string());
+    }
+
+    let mut chunk = b"iCCP".to_vec();
+    chunk.extend_from_slice(b"ICC Profile\0");
+    // compression method, zlib is the only one allowed
+    chunk.push(0);
+    chunk.extend(zlib_store(profile));
+
+    let mut result = Vec::with_capacity(data.len() + chunk.len() + 8);
+    result.extend_from_slice(&data[..HEADER_END]);
+    result.extend_from_slice(&((chunk.len() - 4) as u32).to_be_bytes());
+    result.extend_from_slice(&chunk);
+    result.extend_from_slice(&crc32(&chunk).to_be_bytes());
+    result.extend_from_slice(&data[HEADER_END..]);
+    Ok(result)
+}
+
+/// Inserts APP2 segments holding the profile after the start of image marker and JFIF segment
+fn embed_jpeg(data: &[u8], profile: &[u8]) -> Result<Vec<u8>, String> {
+    // segment length field only allows 65535 bytes including itself and the identifiers
+    const CHUNK_SIZE: usize = 65535 - 2 - 14;
+    if data.len() < 4 || data[0..2] != [0xFF, 0xD8] {
+        return Err("Invalid JPEG data".to_string());
+    }
+    let count = profile.len().div_ceil(CHUNK_SIZE);
+    if count > 255 {
+        return Err("Color profile is too large".to_string());
+    }
+
+    let mut insert_at = 2;
+    if data[2..4] == [0xFF, 0xE0] && data.len() >= 6 {
+        insert_at += 2 + u16::from_be_bytes([data[4], data[5]]) as usize;
+    }
+    if insert_at > data.len() {
+        return Err("Invalid JPEG data".to_string());
+    }
+
+    let mut result = Vec::with_capacity(data.len() + profile.len() + count * 18);
+    result.extend_from_slice(&data[..insert_at]);
+    for (i, chunk) in profile.chunks(CHUNK_SIZE).enumerate() {
+        result.extend_from_slice(&[0xFF, 0xE2]);
+        result.extend_from_slice(&((chunk.len() + 2 + 14) as u16).to_be_bytes());
+        result.extend_from_slice(b"ICC_PROFILE\0");
+        result.push(i as u8 + 1);
+        result.push(count as u8);
+        result.extend_from_slice(chunk);
+    }
+    result.extend_from_slice(&data[insert_at..]);
+    Ok(result)
+}
+
+/// Wraps the data in a zlib stream without compressing it
+fn zlib_store(data: &[u8]) -> Vec<u8> {
+    let mut result = vec![0x78, 0x01];
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        result.push(last as u8);
+        let len = block.len() as u16;
+        result.extend_from_slice(&len.to_le_bytes());
+        result.extend_from_slice(&(!len).to_le_bytes());
+        result.extend_from_slice(block);
+    }
+
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), x| {
+        let a = (a + *x as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    result.extend_from_slice(&((b << 16) | a).to_be_bytes());
+    result
+}
+
+/// Checksum used by PNG chunks
+pub(super) fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(u32::MAX, |crc, x| {
+        (0..8).fold(crc ^ *x as u32, |c, _| {
+            if c & 1 == 1 {
+                0xEDB88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// Encodes a small gray image in the format
+    fn encoded(format: image::ImageFormat) -> Vec<u8> {
+        let mut data = Cursor::new(Vec::new());
+        image::write_buffer_with_format(
+            &mut data,
+            &[128; 4 * 4 * 3],
+            4,
+            4,
+            image::ColorType::Rgb8,
+            format,
+        )
+        .unwrap();
+        data.into_inner()
+    }
+
+    /// Profile stand in, decoders don't look inside of it
+    fn profile(size: usize) -> Vec<u8> {
+        (0..size).map(|x| (x % 251) as u8).collect()
+    }
+
+    #[test]
+    fn png_round_trip() {
+        for size in [1, 3000, u16::MAX as usize + 10] {
+            let profile = profile(size);
+            let data = embed_profile(
+                &encoded(image::ImageFormat::Png),
+                ImageFormat::Png,
+                &profile,
+            )
+            .unwrap();
+            let mut decoder = PngDecoder::new(Cursor::new(&data)).unwrap();
+            assert_eq!(decoder.icc_profile(), Some(profile), "size {size}");
+            // the rest of the file has to stay intact
+            image::load_from_memory(&data).unwrap();
+        }
+    }
+
+    #[test]
+    fn jpeg_round_trip() {
+        // the largest profile is split over two APP2 segments
+        for size in [1, 3000, u16::MAX as usize + 10] {
+            let profile = profile(size);
+            let data = embed_profile(
+                &encoded(image::ImageFormat::Jpeg),
+                ImageFormat::Jpeg,
+                &profile,
+            )
+            .unwrap();
+            let mut decoder = JpegDecoder::new(Cursor::new(&data)).unwrap();
+            assert_eq!(decoder.icc_profile(), Some(profile), "size {size}");
+            image::load_from_memory(&data).unwrap();
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        let png = encoded(image::ImageFormat::Png);
+        assert!(embed_profile(&png, ImageFormat::Png, &[]).is_err());
+        assert!(embed_profile(&png, ImageFormat::WebP, &profile(10)).is_err());
+        assert!(embed_profile(&png[..20], ImageFormat::Png, &profile(10)).is_err());
+        assert!(embed_profile(&png, ImageFormat::Jpeg, &profile(10)).is_err());
+        let jpeg = encoded(image::ImageFormat::Jpeg);
+        assert!(embed_profile(&jpeg, ImageFormat::Png, &profile(10)).is_err());
+        assert!(embed_profile(&jpeg, ImageFormat::Jpeg, &profile(256 * 65519)).is_err());
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+        assert_eq!(crc32(b""), 0);
+    }
+}
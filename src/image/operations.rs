@@ -1,9 +1,25 @@
-use std::sync::Arc;
+use std::{fmt::Display, sync::Arc};
 
-use iced::{Color, Point, Size, Vector};
-use image::{GenericImageView, ImageBuffer, Pixel, Primitive, Rgba};
+use iced::{Color, Point, Rectangle, Size, Vector};
+use image::{imageops, GenericImageView, ImageBuffer, Pixel, Primitive, Rgba};
+use serde::{Deserialize, Serialize};
 
-use super::{convert::pixel_to_color, GrayscaleImage, RgbaImage};
+use super::{
+    convert::{hsv_to_rgb, pixel_to_color, rgb_to_hsv},
+    text::{draw_text, text_width, TextAlignment},
+    GrayscaleImage, PixelOperation, RgbaImage,
+};
+
+/// Returns how many rows of the image a single resampling task should process
+///
+/// Zero workers means the image is split based on available parallelism of the processor
+fn worker_rows(height: u32, workers: usize) -> u32 {
+    let workers = match workers {
+        0 => std::thread::available_parallelism().map_or(4, |x| x.get()),
+        count => count,
+    };
+    height.div_ceil(workers as u32).max(1)
+}
 
 /// Resizes the image, clipping out the image parts or adding transparent pixels to the borders
 ///
@@ -12,6 +28,8 @@ use super::{convert::pixel_to_color, GrayscaleImage, RgbaImage};
 /// `resolution`   - desired size of the image
 /// `center_point` - 2D position which should be considered as the center of the image
 /// `size`         - any value other than 1.0 will scale up or down the source image in comparison to the output, together with `offset` this allows to zoom in on specific part of the image
+/// `mirror`       - flips the result horizontally
+/// `workers`      - number of tasks the work is split into, zero picks it based on the processor
 ///
 /// # Panics
 /// Panic will also happen if supplied image or requested resolution has width or height of 0 pixels.
@@ -20,9 +38,12 @@ pub async fn resample_image<T, P>(
     resolution: Size<u32>,
     center_point: Point,
     size: f32,
-) -> ImageBuffer<P, Vec<u8>>
+    mirror: bool,
+    workers: usize,
+) -> ImageBuffer<P, Vec<P::Subpixel>>
 where
-    P: Pixel<Subpixel = u8> + Send + 'static,
+    P: Pixel + Send + 'static,
+    P::Subpixel: Send,
     T: GenericImageView<Pixel = P> + Sync + Send + 'static,
 {
     let aspect = {
@@ -40,7 +61,7 @@ where
         height: image.height() as i32,
     };
 
-    let worker_size = 128;
+    let worker_size = worker_rows(resolution.height, workers);
     let workers = resolution.height / worker_size
         + if resolution.height % worker_size > 0 {
             1
@@ -52,15 +73,19 @@ where
     for i in 0..workers {
         let th = tokio::spawn({
             let image = image.clone();
-            let empty = image.get_pixel(0, 0).map(|_| 0);
+            let empty = image
+                .get_pixel(0, 0)
+                .map(|_| P::Subpixel::DEFAULT_MIN_VALUE);
             async move {
                 let start = worker_size * i;
                 let end = (start + worker_size).min(resolution.height);
-                let mut res: Vec<u8> =
+                let mut res: Vec<P::Subpixel> =
                     Vec::with_capacity(((end - start) * resolution.width) as usize);
                 for y in start..end {
                     for x in 0..resolution.width {
                         let tx = {
+                            // mirrored image samples columns from the opposite side
+                            let x = if mirror { resolution.width - 1 - x } else { x };
                             // calculate position in range -half.width..half.width
                             let center = x as i32 - half.width as i32;
                             // calculate position of the target pixel from the image
@@ -131,6 +156,8 @@ pub async fn mask_image_with_offset(
     mask: Arc<GrayscaleImage>,
     center: Point,
     size: f32,
+    mirror: bool,
+    workers: usize,
 ) -> RgbaImage {
     let mask = resample_image(
         mask,
@@ -140,6 +167,8 @@ pub async fn mask_image_with_offset(
         },
         center,
         size,
+        mirror,
+        workers,
     )
     .await;
     mask_image(image, &mask)
@@ -155,13 +184,13 @@ pub fn blend_images(mut image: RgbaImage, overlay: &RgbaImage) -> RgbaImage {
     image
 }
 
-/// Adds color as a background to the image
+/// Adds color as a background to the image, translucent colors leave the background partially transparent
 pub fn underlay_color(mut image: RgbaImage, color: Color) -> RgbaImage {
     let color = [
         (color.r * 255.0) as u8,
         (color.g * 255.0) as u8,
         (color.b * 255.0) as u8,
-        u8::MAX,
+        (color.a * 255.0) as u8,
     ];
     let color: Rgba<u8> = color.into();
     image.pixels_mut().filter(|x| x[3] < 255).for_each(|x| {
@@ -221,6 +250,714 @@ pub fn mask_color(mut image: RgbaImage, color: Color, range: f32, blending: f32)
     image
 }
 
+/// Remaps color channels so the black point becomes black and the white point becomes white, then per channel and into the output range, alpha is left untouched
+pub fn levels(
+    mut image: RgbaImage,
+    black: f32,
+    white: f32,
+    channels: [(f32, f32); 3],
+    output: (f32, f32),
+) -> RgbaImage {
+    // values are mapped into 0 to 1 between the points
+    let stretch = |x: f32, black: f32, white: f32| {
+        let black = black.clamp(0.0, 1.0);
+        let range = (white.clamp(0.0, 1.0) - black).max(1.0 / 255.0);
+        ((x - black) / range).clamp(0.0, 1.0)
+    };
+    let (low, high) = (output.0.clamp(0.0, 1.0), output.1.clamp(0.0, 1.0));
+    let tables: Vec<Vec<u8>> = channels
+        .iter()
+        .map(|&(channel_black, channel_white)| {
+            (0..=255)
+                .map(|x| {
+                    let x = stretch(x as f32 / 255.0, black, white);
+                    let x = stretch(x, channel_black, channel_white);
+                    ((low + x * (high - low)) * 255.0).round() as u8
+                })
+                .collect()
+        })
+        .collect();
+
+    image.pixels_mut().for_each(|p| {
+        p[0] = tables[0][p[0] as usize];
+        p[1] = tables[1][p[1] as usize];
+        p[2] = tables[2][p[2] as usize];
+    });
+
+    image
+}
+
+/// Recolors semi-transparent edge pixels with colors of their opaque neighbors, removing halos left by the old background
+///
+/// Each pass recolors pixels touching the ones fixed in the previous pass, neighbors are weighted by their alpha.
+/// Fully transparent pixels are left untouched.
+pub fn defringe(mut image: RgbaImage, width: u32) -> RgbaImage {
+    let (w, h) = image.dimensions();
+    // pixels with colors that can be trusted, grows with each pass as edge pixels get recolored
+    let mut solid: Vec<bool> = image.pixels().map(|p| p[3] == u8::MAX).collect();
+
+    for _ in 0..width {
+        let mut fixed = Vec::new();
+        for y in 0..h {
+            for x in 0..w {
+                let index = (y * w + x) as usize;
+                if solid[index] || image.get_pixel(x, y)[3] == 0 {
+                    continue;
+                }
+
+                let mut sum = [0.0; 3];
+                let mut weight = 0.0;
+                for ny in y.saturating_sub(1)..=(y + 1).min(h - 1) {
+                    for nx in x.saturating_sub(1)..=(x + 1).min(w - 1) {
+                        if !solid[(ny * w + nx) as usize] {
+                            continue;
+                        }
+                        let neighbor = image.get_pixel(nx, ny);
+                        let alpha = neighbor[3] as f32;
+                        for (s, c) in sum.iter_mut().zip(neighbor.0) {
+                            *s += c as f32 * alpha;
+                        }
+                        weight += alpha;
+                    }
+                }
+
+                if weight > 0.0 {
+                    fixed.push((x, y, sum.map(|x| (x / weight).round() as u8)));
+                }
+            }
+        }
+
+        if fixed.is_empty() {
+            break;
+        }
+        for (x, y, color) in fixed {
+            let pixel = image.get_pixel_mut(x, y);
+            pixel.0[..3].copy_from_slice(&color);
+            solid[(y * w + x) as usize] = true;
+        }
+    }
+
+    image
+}
+
+/// Adds pseudo-random noise to color channels of visible pixels, alpha is left untouched
+///
+/// Noise is derived from the seed and position of the pixel, so the same seed always produces the same grain
+pub fn add_noise(mut image: RgbaImage, intensity: f32, monochrome: bool, seed: u64) -> RgbaImage {
+    let amplitude = intensity.clamp(0.0, 1.0) * u8::MAX as f32;
+    let width = image.width() as u64;
+    image
+        .enumerate_pixels_mut()
+        .filter(|(_, _, p)| p[3] > 0)
+        .for_each(|(x, y, p)| {
+            let index = (y as u64 * width + x as u64) * 3;
+            for channel in 0..3 {
+                // monochrome noise changes all channels by the same amount
+                let sample = if monochrome {
+                    index
+                } else {
+                    index + channel as u64
+                };
+                let value = p[channel] as f32 + noise_value(seed, sample) * amplitude;
+                p[channel] = value.round().clamp(0.0, u8::MAX as f32) as u8;
+            }
+        });
+    image
+}
+
+/// Adds a soft dark ellipse under the image, making the subject look like it stands on the ground
+///
+/// # Parameters
+/// `offset` - vertical distance of the shadow center from the center of the image, as a fraction of image height
+/// `width` - width of the shadow as a fraction of image width
+/// `height` - height of the shadow as a fraction of image height
+/// `blur` - blur radius as a fraction of the smaller side of the image
+/// `opacity` - opacity of the shadow at its center
+pub fn ground_shadow(
+    image: RgbaImage,
+    offset: f32,
+    width: f32,
+    height: f32,
+    blur: f32,
+    opacity: f32,
+) -> RgbaImage {
+    let center_x = image.width() as f32 * 0.5;
+    let center_y = image.height() as f32 * (0.5 + offset);
+    let radius_x = (image.width() as f32 * width * 0.5).max(1.0);
+    let radius_y = (image.height() as f32 * height * 0.5).max(1.0);
+    let alpha = (opacity.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8;
+
+    let shape = GrayscaleImage::from_fn(image.width(), image.height(), |x, y| {
+        let dx = (x as f32 + 0.5 - center_x) / radius_x;
+        let dy = (y as f32 + 0.5 - center_y) / radius_y;
+        if dx * dx + dy * dy <= 1.0 {
+            [alpha].into()
+        } else {
+            [0].into()
+        }
+    });
+    let sigma = blur * image.width().min(image.height()) as f32;
+    let shape = if sigma > 0.0 {
+        imageops::blur(&shape, sigma)
+    } else {
+        shape
+    };
+
+    let shadow = RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+        [0, 0, 0, shape.get_pixel(x, y)[0]].into()
+    });
+    underlay_image(image, Arc::new(shadow))
+}
+
+/// Adds a blurred copy of the image silhouette under the image, making it look like it floats above the background
+///
+/// The shadow is clipped to the image, parts of it moved past the edges are lost.
+///
+/// # Parameters
+/// `offset` - distance the shadow is moved by from the image, as a fraction of the image size
+/// `blur` - blur radius as a fraction of the smaller side of the image
+/// `color` - color of the shadow, its alpha sets opacity of the shadow
+pub fn drop_shadow(image: RgbaImage, offset: Point, blur: f32, color: Color) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let shift_x = (offset.x * width as f32).round() as i64;
+    let shift_y = (offset.y * height as f32).round() as i64;
+
+    let shape = GrayscaleImage::from_fn(width, height, |x, y| {
+        let source_x = x as i64 - shift_x;
+        let source_y = y as i64 - shift_y;
+        if source_x < 0 || source_y < 0 || source_x >= width as i64 || source_y >= height as i64 {
+            [0].into()
+        } else {
+            [image.get_pixel(source_x as u32, source_y as u32)[3]].into()
+        }
+    });
+    let sigma = blur * width.min(height) as f32;
+    let shape = if sigma > 0.0 {
+        imageops::blur(&shape, sigma)
+    } else {
+        shape
+    };
+
+    let [r, g, b, _] = color.into_rgba8();
+    let opacity = color.a.clamp(0.0, 1.0);
+    let shadow = RgbaImage::from_fn(width, height, |x, y| {
+        let alpha = (shape.get_pixel(x, y)[0] as f32 * opacity).round() as u8;
+        [r, g, b, alpha].into()
+    });
+    underlay_image(image, Arc::new(shadow))
+}
+
+/// Lowest alpha of a pixel for it to count as a part of the silhouette being outlined
+const OUTLINE_THRESHOLD: u8 = 128;
+
+/// Draws a solid outline around the silhouette of the image
+///
+/// Pixels that aren't fully opaque and lie within the thickness from the silhouette get the outline color under them,
+/// so transparent pixels are filled with the color at full alpha while semi-transparent edges blend into it.
+/// Pixels with at least half opacity make up the silhouette.
+pub fn outline_silhouette(mut image: RgbaImage, thickness: u32, color: Color) -> RgbaImage {
+    if thickness == 0 {
+        return image;
+    }
+    let (width, height) = image.dimensions();
+
+    // horizontal distance from each pixel to the closest silhouette pixel in the same row,
+    // which turns the distance check into looking through a single column of rows for each pixel
+    let mut distance = vec![u32::MAX; width as usize * height as usize];
+    for y in 0..height {
+        let row = &mut distance[(y * width) as usize..((y + 1) * width) as usize];
+        let mut last = None;
+        for x in 0..width {
+            if image.get_pixel(x, y)[3] >= OUTLINE_THRESHOLD {
+                last = Some(x);
+            }
+            if let Some(last) = last {
+                row[x as usize] = x - last;
+            }
+        }
+        let mut last = None;
+        for x in (0..width).rev() {
+            if image.get_pixel(x, y)[3] >= OUTLINE_THRESHOLD {
+                last = Some(x);
+            }
+            if let Some(last) = last {
+                row[x as usize] = row[x as usize].min(last - x);
+            }
+        }
+    }
+
+    let [r, g, b, _] = color.into_rgba8();
+    let color: Rgba<u8> = [r, g, b, u8::MAX].into();
+    let reach = thickness as i64;
+    let limit = reach * reach;
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = *image.get_pixel(x, y);
+            if pixel[3] == u8::MAX {
+                continue;
+            }
+            let near = (-reach..=reach).any(|dy| {
+                let row = y as i64 + dy;
+                if row < 0 || row >= height as i64 {
+                    return false;
+                }
+                let dx = distance[(row as u32 * width + x) as usize];
+                dx != u32::MAX && (dx as i64).pow(2) + dy * dy <= limit
+            });
+            if near {
+                let mut outline = color;
+                outline.blend(&pixel);
+                image.put_pixel(x, y, outline);
+            }
+        }
+    }
+    image
+}
+
+/// Mirrors a wedge of the image around its center into the given number of symmetric segments
+///
+/// # Parameters
+/// `segments` - how many times the wedge repeats around the center, each repetition is mirrored in its middle
+/// `rotation` - angle in degrees the wedge is taken from, it turns the whole pattern
+pub fn kaleidoscope(image: RgbaImage, segments: u32, rotation: f32) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let center_x = width as f32 * 0.5;
+    let center_y = height as f32 * 0.5;
+    let wedge = std::f32::consts::TAU / segments.max(1) as f32;
+    let rotation = rotation.to_radians();
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let dx = x as f32 + 0.5 - center_x;
+        let dy = y as f32 + 0.5 - center_y;
+        let radius = (dx * dx + dy * dy).sqrt();
+        // folding the angle into the first half of the wedge, the other half mirrors it
+        let angle = (dy.atan2(dx) - rotation).rem_euclid(wedge);
+        let angle = angle.min(wedge - angle) + rotation;
+        // sample coordinates are relative to pixel centers
+        let sx = center_x + radius * angle.cos() - 0.5;
+        let sy = center_y + radius * angle.sin() - 0.5;
+        imageops::interpolate_bilinear(&image, sx, sy).unwrap_or([0, 0, 0, 0].into())
+    })
+}
+
+/// Shape the colors of a gradient are spread in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GradientShape {
+    /// Colors change along a straight line across the image
+    #[default]
+    Linear,
+    /// Colors change from the center of the image towards its corners
+    Radial,
+}
+
+impl GradientShape {
+    pub const ALL: [GradientShape; 2] = [GradientShape::Linear, GradientShape::Radial];
+}
+
+impl Display for GradientShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                GradientShape::Linear => "Linear",
+                GradientShape::Radial => "Radial",
+            }
+        )
+    }
+}
+
+/// Math used to combine colors of an overlay with colors of the image
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlendMode {
+    /// Overlay colors replace the image colors
+    Normal,
+    /// Darkens the image, white leaves it unchanged
+    Multiply,
+    /// Brightens the image, black leaves it unchanged
+    Screen,
+    /// Increases contrast, darkening dark parts and brightening bright parts
+    Overlay,
+    /// Gentle version of overlay, middle gray leaves the image unchanged
+    #[default]
+    SoftLight,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 5] = [
+        BlendMode::Normal,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Overlay,
+        BlendMode::SoftLight,
+    ];
+
+    /// Combines a single channel of the image with the same channel of the overlay, both in 0 to 1 range
+    fn apply(&self, base: f32, blend: f32) -> f32 {
+        match self {
+            BlendMode::Normal => blend,
+            BlendMode::Multiply => base * blend,
+            BlendMode::Screen => 1.0 - (1.0 - base) * (1.0 - blend),
+            BlendMode::Overlay => {
+                if base < 0.5 {
+                    2.0 * base * blend
+                } else {
+                    1.0 - 2.0 * (1.0 - base) * (1.0 - blend)
+                }
+            }
+            BlendMode::SoftLight => (1.0 - 2.0 * blend) * base * base + 2.0 * blend * base,
+        }
+    }
+}
+
+impl Display for BlendMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                BlendMode::Normal => "Normal",
+                BlendMode::Multiply => "Multiply",
+                BlendMode::Screen => "Screen",
+                BlendMode::Overlay => "Overlay",
+                BlendMode::SoftLight => "Soft Light",
+            }
+        )
+    }
+}
+
+/// Blends a gradient going from the start color to the end color over the image
+///
+/// Transparency of the image is left unchanged, the gradient only changes colors of visible pixels.
+///
+/// # Parameters
+/// `angle`   - direction of a linear gradient in degrees, at 0 the start color is at the top and the end color at the bottom
+/// `opacity` - strength of the effect, alpha of the gradient colors scales it further
+pub fn gradient_overlay(
+    mut image: RgbaImage,
+    shape: GradientShape,
+    start: Color,
+    end: Color,
+    angle: f32,
+    mode: BlendMode,
+    opacity: f32,
+) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let (sin, cos) = angle.to_radians().sin_cos();
+    // the longest projection of the image on the gradient direction, so the gradient always reaches the corners
+    let reach = (sin.abs() + cos.abs()).max(f32::EPSILON);
+
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        if pixel[3] == 0 {
+            continue;
+        }
+        // position relative to the center of the image, from -0.5 to 0.5 in both directions
+        let px = (x as f32 + 0.5) / width as f32 - 0.5;
+        let py = (y as f32 + 0.5) / height as f32 - 0.5;
+        let t = match shape {
+            GradientShape::Linear => 0.5 + (px * sin + py * cos) / reach,
+            GradientShape::Radial => (px * px + py * py).sqrt() / std::f32::consts::FRAC_1_SQRT_2,
+        }
+        .clamp(0.0, 1.0);
+
+        let color = [
+            start.r + (end.r - start.r) * t,
+            start.g + (end.g - start.g) * t,
+            start.b + (end.b - start.b) * t,
+        ];
+        let strength = (start.a + (end.a - start.a) * t) * opacity;
+        for (channel, blend) in pixel.0.iter_mut().zip(color) {
+            let base = *channel as f32 / u8::MAX as f32;
+            let mixed = base + (mode.apply(base, blend) - base) * strength;
+            *channel = (mixed.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8;
+        }
+    }
+    image
+}
+
+/// Draws a single line of text over the image
+///
+/// Offset places the anchor of the text relative to the center of the image and size sets height of the line,
+/// both are fractions of the image size. Alignment decides which side of the text lies at the anchor.
+pub fn draw_label(
+    mut image: RgbaImage,
+    text: &str,
+    offset: Point,
+    size: f32,
+    color: Color,
+    alignment: TextAlignment,
+) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let line = height as f32 * size;
+    let anchor = Point::new(
+        width as f32 * (0.5 + offset.x),
+        height as f32 * (0.5 + offset.y),
+    );
+    let half = text_width(text, line) * 0.5;
+    let center = match alignment {
+        TextAlignment::Left => Point::new(anchor.x + half, anchor.y),
+        TextAlignment::Center => anchor,
+        TextAlignment::Right => Point::new(anchor.x - half, anchor.y),
+    };
+    draw_text(&mut image, text, center, line, color);
+    image
+}
+
+/// Finds the smallest area of the image containing all of its visible pixels
+///
+/// The area is grown by the padding on all sides, though never past the edges of the image.
+/// Returns none if the image is fully transparent.
+pub fn visible_bounds(image: &RgbaImage, padding: u32) -> Option<Rectangle<u32>> {
+    let (mut left, mut top, mut right, mut bottom) = (u32::MAX, u32::MAX, 0, 0);
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if pixel[3] > 0 {
+            left = left.min(x);
+            top = top.min(y);
+            right = right.max(x);
+            bottom = bottom.max(y);
+        }
+    }
+    if left > right {
+        return None;
+    }
+    let left = left.saturating_sub(padding);
+    let top = top.saturating_sub(padding);
+    let right = right.saturating_add(padding).min(image.width() - 1);
+    let bottom = bottom.saturating_add(padding).min(image.height() - 1);
+    Some(Rectangle {
+        x: left,
+        y: top,
+        width: right - left + 1,
+        height: bottom - top + 1,
+    })
+}
+
+/// Transforms each pixel of the image with the function, rows are split between tasks the same way as in resampling
+///
+/// The function is provided with the pixel and its position in the image
+async fn map_pixels<F>(image: RgbaImage, workers: usize, transform: F) -> RgbaImage
+where
+    F: Fn(Rgba<u8>, u32, u32) -> Rgba<u8> + Send + Sync + 'static,
+{
+    let (width, height) = image.dimensions();
+    let image = Arc::new(image);
+    let transform = Arc::new(transform);
+    let worker_size = worker_rows(height, workers);
+
+    let mut threads = Vec::new();
+    for start in (0..height).step_by(worker_size as usize) {
+        let image = image.clone();
+        let transform = transform.clone();
+        threads.push(tokio::spawn(async move {
+            let end = (start + worker_size).min(height);
+            let mut res = Vec::with_capacity(((end - start) * width * 4) as usize);
+            for y in start..end {
+                for x in 0..width {
+                    res.extend(transform(*image.get_pixel(x, y), x, y).0);
+                }
+            }
+            res
+        }));
+    }
+    let mut pixels = Vec::with_capacity(image.as_raw().len());
+    for th in threads {
+        let mut r = th.await.unwrap();
+        pixels.append(&mut r);
+    }
+    RgbaImage::from_raw(width, height, pixels).unwrap()
+}
+
+/// Transforms each pixel of the image with the operation
+pub async fn custom_operation(
+    image: RgbaImage,
+    operation: Arc<dyn PixelOperation>,
+    workers: usize,
+) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let size = Size { width, height };
+    map_pixels(image, workers, move |pixel, x, y| {
+        let color = pixel.0.map(|c| c as f32 / 255.0);
+        let color = operation.transform(color, x, y, size);
+        // NaN turns into zero when converted
+        color
+            .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+            .into()
+    })
+    .await
+}
+
+/// Changes brightness and contrast of the color channels, alpha is left as it is
+///
+/// Both values are in -1 to 1 range, each channel is mapped as `(c - 0.5) * (1 + contrast) + 0.5 + brightness`
+pub async fn brightness_contrast(
+    image: RgbaImage,
+    brightness: f32,
+    contrast: f32,
+    workers: usize,
+) -> RgbaImage {
+    // channels only have 256 possible values so all of them are mapped up front
+    let table: [u8; 256] = std::array::from_fn(|c| {
+        let c = c as f32 / 255.0;
+        let c = (c - 0.5) * (1.0 + contrast) + 0.5 + brightness;
+        (c.clamp(0.0, 1.0) * 255.0).round() as u8
+    });
+    map_pixels(image, workers, move |pixel, _, _| {
+        let [r, g, b, a] = pixel.0;
+        [table[r as usize], table[g as usize], table[b as usize], a].into()
+    })
+    .await
+}
+
+/// Shifts hue, saturation and value of the color channels, alpha is left as it is
+///
+/// # Parameters
+/// `hue`        - shift of the hue in degrees, hues wrap around the color wheel
+/// `saturation` - value added to saturation of the colors, from -1 to 1
+/// `value`      - value added to brightness of the colors, from -1 to 1
+pub async fn hsv_adjust(
+    image: RgbaImage,
+    hue: f32,
+    saturation: f32,
+    value: f32,
+    workers: usize,
+) -> RgbaImage {
+    let shift = hue / 360.0;
+    map_pixels(image, workers, move |pixel, _, _| {
+        let [r, g, b, a] = pixel.0;
+        let (h, s, v) = rgb_to_hsv([r, g, b].map(|c| c as f32 / 255.0));
+        let [r, g, b] = hsv_to_rgb(
+            (h + shift).rem_euclid(1.0),
+            (s + saturation).clamp(0.0, 1.0),
+            (v + value).clamp(0.0, 1.0),
+        )
+        .map(|c| (c * 255.0).round() as u8);
+        [r, g, b, a].into()
+    })
+    .await
+}
+
+/// Rotates the image clockwise by the number of quarter turns
+///
+/// The image keeps its size, when the rotation swaps its width and height the result is centered,
+/// cutting off its overhanging parts and leaving the rest of the image transparent.
+pub fn rotate_quarters(image: RgbaImage, turns: u32) -> RgbaImage {
+    let rotated = match turns % 4 {
+        0 => return image,
+        1 => imageops::rotate90(&image),
+        2 => return imageops::rotate180(&image),
+        _ => imageops::rotate270(&image),
+    };
+    if rotated.dimensions() == image.dimensions() {
+        return rotated;
+    }
+    let mut result = RgbaImage::new(image.width(), image.height());
+    let x = (image.width() as i64 - rotated.width() as i64) / 2;
+    let y = (image.height() as i64 - rotated.height() as i64) / 2;
+    imageops::replace(&mut result, &rotated, x, y);
+    result
+}
+
+/// Rotates the image clockwise around its center by the angle in degrees
+///
+/// The image keeps its size, parts rotated past the edges are cut off and exposed corners are transparent.
+pub async fn rotate_free(image: RgbaImage, angle: f32, workers: usize) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let (sin, cos) = (-angle.to_radians()).sin_cos();
+    let center_x = width as f32 * 0.5;
+    let center_y = height as f32 * 0.5;
+    map_pixels(RgbaImage::new(width, height), workers, move |_, x, y| {
+        // each pixel is sampled from the source rotated back the other way
+        let dx = x as f32 + 0.5 - center_x;
+        let dy = y as f32 + 0.5 - center_y;
+        let sx = dx * cos - dy * sin + center_x - 0.5;
+        let sy = dx * sin + dy * cos + center_y - 0.5;
+        sample_bilinear(&image, sx, sy)
+    })
+    .await
+}
+
+/// Samples the image at the position, blending four closest pixels
+///
+/// Colors are weighted by their alpha so transparent pixels outside of the image don't darken the edges
+fn sample_bilinear(image: &RgbaImage, x: f32, y: f32) -> Rgba<u8> {
+    let (left, top) = (x.floor(), y.floor());
+    let (fx, fy) = (x - left, y - top);
+    let (left, top) = (left as i64, top as i64);
+
+    let mut color = [0.0; 3];
+    let mut alpha = 0.0;
+    for (ox, oy, weight) in [
+        (0, 0, (1.0 - fx) * (1.0 - fy)),
+        (1, 0, fx * (1.0 - fy)),
+        (0, 1, (1.0 - fx) * fy),
+        (1, 1, fx * fy),
+    ] {
+        let (px, py) = (left + ox, top + oy);
+        if px < 0 || py < 0 || px >= image.width() as i64 || py >= image.height() as i64 {
+            continue;
+        }
+        let pixel = image.get_pixel(px as u32, py as u32);
+        let weight = weight * pixel[3] as f32;
+        for (c, p) in color.iter_mut().zip(pixel.0) {
+            *c += p as f32 * weight;
+        }
+        alpha += weight;
+    }
+    if alpha <= 0.0 {
+        return Rgba([0, 0, 0, 0]);
+    }
+    let [r, g, b] = color.map(|c| (c / alpha).round() as u8);
+    Rgba([r, g, b, alpha.round().min(u8::MAX as f32) as u8])
+}
+
+/// Hashes the seed and index of the sample into a value between -1 and 1
+fn noise_value(seed: u64, sample: u64) -> f32 {
+    // splitmix64 finalizer, cheap and with good enough distribution for visual noise
+    let mut z = seed.wrapping_add(sample.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 24) as f32 * 2.0 - 1.0
+}
+
+/// Finds the channel values below which the given fractions of the image fall, using a histogram of all color channels
+///
+/// Transparent pixels are ignored, bigger images are sampled with a stride. Returned values are in 0 to 1 range.
+pub fn histogram_percentiles(image: &RgbaImage, low: f32, high: f32) -> Option<(f32, f32)> {
+    const MAX_SAMPLES: usize = 1 << 18;
+    let total = (image.width() * image.height()) as usize;
+    let stride = (total / MAX_SAMPLES).max(1);
+
+    let mut histogram = [0usize; 256];
+    image
+        .pixels()
+        .step_by(stride)
+        .filter(|p| p[3] >= 128)
+        .for_each(|p| {
+            histogram[p[0] as usize] += 1;
+            histogram[p[1] as usize] += 1;
+            histogram[p[2] as usize] += 1;
+        });
+
+    let count: usize = histogram.iter().sum();
+    if count == 0 {
+        return None;
+    }
+
+    let percentile = |fraction: f32| {
+        let target = (count as f32 * fraction.clamp(0.0, 1.0)) as usize;
+        let mut sum = 0;
+        for (value, amount) in histogram.iter().enumerate() {
+            sum += amount;
+            if sum > target {
+                return value as f32 / 255.0;
+            }
+        }
+        1.0
+    };
+    Some((percentile(low), percentile(high)))
+}
+
 /// Creates a grayscale image by flood filling it pixel by pixel
 ///
 /// # Parameters
@@ -0,0 +1,123 @@
+use std::{collections::HashMap, fmt::Write};
+
+/// Alpha value from which pixels are considered a part of the shape
+const ALPHA_THRESHOLD: u8 = 128;
+
+/// Traces outline of visible pixels in RGBA pixel data into closed polygons
+///
+/// The polygons follow pixel edges, outer boundaries go clockwise and holes counter clockwise
+pub fn trace_outline(width: u32, height: u32, pixels: &[u8]) -> Vec<Vec<(u32, u32)>> {
+    let inside = |x: i64, y: i64| -> bool {
+        if x < 0 || y < 0 || x >= width as i64 || y >= height as i64 {
+            return false;
+        }
+        let index = (y as usize * width as usize + x as usize) * 4 + 3;
+        pixels.get(index).is_some_and(|a| *a >= ALPHA_THRESHOLD)
+    };
+
+    // collecting edges between visible and invisible pixels, keyed by their starting vertex
+    let mut edges: HashMap<(u32, u32), Vec<(u32, u32)>> = HashMap::new();
+    let mut add = |from: (u32, u32), to: (u32, u32)| edges.entry(from).or_default().push(to);
+    for y in 0..height {
+        for x in 0..width {
+            let (ix, iy) = (x as i64, y as i64);
+            if !inside(ix, iy) {
+                continue;
+            }
+            if !inside(ix, iy - 1) {
+                add((x, y), (x + 1, y));
+            }
+            if !inside(ix + 1, iy) {
+                add((x + 1, y), (x + 1, y + 1));
+            }
+            if !inside(ix, iy + 1) {
+                add((x + 1, y + 1), (x, y + 1));
+            }
+            if !inside(ix - 1, iy) {
+                add((x, y + 1), (x, y));
+            }
+        }
+    }
+
+    // linking the edges into loops
+    let mut polygons = Vec::new();
+    let mut starts: Vec<(u32, u32)> = edges.keys().copied().collect();
+    starts.sort_unstable_by_key(|(x, y)| (*y, *x));
+    for start in starts {
+        while let Some(next) = edges.get_mut(&start).and_then(|x| x.pop()) {
+            let mut polygon = vec![start];
+            let mut current = next;
+            while current != start {
+                polygon.push(current);
+                let Some(next) = edges.get_mut(&current).and_then(|x| x.pop()) else {
+                    break;
+                };
+                current = next;
+            }
+            polygons.push(simplify(polygon));
+        }
+    }
+    polygons
+}
+
+/// Removes points laying on a straight line between their neighbors
+fn simplify(polygon: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+    let len = polygon.len();
+    if len < 3 {
+        return polygon;
+    }
+    (0..len)
+        .filter(|i| {
+            let prev = polygon[(i + len - 1) % len];
+            let point = polygon[*i];
+            let next = polygon[(i + 1) % len];
+            let straight = (prev.0 == point.0 && point.0 == next.0)
+                || (prev.1 == point.1 && point.1 == next.1);
+            !straight
+        })
+        .map(|i| polygon[i])
+        .collect()
+}
+
+/// Creates an SVG document containing the outline of visible pixels as a single path
+pub fn outline_svg(width: u32, height: u32, pixels: &[u8]) -> String {
+    let mut path = String::new();
+    for polygon in trace_outline(width, height, pixels)
+        .iter()
+        .filter(|x| x.len() > 2)
+    {
+        if !path.is_empty() {
+            path.push(' ');
+        }
+        let mut points = polygon.iter();
+        let first = points.next().unwrap();
+        // writing into a String can't fail
+        let _ = write!(path, "M{} {}", first.0, first.1);
+        for p in points {
+            let _ = write!(path, " L{} {}", p.0, p.1);
+        }
+        path.push_str(" Z");
+    }
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n\
+         <path d=\"{path}\" fill=\"black\" fill-rule=\"evenodd\"/>\n\
+         </svg>\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn svg_path() {
+        // two opaque pixels separated by a transparent one make two separate polygons
+        let pixels = [0, 0, 0, 255, 0, 0, 0, 0, 0, 0, 0, 255];
+        let svg = outline_svg(3, 1, &pixels);
+        assert!(
+            svg.contains("d=\"M0 0 L1 0 L1 1 L0 1 Z M2 0 L3 0 L3 1 L2 1 Z\""),
+            "{svg}"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use iced::Color;
+
+/// Maximum amount of pixels sampled from the image, bigger images are sampled with a stride
+const MAX_SAMPLES: usize = 1 << 16;
+
+/// Extracts the dominant colors from RGBA pixel data using median cut quantization
+///
+/// Transparent pixels are ignored. Resulting colors are sorted by how many pixels they represent.
+pub fn extract_palette(pixels: &[u8], count: usize) -> Vec<Color> {
+    let total = pixels.len() / 4;
+    let stride = (total / MAX_SAMPLES).max(1);
+    let samples: Vec<[u8; 3]> = pixels
+        .chunks_exact(4)
+        .step_by(stride)
+        .filter(|x| x[3] >= 128)
+        .map(|x| [x[0], x[1], x[2]])
+        .collect();
+
+    if samples.is_empty() || count == 0 {
+        return Vec::new();
+    }
+
+    let mut boxes = vec![samples];
+    while boxes.len() < count {
+        // splitting the box with the widest color range
+        let Some((index, channel, _)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| {
+                let (channel, range) = widest_channel(b);
+                (i, channel, range)
+            })
+            .filter(|(_, _, range)| *range > 0)
+            .max_by_key(|(_, _, range)| *range)
+        else {
+            break;
+        };
+
+        let mut split = boxes.swap_remove(index);
+        split.sort_unstable_by_key(|x| x[channel]);
+        let upper = split.split_off(split.len() / 2);
+        boxes.push(split);
+        boxes.push(upper);
+    }
+
+    boxes.sort_unstable_by_key(|b| std::cmp::Reverse(b.len()));
+    boxes
+        .iter()
+        .map(|b| {
+            let sum = b.iter().fold([0u64; 3], |mut s, x| {
+                s[0] += x[0] as u64;
+                s[1] += x[1] as u64;
+                s[2] += x[2] as u64;
+                s
+            });
+            let len = b.len() as u64;
+            Color::from_rgb8(
+                (sum[0] / len) as u8,
+                (sum[1] / len) as u8,
+                (sum[2] / len) as u8,
+            )
+        })
+        .collect()
+}
+
+/// Finds the color channel with the biggest difference between its lowest and highest value
+fn widest_channel(colors: &[[u8; 3]]) -> (usize, u8) {
+    (0..3)
+        .map(|c| {
+            let (min, max) = colors.iter().fold((u8::MAX, u8::MIN), |(min, max), x| {
+                (min.min(x[c]), max.max(x[c]))
+            });
+            (c, max - min)
+        })
+        .max_by_key(|(_, range)| *range)
+        .unwrap()
+}
@@ -0,0 +1,726 @@
+use std::path::Path;
+
+use super::RgbaImage;
+
+/// Flattened image and individual layers read from a Photoshop document
+pub struct Psd {
+    /// Merged image of all visible layers as saved by the program that created the file
+    pub composite: RgbaImage,
+    /// Pixel layers of the document, each holding only the pixels within its bounds
+    pub layers: Vec<PsdLayer>,
+}
+
+/// Single pixel layer of a Photoshop document
+pub struct PsdLayer {
+    pub name: String,
+    pub image: RgbaImage,
+    /// Position of the top left corner of the layer in the document, it can lie outside of the document
+    pub left: i32,
+    pub top: i32,
+}
+
+impl PsdLayer {
+    /// Places the layer at its position on a transparent canvas, parts outside of the canvas are cut off
+    pub fn place(&self, width: u32, height: u32) -> RgbaImage {
+        let mut canvas = RgbaImage::new(width, height);
+        image::imageops::replace(&mut canvas, &self.image, self.left as i64, self.top as i64);
+        canvas
+    }
+}
+
+/// Tests whatever the path points to a Photoshop document based on its extension
+pub fn is_psd(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|x| x.to_string_lossy().to_lowercase() == "psd")
+}
+
+/// Reads the Photoshop document from the file
+pub fn open_psd(path: &Path) -> Result<Psd, String> {
+    let data = std::fs::read(path).map_err(|e| e.to_string())?;
+    read_psd(&data)
+}
+
+/// Parses Photoshop document, only 8 bit grayscale and RGB documents are supported
+pub fn read_psd(data: &[u8]) -> Result<Psd, String> {
+    let mut reader = Reader::new(data);
+    if reader.bytes(4)? != b"8BPS" || reader.u16()? != 1 {
+        return Err("File is not a valid PSD document".to_string());
+    }
+    reader.skip(6)?;
+    let channels = reader.u16()? as usize;
+    let height = reader.u32()?;
+    let width = reader.u32()?;
+    let depth = reader.u16()?;
+    let colors = match reader.u16()? {
+        1 => 1,
+        3 => 3,
+        _ => return Err("Only grayscale and RGB PSD documents are supported".to_string()),
+    };
+    if depth != 8 {
+        return Err("Only 8 bit PSD documents are supported".to_string());
+    }
+    plane_size(width, height)?;
+
+    // color mode data and image resources aren't needed
+    let length = reader.u32()? as usize;
+    reader.skip(length)?;
+    let length = reader.u32()? as usize;
+    reader.skip(length)?;
+
+    let length = reader.u32()? as usize;
+    let layer_section = reader.bytes(length)?;
+    let (layers, merged_alpha) = if layer_section.len() >= 4 {
+        read_layers(layer_section, colors)?
+    } else {
+        (Vec::new(), false)
+    };
+
+    let composite = read_composite(&mut reader, width, height, channels, colors, merged_alpha)?;
+    Ok(Psd { composite, layers })
+}
+
+/// Reads layer records and their pixel data, returns the layers and whatever the merged image has transparency
+fn read_layers(section: &[u8], colors: usize) -> Result<(Vec<PsdLayer>, bool), String> {
+    let mut reader = Reader::new(section);
+    let length = reader.u32()? as usize;
+    if length == 0 {
+        return Ok((Vec::new(), false));
+    }
+    let mut reader = Reader::new(reader.bytes(length)?);
+    let count = reader.i16()?;
+    // negative count means first alpha channel of the merged image contains its transparency
+    let merged_alpha = count < 0;
+
+    struct Record {
+        name: String,
+        top: i32,
+        left: i32,
+        bottom: i32,
+        right: i32,
+        channels: Vec<(i16, usize)>,
+        opacity: u8,
+        visible: bool,
+        divider: bool,
+    }
+
+    let mut records = Vec::new();
+    for _ in 0..count.unsigned_abs() {
+        let top = reader.i32()?;
+        let left = reader.i32()?;
+        let bottom = reader.i32()?;
+        let right = reader.i32()?;
+        let channel_count = reader.u16()?;
+        let mut channels = Vec::new();
+        for _ in 0..channel_count {
+            channels.push((reader.i16()?, reader.u32()? as usize));
+        }
+        reader.skip(8)?;
+        let opacity = reader.u8()?;
+        reader.skip(1)?;
+        let flags = reader.u8()?;
+        reader.skip(1)?;
+
+        let length = reader.u32()? as usize;
+        let mut extra = Reader::new(reader.bytes(length)?);
+        let length = extra.u32()? as usize;
+        extra.skip(length)?;
+        let length = extra.u32()? as usize;
+        extra.skip(length)?;
+        // pascal string padded to a multiple of 4 bytes
+        let length = extra.u8()? as usize;
+        let mut name = String::from_utf8_lossy(extra.bytes(length)?).to_string();
+        extra.skip((4 - (length + 1) % 4) % 4)?;
+
+        let mut divider = false;
+        while extra.remaining() >= 12 {
+            extra.skip(4)?;
+            let key = extra.bytes(4)?;
+            let length = extra.u32()? as usize;
+            let mut info = Reader::new(extra.bytes(length.min(extra.remaining()))?);
+            match key {
+                b"luni" => {
+                    let characters = info.u32()? as usize;
+                    let units = (0..characters)
+                        .map(|_| info.u16())
+                        .collect::<Result<Vec<_>, _>>()?;
+                    name = String::from_utf16_lossy(&units)
+                        .trim_end_matches('\0')
+                        .to_string();
+                }
+                b"lsct" | b"lsdk" => {
+                    // group opening and closing markers don't carry pixels
+                    divider = info.u32()? != 0;
+                }
+                _ => {}
+            }
+        }
+
+        records.push(Record {
+            name,
+            top,
+            left,
+            bottom,
+            right,
+            channels,
+            opacity,
+            visible: flags & 0x02 == 0,
+            divider,
+        });
+    }
+
+    let mut layers = Vec::new();
+    for record in records {
+        let bad_bounds = || "PSD layer has invalid bounds".to_string();
+        let layer_width = record
+            .right
+            .checked_sub(record.left)
+            .and_then(|x| u32::try_from(x).ok())
+            .ok_or_else(bad_bounds)?;
+        let layer_height = record
+            .bottom
+            .checked_sub(record.top)
+            .and_then(|x| u32::try_from(x).ok())
+            .ok_or_else(bad_bounds)?;
+        plane_size(layer_width, layer_height)?;
+        let mut planes: [Option<Vec<u8>>; 4] = Default::default();
+        for (id, length) in &record.channels {
+            let data = reader.bytes(*length)?;
+            let plane = match *id {
+                0..=2 if (*id as usize) < colors => *id as usize,
+                -1 => 3,
+                // masks use different bounds and aren't part of the layer pixels
+                _ => continue,
+            };
+            let mut channel = Reader::new(data);
+            let compression = channel.u16()?;
+            planes[plane] = Some(decode_plane(
+                &mut channel,
+                compression,
+                layer_width,
+                layer_height,
+            )?);
+        }
+
+        if record.divider || layer_width == 0 || layer_height == 0 {
+            continue;
+        }
+
+        let mut image = RgbaImage::new(layer_width, layer_height);
+        for y in 0..layer_height {
+            for x in 0..layer_width {
+                let index = (y * layer_width + x) as usize;
+                let value = |plane: usize, default: u8| {
+                    planes[plane]
+                        .as_ref()
+                        .and_then(|x| x.get(index).copied())
+                        .unwrap_or(default)
+                };
+                let (r, g, b) = if colors == 1 {
+                    let v = value(0, 0);
+                    (v, v, v)
+                } else {
+                    (value(0, 0), value(1, 0), value(2, 0))
+                };
+                let a = (value(3, u8::MAX) as u32 * record.opacity as u32 / 255) as u8;
+                image.put_pixel(x, y, [r, g, b, a].into());
+            }
+        }
+
+        let name = if record.visible {
+            record.name
+        } else {
+            format!("{} (hidden)", record.name)
+        };
+        layers.push(PsdLayer {
+            name,
+            image,
+            left: record.left,
+            top: record.top,
+        });
+    }
+
+    Ok((layers, merged_alpha))
+}
+
+/// Reads the merged image stored at the end of the document
+fn read_composite(
+    reader: &mut Reader,
+    width: u32,
+    height: u32,
+    channels: usize,
+    colors: usize,
+    merged_alpha: bool,
+) -> Result<RgbaImage, String> {
+    let compression = reader.u16()?;
+    let used = if merged_alpha && channels > colors {
+        colors + 1
+    } else {
+        colors
+    };
+    if channels < used {
+        return Err("PSD document has fewer channels than its color mode needs".to_string());
+    }
+    // compressed data starts with row lengths of all channels before any pixel data
+    let planes = if compression == 1 {
+        let counts = (0..plane_size(channels as u32, height)?)
+            .map(|_| reader.u16().map(|x| x as usize))
+            .collect::<Result<Vec<_>, _>>()?;
+        counts
+            .chunks(height.max(1) as usize)
+            .take(used)
+            .map(|rows| {
+                let mut plane = Vec::with_capacity(plane_size(width, height)?);
+                for length in rows {
+                    unpack_bits(reader.bytes(*length)?, width as usize, &mut plane)?;
+                }
+                Ok(plane)
+            })
+            .collect::<Result<Vec<_>, String>>()?
+    } else {
+        (0..used)
+            .map(|_| decode_plane(reader, compression, width, height))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+
+    let mut image = RgbaImage::new(width, height);
+    for (i, pixel) in image.pixels_mut().enumerate() {
+        let a = planes.get(colors).map_or(255, |x| x[i]);
+        let color = |v: u8| unmatte(v, a);
+        *pixel = if colors == 1 {
+            let v = color(planes[0][i]);
+            [v, v, v, a].into()
+        } else {
+            let (r, g, b) = (planes[0][i], planes[1][i], planes[2][i]);
+            [color(r), color(g), color(b), a].into()
+        };
+    }
+    Ok(image)
+}
+
+/// Merged image colors are blended with white where the image is transparent, this reverses the blending
+fn unmatte(value: u8, alpha: u8) -> u8 {
+    if alpha == 0 || alpha == 255 {
+        return value;
+    }
+    let a = alpha as f32 / 255.0;
+    let v = (value as f32 - 255.0 * (1.0 - a)) / a;
+    v.clamp(0.0, 255.0) as u8
+}
+
+/// Writes the images as layers of a Photoshop document, the first image is the bottom layer
+///
+/// Layers are placed at their position on a canvas reaching from the top left corner of the document to the furthest layer edge,
+/// the merged image of the document is made by blending all layers over each other
+pub fn write_psd(layers: &[PsdLayer]) -> Result<Vec<u8>, String> {
+    if layers.is_empty() {
+        return Err("There are no layers to write".to_string());
+    }
+    let width = layers
+        .iter()
+        .map(|x| x.left as i64 + x.image.width() as i64)
+        .max()
+        .unwrap_or(0);
+    let height = layers
+        .iter()
+        .map(|x| x.top as i64 + x.image.height() as i64)
+        .max()
+        .unwrap_or(0);
+    if width <= 0 || height <= 0 || width > 30000 || height > 30000 {
+        return Err(
+            "PSD documents have to be between 1 and 30000 pixels wide and tall".to_string(),
+        );
+    }
+    let (width, height) = (width as u32, height as u32);
+
+    let mut data = Vec::new();
+    data.extend_from_slice(b"8BPS");
+    data.extend_from_slice(&1u16.to_be_bytes());
+    data.extend_from_slice(&[0; 6]);
+    // RGB with transparency
+    data.extend_from_slice(&4u16.to_be_bytes());
+    data.extend_from_slice(&height.to_be_bytes());
+    data.extend_from_slice(&width.to_be_bytes());
+    data.extend_from_slice(&8u16.to_be_bytes());
+    data.extend_from_slice(&3u16.to_be_bytes());
+    // no color mode data nor image resources
+    data.extend_from_slice(&0u32.to_be_bytes());
+    data.extend_from_slice(&0u32.to_be_bytes());
+
+    let mut records = Vec::new();
+    let mut pixels = Vec::new();
+    for layer in layers {
+        let (w, h) = layer.image.dimensions();
+        // the canvas fits all layers, so the far edges can't overflow
+        records.extend_from_slice(&layer.top.to_be_bytes());
+        records.extend_from_slice(&layer.left.to_be_bytes());
+        records.extend_from_slice(&(layer.top + h as i32).to_be_bytes());
+        records.extend_from_slice(&(layer.left + w as i32).to_be_bytes());
+        records.extend_from_slice(&4u16.to_be_bytes());
+        for id in [-1i16, 0, 1, 2] {
+            records.extend_from_slice(&id.to_be_bytes());
+            records.extend_from_slice(&(2 + w * h).to_be_bytes());
+        }
+        records.extend_from_slice(b"8BIMnorm");
+        // full opacity, no clipping, visible layer, filler
+        records.extend_from_slice(&[255, 0, 0, 0]);
+
+        let mut extra = Vec::new();
+        // no layer mask nor blending ranges
+        extra.extend_from_slice(&0u32.to_be_bytes());
+        extra.extend_from_slice(&0u32.to_be_bytes());
+        let name: Vec<u8> = layer
+            .name
+            .chars()
+            .map(|x| if x.is_ascii() { x as u8 } else { b'_' })
+            .take(255)
+            .collect();
+        extra.push(name.len() as u8);
+        extra.extend_from_slice(&name);
+        extra.resize(extra.len() + (4 - (name.len() + 1) % 4) % 4, 0);
+        // full name for names that don't fit into ASCII
+        let units: Vec<u16> = layer.name.encode_utf16().collect();
+        let mut unicode = (units.len() as u32).to_be_bytes().to_vec();
+        units
+            .iter()
+            .for_each(|x| unicode.extend_from_slice(&x.to_be_bytes()));
+        unicode.resize(unicode.len() + (4 - unicode.len() % 4) % 4, 0);
+        extra.extend_from_slice(b"8BIMluni");
+        extra.extend_from_slice(&(unicode.len() as u32).to_be_bytes());
+        extra.extend_from_slice(&unicode);
+
+        records.extend_from_slice(&(extra.len() as u32).to_be_bytes());
+        records.extend_from_slice(&extra);
+
+        for channel in [3, 0, 1, 2] {
+            // uncompressed
+            pixels.extend_from_slice(&0u16.to_be_bytes());
+            pixels.extend(layer.image.pixels().map(|x| x[channel]));
+        }
+    }
+
+    let mut info = (layers.len() as i16).wrapping_neg().to_be_bytes().to_vec();
+    info.extend_from_slice(&records);
+    info.extend_from_slice(&pixels);
+    if !info.len().is_multiple_of(2) {
+        info.push(0);
+    }
+    let section = 4 + info.len() + 4;
+    data.extend_from_slice(&(section as u32).to_be_bytes());
+    data.extend_from_slice(&(info.len() as u32).to_be_bytes());
+    data.extend_from_slice(&info);
+    // no global layer mask
+    data.extend_from_slice(&0u32.to_be_bytes());
+
+    let mut composite = RgbaImage::new(width, height);
+    for layer in layers {
+        image::imageops::overlay(
+            &mut composite,
+            &layer.image,
+            layer.left as i64,
+            layer.top as i64,
+        );
+    }
+    data.extend_from_slice(&0u16.to_be_bytes());
+    for channel in 0..4 {
+        data.extend(composite.pixels().map(|x| {
+            if channel == 3 {
+                return x[3];
+            }
+            // merged colors are blended with white where the image is transparent
+            let a = x[3] as f32 / 255.0;
+            (x[channel] as f32 * a + 255.0 * (1.0 - a)).round() as u8
+        }));
+    }
+
+    Ok(data)
+}
+
+/// Decodes a single channel of pixel data with row lengths stored before the data when compressed
+fn decode_plane(
+    reader: &mut Reader,
+    compression: u16,
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, String> {
+    let size = plane_size(width, height)?;
+    match compression {
+        0 => Ok(reader.bytes(size)?.to_vec()),
+        1 => {
+            let counts = (0..height)
+                .map(|_| reader.u16().map(|x| x as usize))
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut plane = Vec::with_capacity(size);
+            for length in counts {
+                unpack_bits(reader.bytes(length)?, width as usize, &mut plane)?;
+            }
+            Ok(plane)
+        }
+        _ => Err("Compressed PSD layers with ZIP are not supported".to_string()),
+    }
+}
+
+/// Number of values in a channel of the given size, fails when it doesn't fit in 32 bits
+fn plane_size(width: u32, height: u32) -> Result<usize, String> {
+    width
+        .checked_mul(height)
+        .map(|x| x as usize)
+        .ok_or_else(|| "PSD document is too large".to_string())
+}
+
+/// Decodes a single row compressed with PackBits algorithm
+fn unpack_bits(data: &[u8], width: usize, out: &mut Vec<u8>) -> Result<(), String> {
+    let end = out.len() + width;
+    let mut reader = Reader::new(data);
+    while reader.remaining() > 0 && out.len() < end {
+        let header = reader.u8()? as i8;
+        match header {
+            -128 => {}
+            0.. => out.extend_from_slice(reader.bytes(header as usize + 1)?),
+            _ => {
+                let value = reader.u8()?;
+                out.extend(std::iter::repeat_n(
+                    value,
+                    1 + header.unsigned_abs() as usize,
+                ));
+            }
+        }
+    }
+    out.resize(end, 0);
+    Ok(())
+}
+
+/// Big endian reader with bound checks
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], String> {
+        if count > self.remaining() {
+            return Err("PSD document is truncated".to_string());
+        }
+        let bytes = &self.data[self.position..self.position + count];
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, count: usize) -> Result<(), String> {
+        self.bytes(count).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn i16(&mut self) -> Result<i16, String> {
+        self.u16().map(|x| x as i16)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        self.u32().map(|x| x as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Layer with an opaque red, green, blue and gray pixel
+    fn layer() -> PsdLayer {
+        PsdLayer {
+            name: "Token".to_string(),
+            image: RgbaImage::from_raw(
+                2,
+                2,
+                vec![
+                    255, 0, 0, 255, //
+                    0, 255, 0, 255, //
+                    0, 0, 255, 255, //
+                    128, 128, 128, 255,
+                ],
+            )
+            .unwrap(),
+            left: 0,
+            top: 0,
+        }
+    }
+
+    /// Overwrites the big endian value at the offset of the document header
+    fn patch(data: &mut [u8], offset: usize, value: &[u8]) {
+        data[offset..offset + value.len()].copy_from_slice(value);
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = write_psd(&[layer()]).unwrap();
+        let psd = read_psd(&data).unwrap();
+        assert_eq!(psd.composite, layer().image);
+        assert_eq!(psd.layers.len(), 1);
+        assert_eq!(psd.layers[0].name, "Token");
+        assert_eq!(psd.layers[0].image, layer().image);
+    }
+
+    #[test]
+    fn round_trip_transparency() {
+        let mut layer = layer();
+        layer.image.put_pixel(1, 1, [64, 128, 192, 128].into());
+        let psd = read_psd(&write_psd(&[layer]).unwrap()).unwrap();
+        assert_eq!(psd.layers[0].image.get_pixel(1, 1).0, [64, 128, 192, 128]);
+        // merged image is blended with white so unblending it can be off by rounding
+        let pixel = psd.composite.get_pixel(1, 1).0;
+        let close = pixel
+            .iter()
+            .zip([64, 128, 192, 128])
+            .all(|(a, b)| a.abs_diff(b) <= 2);
+        assert!(close, "got {:?}", pixel);
+    }
+
+    #[test]
+    fn two_layers_through_file() {
+        let top = PsdLayer {
+            name: "Żółw ring".to_string(),
+            image: RgbaImage::from_raw(1, 1, vec![10, 20, 30, 200]).unwrap(),
+            left: 0,
+            top: 0,
+        };
+        let path = std::env::temp_dir().join("token-maker-two-layers.psd");
+        std::fs::write(&path, write_psd(&[layer(), top]).unwrap()).unwrap();
+        let psd = open_psd(&path);
+        std::fs::remove_file(&path).unwrap();
+        let psd = psd.unwrap();
+
+        assert_eq!(psd.layers.len(), 2);
+        assert_eq!(psd.layers[0].name, "Token");
+        assert_eq!(psd.layers[0].image, layer().image);
+        assert_eq!(psd.layers[1].name, "Żółw ring");
+        assert_eq!(psd.layers[1].image.dimensions(), (1, 1));
+        let mut expected = RgbaImage::new(2, 2);
+        expected.put_pixel(0, 0, [10, 20, 30, 200].into());
+        assert_eq!(psd.layers[1].place(2, 2), expected);
+    }
+
+    #[test]
+    fn layer_offset() {
+        let top = PsdLayer {
+            name: "Badge".to_string(),
+            image: RgbaImage::from_raw(1, 1, vec![10, 20, 30, 255]).unwrap(),
+            left: 2,
+            top: 1,
+        };
+        let psd = read_psd(&write_psd(&[layer(), top]).unwrap()).unwrap();
+        assert_eq!(psd.composite.dimensions(), (3, 2));
+        assert_eq!((psd.layers[1].left, psd.layers[1].top), (2, 1));
+        assert_eq!(psd.layers[1].image.dimensions(), (1, 1));
+        let placed = psd.layers[1].place(3, 2);
+        assert_eq!(placed.get_pixel(2, 1).0, [10, 20, 30, 255]);
+        assert_eq!(placed.get_pixel(0, 0).0, [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn invalid_layer_bounds() {
+        // layer record bounds follow the header, empty sections, section lengths and the layer count
+        let data = write_psd(&[layer()]).unwrap();
+        let (top, left, right) = (44, 48, 56);
+
+        let mut inverted = data.clone();
+        patch(&mut inverted, right, &(-5i32).to_be_bytes());
+        assert_eq!(
+            read_psd(&inverted).err().as_deref(),
+            Some("PSD layer has invalid bounds")
+        );
+
+        let mut overflow = data.clone();
+        patch(&mut overflow, left, &i32::MIN.to_be_bytes());
+        patch(&mut overflow, right, &i32::MAX.to_be_bytes());
+        assert_eq!(
+            read_psd(&overflow).err().as_deref(),
+            Some("PSD layer has invalid bounds")
+        );
+
+        let mut overflow = data;
+        patch(&mut overflow, top, &i32::MAX.to_be_bytes());
+        assert_eq!(
+            read_psd(&overflow).err().as_deref(),
+            Some("PSD layer has invalid bounds")
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        let data = write_psd(&[layer()]).unwrap();
+        for length in [0, 3, 20, 40, data.len() - 1] {
+            assert_eq!(
+                read_psd(&data[..length]).err().as_deref(),
+                Some("PSD document is truncated"),
+                "length {length}"
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_header() {
+        let data = write_psd(&[layer()]).unwrap();
+
+        let mut signature = data.clone();
+        patch(&mut signature, 0, b"8BPX");
+        assert_eq!(
+            read_psd(&signature).err().as_deref(),
+            Some("File is not a valid PSD document")
+        );
+
+        let mut version = data.clone();
+        patch(&mut version, 4, &2u16.to_be_bytes());
+        assert_eq!(
+            read_psd(&version).err().as_deref(),
+            Some("File is not a valid PSD document")
+        );
+
+        let mut channels = data.clone();
+        patch(&mut channels, 12, &1u16.to_be_bytes());
+        assert_eq!(
+            read_psd(&channels).err().as_deref(),
+            Some("PSD document has fewer channels than its color mode needs")
+        );
+
+        let mut size = data.clone();
+        patch(&mut size, 14, &u32::MAX.to_be_bytes());
+        patch(&mut size, 18, &u32::MAX.to_be_bytes());
+        assert_eq!(
+            read_psd(&size).err().as_deref(),
+            Some("PSD document is too large")
+        );
+
+        let mut depth = data.clone();
+        patch(&mut depth, 22, &16u16.to_be_bytes());
+        assert_eq!(
+            read_psd(&depth).err().as_deref(),
+            Some("Only 8 bit PSD documents are supported")
+        );
+
+        let mut mode = data;
+        patch(&mut mode, 24, &4u16.to_be_bytes());
+        assert_eq!(
+            read_psd(&mode).err().as_deref(),
+            Some("Only grayscale and RGB PSD documents are supported")
+        );
+    }
+}
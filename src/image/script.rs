@@ -0,0 +1,661 @@
+use std::fmt::Display;
+
+use iced::Size;
+
+use super::PixelOperation;
+
+/// Values an expression can refer to while it is evaluated for a single pixel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Variable {
+    /// Red channel of the pixel, from 0 to 1
+    Red,
+    /// Green channel of the pixel, from 0 to 1
+    Green,
+    /// Blue channel of the pixel, from 0 to 1
+    Blue,
+    /// Alpha channel of the pixel, from 0 to 1
+    Alpha,
+    /// Horizontal position of the pixel center, 0 at the left edge and 1 at the right edge
+    X,
+    /// Vertical position of the pixel center, 0 at the top edge and 1 at the bottom edge
+    Y,
+    /// Width of the image in pixels
+    Width,
+    /// Height of the image in pixels
+    Height,
+    /// Ratio of a circle's circumference to its diameter
+    Pi,
+}
+
+impl Variable {
+    fn from_name(name: &str) -> Option<Self> {
+        let var = match name {
+            "r" => Variable::Red,
+            "g" => Variable::Green,
+            "b" => Variable::Blue,
+            "a" => Variable::Alpha,
+            "x" => Variable::X,
+            "y" => Variable::Y,
+            "w" => Variable::Width,
+            "h" => Variable::Height,
+            "pi" => Variable::Pi,
+            _ => return None,
+        };
+        Some(var)
+    }
+}
+
+/// Functions available in expressions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    Abs,
+    Min,
+    Max,
+    Clamp,
+    Mix,
+    Step,
+    Smoothstep,
+    Sqrt,
+    Pow,
+    Sin,
+    Cos,
+    Floor,
+    Fract,
+    /// Picks the second argument if the first is positive, the third one otherwise
+    If,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        let fun = match name {
+            "abs" => Function::Abs,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            "clamp" => Function::Clamp,
+            "mix" => Function::Mix,
+            "step" => Function::Step,
+            "smoothstep" => Function::Smoothstep,
+            "sqrt" => Function::Sqrt,
+            "pow" => Function::Pow,
+            "sin" => Function::Sin,
+            "cos" => Function::Cos,
+            "floor" => Function::Floor,
+            "fract" => Function::Fract,
+            "if" => Function::If,
+            _ => return None,
+        };
+        Some(fun)
+    }
+
+    /// Number of arguments the function has to be called with
+    fn arguments(&self) -> usize {
+        match self {
+            Function::Abs
+            | Function::Sqrt
+            | Function::Sin
+            | Function::Cos
+            | Function::Floor
+            | Function::Fract => 1,
+            Function::Min | Function::Max | Function::Step | Function::Pow => 2,
+            Function::Clamp | Function::Mix | Function::Smoothstep | Function::If => 3,
+        }
+    }
+
+    fn call(&self, args: &[f32; 3]) -> f32 {
+        match self {
+            Function::Abs => args[0].abs(),
+            Function::Min => args[0].min(args[1]),
+            Function::Max => args[0].max(args[1]),
+            Function::Clamp => args[0].max(args[1]).min(args[2]),
+            Function::Mix => args[0] + (args[1] - args[0]) * args[2],
+            Function::Step => {
+                if args[1] < args[0] {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+            Function::Smoothstep => {
+                let t = ((args[2] - args[0]) / (args[1] - args[0])).clamp(0.0, 1.0);
+                t * t * (3.0 - 2.0 * t)
+            }
+            Function::Sqrt => args[0].sqrt(),
+            Function::Pow => args[0].powf(args[1]),
+            Function::Sin => args[0].sin(),
+            Function::Cos => args[0].cos(),
+            Function::Floor => args[0].floor(),
+            Function::Fract => args[0].fract(),
+            Function::If => {
+                if args[0] > 0.0 {
+                    args[1]
+                } else {
+                    args[2]
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Power,
+    Less,
+    Greater,
+    LessOrEqual,
+    GreaterOrEqual,
+}
+
+impl Operator {
+    fn apply(&self, left: f32, right: f32) -> f32 {
+        let test = |x: bool| if x { 1.0 } else { 0.0 };
+        match self {
+            Operator::Add => left + right,
+            Operator::Subtract => left - right,
+            Operator::Multiply => left * right,
+            Operator::Divide => left / right,
+            Operator::Remainder => left % right,
+            Operator::Power => left.powf(right),
+            Operator::Less => test(left < right),
+            Operator::Greater => test(left > right),
+            Operator::LessOrEqual => test(left <= right),
+            Operator::GreaterOrEqual => test(left >= right),
+        }
+    }
+}
+
+/// Parsed mathematical expression ready to be evaluated for each pixel
+#[derive(Debug, Clone)]
+enum Expression {
+    Number(f32),
+    Variable(Variable),
+    Negate(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+    Call(Function, Vec<Expression>),
+}
+
+impl Expression {
+    /// Parses the expression, returning description of the problem if the text isn't a valid expression
+    fn parse(source: &str) -> Result<Self, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            index: 0,
+            depth: 0,
+        };
+        let expression = parser.comparison()?;
+        match parser.peek() {
+            None => Ok(expression),
+            Some(token) => Err(format!("Unexpected {token}")),
+        }
+    }
+
+    /// Calculates value of the expression with values of variables ordered the same as in `Variable`
+    fn eval(&self, vars: &[f32; 9]) -> f32 {
+        match self {
+            Expression::Number(x) => *x,
+            Expression::Variable(v) => vars[*v as usize],
+            Expression::Negate(x) => -x.eval(vars),
+            Expression::Binary(op, left, right) => op.apply(left.eval(vars), right.eval(vars)),
+            Expression::Call(fun, args) => {
+                // arguments past the function's arity are never read
+                let mut values = [0.0; 3];
+                for (value, arg) in values.iter_mut().zip(args) {
+                    *value = arg.eval(vars);
+                }
+                fun.call(&values)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f32),
+    Name(String),
+    Operator(Operator),
+    Open,
+    Close,
+    Comma,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(x) => write!(f, "number {x}"),
+            Token::Name(x) => write!(f, "'{x}'"),
+            Token::Operator(op) => write!(
+                f,
+                "'{}'",
+                match op {
+                    Operator::Add => "+",
+                    Operator::Subtract => "-",
+                    Operator::Multiply => "*",
+                    Operator::Divide => "/",
+                    Operator::Remainder => "%",
+                    Operator::Power => "^",
+                    Operator::Less => "<",
+                    Operator::Greater => ">",
+                    Operator::LessOrEqual => "<=",
+                    Operator::GreaterOrEqual => ">=",
+                }
+            ),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::Comma => write!(f, "','"),
+        }
+    }
+}
+
+/// Splits the text into tokens of the expression
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '0'..='9' | '.' => {
+                let mut number = String::from(c);
+                while let Some(c) = chars.next_if(|x| x.is_ascii_digit() || *x == '.') {
+                    number.push(c);
+                }
+                let Ok(number) = number.parse() else {
+                    return Err(format!("Invalid number {number}"));
+                };
+                Token::Number(number)
+            }
+            c if c.is_alphabetic() => {
+                let mut name = String::from(c);
+                while let Some(c) = chars.next_if(|x| x.is_alphanumeric() || *x == '_') {
+                    name.push(c);
+                }
+                Token::Name(name.to_lowercase())
+            }
+            '+' => Token::Operator(Operator::Add),
+            '-' => Token::Operator(Operator::Subtract),
+            '*' => Token::Operator(Operator::Multiply),
+            '/' => Token::Operator(Operator::Divide),
+            '%' => Token::Operator(Operator::Remainder),
+            '^' => Token::Operator(Operator::Power),
+            '<' if chars.next_if_eq(&'=').is_some() => Token::Operator(Operator::LessOrEqual),
+            '>' if chars.next_if_eq(&'=').is_some() => Token::Operator(Operator::GreaterOrEqual),
+            '<' => Token::Operator(Operator::Less),
+            '>' => Token::Operator(Operator::Greater),
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ',' => Token::Comma,
+            c => return Err(format!("Unexpected character '{c}'")),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser building the expression from tokens, each method parses one level of operator precedence
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    /// Depth of the expression tree built so far, limited so parsing and evaluating can't overflow the stack
+    depth: usize,
+}
+
+/// Deepest expression tree the parser accepts
+const MAX_DEPTH: usize = 100;
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        token
+    }
+
+    /// Goes one level deeper into the expression tree, failing when the expression gets too deep
+    fn descend(&mut self) -> Result<(), String> {
+        if self.depth >= MAX_DEPTH {
+            return Err(String::from("Expression is nested too deeply"));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Consumes the next token if it is one of the operators
+    fn operator(&mut self, operators: &[Operator]) -> Option<Operator> {
+        match self.peek() {
+            Some(Token::Operator(op)) if operators.contains(op) => {
+                let op = *op;
+                self.index += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expression, String> {
+        let left = self.additive()?;
+        let comparisons = [
+            Operator::Less,
+            Operator::Greater,
+            Operator::LessOrEqual,
+            Operator::GreaterOrEqual,
+        ];
+        match self.operator(&comparisons) {
+            Some(op) => Ok(Expression::Binary(
+                op,
+                Box::new(left),
+                Box::new(self.additive()?),
+            )),
+            None => Ok(left),
+        }
+    }
+
+    fn additive(&mut self) -> Result<Expression, String> {
+        let depth = self.depth;
+        let mut left = self.term()?;
+        // each operation in a chain nests the previous ones one level deeper
+        while let Some(op) = self.operator(&[Operator::Add, Operator::Subtract]) {
+            self.descend()?;
+            left = Expression::Binary(op, Box::new(left), Box::new(self.term()?));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Expression, String> {
+        let depth = self.depth;
+        let mut left = self.unary()?;
+        while let Some(op) =
+            self.operator(&[Operator::Multiply, Operator::Divide, Operator::Remainder])
+        {
+            self.descend()?;
+            left = Expression::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    /// Every nested expression passes through here, which makes it the place to limit the depth
+    fn unary(&mut self) -> Result<Expression, String> {
+        let depth = self.depth;
+        self.descend()?;
+        let expression = if self.operator(&[Operator::Subtract]).is_some() {
+            Expression::Negate(Box::new(self.unary()?))
+        } else {
+            self.power()?
+        };
+        self.depth = depth;
+        Ok(expression)
+    }
+
+    fn power(&mut self) -> Result<Expression, String> {
+        let base = self.primary()?;
+        match self.operator(&[Operator::Power]) {
+            // power is right associative so the exponent may hold another power
+            Some(op) => Ok(Expression::Binary(
+                op,
+                Box::new(base),
+                Box::new(self.unary()?),
+            )),
+            None => Ok(base),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expression, String> {
+        match self.next() {
+            Some(Token::Number(x)) => Ok(Expression::Number(x)),
+            Some(Token::Open) => {
+                let inner = self.comparison()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(inner),
+                    _ => Err(String::from("Missing ')'")),
+                }
+            }
+            Some(Token::Name(name)) if self.peek() == Some(&Token::Open) => {
+                let Some(fun) = Function::from_name(&name) else {
+                    return Err(format!("Unknown function '{name}'"));
+                };
+                self.index += 1;
+                let mut args = Vec::new();
+                if self.peek() != Some(&Token::Close) {
+                    loop {
+                        args.push(self.comparison()?);
+                        match self.next() {
+                            Some(Token::Comma) => continue,
+                            Some(Token::Close) => break,
+                            _ => return Err(format!("Missing ')' after arguments of '{name}'")),
+                        }
+                    }
+                } else {
+                    self.index += 1;
+                }
+                if args.len() != fun.arguments() {
+                    return Err(format!(
+                        "Function '{name}' takes {} argument{}, found {}",
+                        fun.arguments(),
+                        if fun.arguments() == 1 { "" } else { "s" },
+                        args.len()
+                    ));
+                }
+                Ok(Expression::Call(fun, args))
+            }
+            Some(Token::Name(name)) => match Variable::from_name(&name) {
+                Some(var) => Ok(Expression::Variable(var)),
+                None => Err(format!("Unknown value '{name}'")),
+            },
+            Some(token) => Err(format!("Unexpected {token}")),
+            None => Err(String::from("Expression ended unexpectedly")),
+        }
+    }
+}
+
+/// Expressions calculating each channel of a pixel from its original color and position
+#[derive(Debug, Clone)]
+pub struct PixelScript {
+    /// Expressions for red, green, blue and alpha channels, in that order
+    channels: [Expression; 4],
+    /// Text the expressions were parsed from, used to describe the script
+    sources: [String; 4],
+}
+
+impl PixelScript {
+    /// Script which leaves the image unchanged
+    pub const IDENTITY: [&'static str; 4] = ["r", "g", "b", "a"];
+
+    /// Parses expressions for each of the channels, returning problems found in each of them if any expression is invalid
+    pub fn parse(sources: &[String; 4]) -> Result<Self, [Option<String>; 4]> {
+        let parsed = sources.clone().map(|x| Expression::parse(&x));
+        if parsed.iter().any(|x| x.is_err()) {
+            return Err(parsed.map(|x| x.err()));
+        }
+        Ok(Self {
+            channels: parsed.map(|x| x.unwrap()),
+            sources: sources.clone(),
+        })
+    }
+
+    /// Tests whatever all channels are left unchanged by the script
+    pub fn is_identity(&self) -> bool {
+        self.sources
+            .iter()
+            .zip(Self::IDENTITY)
+            .all(|(source, identity)| source.trim() == identity)
+    }
+}
+
+impl PixelOperation for PixelScript {
+    fn transform(&self, color: [f32; 4], x: u32, y: u32, size: Size<u32>) -> [f32; 4] {
+        let vars = [
+            color[0],
+            color[1],
+            color[2],
+            color[3],
+            (x as f32 + 0.5) / size.width as f32,
+            (y as f32 + 0.5) / size.height as f32,
+            size.width as f32,
+            size.height as f32,
+            std::f32::consts::PI,
+        ];
+        self.channels.each_ref().map(|x| x.eval(&vars))
+    }
+}
+
+impl Display for PixelScript {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "script r = {}, g = {}, b = {}, a = {}",
+            self.sources[0], self.sources[1], self.sources[2], self.sources[3]
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Red, green, blue, alpha, x, y, width, height and pi
+    const VARS: [f32; 9] = [
+        0.2,
+        0.4,
+        0.6,
+        1.0,
+        0.25,
+        0.75,
+        64.0,
+        32.0,
+        std::f32::consts::PI,
+    ];
+
+    fn eval(source: &str) -> f32 {
+        Expression::parse(source).unwrap().eval(&VARS)
+    }
+
+    fn error(source: &str) -> String {
+        Expression::parse(source).unwrap_err()
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3"), 7.0);
+        assert_eq!(eval("(1 + 2) * 3"), 9.0);
+        assert_eq!(eval("10 - 4 - 3"), 3.0);
+        assert_eq!(eval("12 / 3 / 2"), 2.0);
+        assert_eq!(eval("7 % 4 * 2"), 6.0);
+        assert_eq!(eval("1 + 2 < 4"), 1.0);
+        assert_eq!(eval("2 * 2 >= 5"), 0.0);
+    }
+
+    #[test]
+    fn power_is_right_associative() {
+        assert_eq!(eval("2 ^ 3 ^ 2"), 512.0);
+        assert_eq!(eval("2 ^ -1"), 0.5);
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(eval("-2 ^ 2"), -4.0);
+        assert_eq!(eval("--3"), 3.0);
+        assert_eq!(eval("2 * -3"), -6.0);
+    }
+
+    #[test]
+    fn variables() {
+        assert_eq!(eval("r + g"), 0.6);
+        assert_eq!(eval("B * a"), 0.6);
+        assert_eq!(eval("x + y"), 1.0);
+        assert_eq!(eval("w / h"), 2.0);
+        assert_eq!(eval("pi"), std::f32::consts::PI);
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!(eval("abs(-2)"), 2.0);
+        assert_eq!(eval("min(1, 2) + max(1, 2)"), 3.0);
+        assert_eq!(eval("clamp(2, 0, 1)"), 1.0);
+        assert_eq!(eval("mix(0, 10, 0.25)"), 2.5);
+        assert_eq!(eval("step(0.5, x)"), 0.0);
+        assert_eq!(eval("smoothstep(0, 1, 0.5)"), 0.5);
+        assert_eq!(eval("sqrt(16) + pow(2, 3)"), 12.0);
+        assert_eq!(eval("floor(2.5) + fract(2.5)"), 2.5);
+        assert_eq!(eval("if(x - 0.5, 1, 2)"), 2.0);
+        assert_eq!(eval("if(y - 0.5, 1, 2)"), 1.0);
+        assert_eq!(eval("max(min(r, g), clamp(b, 0, 0.5))"), 0.5);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(error(""), "Expression ended unexpectedly");
+        assert_eq!(error("1 +"), "Expression ended unexpectedly");
+        assert_eq!(error("(1"), "Missing ')'");
+        assert_eq!(error("1 2"), "Unexpected number 2");
+        assert_eq!(error("1)"), "Unexpected ')'");
+        assert_eq!(error("*2"), "Unexpected '*'");
+        assert_eq!(error("1 $ 2"), "Unexpected character '$'");
+        assert_eq!(error("1..2"), "Invalid number 1..2");
+        assert_eq!(error("q"), "Unknown value 'q'");
+        assert_eq!(error("foo(1)"), "Unknown function 'foo'");
+        assert_eq!(error("min(1 2)"), "Missing ')' after arguments of 'min'");
+        assert_eq!(error("min(1)"), "Function 'min' takes 2 arguments, found 1");
+        assert_eq!(
+            error("abs(1, 2)"),
+            "Function 'abs' takes 1 argument, found 2"
+        );
+        assert_eq!(error("sin()"), "Function 'sin' takes 1 argument, found 0");
+    }
+
+    #[test]
+    fn depth_limit() {
+        let nested = |depth: usize| format!("{}x{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(Expression::parse(&nested(50)).unwrap().eval(&VARS), 0.25);
+        assert_eq!(error(&nested(100_000)), "Expression is nested too deeply");
+        assert_eq!(
+            error(&format!("{}x", "-".repeat(100_000))),
+            "Expression is nested too deeply"
+        );
+        assert_eq!(
+            error(&format!("x{}", "^x".repeat(100_000))),
+            "Expression is nested too deeply"
+        );
+        assert_eq!(
+            error(&format!("x{}", "+x".repeat(100_000))),
+            "Expression is nested too deeply"
+        );
+        assert_eq!(
+            error(&format!("{}x{}", "abs(".repeat(1000), ")".repeat(1000))),
+            "Expression is nested too deeply"
+        );
+    }
+
+    #[test]
+    fn identity_script() {
+        let sources = PixelScript::IDENTITY.map(String::from);
+        let script = PixelScript::parse(&sources).unwrap();
+        assert!(script.is_identity());
+        let color = [0.1, 0.2, 0.3, 0.4];
+        assert_eq!(script.transform(color, 0, 0, Size::new(1, 1)), color);
+    }
+
+    #[test]
+    fn script_errors_per_channel() {
+        let sources = ["r", "g +", "b", "q"].map(String::from);
+        let Err(errors) = PixelScript::parse(&sources) else {
+            panic!("invalid script was parsed");
+        };
+        assert_eq!(
+            errors,
+            [
+                None,
+                Some("Expression ended unexpectedly".to_string()),
+                None,
+                Some("Unknown value 'q'".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt::Display;
+
+use ab_glyph::{point, Font, FontRef, Glyph, PxScale, ScaleFont};
+use iced::{Color, Point};
+use serde::{Deserialize, Serialize};
+
+use super::RgbaImage;
+
+/// Side of the text placed at its anchor point
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextAlignment {
+    Left,
+    #[default]
+    Center,
+    Right,
+}
+
+impl TextAlignment {
+    pub const ALL: [TextAlignment; 3] = [
+        TextAlignment::Left,
+        TextAlignment::Center,
+        TextAlignment::Right,
+    ];
+}
+
+impl Display for TextAlignment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                TextAlignment::Left => "Left",
+                TextAlignment::Center => "Center",
+                TextAlignment::Right => "Right",
+            }
+        )
+    }
+}
+
+/// Font used for drawing text into images, it is the same font the interface uses
+fn font() -> FontRef<'static> {
+    FontRef::try_from_slice(iced_graphics::font::FALLBACK).expect("Built-in font is valid")
+}
+
+/// Places glyphs of the text on a single line starting at the origin, returns the glyphs and width of the line
+fn layout(font: &FontRef, text: &str, height: f32) -> (Vec<Glyph>, f32) {
+    let font = font.as_scaled(PxScale::from(height));
+    let mut glyphs = Vec::new();
+    let mut caret = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += font.kern(previous, id);
+        }
+        glyphs.push(id.with_scale_and_position(height, point(caret, 0.0)));
+        caret += font.h_advance(id);
+        previous = Some(id);
+    }
+    (glyphs, caret)
+}
+
+/// Measures how wide the text drawn with the line height would be
+pub fn text_width(text: &str, height: f32) -> f32 {
+    layout(&font(), text, height).1
+}
+
+/// Draws a single line of text centered on the point
+///
+/// # Parameters
+/// `image`  - image to draw the text onto, the text is blended over existing pixels
+/// `text`   - the text to draw
+/// `center` - point in the image the middle of the text is placed at
+/// `height` - height of the line in pixels
+/// `color`  - color of the letters
+pub fn draw_text(image: &mut RgbaImage, text: &str, center: Point, height: f32, color: Color) {
+    let font = font();
+    let (glyphs, width) = layout(&font, text, height);
+    let scaled = font.as_scaled(PxScale::from(height));
+    // baseline is placed so the space between the top of tall letters and the bottom of descending ones is centered
+    let origin = point(
+        center.x - width * 0.5,
+        center.y + (scaled.ascent() + scaled.descent()) * 0.5,
+    );
+
+    for glyph in glyphs {
+        let position = glyph.position;
+        let glyph = Glyph {
+            position: point(origin.x + position.x, origin.y + position.y),
+            ..glyph
+        };
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|x, y, coverage| {
+            let x = bounds.min.x as i64 + x as i64;
+            let y = bounds.min.y as i64 + y as i64;
+            if x < 0 || y < 0 || x >= image.width() as i64 || y >= image.height() as i64 {
+                return;
+            }
+            let pixel = image.get_pixel_mut(x as u32, y as u32);
+            blend_pixel(pixel, color, coverage);
+        });
+    }
+}
+
+/// Lays the color over the pixel with the coverage serving as additional transparency
+pub fn blend_pixel(pixel: &mut image::Rgba<u8>, color: Color, coverage: f32) {
+    let alpha = color.a * coverage.clamp(0.0, 1.0);
+    if alpha <= 0.0 {
+        return;
+    }
+    let base = pixel.0.map(|x| x as f32 / u8::MAX as f32);
+    let rest = base[3] * (1.0 - alpha);
+    let total = alpha + rest;
+    let mix = |color: f32, base: f32| (color * alpha + base * rest) / total;
+    let result = [
+        mix(color.r, base[0]),
+        mix(color.g, base[1]),
+        mix(color.b, base[2]),
+        total,
+    ];
+    pixel.0 = result.map(|x| (x * u8::MAX as f32).round() as u8);
+}
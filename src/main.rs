@@ -4,6 +4,7 @@ mod image;
 mod modifier;
 mod naming_convention;
 mod persistence;
+mod session;
 mod status_bar;
 mod style;
 mod token_maker;
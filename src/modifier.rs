@@ -1,7 +1,24 @@
+mod adjust;
 mod background;
+mod badge;
+mod color_balance;
+mod defringe;
+mod drop_shadow;
 mod flood_mask;
 mod frame;
+mod gradient_overlay;
+mod grain;
 mod greenscreen;
+mod ground_shadow;
+mod kaleidoscope;
+mod levels;
+mod note;
+mod outline;
+mod ring;
+mod script;
+mod text;
+mod transform;
+mod workspace_mask;
 
 use std::fmt::{Debug, Display};
 
@@ -10,11 +27,30 @@ use crate::{
     image::ImageOperation,
 };
 
+use adjust::{Adjust, AdjustMessage};
 use background::{Background, BackgroundMessage};
+use badge::{Badge, BadgeMessage};
+use color_balance::{ColorBalance, ColorBalanceMessage};
+use defringe::{Defringe, DefringeMessage};
+use drop_shadow::{DropShadow, DropShadowMessage};
 use flood_mask::{FloodMask, FloodMaskMessage};
 use frame::{Frame, FrameMessage};
+use gradient_overlay::{GradientOverlay, GradientOverlayMessage};
+use grain::{Grain, GrainMessage};
 use greenscreen::{Greenscreen, GreenscreenMessage};
-use iced::{Command, Element, Renderer};
+use ground_shadow::{GroundShadow, GroundShadowMessage};
+use iced::{Color, Command, Element, Renderer, Size};
+use iced_native::image::Handle;
+use kaleidoscope::{Kaleidoscope, KaleidoscopeMessage};
+use levels::{Levels, LevelsMessage};
+use note::{Note, NoteMessage};
+use outline::{Outline, OutlineMessage};
+use ring::{Ring, RingMessage};
+use script::{Script, ScriptMessage};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use text::{Text, TextMessage};
+use transform::{Transform, TransformMessage};
+use workspace_mask::{WorkspaceMask, WorkspaceMaskMessage};
 
 /// Trait for modifiers to implement
 ///
@@ -35,6 +71,23 @@ pub trait Modifier<'a> {
     /// Returns tooltip text for the modifier
     fn tooltip() -> &'static str;
 
+    /// Hint about where in the rendering order the modifier is expected to be applied
+    ///
+    /// The hint is only used to warn the user about a likely mistake, it never prevents any ordering of modifiers
+    fn order_hint() -> OrderHint {
+        OrderHint::Any
+    }
+
+    /// Text representing the modifier in the modifier list, by default it is the label of the modifier
+    fn list_label(&self) -> String {
+        Self::label().to_string()
+    }
+
+    /// Settings of the modifier as name and value pairs, written into exported metadata
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
     /// Tests whatever the modifier data has been changed in a way that requires redrawing the image
     fn is_dirty(&self) -> bool;
 
@@ -62,6 +115,14 @@ pub trait Modifier<'a> {
         false
     }
 
+    /// Optional image drawn over the workspace preview to help with positioning the image
+    ///
+    /// The overlay is only a visual aid and never becomes a part of the exported image
+    #[allow(unused_variables)]
+    fn overlay(&self, pdata: &ProgramData, wdata: &WorkspaceData) -> Option<Handle> {
+        None
+    }
+
     /// Optional UI elements to drive properties of the modifier
     #[allow(unused_variables)]
     fn properties_view(
@@ -83,6 +144,24 @@ pub trait Modifier<'a> {
         Command::none()
     }
 
+    /// Largest export size the modifier can produce a good result for, the workspace shrinks its export size to fit within it
+    #[allow(unused_variables)]
+    fn size_limit(&self, pdata: &ProgramData, wdata: &WorkspaceData) -> Option<Size<u32>> {
+        None
+    }
+
+    /// Restores the modifier to the state it had right after creation
+    ///
+    /// By default the modifier is replaced with a freshly created one
+    fn reset(&mut self, pdata: &ProgramData, wdata: &WorkspaceData) -> Command<Self::Message>
+    where
+        Self: Sized,
+    {
+        let (command, modifier) = Self::create(pdata, wdata);
+        *self = modifier;
+        command
+    }
+
     /// Sends an update to the modifier after the workspace data has been modified
     ///
     /// This function allows modifiers to regenerate their properties or perform commands if they depend on workspace data
@@ -94,6 +173,69 @@ pub trait Modifier<'a> {
     ) -> Command<Self::Message> {
         Command::none()
     }
+
+    /// Settings of the modifier saved along with the session, they are given back to `restore_state` when the session is restored
+    ///
+    /// Modifiers without settings worth keeping return None and are recreated with their default settings
+    fn save_state(&self) -> Option<String> {
+        None
+    }
+
+    /// Applies settings saved by `save_state` to a freshly created modifier
+    ///
+    /// The returned command replaces the one returned by `create`, so it has to redo any work the modifier needs after the settings change.
+    /// Returns an error when the saved settings can't be read, the modifier keeps its default settings in that case
+    #[allow(unused_variables)]
+    fn restore_state(
+        &mut self,
+        state: &str,
+        pdata: &ProgramData,
+        wdata: &WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        Ok(Command::none())
+    }
+}
+
+/// Serializes settings of a modifier for saving them in a session
+fn write_state<T: Serialize>(state: &T) -> Option<String> {
+    ron::to_string(state).ok()
+}
+
+/// Reads settings of a modifier written by [write_state]
+fn read_state<T: DeserializeOwned>(state: &str) -> Result<T, String> {
+    ron::from_str(state).map_err(|e| e.to_string())
+}
+
+/// Saves colors in modifier settings in the same hex notation the rest of the program stores them in
+mod hex_color {
+    use iced::Color;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use crate::image::convert::{color_to_hex, hex_to_color};
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&color_to_hex(*color))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        hex_to_color(&hex).ok_or_else(|| D::Error::custom(format!("invalid color {}", hex)))
+    }
+}
+
+/// Saves points in modifier settings as a pair of coordinates
+mod point_pair {
+    use iced::Point;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(point: &Point, serializer: S) -> Result<S::Ok, S::Error> {
+        (point.x, point.y).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Point, D::Error> {
+        let (x, y) = <(f32, f32)>::deserialize(deserializer)?;
+        Ok(Point { x, y })
+    }
 }
 
 /// Carrier enum to allow modifiers to provide what kind of operations they need to apply to the image
@@ -108,6 +250,36 @@ pub enum ModifierOperation {
     Multiple(Vec<ImageOperation>),
 }
 
+/// Describes at which stage of the rendering process the modifier is best applied
+///
+/// Stages are ordered, modifiers with earlier stage are expected to be applied before modifiers with later stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OrderHint {
+    /// Modifier works on the source image and should be applied before anything else
+    Source,
+    /// Modifier doesn't care about its position in the stack
+    Any,
+    /// Modifier fills in empty space of the image and should be applied after source is prepared
+    Underlay,
+    /// Modifier finalizes the image and should be applied last
+    Overlay,
+}
+
+impl ModifierOperation {
+    /// Appends the operations at the end of the list while keeping their order
+    pub fn push_into(self, ops: &mut Vec<ImageOperation>) {
+        match self {
+            ModifierOperation::None => {}
+            ModifierOperation::Single(o) => ops.push(o),
+            ModifierOperation::Double(first, second) => {
+                ops.push(first);
+                ops.push(second);
+            }
+            ModifierOperation::Multiple(mut o) => ops.append(&mut o),
+        }
+    }
+}
+
 impl From<ImageOperation> for ModifierOperation {
     fn from(value: ImageOperation) -> Self {
         ModifierOperation::Single(value)
@@ -124,12 +296,61 @@ impl From<Vec<ImageOperation>> for ModifierOperation {
     }
 }
 
-make_modifier!(Frame, Background, Greenscreen, FloodMask);
+make_modifier!(
+    Frame,
+    Background,
+    GroundShadow,
+    DropShadow,
+    Greenscreen,
+    FloodMask,
+    Kaleidoscope,
+    Transform,
+    Levels,
+    Adjust,
+    ColorBalance,
+    Defringe,
+    Outline,
+    Grain,
+    GradientOverlay,
+    Script,
+    WorkspaceMask,
+    Ring,
+    Badge,
+    Text,
+    Note
+);
+
+impl ModifierBox {
+    /// Creates a message that sets tint of the modifier, returns none if the modifier can't be tinted
+    pub fn tint_message(&self, color: Color) -> Option<ModifierMessage> {
+        match self {
+            ModifierBox::Frame(_) => Some(FrameMessage::SetTint(color).into()),
+            _ => None,
+        }
+    }
+}
 make_modifier_message!(
     FrameMessage,
     BackgroundMessage,
+    GroundShadowMessage,
+    DropShadowMessage,
     GreenscreenMessage,
-    FloodMaskMessage
+    FloodMaskMessage,
+    KaleidoscopeMessage,
+    TransformMessage,
+    LevelsMessage,
+    AdjustMessage,
+    ColorBalanceMessage,
+    DefringeMessage,
+    OutlineMessage,
+    GrainMessage,
+    GradientOverlayMessage,
+    ScriptMessage,
+    WorkspaceMaskMessage,
+    RingMessage,
+    BadgeMessage,
+    TextMessage,
+    NoteMessage
 );
 
 /// This makro creates `ModifierBox` enum which is responsible for providing polymorphism feature for all modifiers.
@@ -156,6 +377,22 @@ macro_rules! make_modifier {
                     )+
                 }
             }
+            /// Provides image operation of the boxed modifier without changing its dirty status
+            pub fn peek_image_operation(&self, pdata: &ProgramData, wdata: &WorkspaceData) -> ModifierOperation {
+                match self {
+                    $(
+                        ModifierBox::$md(x) => x.get_image_operation(pdata, wdata),
+                    )+
+                }
+            }
+            /// Returns the tag identifying type of the boxed modifier
+            pub fn tag(&self) -> ModifierTag {
+                match self {
+                    $(
+                        ModifierBox::$md(_) => ModifierTag::$md,
+                    )+
+                }
+            }
             /// Label of the modifier
             pub fn label(&self) -> &'static str {
                 match self {
@@ -171,6 +408,30 @@ macro_rules! make_modifier {
                     )+
                 }
             }
+            /// Text representing the modifier in the modifier list
+            pub fn list_label(&self) -> String {
+                match self {
+                    $(
+                        ModifierBox::$md(x) => x.list_label(),
+                    )+
+                }
+            }
+            /// Settings of the modifier as name and value pairs
+            pub fn metadata(&self) -> Vec<(&'static str, String)> {
+                match self {
+                    $(
+                        ModifierBox::$md(x) => x.metadata(),
+                    )+
+                }
+            }
+            /// Hint about where in the rendering order the modifier should be
+            pub fn order_hint(&self) -> OrderHint {
+                match self {
+                    $(
+                        ModifierBox::$md(_) => $md::order_hint(),
+                    )+
+                }
+            }
             /// Tells whatever the modifier has been changed in a way that needs rerendering of the image
             pub fn is_dirty(&self) -> bool {
                 match self {
@@ -198,6 +459,30 @@ macro_rules! make_modifier {
                     )+
                 }
             }
+            /// Overlay to draw over the preview of the workspace
+            pub fn overlay(&self, pdata: &ProgramData, wdata: &WorkspaceData) -> Option<Handle> {
+                match self {
+                    $(
+                        ModifierBox::$md(x) => x.overlay(pdata, wdata),
+                    )+
+                }
+            }
+            /// Largest export size the modifier supports, if it limits it at all
+            pub fn size_limit(&self, pdata: &ProgramData, wdata: &WorkspaceData) -> Option<Size<u32>> {
+                match self {
+                    $(
+                        ModifierBox::$md(x) => x.size_limit(pdata, wdata),
+                    )+
+                }
+            }
+            /// Restores default settings of the modifier
+            pub fn reset(&mut self, pdata: &ProgramData, wdata: &WorkspaceData) -> Command<ModifierMessage> {
+                match self {
+                    $(
+                        ModifierBox::$md(x) => x.reset(pdata, wdata).map(|x| x.into()),
+                    )+
+                }
+            }
             /// Signal sent to the modifier that workspace data has changed and the modifier may need to recalculate itself
             pub fn workspace_update(&mut self, pdata: &ProgramData, wdata: &WorkspaceData) -> Command<ModifierMessage> {
                 match self {
@@ -214,6 +499,22 @@ macro_rules! make_modifier {
                     )+
                 }
             }
+            /// Settings of the modifier to save in a session
+            pub fn save_state(&self) -> Option<String> {
+                match self {
+                    $(
+                        ModifierBox::$md(x) => x.save_state(),
+                    )+
+                }
+            }
+            /// Applies settings saved in a session
+            pub fn restore_state(&mut self, state: &str, pdata: &ProgramData, wdata: &WorkspaceData) -> Result<Command<ModifierMessage>, String> {
+                match self {
+                    $(
+                        ModifierBox::$md(x) => x.restore_state(state, pdata, wdata).map(|c| c.map(|x| x.into())),
+                    )+
+                }
+            }
             /// Tests whatever the modifier wants to take over the main workspace preview area UI
             pub fn wants_main_view(&self, pdata: &ProgramData, wdata: &WorkspaceData) -> bool {
                 match self {
@@ -225,7 +526,7 @@ macro_rules! make_modifier {
         }
 
         /// Modifier Tag is used to identify modifiers and their type
-        #[derive(Debug, Clone, PartialEq, Eq)]
+        #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
         pub enum ModifierTag {
             $(
                 $md,
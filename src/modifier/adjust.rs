@@ -0,0 +1,163 @@
+use iced::{
+    widget::{button, column as col, horizontal_space, row, slider, text, tooltip},
+    Command, Length,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{ProgramData, WorkspaceData},
+    image::ImageOperation,
+    style::Style,
+};
+
+use super::{read_state, write_state, Modifier, ModifierOperation, OrderHint};
+
+/// Changes brightness and contrast of the image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Adjust {
+    /// Value added to all color channels, from -1 to 1
+    brightness: f32,
+    /// Scale of the distance of color channels from middle gray, from -1 to 1 where 0 leaves the image unchanged
+    contrast: f32,
+
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum AdjustMessage {
+    SetBrightness(f32),
+    SetContrast(f32),
+    /// Sets both values back to leaving the image unchanged
+    Reset,
+}
+
+impl<'a> Modifier<'a> for Adjust {
+    type Message = AdjustMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &ProgramData,
+        _wdata: &WorkspaceData,
+    ) -> ModifierOperation {
+        if self.brightness == 0.0 && self.contrast == 0.0 {
+            return ModifierOperation::None;
+        }
+        ImageOperation::BrightnessContrast {
+            brightness: self.brightness,
+            contrast: self.contrast,
+        }
+        .into()
+    }
+
+    fn create(_pdata: &ProgramData, _wdata: &WorkspaceData) -> (Command<Self::Message>, Self) {
+        (
+            Command::none(),
+            Self {
+                brightness: 0.0,
+                contrast: 0.0,
+                dirty: true,
+            },
+        )
+    }
+
+    fn label() -> &'static str {
+        "Adjust"
+    }
+
+    fn tooltip() -> &'static str {
+        "Changes brightness and contrast of the image"
+    }
+
+    fn order_hint() -> OrderHint {
+        OrderHint::Any
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("brightness", self.brightness.to_string()),
+            ("contrast", self.contrast.to_string()),
+        ]
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        _pdata: &mut ProgramData,
+        _wdata: &mut WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            AdjustMessage::SetBrightness(brightness) => self.brightness = brightness,
+            AdjustMessage::SetContrast(contrast) => self.contrast = contrast,
+            AdjustMessage::Reset => {
+                self.brightness = 0.0;
+                self.contrast = 0.0;
+            }
+        }
+        self.dirty = true;
+        Command::none()
+    }
+
+    fn properties_view(
+        &'a self,
+        _pdata: &'a ProgramData,
+        _wdata: &'a WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        let contrast = tooltip(
+            text(format!("Contrast: {:.2}", self.contrast)).width(Length::Fill),
+            "Positive values push colors away from middle gray, negative values pull them towards it.",
+            tooltip::Position::Bottom,
+        )
+        .style(Style::Frame);
+
+        let slider_brightness =
+            slider(-1.0..=1.0, self.brightness, AdjustMessage::SetBrightness).step(0.01);
+        let slider_contrast =
+            slider(-1.0..=1.0, self.contrast, AdjustMessage::SetContrast).step(0.01);
+
+        Some(
+            col![
+                row![
+                    text(format!("Brightness: {:.2}", self.brightness)).width(Length::Fill),
+                    slider_brightness.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+                row![
+                    contrast,
+                    slider_contrast.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+                button("Reset").on_press(AdjustMessage::Reset),
+            ]
+            .spacing(6)
+            .into(),
+        )
+    }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(self)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &ProgramData,
+        _wdata: &WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        *self = Self {
+            dirty: true,
+            ..read_state(state)?
+        };
+        Ok(Command::none())
+    }
+}
@@ -1,27 +1,32 @@
 use std::{path::PathBuf, sync::Arc};
 
 use iced::{
-    widget::{button, column as col, radio, row, tooltip},
-    Color, Command, Point, Size,
+    widget::{button, column as col, progress_bar, radio, row, text, tooltip},
+    Alignment, Color, Command, Point, Size, Vector,
 };
 use iced_native::image::Handle;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     image::{
-        convert::image_arc_to_handle, download_image, image_filter, operations::resample_image,
-        ImageOperation, RgbaImage,
+        convert::{color_to_hex, handle_to_image, image_arc_to_handle, pixel_to_color},
+        download_image_resumable, open_image,
+        operations::resample_image,
+        Download, ImageOperation, RgbaImage,
     },
     style::Style,
-    widgets::{BrowserOperation, BrowsingResult, ColorPicker, Trackpad},
+    widgets::{swatches, BrowserOperation, BrowsingResult, ColorPicker, PixelSampler, Trackpad},
 };
 
-use super::{Modifier, ModifierOperation};
+use super::{read_state, write_state, Modifier, ModifierOperation, OrderHint};
 
 #[derive(Debug, Clone)]
 pub struct Background {
     background: BackgroundType,
     color: Color,
     source: Option<Arc<RgbaImage>>,
+    /// File the background image was loaded from, downloaded images have none
+    image_path: Option<PathBuf>,
     image: Option<Arc<RgbaImage>>,
     preview: Option<Handle>,
 
@@ -29,8 +34,14 @@ pub struct Background {
     rendering: bool,
     browsing: bool,
     repositioning: bool,
+    /// Whatever the user is picking the color from the rendered image
+    picking_color: bool,
     offset: Point,
     zoom: f32,
+    /// Address and progress of the latest download, kept after a failure to allow retrying it
+    download: Option<(String, Arc<Download>)>,
+    /// Whatever the latest download failed
+    download_failed: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -39,23 +50,122 @@ pub enum BackgroundMessage {
     SetMode(BackgroundType),
     SetOffset(Point),
     SetZoom(f32),
-    SetImage(Result<(Arc<RgbaImage>, Arc<RgbaImage>, Handle), PathBuf>),
+    SetImage(Result<(PathBuf, Arc<RgbaImage>, Arc<RgbaImage>, Handle), PathBuf>),
     UpdateImage(Arc<RgbaImage>, Handle),
     LookForImage,
     LookForUrl,
     DownloadImage(String),
+    /// Continues the failed download from where it stopped
+    RetryDownload,
     DownloadedImage(Result<RgbaImage, String>),
     RepositionImage,
+    /// Removes the image and goes back to the solid color
+    ClearImage,
+    /// Switches to sampling the color from the rendered image
+    StartPicking,
+    StopPicking,
+    /// Sets the color to the one of the rendered image pixel at the position
+    PickedColor(Vector<u32>),
     Browser(BrowserOperation),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Serialize, Deserialize)]
 pub enum BackgroundType {
     Image,
     Solid,
     // TODO add gradients
 }
 
+/// Settings of the background modifier saved along with the session
+#[derive(Serialize, Deserialize)]
+struct BackgroundState {
+    background: BackgroundType,
+    #[serde(with = "super::hex_color")]
+    color: Color,
+    #[serde(with = "super::point_pair")]
+    offset: Point,
+    zoom: f32,
+    image_path: Option<PathBuf>,
+}
+
+impl Background {
+    /// Creates a task loading the image from the file and fitting it to the export size
+    fn load_image(
+        &self,
+        path: PathBuf,
+        pdata: &crate::data::ProgramData,
+        size: Size<u32>,
+    ) -> Command<BackgroundMessage> {
+        let offset = self.offset;
+        let zoom = self.zoom;
+        let workers = pdata.render_workers();
+        let cached = pdata.images.get(&path);
+        Command::perform(
+            async move {
+                let img = match cached {
+                    Some(img) => img,
+                    None => {
+                        let Ok(img) = open_image(&path) else {
+                            return Err(path);
+                        };
+                        Arc::new(img)
+                    }
+                };
+                let result = resize_image(img.clone(), offset, zoom, size, workers).await;
+                Ok((path, img, result.0, result.1))
+            },
+            BackgroundMessage::SetImage,
+        )
+    }
+
+    /// Starts or continues the current download
+    fn start_download(
+        &mut self,
+        pdata: &mut crate::data::ProgramData,
+    ) -> Command<BackgroundMessage> {
+        let Some((url, download)) = self.download.clone() else {
+            return Command::none();
+        };
+        pdata.status.log("Downloading image...");
+        self.download_failed = false;
+        Command::perform(
+            download_image_resumable(url, download),
+            BackgroundMessage::DownloadedImage,
+        )
+    }
+
+    /// Shows progress of the download or a button for retrying it when it failed
+    fn download_view(&self) -> iced::Element<'_, BackgroundMessage, iced::Renderer> {
+        let Some((_, download)) = &self.download else {
+            return col![].into();
+        };
+        let (received, total) = download.progress();
+        let megabytes = |x: u64| x as f32 / 1_000_000.0;
+        if self.download_failed {
+            return row![
+                text(format!("Failed at {:.1} MB", megabytes(received))),
+                button("Retry").on_press(BackgroundMessage::RetryDownload),
+            ]
+            .spacing(4)
+            .align_items(Alignment::Center)
+            .into();
+        }
+        match total {
+            Some(total) => col![
+                text(format!(
+                    "Downloading {:.1} / {:.1} MB",
+                    megabytes(received),
+                    megabytes(total)
+                )),
+                progress_bar(0.0..=total as f32, received as f32).height(8),
+            ]
+            .spacing(2)
+            .into(),
+            None => text(format!("Downloading {:.1} MB", megabytes(received))).into(),
+        }
+    }
+}
+
 impl<'a> Modifier<'a> for Background {
     type Message = BackgroundMessage;
 
@@ -82,13 +192,17 @@ impl<'a> Modifier<'a> for Background {
             dirty: true,
             color: Color::WHITE,
             source: None,
+            image_path: None,
             image: None,
             preview: None,
             browsing: false,
             repositioning: false,
+            picking_color: false,
             rendering: false,
             offset: Point::ORIGIN,
             zoom: 1.0,
+            download: None,
+            download_failed: false,
         };
         (Command::none(), s)
     }
@@ -107,6 +221,32 @@ impl<'a> Modifier<'a> for Background {
             }
             BackgroundMessage::SetMode(mode) => {
                 self.background = mode;
+                self.picking_color = false;
+                self.dirty = true;
+                Command::none()
+            }
+            BackgroundMessage::StartPicking => {
+                self.picking_color = true;
+                Command::none()
+            }
+            BackgroundMessage::StopPicking => {
+                self.picking_color = false;
+                Command::none()
+            }
+            BackgroundMessage::PickedColor(position) => {
+                self.picking_color = false;
+                let Some(image) = handle_to_image(&wdata.image_result) else {
+                    pdata
+                        .status
+                        .error("Rendered image is not available for picking colors");
+                    return Command::none();
+                };
+                if image.width() == 0 || image.height() == 0 {
+                    return Command::none();
+                }
+                let x = position.x.min(image.width() - 1);
+                let y = position.y.min(image.height() - 1);
+                self.color = pixel_to_color(image.get_pixel(x, y));
                 self.dirty = true;
                 Command::none()
             }
@@ -121,20 +261,7 @@ impl<'a> Modifier<'a> for Background {
                     BrowsingResult::Done(path) => {
                         self.browsing = false;
                         pdata.status.log(&format!("loading background: {:?}", path));
-                        let offset = self.offset;
-                        let zoom = self.zoom;
-                        let size = wdata.export_size;
-                        Command::perform(
-                            async move {
-                                let Ok(img) = image::open(&path) else {
-                                    return Err(path);
-                                };
-                                let img = Arc::new(img.into_rgba8());
-                                let result = resize_image(img.clone(), offset, zoom, size).await;
-                                Ok((img, result.0, result.1))
-                            },
-                            BackgroundMessage::SetImage,
-                        )
+                        self.load_image(path, pdata, wdata.export_size)
                     }
                 },
                 Err(e) => {
@@ -143,7 +270,9 @@ impl<'a> Modifier<'a> for Background {
                     Command::none()
                 }
             },
-            BackgroundMessage::SetImage(Ok((src, img, rendr))) => {
+            BackgroundMessage::SetImage(Ok((path, src, img, rendr))) => {
+                self.image_path = Some(path.clone());
+                pdata.images.insert(path, &src);
                 self.source = Some(src);
                 self.image = Some(img);
                 self.preview = Some(rendr);
@@ -181,6 +310,7 @@ impl<'a> Modifier<'a> for Background {
                         self.offset,
                         self.zoom,
                         wdata.export_size,
+                        pdata.render_workers(),
                     ),
                     |x| BackgroundMessage::UpdateImage(x.0, x.1),
                 )
@@ -199,6 +329,7 @@ impl<'a> Modifier<'a> for Background {
                         self.offset,
                         self.zoom,
                         wdata.export_size,
+                        pdata.render_workers(),
                     ),
                     |x| BackgroundMessage::UpdateImage(x.0, x.1),
                 )
@@ -212,9 +343,19 @@ impl<'a> Modifier<'a> for Background {
                 }
                 Command::none()
             }
+            BackgroundMessage::ClearImage => {
+                self.source = None;
+                self.image_path = None;
+                self.image = None;
+                self.preview = None;
+                self.repositioning = false;
+                self.background = BackgroundType::Solid;
+                self.dirty = true;
+                Command::none()
+            }
             BackgroundMessage::LookForImage => {
                 self.browsing = true;
-                pdata.file.set_filter(image_filter);
+                pdata.file.set_filter(pdata.image_filter());
                 Command::none()
             }
             BackgroundMessage::LookForUrl => iced::clipboard::read(|x| {
@@ -224,26 +365,25 @@ impl<'a> Modifier<'a> for Background {
                 BackgroundMessage::DownloadImage(url)
             }),
             BackgroundMessage::DownloadImage(url) => {
-                pdata.status.log("Downloading image...");
-                Command::perform(
-                    async move {
-                        let img = download_image(url).await;
-                        BackgroundMessage::DownloadedImage(img)
-                    },
-                    |x| x,
-                )
+                self.download = Some((url, Arc::new(Download::default())));
+                self.start_download(pdata)
             }
+            BackgroundMessage::RetryDownload => self.start_download(pdata),
             BackgroundMessage::DownloadedImage(img) => match img {
                 Ok(img) => {
                     pdata.status.log("Image downloaded");
+                    self.download = None;
+                    self.download_failed = false;
                     let img = Arc::new(img);
                     self.source = Some(img.clone());
+                    self.image_path = None;
                     let offset = self.offset;
                     let zoom = self.zoom;
                     let size = wdata.export_size;
+                    let workers = pdata.render_workers();
                     Command::perform(
                         async move {
-                            let img = resize_image(img, offset, zoom, size).await;
+                            let img = resize_image(img, offset, zoom, size, workers).await;
                             BackgroundMessage::UpdateImage(img.0, img.1)
                         },
                         |x| x,
@@ -251,6 +391,7 @@ impl<'a> Modifier<'a> for Background {
                 }
                 Err(er) => {
                     pdata.status.error(&er);
+                    self.download_failed = true;
                     Command::none()
                 }
             },
@@ -259,7 +400,7 @@ impl<'a> Modifier<'a> for Background {
 
     fn workspace_update(
         &mut self,
-        _pdata: &crate::data::ProgramData,
+        pdata: &crate::data::ProgramData,
         wdata: &crate::data::WorkspaceData,
     ) -> Command<Self::Message> {
         if let Some(img) = &self.image {
@@ -268,9 +409,10 @@ impl<'a> Modifier<'a> for Background {
                 let offset = self.offset;
                 let zoom = self.zoom;
                 let size = wdata.export_size;
+                let workers = pdata.render_workers();
                 return Command::perform(
                     async move {
-                        let img = resize_image(img, offset, zoom, size).await;
+                        let img = resize_image(img, offset, zoom, size, workers).await;
                         BackgroundMessage::UpdateImage(img.0, img.1)
                     },
                     |x| x,
@@ -282,8 +424,8 @@ impl<'a> Modifier<'a> for Background {
 
     fn properties_view(
         &self,
-        _pdata: &crate::data::ProgramData,
-        _wdata: &crate::data::WorkspaceData,
+        pdata: &crate::data::ProgramData,
+        wdata: &crate::data::WorkspaceData,
     ) -> Option<iced::Element<Self::Message, iced::Renderer>> {
         let modes = col![
             radio("Color", BackgroundType::Solid, Some(self.background), |x| {
@@ -303,7 +445,12 @@ impl<'a> Modifier<'a> for Background {
                     tooltip::Position::Bottom,
                 )
                 .style(Style::Frame);
-                let down = button("Paste URL").on_press(BackgroundMessage::LookForUrl);
+                let downloading = self.download.is_some() && !self.download_failed;
+                let down = if downloading {
+                    button("Paste URL")
+                } else {
+                    button("Paste URL").on_press(BackgroundMessage::LookForUrl)
+                };
                 let down = tooltip(
                     down,
                     "Copy URL and press the button to automatically load the image in from the internet.",
@@ -321,13 +468,44 @@ impl<'a> Modifier<'a> for Background {
                 } else {
                     button("Reposition Image")
                 };
-                col![file, down, transform].spacing(4)
+                let clear = if self.image.is_some() {
+                    button("Clear Image").on_press(BackgroundMessage::ClearImage)
+                } else {
+                    button("Clear Image")
+                };
+                let clear = tooltip(
+                    clear,
+                    "Removes the image and goes back to the solid color",
+                    tooltip::Position::Bottom,
+                )
+                .style(Style::Frame);
+                col![file, down, transform, clear, self.download_view()].spacing(4)
             }
             BackgroundType::Solid => {
                 let col = ColorPicker::new(self.color, |x| BackgroundMessage::SetColor(x))
+                    .recent(&pdata.recent_colors)
                     .width(32)
                     .height(32);
-                col![col]
+                let pick = if self.picking_color {
+                    button("Cancel picking")
+                        .on_press(BackgroundMessage::StopPicking)
+                        .style(Style::Highlight.into())
+                } else {
+                    button("Eyedropper").on_press(BackgroundMessage::StartPicking)
+                };
+                let pick = tooltip(
+                    pick,
+                    "Click on the image to use color of the pixel as the background",
+                    tooltip::Position::Bottom,
+                )
+                .style(Style::Frame);
+                col![
+                    row![col, swatches(&wdata.palette, BackgroundMessage::SetColor)]
+                        .spacing(4)
+                        .align_items(Alignment::Center),
+                    pick,
+                ]
+                .spacing(4)
             }
         };
 
@@ -338,13 +516,17 @@ impl<'a> Modifier<'a> for Background {
     fn main_view(
         &'a self,
         pdata: &'a crate::data::ProgramData,
-        _wdata: &'a crate::data::WorkspaceData,
+        wdata: &'a crate::data::WorkspaceData,
     ) -> iced::Element<Self::Message, iced::Renderer> {
         if self.browsing {
-            return pdata.file.view().map(|x| BackgroundMessage::Browser(x));
+            return pdata
+                .file
+                .view(pdata.input_style())
+                .map(BackgroundMessage::Browser);
         }
 
         if self.repositioning {
+            let step = pdata.scroll_zoom_step();
             let tr = Trackpad::new(self.preview.as_ref().unwrap().clone())
                 .with_drag(self.offset, |mods, _button, _point, delta| {
                     let offset = if mods.shift() {
@@ -360,16 +542,21 @@ impl<'a> Modifier<'a> for Background {
                     };
                     Some(BackgroundMessage::SetOffset(offset))
                 })
-                .with_scroll(|mods, scroll| match scroll {
+                .with_scroll(move |mods, scroll| match scroll {
                     iced::mouse::ScrollDelta::Lines { x: _, y }
                     | iced::mouse::ScrollDelta::Pixels { x: _, y } => {
-                        let y = if mods.alt() { y * 0.01 } else { y * 0.1 };
+                        let y = if mods.alt() { y * step * 0.1 } else { y * step };
                         Some(BackgroundMessage::SetZoom(y))
                     }
                 });
             return tr.into();
         }
 
+        if self.picking_color {
+            return PixelSampler::new(wdata.image_result.clone(), BackgroundMessage::PickedColor)
+                .into();
+        }
+
         unreachable!()
     }
 
@@ -378,7 +565,7 @@ impl<'a> Modifier<'a> for Background {
         _pdata: &crate::data::ProgramData,
         _wdata: &crate::data::WorkspaceData,
     ) -> bool {
-        self.browsing || self.repositioning
+        self.browsing || self.repositioning || self.picking_color
     }
 
     fn label() -> &'static str {
@@ -389,6 +576,20 @@ impl<'a> Modifier<'a> for Background {
         "Fills transparent pixels with solid color or image, providing background to your image"
     }
 
+    fn order_hint() -> OrderHint {
+        OrderHint::Underlay
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        match self.background {
+            BackgroundType::Solid => vec![("color", color_to_hex(self.color))],
+            BackgroundType::Image => vec![
+                ("offset", format!("{},{}", self.offset.x, self.offset.y)),
+                ("zoom", self.zoom.to_string()),
+            ],
+        }
+    }
+
     fn is_dirty(&self) -> bool {
         self.dirty
     }
@@ -396,6 +597,35 @@ impl<'a> Modifier<'a> for Background {
     fn set_clean(&mut self) {
         self.dirty = false;
     }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(&BackgroundState {
+            background: self.background,
+            color: self.color,
+            offset: self.offset,
+            zoom: self.zoom,
+            image_path: self.image_path.clone(),
+        })
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        pdata: &crate::data::ProgramData,
+        wdata: &crate::data::WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        let saved: BackgroundState = read_state(state)?;
+        self.background = saved.background;
+        self.color = saved.color;
+        self.offset = saved.offset;
+        self.zoom = saved.zoom;
+        self.dirty = true;
+        // downloaded images aren't kept anywhere so only images opened from files come back
+        match saved.image_path {
+            Some(path) => Ok(self.load_image(path, pdata, wdata.export_size)),
+            None => Ok(Command::none()),
+        }
+    }
 }
 
 async fn resize_image(
@@ -403,12 +633,13 @@ async fn resize_image(
     offset: Point,
     zoom: f32,
     size: Size<u32>,
+    workers: usize,
 ) -> (Arc<RgbaImage>, Handle) {
     let center = Point {
         x: source.width() as f32 * 0.5 - offset.x,
         y: source.height() as f32 * 0.5 - offset.y,
     };
-    let img = resample_image(source, size, center, zoom).await;
+    let img = resample_image(source, size, center, zoom, false, workers).await;
 
     let img = Arc::new(img);
     let preview = image_arc_to_handle(&img);
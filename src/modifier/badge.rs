@@ -0,0 +1,666 @@
+use std::{fmt::Display, sync::Arc};
+
+use iced::{
+    widget::{
+        button, checkbox, column as col, container, pick_list, radio, row, slider, text,
+        text_input, tooltip,
+    },
+    Alignment, Color, Command, Length, Point, Rectangle, Size,
+};
+use iced_native::image::Data;
+use image::imageops::crop_imm;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{ProgramData, WorkspaceData},
+    image::{
+        convert::{color_to_hex, contrast_ratio},
+        text::{blend_pixel, draw_text, text_width},
+        ImageOperation, RgbaImage,
+    },
+    style::Style,
+    widgets::{swatches, ColorPicker},
+};
+
+use super::{read_state, write_state, Modifier, ModifierOperation, OrderHint};
+
+/// Longest text that still fits into the badge legibly
+const MAX_TEXT_LENGTH: usize = 4;
+/// Lowest contrast between text and background WCAG considers readable for regular text
+const MIN_CONTRAST: f32 = 4.5;
+
+/// Draws a small badge with a number or short text in a corner of the image, meant for initiative or health markers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Badge {
+    style: BadgeStyle,
+    /// Rendered badge matching the export size
+    #[serde(skip)]
+    image: Option<Arc<RgbaImage>>,
+    /// Pixels right next to the letters, contrast of the text is measured against the result image in these places
+    #[serde(skip)]
+    surroundings: Arc<Vec<(u32, u32)>>,
+
+    #[serde(skip)]
+    dirty: bool,
+}
+
+/// Everything that determines how the badge looks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BadgeStyle {
+    text: String,
+    corner: Corner,
+    shape: BadgeShape,
+    /// Width of the badge as a fraction of the shorter side of the image
+    size: f32,
+    #[serde(with = "super::hex_color")]
+    background: Color,
+    #[serde(with = "super::hex_color")]
+    foreground: Color,
+    /// Whatever the letters are surrounded by a thin line of black or white, whichever stands out more from the text color
+    #[serde(default)]
+    halo: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum BadgeMessage {
+    SetText(String),
+    SetCorner(Corner),
+    SetShape(BadgeShape),
+    SetSize(f32),
+    SetBackground(Color),
+    SetForeground(Color),
+    SetHalo(bool),
+    /// Rendered badge along with the style it was rendered with and the pixels surrounding its letters
+    Rendered(BadgeStyle, Arc<RgbaImage>, Arc<Vec<(u32, u32)>>),
+}
+
+/// Corner of the image the badge is placed in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Corner {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    pub const ALL: [Corner; 4] = [
+        Corner::TopLeft,
+        Corner::TopRight,
+        Corner::BottomLeft,
+        Corner::BottomRight,
+    ];
+}
+
+impl Display for Corner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Corner::TopLeft => "Top Left",
+                Corner::TopRight => "Top Right",
+                Corner::BottomLeft => "Bottom Left",
+                Corner::BottomRight => "Bottom Right",
+            }
+        )
+    }
+}
+
+/// Outline of the badge background
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BadgeShape {
+    #[default]
+    Circle,
+    Shield,
+}
+
+impl BadgeShape {
+    pub const ALL: [BadgeShape; 2] = [BadgeShape::Circle, BadgeShape::Shield];
+
+    /// Tests whatever the point lies within the shape, coordinates are relative to the center and scaled so the shape spans from -1 to 1
+    fn contains(&self, x: f32, y: f32) -> bool {
+        match self {
+            BadgeShape::Circle => x * x + y * y <= 1.0,
+            BadgeShape::Shield => {
+                // straight sides in the upper part narrowing into a point at the bottom
+                let half_width = 0.85;
+                let taper = 0.2;
+                if !(-0.9..=1.0).contains(&y) {
+                    false
+                } else if y <= taper {
+                    x.abs() <= half_width
+                } else {
+                    x.abs() <= half_width * (1.0 - (y - taper) / (1.0 - taper))
+                }
+            }
+        }
+    }
+}
+
+impl Display for BadgeShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                BadgeShape::Circle => "Circle",
+                BadgeShape::Shield => "Shield",
+            }
+        )
+    }
+}
+
+impl Badge {
+    /// Shows contrast of the text against the image around it and warns when it's too low to read easily
+    fn contrast_view(
+        &self,
+        wdata: &WorkspaceData,
+    ) -> iced::Element<'_, BadgeMessage, iced::Renderer> {
+        // the estimate from colors alone is used until the result includes the badge
+        let contrast = self
+            .measured_contrast(wdata)
+            .unwrap_or_else(|| self.style.contrast());
+        if self.style.text.is_empty() || contrast >= MIN_CONTRAST {
+            return text(format!("Contrast: {:.1}:1", contrast)).into();
+        }
+        let warning = if self.style.halo {
+            format!(
+                "Contrast: {:.1}:1, the text may be hard to read. Pick more different colors or make the background more opaque",
+                contrast
+            )
+        } else {
+            format!(
+                "Contrast: {:.1}:1, the text may be hard to read. Add a halo around the letters, pick more different colors or make the background more opaque",
+                contrast
+            )
+        };
+        let mut content = row![text(warning).width(Length::Fill)]
+            .spacing(4)
+            .align_items(Alignment::Center);
+        if !self.style.halo {
+            content = content.push(button("Add Halo").on_press(BadgeMessage::SetHalo(true)));
+        }
+        container(content)
+            .padding(4)
+            .width(Length::Fill)
+            .style(Style::Danger)
+            .into()
+    }
+
+    /// Lowest contrast of the text against the rendered pixels right next to its letters, None when the result doesn't match the badge
+    fn measured_contrast(&self, wdata: &WorkspaceData) -> Option<f32> {
+        let image = self.image.as_ref()?;
+        let Data::Rgba {
+            width,
+            height,
+            pixels,
+        } = wdata.image_result.data()
+        else {
+            return None;
+        };
+        if (*width, *height) != image.dimensions() {
+            return None;
+        }
+        self.surroundings
+            .iter()
+            .filter_map(|&(x, y)| {
+                let index = (y as usize * *width as usize + x as usize) * 4;
+                let pixel = pixels.get(index..index + 4)?;
+                Some(text_contrast(
+                    self.style.foreground,
+                    Color::from_rgba8(pixel[0], pixel[1], pixel[2], pixel[3] as f32 / 255.0),
+                ))
+            })
+            .reduce(f32::min)
+    }
+
+    /// Creates a task rendering the badge in the export size
+    fn render(&self, wdata: &WorkspaceData) -> Command<BadgeMessage> {
+        let style = self.style.clone();
+        let size = wdata.export_size;
+        Command::perform(
+            async move {
+                let (image, surroundings) = render_badge(&style, size);
+                (style, Arc::new(image), Arc::new(surroundings))
+            },
+            |x| BadgeMessage::Rendered(x.0, x.1, x.2),
+        )
+    }
+}
+
+impl BadgeStyle {
+    /// Contrast of the text against the background estimated from the colors alone
+    fn contrast(&self) -> f32 {
+        if self.halo {
+            return text_contrast(self.foreground, halo_color(self.foreground));
+        }
+        text_contrast(self.foreground, self.background)
+    }
+}
+
+/// Contrast of the text color against the color behind it, translucent colors are tested over both black and white so the worse case is returned
+fn text_contrast(foreground: Color, background: Color) -> f32 {
+    let over = |color: Color, base: Color| {
+        let mix = |c: f32, b: f32| c * color.a + b * (1.0 - color.a);
+        Color::from_rgb(
+            mix(color.r, base.r),
+            mix(color.g, base.g),
+            mix(color.b, base.b),
+        )
+    };
+    [Color::BLACK, Color::WHITE]
+        .map(|base| {
+            let background = over(background, base);
+            contrast_ratio(over(foreground, background), background)
+        })
+        .into_iter()
+        .fold(f32::MAX, f32::min)
+}
+
+/// Black or white, whichever stands out more from the text color
+fn halo_color(foreground: Color) -> Color {
+    if contrast_ratio(foreground, Color::BLACK) >= contrast_ratio(foreground, Color::WHITE) {
+        Color::BLACK
+    } else {
+        Color::WHITE
+    }
+}
+
+impl<'a> Modifier<'a> for Badge {
+    type Message = BadgeMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &ProgramData,
+        wdata: &WorkspaceData,
+    ) -> ModifierOperation {
+        match &self.image {
+            Some(image)
+                if image.width() == wdata.export_size.width
+                    && image.height() == wdata.export_size.height =>
+            {
+                ImageOperation::Blend {
+                    overlay: image.clone(),
+                }
+                .into()
+            }
+            _ => ModifierOperation::None,
+        }
+    }
+
+    fn create(_pdata: &ProgramData, wdata: &WorkspaceData) -> (Command<Self::Message>, Self) {
+        let badge = Self {
+            style: BadgeStyle {
+                text: String::from("1"),
+                corner: Corner::default(),
+                shape: BadgeShape::default(),
+                size: 0.25,
+                background: Color::from_rgb8(30, 30, 30),
+                foreground: Color::WHITE,
+                halo: false,
+            },
+            image: None,
+            surroundings: Arc::new(Vec::new()),
+            dirty: false,
+        };
+        (badge.render(wdata), badge)
+    }
+
+    fn label() -> &'static str {
+        "Badge"
+    }
+
+    fn tooltip() -> &'static str {
+        "Draws a small badge with a number or short text in a corner of the token"
+    }
+
+    fn order_hint() -> OrderHint {
+        OrderHint::Overlay
+    }
+
+    fn list_label(&self) -> String {
+        if self.style.text.is_empty() {
+            Self::label().to_string()
+        } else {
+            format!("{}: {}", Self::label(), self.style.text)
+        }
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("text", self.style.text.clone()),
+            ("corner", self.style.corner.to_string()),
+            ("shape", self.style.shape.to_string()),
+            ("size", self.style.size.to_string()),
+            ("background", color_to_hex(self.style.background)),
+            ("text color", color_to_hex(self.style.foreground)),
+            ("halo", self.style.halo.to_string()),
+        ]
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        _pdata: &mut ProgramData,
+        wdata: &mut WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            BadgeMessage::SetText(text) => {
+                self.style.text = text.chars().take(MAX_TEXT_LENGTH).collect();
+                self.render(wdata)
+            }
+            BadgeMessage::SetCorner(corner) => {
+                self.style.corner = corner;
+                self.render(wdata)
+            }
+            BadgeMessage::SetShape(shape) => {
+                self.style.shape = shape;
+                self.render(wdata)
+            }
+            BadgeMessage::SetSize(size) => {
+                self.style.size = size;
+                self.render(wdata)
+            }
+            BadgeMessage::SetBackground(color) => {
+                self.style.background = color;
+                self.render(wdata)
+            }
+            BadgeMessage::SetForeground(color) => {
+                self.style.foreground = color;
+                self.render(wdata)
+            }
+            BadgeMessage::SetHalo(halo) => {
+                self.style.halo = halo;
+                self.render(wdata)
+            }
+            BadgeMessage::Rendered(style, image, surroundings) => {
+                // results of older settings arriving late would undo the latest change
+                if style == self.style {
+                    self.image = Some(image);
+                    self.surroundings = surroundings;
+                    self.dirty = true;
+                }
+                Command::none()
+            }
+        }
+    }
+
+    fn properties_view(
+        &'a self,
+        pdata: &'a ProgramData,
+        wdata: &'a WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        let color = |label: &'static str, color: Color, message: fn(Color) -> BadgeMessage| {
+            row![
+                text(label).width(Length::Fill),
+                ColorPicker::new(color, message)
+                    .recent(&pdata.recent_colors)
+                    .width(Length::Fixed(32.0))
+                    .height(Length::Fixed(32.0)),
+                swatches(&wdata.palette, message),
+            ]
+            .spacing(4)
+            .align_items(Alignment::Center)
+        };
+
+        let ui = col![
+            row![
+                text("Text: ").width(Length::Fill),
+                text_input("Number", &self.style.text, BadgeMessage::SetText)
+                    .style(pdata.input_style())
+                    .width(Length::FillPortion(4)),
+            ]
+            .spacing(4)
+            .align_items(Alignment::Center),
+            row![
+                text("Corner: ").width(Length::Fill),
+                pick_list(
+                    &Corner::ALL[..],
+                    Some(self.style.corner),
+                    BadgeMessage::SetCorner
+                )
+                .width(Length::FillPortion(4)),
+            ]
+            .spacing(4)
+            .align_items(Alignment::Center),
+            BadgeShape::ALL.iter().fold(
+                row![text("Shape: ").width(Length::Fill)]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                |r, s| r.push(radio(
+                    s.to_string(),
+                    *s,
+                    Some(self.style.shape),
+                    BadgeMessage::SetShape
+                ))
+            ),
+            row![
+                tooltip(
+                    text("Size: ").width(Length::Fill),
+                    "Width of the badge compared to the token",
+                    tooltip::Position::Bottom,
+                )
+                .style(Style::Frame),
+                slider(0.1..=0.5, self.style.size, BadgeMessage::SetSize)
+                    .step(0.01)
+                    .width(Length::FillPortion(4)),
+            ]
+            .spacing(4)
+            .align_items(Alignment::Center),
+            color(
+                "Background: ",
+                self.style.background,
+                BadgeMessage::SetBackground
+            ),
+            color(
+                "Text color: ",
+                self.style.foreground,
+                BadgeMessage::SetForeground
+            ),
+            tooltip(
+                checkbox("Halo", self.style.halo, BadgeMessage::SetHalo),
+                "Surrounds the letters with a thin black or white line so they stand out from any background",
+                tooltip::Position::Bottom,
+            )
+            .style(Style::Frame),
+            self.contrast_view(wdata),
+        ]
+        .spacing(6);
+
+        Some(ui.into())
+    }
+
+    fn workspace_update(
+        &mut self,
+        _pdata: &ProgramData,
+        wdata: &WorkspaceData,
+    ) -> Command<Self::Message> {
+        match &self.image {
+            Some(image)
+                if image.width() == wdata.export_size.width
+                    && image.height() == wdata.export_size.height =>
+            {
+                Command::none()
+            }
+            _ => self.render(wdata),
+        }
+    }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(self)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &ProgramData,
+        wdata: &WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        *self = read_state(state)?;
+        Ok(self.render(wdata))
+    }
+}
+
+/// Draws the badge into a transparent image of the export size, returns it along with the pixels right next to the letters
+fn render_badge(style: &BadgeStyle, size: Size<u32>) -> (RgbaImage, Vec<(u32, u32)>) {
+    let mut image = RgbaImage::new(size.width, size.height);
+    let shorter = size.width.min(size.height) as f32;
+    let radius = shorter * style.size * 0.5;
+    let margin = shorter * 0.03;
+
+    let (left, top) = (radius + margin, radius + margin);
+    let (right, bottom) = (
+        size.width as f32 - radius - margin,
+        size.height as f32 - radius - margin,
+    );
+    let center = match style.corner {
+        Corner::TopLeft => Point::new(left, top),
+        Corner::TopRight => Point::new(right, top),
+        Corner::BottomLeft => Point::new(left, bottom),
+        Corner::BottomRight => Point::new(right, bottom),
+    };
+
+    // shape edges are smoothed by sampling each pixel multiple times
+    const SAMPLES: u32 = 4;
+    let x_range =
+        (center.x - radius).max(0.0) as u32..((center.x + radius).ceil() as u32).min(size.width);
+    let y_range =
+        (center.y - radius).max(0.0) as u32..((center.y + radius).ceil() as u32).min(size.height);
+    for y in y_range {
+        for x in x_range.clone() {
+            let mut inside = 0;
+            for sy in 0..SAMPLES {
+                for sx in 0..SAMPLES {
+                    let px = x as f32 + (sx as f32 + 0.5) / SAMPLES as f32;
+                    let py = y as f32 + (sy as f32 + 0.5) / SAMPLES as f32;
+                    if style
+                        .shape
+                        .contains((px - center.x) / radius, (py - center.y) / radius)
+                    {
+                        inside += 1;
+                    }
+                }
+            }
+            let coverage = inside as f32 / (SAMPLES * SAMPLES) as f32;
+            blend_pixel(image.get_pixel_mut(x, y), style.background, coverage);
+        }
+    }
+
+    if !style.text.is_empty() {
+        // text is shrunk when it would be wider than the shape
+        let mut height = radius * 1.1;
+        let width = text_width(&style.text, height);
+        let limit = radius * 1.3;
+        if width > limit {
+            height *= limit / width;
+        }
+        let center = match style.shape {
+            BadgeShape::Circle => center,
+            // the shield is wider at the top
+            BadgeShape::Shield => Point::new(center.x, center.y - radius * 0.1),
+        };
+        if style.halo {
+            let halo = halo_color(style.foreground);
+            let offset = (height * 0.06).max(1.0);
+            // the text drawn shifted in all eight directions leaves a line around the letters
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let shifted =
+                        Point::new(center.x + dx as f32 * offset, center.y + dy as f32 * offset);
+                    if (dx, dy) != (0, 0) {
+                        draw_text(&mut image, &style.text, shifted, height, halo);
+                    }
+                }
+            }
+        }
+        // letters are told apart from what's behind them by comparing the area before and after drawing them
+        let width = text_width(&style.text, height);
+        let area = Rectangle {
+            x: (center.x - width * 0.5 - 2.0).max(0.0) as u32,
+            y: (center.y - height * 0.5 - 2.0).max(0.0) as u32,
+            width: (width + 4.0).ceil() as u32,
+            height: (height + 4.0).ceil() as u32,
+        };
+        let before = crop_imm(&image, area.x, area.y, area.width, area.height).to_image();
+        draw_text(&mut image, &style.text, center, height, style.foreground);
+        let surroundings = letter_surroundings(&before, &image, area.x, area.y);
+        return (image, surroundings);
+    }
+
+    (image, Vec::new())
+}
+
+/// Lists pixels which didn't change by drawing the letters but touch a pixel that did
+///
+/// `before` is the area of the image around the text before drawing it, placed at `left` and `top` in `after`
+fn letter_surroundings(
+    before: &RgbaImage,
+    after: &RgbaImage,
+    left: u32,
+    top: u32,
+) -> Vec<(u32, u32)> {
+    let (width, height) = before.dimensions();
+    let letter = |x: i64, y: i64| {
+        x >= 0
+            && y >= 0
+            && x < width as i64
+            && y < height as i64
+            && before.get_pixel(x as u32, y as u32)
+                != after.get_pixel(left + x as u32, top + y as u32)
+    };
+    let mut surroundings = Vec::new();
+    for y in 0..height as i64 {
+        for x in 0..width as i64 {
+            if letter(x, y) {
+                continue;
+            }
+            let touches = (-1..=1).any(|dy| (-1..=1).any(|dx| letter(x + dx, y + dy)));
+            if touches {
+                surroundings.push((left + x as u32, top + y as u32));
+            }
+        }
+    }
+    surroundings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::convert::pixel_to_color;
+
+    /// Lowest contrast of the text against the pixels next to its letters in the rendered badge
+    fn rendered_contrast(style: &BadgeStyle) -> f32 {
+        let (image, surroundings) = render_badge(style, Size::new(200, 200));
+        assert!(!surroundings.is_empty());
+        surroundings
+            .iter()
+            .map(|&(x, y)| text_contrast(style.foreground, pixel_to_color(image.get_pixel(x, y))))
+            .fold(f32::MAX, f32::min)
+    }
+
+    #[test]
+    fn halo_raises_contrast() {
+        let mut style = BadgeStyle {
+            text: String::from("12"),
+            corner: Corner::TopLeft,
+            shape: BadgeShape::Circle,
+            size: 0.5,
+            background: Color::from_rgb(0.9, 0.9, 0.9),
+            foreground: Color::WHITE,
+            halo: false,
+        };
+        assert!(rendered_contrast(&style) < MIN_CONTRAST);
+        style.halo = true;
+        assert!(rendered_contrast(&style) >= MIN_CONTRAST);
+    }
+}
@@ -0,0 +1,181 @@
+use iced::{
+    widget::{button, column as col, horizontal_space, row, slider, text, tooltip},
+    Command, Length,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{ProgramData, WorkspaceData},
+    image::ImageOperation,
+    style::Style,
+};
+
+use super::{read_state, write_state, Modifier, ModifierOperation, OrderHint};
+
+/// Shifts hue, saturation and value of the image colors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColorBalance {
+    /// Rotation of hues around the color wheel in degrees
+    hue: f32,
+    /// Value added to saturation of the colors, from -1 to 1
+    saturation: f32,
+    /// Value added to brightness of the colors, from -1 to 1
+    value: f32,
+
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum ColorBalanceMessage {
+    SetHue(f32),
+    SetSaturation(f32),
+    SetValue(f32),
+    /// Sets all values back to leaving the image unchanged
+    Reset,
+}
+
+impl<'a> Modifier<'a> for ColorBalance {
+    type Message = ColorBalanceMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &ProgramData,
+        _wdata: &WorkspaceData,
+    ) -> ModifierOperation {
+        if self.hue == 0.0 && self.saturation == 0.0 && self.value == 0.0 {
+            return ModifierOperation::None;
+        }
+        ImageOperation::HsvAdjust {
+            hue: self.hue,
+            saturation: self.saturation,
+            value: self.value,
+        }
+        .into()
+    }
+
+    fn create(_pdata: &ProgramData, _wdata: &WorkspaceData) -> (Command<Self::Message>, Self) {
+        (
+            Command::none(),
+            Self {
+                hue: 0.0,
+                saturation: 0.0,
+                value: 0.0,
+                dirty: true,
+            },
+        )
+    }
+
+    fn label() -> &'static str {
+        "Color Balance"
+    }
+
+    fn tooltip() -> &'static str {
+        "Shifts hue, saturation and brightness of the image colors"
+    }
+
+    fn order_hint() -> OrderHint {
+        OrderHint::Any
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("hue", self.hue.to_string()),
+            ("saturation", self.saturation.to_string()),
+            ("value", self.value.to_string()),
+        ]
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        _pdata: &mut ProgramData,
+        _wdata: &mut WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            ColorBalanceMessage::SetHue(hue) => self.hue = hue,
+            ColorBalanceMessage::SetSaturation(saturation) => self.saturation = saturation,
+            ColorBalanceMessage::SetValue(value) => self.value = value,
+            ColorBalanceMessage::Reset => {
+                self.hue = 0.0;
+                self.saturation = 0.0;
+                self.value = 0.0;
+            }
+        }
+        self.dirty = true;
+        Command::none()
+    }
+
+    fn properties_view(
+        &'a self,
+        _pdata: &'a ProgramData,
+        _wdata: &'a WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        let hue = tooltip(
+            text(format!("Hue: {:.0}", self.hue)).width(Length::Fill),
+            "Turns all colors around the color wheel by the angle, at 120 reds become greens and greens become blues.",
+            tooltip::Position::Bottom,
+        )
+        .style(Style::Frame);
+
+        let slider_hue = slider(-180.0..=180.0, self.hue, ColorBalanceMessage::SetHue).step(1.0);
+        let slider_saturation = slider(
+            -1.0..=1.0,
+            self.saturation,
+            ColorBalanceMessage::SetSaturation,
+        )
+        .step(0.01);
+        let slider_value = slider(-1.0..=1.0, self.value, ColorBalanceMessage::SetValue).step(0.01);
+
+        Some(
+            col![
+                row![
+                    hue,
+                    slider_hue.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+                row![
+                    text(format!("Saturation: {:.2}", self.saturation)).width(Length::Fill),
+                    slider_saturation.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+                row![
+                    text(format!("Value: {:.2}", self.value)).width(Length::Fill),
+                    slider_value.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+                button("Reset").on_press(ColorBalanceMessage::Reset),
+            ]
+            .spacing(6)
+            .into(),
+        )
+    }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(self)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &ProgramData,
+        _wdata: &WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        *self = Self {
+            dirty: true,
+            ..read_state(state)?
+        };
+        Ok(Command::none())
+    }
+}
@@ -0,0 +1,132 @@
+use iced::{
+    widget::{horizontal_space, row, slider, text, tooltip},
+    Command, Length,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{image::ImageOperation, style::Style};
+
+use super::{read_state, write_state, Modifier, OrderHint};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Defringe {
+    /// How many pixels deep into the semi-transparent edge colors are pulled
+    width: u32,
+
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum DefringeMessage {
+    SetWidth(u32),
+}
+
+impl<'a> Modifier<'a> for Defringe {
+    type Message = DefringeMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> super::ModifierOperation {
+        ImageOperation::Defringe { width: self.width }.into()
+    }
+
+    fn create(
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> (iced::Command<Self::Message>, Self) {
+        (
+            Command::none(),
+            Self {
+                width: 2,
+                dirty: true,
+            },
+        )
+    }
+
+    fn label() -> &'static str {
+        "Defringe"
+    }
+
+    fn tooltip() -> &'static str {
+        "Removes colored halos around semi-transparent edges of the image"
+    }
+
+    fn order_hint() -> OrderHint {
+        OrderHint::Any
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        vec![("width", self.width.to_string())]
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        _pdata: &mut crate::data::ProgramData,
+        _wdata: &mut crate::data::WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            DefringeMessage::SetWidth(width) => {
+                self.width = width;
+                self.dirty = true;
+            }
+        }
+        Command::none()
+    }
+
+    fn properties_view(
+        &'a self,
+        _pdata: &'a crate::data::ProgramData,
+        _wdata: &'a crate::data::WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        let label = tooltip(
+            text(format!("Width: {}", self.width)).width(Length::Fill),
+            "How many pixels deep into the edge the colors of opaque pixels are pulled.",
+            tooltip::Position::Bottom,
+        )
+        .style(Style::Frame);
+
+        let slider_width = slider(1.0..=8.0, self.width as f32, |x| {
+            DefringeMessage::SetWidth(x as u32)
+        })
+        .step(1.0);
+
+        Some(
+            row![
+                label,
+                slider_width.width(Length::FillPortion(4)),
+                horizontal_space(Length::FillPortion(2))
+            ]
+            .spacing(4)
+            .into(),
+        )
+    }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(self)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        *self = Self {
+            dirty: true,
+            ..read_state(state)?
+        };
+        Ok(Command::none())
+    }
+}
@@ -0,0 +1,192 @@
+use iced::{
+    widget::{column as col, horizontal_space, row, slider, text, tooltip},
+    Alignment, Color, Command, Length, Point,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{ProgramData, WorkspaceData},
+    image::{convert::color_to_hex, ImageOperation},
+    style::Style,
+    widgets::{swatches, ColorPicker},
+};
+
+use super::{read_state, write_state, Modifier, ModifierOperation, OrderHint};
+
+/// Casts a shadow of the subject silhouette under the image, which makes it stand out on light backgrounds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DropShadow {
+    /// Distance of the shadow from the image, as a fraction of the image size
+    #[serde(with = "super::point_pair")]
+    offset: Point,
+    /// Blur radius as a fraction of the smaller side of the image
+    blur: f32,
+    /// Color of the shadow, its alpha is the shadow opacity
+    #[serde(with = "super::hex_color")]
+    color: Color,
+
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum DropShadowMessage {
+    SetOffsetX(f32),
+    SetOffsetY(f32),
+    SetBlur(f32),
+    SetOpacity(f32),
+    SetColor(Color),
+}
+
+impl<'a> Modifier<'a> for DropShadow {
+    type Message = DropShadowMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &ProgramData,
+        _wdata: &WorkspaceData,
+    ) -> ModifierOperation {
+        ImageOperation::DropShadow {
+            offset: self.offset,
+            blur: self.blur,
+            color: self.color,
+        }
+        .into()
+    }
+
+    fn create(_pdata: &ProgramData, _wdata: &WorkspaceData) -> (Command<Self::Message>, Self) {
+        (
+            Command::none(),
+            Self {
+                offset: Point::new(0.02, 0.03),
+                blur: 0.015,
+                color: Color::from_rgba(0.0, 0.0, 0.0, 0.5),
+                dirty: true,
+            },
+        )
+    }
+
+    fn label() -> &'static str {
+        "Drop Shadow"
+    }
+
+    fn tooltip() -> &'static str {
+        "Casts a shadow of the subject under the image so it stands out on light backgrounds"
+    }
+
+    fn order_hint() -> OrderHint {
+        OrderHint::Underlay
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("offset", format!("{},{}", self.offset.x, self.offset.y)),
+            ("blur", self.blur.to_string()),
+            ("color", color_to_hex(self.color)),
+        ]
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        _pdata: &mut ProgramData,
+        _wdata: &mut WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            DropShadowMessage::SetOffsetX(x) => self.offset.x = x,
+            DropShadowMessage::SetOffsetY(y) => self.offset.y = y,
+            DropShadowMessage::SetBlur(blur) => self.blur = blur,
+            DropShadowMessage::SetOpacity(opacity) => self.color.a = opacity,
+            DropShadowMessage::SetColor(color) => self.color = color,
+        }
+        self.dirty = true;
+        Command::none()
+    }
+
+    fn properties_view(
+        &'a self,
+        pdata: &'a ProgramData,
+        wdata: &'a WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        let offset = tooltip(
+            text("Offset:"),
+            "Moves the shadow away from the image, parts of the shadow moved past the edges of the image are cut off.",
+            tooltip::Position::Bottom,
+        )
+        .style(Style::Frame);
+
+        let slider_x = slider(-0.2..=0.2, self.offset.x, DropShadowMessage::SetOffsetX).step(0.005);
+        let slider_y = slider(-0.2..=0.2, self.offset.y, DropShadowMessage::SetOffsetY).step(0.005);
+        let slider_blur = slider(0.0..=0.1, self.blur, DropShadowMessage::SetBlur).step(0.001);
+        let slider_opacity =
+            slider(0.0..=1.0, self.color.a, DropShadowMessage::SetOpacity).step(0.01);
+
+        Some(
+            col![
+                offset,
+                row![
+                    text("Horizontal: ").width(Length::Fill),
+                    slider_x.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+                row![
+                    text("Vertical: ").width(Length::Fill),
+                    slider_y.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+                row![
+                    text("Blur: ").width(Length::Fill),
+                    slider_blur.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+                row![
+                    text("Opacity: ").width(Length::Fill),
+                    slider_opacity.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+                row![
+                    text("Color: ").width(Length::Fill),
+                    ColorPicker::new(self.color, DropShadowMessage::SetColor)
+                        .recent(&pdata.recent_colors)
+                        .width(Length::Fixed(32.0))
+                        .height(Length::Fixed(32.0)),
+                    swatches(&wdata.palette, DropShadowMessage::SetColor),
+                    horizontal_space(Length::FillPortion(4)),
+                ]
+                .spacing(4)
+                .align_items(Alignment::Center),
+            ]
+            .spacing(6)
+            .into(),
+        )
+    }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(self)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &ProgramData,
+        _wdata: &WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        *self = Self {
+            dirty: true,
+            ..read_state(state)?
+        };
+        Ok(Command::none())
+    }
+}
@@ -2,25 +2,37 @@ use std::sync::Arc;
 
 use iced::widget::{button, column as col, horizontal_space, row, slider, text, tooltip};
 use iced::{Command, Length, Point, Vector};
+use image::imageops::blur;
+use serde::{Deserialize, Serialize};
 
 use crate::image::convert::pixel_to_color;
 use crate::image::operations::flood_fill_mask;
 use crate::image::{GrayscaleImage, ImageOperation, RgbaImage};
 use crate::style::Style;
-use crate::widgets::PixelSampler;
+use crate::widgets::{collapsible, PixelSampler};
 
-use super::{Modifier, ModifierOperation};
+use super::{read_state, write_state, Modifier, ModifierOperation, OrderHint};
 
 #[derive(Debug, Clone)]
 pub struct FloodMask {
     mask: Option<Arc<GrayscaleImage>>,
+    /// Source image the mask was made from, the mask is made again when the workspace has a different one
+    masked_source: Option<Arc<RgbaImage>>,
     treshhold: f32,
     soft_border: f32,
+    /// Blur radius applied to the mask edges after filling
+    feather: f32,
+    /// Tolerance of red, green and blue channels relative to the threshold, higher values let the channel differ more
+    channel_tolerance: [f32; 3],
     start: Point,
 
     dirty: bool,
     picking_pixel: bool,
     rendering: bool,
+    /// Whatever the section with edge settings is expanded
+    show_edges: bool,
+    /// Whatever the section with channel tolerances is expanded
+    show_channels: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -31,6 +43,24 @@ pub enum FloodMaskMessage {
     GotMask(Arc<GrayscaleImage>),
     SetTolerance(f32),
     SetSoftBorder(f32),
+    SetFeather(f32),
+    /// Sets tolerance of the color channel on the index, red, green or blue
+    SetChannelTolerance(usize, f32),
+    ShowEdges(bool),
+    ShowChannels(bool),
+}
+
+/// Settings of the flood mask modifier saved along with the session
+#[derive(Serialize, Deserialize)]
+struct FloodMaskState {
+    threshold: f32,
+    soft_border: f32,
+    feather: f32,
+    channel_tolerance: [f32; 3],
+    #[serde(with = "super::point_pair")]
+    start: Point,
+    /// Whatever the starting pixel was picked, the mask is only made after that
+    picked: bool,
 }
 
 impl<'a> Modifier<'a> for FloodMask {
@@ -53,15 +83,7 @@ impl<'a> Modifier<'a> for FloodMask {
             }
             FloodMaskMessage::Picked(point) => {
                 self.start = point;
-                Command::perform(
-                    regenerate_mask(
-                        wdata.source.clone(),
-                        self.start,
-                        self.treshhold,
-                        self.soft_border,
-                    ),
-                    |x| FloodMaskMessage::GotMask(x),
-                )
+                self.regenerate(wdata)
             }
             FloodMaskMessage::SetTolerance(v) => {
                 self.treshhold = v;
@@ -69,15 +91,7 @@ impl<'a> Modifier<'a> for FloodMask {
                     return Command::none();
                 }
                 self.rendering = true;
-                Command::perform(
-                    regenerate_mask(
-                        wdata.source.clone(),
-                        self.start,
-                        self.treshhold,
-                        self.soft_border,
-                    ),
-                    |x| FloodMaskMessage::GotMask(x),
-                )
+                self.regenerate(wdata)
             }
             FloodMaskMessage::SetSoftBorder(v) => {
                 self.soft_border = v;
@@ -85,15 +99,34 @@ impl<'a> Modifier<'a> for FloodMask {
                     return Command::none();
                 }
                 self.rendering = true;
-                Command::perform(
-                    regenerate_mask(
-                        wdata.source.clone(),
-                        self.start,
-                        self.treshhold,
-                        self.soft_border,
-                    ),
-                    |x| FloodMaskMessage::GotMask(x),
-                )
+                self.regenerate(wdata)
+            }
+            FloodMaskMessage::SetFeather(v) => {
+                self.feather = v;
+                if self.rendering {
+                    return Command::none();
+                }
+                self.rendering = true;
+                self.regenerate(wdata)
+            }
+            FloodMaskMessage::SetChannelTolerance(channel, v) => {
+                let Some(tolerance) = self.channel_tolerance.get_mut(channel) else {
+                    return Command::none();
+                };
+                *tolerance = v;
+                if self.rendering {
+                    return Command::none();
+                }
+                self.rendering = true;
+                self.regenerate(wdata)
+            }
+            FloodMaskMessage::ShowEdges(show) => {
+                self.show_edges = show;
+                Command::none()
+            }
+            FloodMaskMessage::ShowChannels(show) => {
+                self.show_channels = show;
+                Command::none()
             }
             FloodMaskMessage::GotMask(mask) => {
                 self.mask = Some(mask);
@@ -119,6 +152,7 @@ impl<'a> Modifier<'a> for FloodMask {
         };
         let label_threshold = text("Threshold: ").width(Length::Fill);
         let label_edge = text("Soft Edge: ").width(Length::Fill);
+        let label_feather = text("Feather: ").width(Length::Fill);
 
         let label_threshold = tooltip(
             label_threshold,
@@ -134,6 +168,13 @@ impl<'a> Modifier<'a> for FloodMask {
         )
         .style(Style::Frame);
 
+        let label_feather = tooltip(
+            label_feather,
+            "Blurs the mask edges by the given number of pixels, independently of the colors.",
+            tooltip::Position::Bottom,
+        )
+        .style(Style::Frame);
+
         let slider_threshold = slider(0.0..=1.0, self.treshhold, |x| {
             FloodMaskMessage::SetTolerance(x)
         })
@@ -146,6 +187,32 @@ impl<'a> Modifier<'a> for FloodMask {
         .step(0.001)
         .width(Length::FillPortion(4));
 
+        let slider_feather = slider(0.0..=10.0, self.feather, |x| {
+            FloodMaskMessage::SetFeather(x)
+        })
+        .step(0.1)
+        .width(Length::FillPortion(4));
+
+        let channel = |index: usize, name: &str| {
+            row![
+                text(format!("{}: ", name)).width(Length::Fill),
+                slider(0.1..=3.0, self.channel_tolerance[index], move |x| {
+                    FloodMaskMessage::SetChannelTolerance(index, x)
+                })
+                .step(0.01)
+                .width(Length::FillPortion(4)),
+                horizontal_space(Length::FillPortion(2))
+            ]
+            .spacing(4)
+        };
+        let channels = col![channel(0, "Red"), channel(1, "Green"), channel(2, "Blue")].spacing(6);
+        let channels = tooltip(
+            channels,
+            "Scales the threshold for each color channel, raise a channel to let it differ more from the selected color or lower it to make it stricter.",
+            tooltip::Position::Top,
+        )
+        .style(Style::Frame);
+
         let ui = col![
             butt,
             row![
@@ -154,12 +221,32 @@ impl<'a> Modifier<'a> for FloodMask {
                 horizontal_space(Length::FillPortion(2))
             ]
             .spacing(4),
-            row![
-                label_edge,
-                slider_edge,
-                horizontal_space(Length::FillPortion(2))
-            ]
-            .spacing(4),
+            collapsible(
+                "Edges",
+                self.show_edges,
+                FloodMaskMessage::ShowEdges,
+                col![
+                    row![
+                        label_edge,
+                        slider_edge,
+                        horizontal_space(Length::FillPortion(2))
+                    ]
+                    .spacing(4),
+                    row![
+                        label_feather,
+                        slider_feather,
+                        horizontal_space(Length::FillPortion(2))
+                    ]
+                    .spacing(4),
+                ]
+                .spacing(6)
+            ),
+            collapsible(
+                "Channels",
+                self.show_channels,
+                FloodMaskMessage::ShowChannels,
+                channels
+            ),
         ]
         .spacing(6);
 
@@ -196,11 +283,9 @@ impl<'a> Modifier<'a> for FloodMask {
         if let Some(mask) = &self.mask {
             ImageOperation::MaskWithOffset {
                 mask: mask.clone(),
-                center: Point {
-                    x: wdata.source.width() as f32 * 0.5 - wdata.offset.x,
-                    y: wdata.source.height() as f32 * 0.5 - wdata.offset.y,
-                },
+                center: wdata.focus_point(),
                 size: wdata.zoom,
+                mirror: wdata.mirror,
             }
             .into()
         } else {
@@ -216,12 +301,17 @@ impl<'a> Modifier<'a> for FloodMask {
             Command::none(),
             Self {
                 mask: None,
+                masked_source: None,
                 treshhold: 0.1,
                 soft_border: 0.1,
+                feather: 0.0,
+                channel_tolerance: [1.0; 3],
                 start: Point::ORIGIN,
                 dirty: false,
                 rendering: false,
                 picking_pixel: true,
+                show_edges: false,
+                show_channels: false,
             },
         )
     }
@@ -234,6 +324,26 @@ impl<'a> Modifier<'a> for FloodMask {
         "Hides parts of the image spreading from selected point through similar colors"
     }
 
+    fn order_hint() -> OrderHint {
+        OrderHint::Source
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("start", format!("{},{}", self.start.x, self.start.y)),
+            ("threshold", self.treshhold.to_string()),
+            ("soft_border", self.soft_border.to_string()),
+            ("feather", self.feather.to_string()),
+            (
+                "channel_tolerance",
+                format!(
+                    "{},{},{}",
+                    self.channel_tolerance[0], self.channel_tolerance[1], self.channel_tolerance[2]
+                ),
+            ),
+        ]
+    }
+
     fn is_dirty(&self) -> bool {
         self.dirty
     }
@@ -241,6 +351,73 @@ impl<'a> Modifier<'a> for FloodMask {
     fn set_clean(&mut self) {
         self.dirty = false;
     }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(&FloodMaskState {
+            threshold: self.treshhold,
+            soft_border: self.soft_border,
+            feather: self.feather,
+            channel_tolerance: self.channel_tolerance,
+            start: self.start,
+            picked: self.mask.is_some(),
+        })
+    }
+
+    fn workspace_update(
+        &mut self,
+        _pdata: &crate::data::ProgramData,
+        wdata: &crate::data::WorkspaceData,
+    ) -> Command<Self::Message> {
+        let Some(source) = &self.masked_source else {
+            return Command::none();
+        };
+        if Arc::ptr_eq(source, &wdata.source) {
+            return Command::none();
+        }
+        // the mask was made for another image, like after copying settings or replacing the source
+        self.mask = None;
+        self.dirty = true;
+        self.rendering = true;
+        self.regenerate(wdata)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &crate::data::ProgramData,
+        wdata: &crate::data::WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        let saved: FloodMaskState = read_state(state)?;
+        self.treshhold = saved.threshold;
+        self.soft_border = saved.soft_border;
+        self.feather = saved.feather;
+        self.channel_tolerance = saved.channel_tolerance;
+        self.start = saved.start;
+        if !saved.picked {
+            return Ok(Command::none());
+        }
+        self.picking_pixel = false;
+        self.rendering = true;
+        Ok(self.regenerate(wdata))
+    }
+}
+
+impl FloodMask {
+    /// Returns a task producing the mask from current settings
+    fn regenerate(&mut self, wdata: &crate::data::WorkspaceData) -> Command<FloodMaskMessage> {
+        self.masked_source = Some(wdata.source.clone());
+        Command::perform(
+            regenerate_mask(
+                wdata.source.clone(),
+                self.start,
+                self.treshhold,
+                self.soft_border,
+                self.feather,
+                self.channel_tolerance,
+            ),
+            FloodMaskMessage::GotMask,
+        )
+    }
 }
 
 async fn regenerate_mask(
@@ -248,15 +425,20 @@ async fn regenerate_mask(
     starting: Point,
     tolerance: f32,
     soft_border: f32,
+    feather: f32,
+    channel_tolerance: [f32; 3],
 ) -> Arc<GrayscaleImage> {
+    // the point can lie outside of a smaller image the settings were copied to
     let start = Vector {
-        x: starting.x as u32,
-        y: starting.y as u32,
+        x: (starting.x as u32).min(image.width().saturating_sub(1)),
+        y: (starting.y as u32).min(image.height().saturating_sub(1)),
     };
     let range = tolerance.min(1.0).max(0.0).powi(2);
     let soft_border = soft_border.min(1.0).max(0.0).powi(2);
     let soft_border_range = range + soft_border;
     let color = pixel_to_color(image.get_pixel(start.x, start.y));
+    // differences are divided by the tolerance so a lenient channel counts less towards the distance
+    let [tr, tg, tb] = channel_tolerance.map(|x| x.max(0.01));
 
     let mask = flood_fill_mask(image.as_ref(), start, 255, |p| {
         let (r, g, b) = (
@@ -265,9 +447,9 @@ async fn regenerate_mask(
             p[2] as f32 / 255.0,
         );
 
-        let r = (r - color.r).abs().powi(2);
-        let g = (g - color.g).abs().powi(2);
-        let b = (b - color.b).abs().powi(2);
+        let r = ((r - color.r) / tr).powi(2);
+        let g = ((g - color.g) / tg).powi(2);
+        let b = ((b - color.b) / tb).powi(2);
         let len = r + g + b;
 
         if len < range {
@@ -282,5 +464,10 @@ async fn regenerate_mask(
         }
     });
 
-    Arc::new(mask)
+    if feather > 0.0 {
+        // blurring smooths out the edge geometry which color based soft border can't do on sharp edges
+        Arc::new(blur(&mask, feather))
+    } else {
+        Arc::new(mask)
+    }
 }
@@ -1,33 +1,44 @@
-use std::sync::Arc;
+use std::{collections::HashMap, fmt::Display, sync::Arc};
 
 use iced::{
     widget::{
-        button, column as col, container, radio, row, scrollable, scrollable::Properties, text,
-        tooltip, tooltip::Position, vertical_space,
+        button, checkbox, column as col, container, radio, row, scrollable, scrollable::Properties,
+        text, tooltip, tooltip::Position, vertical_space,
     },
-    Alignment, Color, Command, Length, Size,
+    Alignment, Color, Command, Element, Length, Renderer, Size,
 };
 
-use image::imageops::resize;
+use iced_native::image::Handle;
+use image::imageops::{resize, thumbnail, FilterType};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    data::{FrameImage, ProgramData, WorkspaceData},
+    data::{fit_size, FrameImage, ProgramData, WorkspaceData},
     persistence::PersistentKey,
     style::Style,
 };
 use crate::{
-    image::{GrayscaleImage, ImageOperation, RgbaImage},
-    widgets::ColorPicker,
+    image::{
+        convert::{color_to_hex, color_to_hsv, hsv_to_color, image_to_handle, pixel_to_color},
+        GrayscaleImage, ImageOperation, RgbaImage,
+    },
+    widgets::{swatches, ColorPicker},
 };
 
-use super::{Modifier, ModifierOperation};
+use super::{read_state, write_state, Modifier, ModifierOperation, OrderHint};
 
 #[derive(Debug, Clone)]
 pub enum FrameMessage {
-    /// Result of resizing the frame to expected export size
-    NewFrame(Arc<RgbaImage>, Option<Arc<GrayscaleImage>>),
+    /// Result of resizing the frame to expected export size, along with the safe area overlay
+    NewFrame(Arc<RgbaImage>, Option<Arc<GrayscaleImage>>, Handle),
     /// Changes the tint of the frame
     SetTint(Color),
+    /// Changes how the tint is applied to the frame
+    SetTintMode(TintMode),
+    /// Changes how the frame is scaled to the export size
+    SetResizeFilter(ResizeFilter),
+    /// Toggles limiting the export size to the resolution of the frame
+    SetKeepResolution(bool),
     /// Opens the frame selection screen
     OpenFrameSelect,
     /// Signals the user selected a frame
@@ -36,21 +47,67 @@ pub enum FrameMessage {
     CancelFrame,
     /// Updates the filter for the frame
     SetFilter(String),
+    /// Frame thumbnails with the tint and tint mode applied, keyed by frame identifier
+    TintedPreviews(Color, TintMode, Vec<(String, Handle)>),
+    /// Toggles pinning frames for comparison instead of selecting them right away
+    SetCompare(bool),
+    /// Pins the frame for comparison or unpins it if it already was
+    PinFrame(usize),
+    /// Renders of the image with each pinned frame applied, keyed by frame identifier
+    Compared(Color, TintMode, Vec<(String, Handle)>),
 }
 
+/// Largest size of the renders shown when comparing frames
+const COMPARE_SIZE: u32 = 320;
+
+/// Number of frames that can be pinned for comparison at the same time
+const COMPARE_COUNT: usize = 2;
+
 /// Frame modifier draws a frame around the image, optionally masking out any part that would stick out
 #[derive(Clone, Debug, Default)]
 pub struct Frame {
     /// Frame image to be put onto the source image
     image: Option<Arc<RgbaImage>>,
     mask: Option<Arc<GrayscaleImage>>,
+    /// Overlay outlining area of the image which isn't covered by the frame
+    safe_area: Option<Handle>,
+    /// Identifier of the selected frame
+    frame_id: Option<String>,
     dirty: bool,
     select_frame: bool,
     tint: Color,
+    tint_mode: TintMode,
+    /// Interpolation used when scaling the frame to the export size
+    resize_filter: ResizeFilter,
+    /// Whatever the export size is limited to the frame resolution so the frame is never scaled up
+    keep_resolution: bool,
     filter: String,
 
     source: Option<Arc<RgbaImage>>,
     source_mask: Option<Arc<GrayscaleImage>>,
+
+    /// Frame thumbnails for the frame selection with the tint applied, keyed by frame identifier
+    previews: HashMap<String, Handle>,
+    /// Tint and tint mode the thumbnails were made with
+    previews_tint: Option<(Color, TintMode)>,
+
+    /// Whatever clicking frames in frame selection pins them for comparison
+    compare: bool,
+    /// Identifiers of frames pinned for comparison, in order they were pinned
+    pinned: Vec<String>,
+    /// Image rendered with each of the pinned frames, keyed by frame identifier
+    comparison: Vec<(String, Handle)>,
+}
+
+/// Settings of the frame modifier saved along with the session
+#[derive(Serialize, Deserialize)]
+struct FrameState {
+    frame_id: Option<String>,
+    #[serde(with = "super::hex_color")]
+    tint: Color,
+    tint_mode: TintMode,
+    resize_filter: ResizeFilter,
+    keep_resolution: bool,
 }
 
 impl<'a> Modifier<'a> for Frame {
@@ -59,6 +116,11 @@ impl<'a> Modifier<'a> for Frame {
     fn create(pdata: &ProgramData, wdata: &WorkspaceData) -> (Command<Self::Message>, Self) {
         let mut s = Self {
             tint: Color::WHITE,
+            keep_resolution: pdata
+                .cache
+                .get(PersistentData::KeepResolution, wdata.template)
+                .and_then(|x| x.check_bool())
+                .unwrap_or(false),
             ..Default::default()
         };
         let c = if let Some(frame) = pdata
@@ -67,10 +129,10 @@ impl<'a> Modifier<'a> for Frame {
             .and_then(|x| x.check_string())
         {
             match pdata.available_frames.iter().find(|x| x.id() == frame) {
-                Some(f) => s.set_frame(f, wdata),
+                Some(f) => s.set_frame(f, pdata, wdata),
                 None => {
                     s.select_frame = true;
-                    Command::none()
+                    s.request_previews(pdata)
                 }
             }
         } else {
@@ -87,6 +149,22 @@ impl<'a> Modifier<'a> for Frame {
         "Applies a frame around your image, masking out parts that would go outside of it"
     }
 
+    fn order_hint() -> OrderHint {
+        OrderHint::Overlay
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        let mut data = Vec::new();
+        if let Some(id) = &self.frame_id {
+            data.push(("frame", id.clone()));
+        }
+        data.push(("tint", color_to_hex(self.tint)));
+        data.push(("tint_mode", self.tint_mode.to_string()));
+        data.push(("resize_filter", self.resize_filter.to_string()));
+        data.push(("keep_resolution", self.keep_resolution.to_string()));
+        data
+    }
+
     fn is_dirty(&self) -> bool {
         self.dirty
     }
@@ -124,6 +202,20 @@ impl<'a> Modifier<'a> for Frame {
         self.select_frame
     }
 
+    fn overlay(&self, _pdata: &ProgramData, _wdata: &WorkspaceData) -> Option<Handle> {
+        self.safe_area.clone()
+    }
+
+    fn size_limit(&self, _pdata: &ProgramData, _wdata: &WorkspaceData) -> Option<Size<u32>> {
+        if !self.keep_resolution {
+            return None;
+        }
+        self.source.as_ref().map(|x| Size {
+            width: x.width(),
+            height: x.height(),
+        })
+    }
+
     fn properties_update(
         &mut self,
         message: Self::Message,
@@ -133,46 +225,110 @@ impl<'a> Modifier<'a> for Frame {
         match message {
             FrameMessage::OpenFrameSelect => {
                 self.select_frame = true;
-                Command::none()
+                self.request_previews(pdata)
             }
             FrameMessage::FrameSelected(index) => {
                 let Some(f) = pdata.available_frames.get(index) else {
                     return Command::none();
                 };
                 pdata.cache.set(PersistentData::ID, wdata.template, f.id());
-                self.set_frame(f, wdata)
+                let c = self.set_frame(f, pdata, wdata);
+                if self.is_low_resolution(wdata) {
+                    pdata.status.warning(
+                        "Selected frame is smaller than export size and will look blocky, consider using a higher resolution frame",
+                    );
+                }
+                c
             }
             FrameMessage::CancelFrame => {
                 self.select_frame = false;
+                self.end_comparison();
                 Command::none()
             }
-            FrameMessage::NewFrame(frame, mask) => {
+            FrameMessage::NewFrame(frame, mask, safe_area) => {
                 self.image = Some(frame);
                 self.mask = mask;
+                self.safe_area = Some(safe_area);
                 self.dirty = true;
                 self.select_frame = false;
                 Command::none()
             }
             FrameMessage::SetTint(c) => {
                 self.tint = c;
-                if let Some(frame) = &self.source {
-                    Command::perform(
-                        update_frame(
-                            frame.clone(),
-                            self.source_mask.clone(),
-                            self.tint,
-                            wdata.export_size,
-                        ),
-                        |x| FrameMessage::NewFrame(x.0, x.1),
-                    )
-                } else {
-                    Command::none()
+                Command::batch([
+                    self.update_tint(wdata),
+                    self.request_previews(pdata),
+                    self.request_comparison(pdata, wdata),
+                ])
+            }
+            FrameMessage::SetTintMode(m) => {
+                self.tint_mode = m;
+                Command::batch([
+                    self.update_tint(wdata),
+                    self.request_previews(pdata),
+                    self.request_comparison(pdata, wdata),
+                ])
+            }
+            FrameMessage::SetResizeFilter(f) => {
+                self.resize_filter = f;
+                if let Some(id) = &self.frame_id {
+                    pdata
+                        .cache
+                        .set(PersistentData::ResizeFilter, id.clone(), f.to_string());
+                }
+                self.update_tint(wdata)
+            }
+            FrameMessage::SetKeepResolution(keep) => {
+                self.keep_resolution = keep;
+                pdata
+                    .cache
+                    .set(PersistentData::KeepResolution, wdata.template, keep);
+                // the workspace shrinks the export size on its own, the frame only needs to follow it
+                Command::none()
+            }
+            FrameMessage::TintedPreviews(tint, mode, previews) => {
+                // results for a tint that was changed in the meantime are of no use
+                if tint == self.tint && mode == self.tint_mode {
+                    self.previews = previews.into_iter().collect();
+                    self.previews_tint = Some((tint, mode));
                 }
+                Command::none()
             }
             FrameMessage::SetFilter(f) => {
                 self.filter = f;
                 Command::none()
             }
+            FrameMessage::SetCompare(compare) => {
+                self.end_comparison();
+                self.compare = compare;
+                Command::none()
+            }
+            FrameMessage::PinFrame(index) => {
+                let Some(f) = pdata.available_frames.get(index) else {
+                    return Command::none();
+                };
+                if let Some(pinned) = self.pinned.iter().position(|x| x == f.id()) {
+                    self.pinned.remove(pinned);
+                } else {
+                    // pinning one frame too many replaces the one pinned first
+                    if self.pinned.len() >= COMPARE_COUNT {
+                        self.pinned.remove(0);
+                    }
+                    self.pinned.push(f.id().to_string());
+                }
+                self.comparison.clear();
+                self.request_comparison(pdata, wdata)
+            }
+            FrameMessage::Compared(tint, mode, comparison) => {
+                // results for frames or tint that were changed in the meantime are of no use
+                if tint == self.tint
+                    && mode == self.tint_mode
+                    && comparison.iter().map(|x| &x.0).eq(self.pinned.iter())
+                {
+                    self.comparison = comparison;
+                }
+                Command::none()
+            }
         }
     }
 
@@ -184,16 +340,19 @@ impl<'a> Modifier<'a> for Frame {
         let Some( frame ) = &self.image else {
             return Command::none();
         };
-        if frame.width() != wdata.export_size.width || frame.height() != wdata.export_size.height {
+        let size = self.frame_size(wdata);
+        if frame.width() != size.width || frame.height() != size.height {
             if let Some(source) = &self.source {
                 Command::perform(
                     update_frame(
                         source.clone(),
                         self.source_mask.clone(),
                         self.tint,
-                        wdata.export_size,
+                        self.tint_mode,
+                        self.resize_filter,
+                        size,
                     ),
-                    |x| FrameMessage::NewFrame(x.0, x.1),
+                    |x| FrameMessage::NewFrame(x.0, x.1, x.2),
                 )
             } else {
                 Command::none()
@@ -203,13 +362,60 @@ impl<'a> Modifier<'a> for Frame {
         }
     }
 
+    fn save_state(&self) -> Option<String> {
+        write_state(&FrameState {
+            frame_id: self.frame_id.clone(),
+            tint: self.tint,
+            tint_mode: self.tint_mode,
+            resize_filter: self.resize_filter,
+            keep_resolution: self.keep_resolution,
+        })
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        pdata: &ProgramData,
+        wdata: &WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        let saved: FrameState = read_state(state)?;
+        let Some(id) = saved.frame_id else {
+            *self = Self {
+                tint: saved.tint,
+                tint_mode: saved.tint_mode,
+                resize_filter: saved.resize_filter,
+                keep_resolution: saved.keep_resolution,
+                ..Default::default()
+            };
+            return Ok(Command::none());
+        };
+        let Some(frame) = pdata.available_frames.iter().find(|x| x.id() == id) else {
+            return Err(format!("frame {} is no longer available", id));
+        };
+        self.tint = saved.tint;
+        self.tint_mode = saved.tint_mode;
+        self.resize_filter = saved.resize_filter;
+        self.keep_resolution = saved.keep_resolution;
+        self.use_frame(frame);
+        Ok(self.update_tint(wdata))
+    }
+
     fn properties_view(
         &self,
-        _pdata: &ProgramData,
-        _wdata: &WorkspaceData,
+        pdata: &ProgramData,
+        wdata: &WorkspaceData,
     ) -> Option<iced::Element<Self::Message, iced::Renderer>> {
+        let mut properties = col![];
+        if self.is_low_resolution(wdata) {
+            properties = properties.push(
+                container(text("Frame resolution is lower than export size"))
+                    .padding(4)
+                    .width(Length::Fill)
+                    .style(Style::Danger),
+            );
+        }
         Some(
-            col![
+            properties.push(col![
                 button("Select Frame").on_press(FrameMessage::OpenFrameSelect),
 
                 row![
@@ -220,10 +426,35 @@ impl<'a> Modifier<'a> for Frame {
                     ).style(Style::Frame),
 
                     ColorPicker::new(self.tint, |c| FrameMessage::SetTint(c))
+                        .recent(&pdata.recent_colors)
                         .width(Length::Fixed(32.0))
                         .height(Length::Fixed(32.0)),
+                    swatches(&wdata.palette, FrameMessage::SetTint),
                 ].spacing(4).align_items(Alignment::Center),
+
+                TintMode::ALL.iter().fold(
+                    col![text("Tint Mode:")].spacing(4),
+                    |c, m| c.push(radio(m.to_string(), *m, Some(self.tint_mode), FrameMessage::SetTintMode))
+                ),
+
+                ResizeFilter::ALL.iter().fold(
+                    col![
+                        tooltip(
+                            text("Resize Filter:"),
+                            "How the frame is scaled to the export size. Nearest keeps pixel art frames crisp, the others smooth out photographic frames",
+                            Position::Bottom
+                        ).style(Style::Frame)
+                    ].spacing(4),
+                    |c, f| c.push(radio(f.to_string(), *f, Some(self.resize_filter), FrameMessage::SetResizeFilter))
+                ),
+
+                tooltip(
+                    checkbox("Keep frame resolution", self.keep_resolution, FrameMessage::SetKeepResolution),
+                    "Shrinks the export size to fit the frame instead of scaling a smaller frame up, keeping its edges crisp",
+                    Position::Bottom
+                ).style(Style::Frame),
             ]
+            .spacing(10))
             .spacing(10)
             .into(),
         )
@@ -271,16 +502,29 @@ impl<'a> Modifier<'a> for Frame {
                     .height(Length::Shrink);
             }
             row = row.push({
-                let img = iced::widget::image(img.preview()).content_fit(iced::ContentFit::Contain);
+                let style = if self.pinned.iter().any(|x| x == img.id()) {
+                    Style::Highlight
+                } else {
+                    Style::Frame
+                };
+                let preview = match self.previews.get(img.id()) {
+                    Some(tinted) if !self.is_tint_neutral() => tinted.clone(),
+                    _ => img.preview(),
+                };
+                let img = iced::widget::image(preview).content_fit(iced::ContentFit::Contain);
                 let img = container(img)
                     .center_x()
                     .center_y()
                     .width(Length::Fill)
                     .height(Length::Fill);
                 button(img)
-                    .on_press(FrameMessage::FrameSelected(total))
+                    .on_press(if self.compare {
+                        FrameMessage::PinFrame(total)
+                    } else {
+                        FrameMessage::FrameSelected(total)
+                    })
                     .width(Length::Fill)
-                    .style(Style::Frame.into())
+                    .style(style.into())
             });
             total += 1;
             count += 1;
@@ -322,36 +566,359 @@ impl<'a> Modifier<'a> for Frame {
         let filter = col![filter, vertical_space(10)];
         let filter = scrollable(filter).horizontal_scroll(Properties::default());
 
-        col![
-            row![
-                col![
-                    button("Cancel").on_press(FrameMessage::CancelFrame),
-                    vertical_space(10)
-                ],
-                filter,
-            ]
-            .align_items(Alignment::Center)
-            .spacing(10)
-            .padding(4)
-            .height(Length::Shrink),
-            scrollable(images).height(Length::Fill)
+        let compare = tooltip(
+            checkbox("Compare", self.compare, FrameMessage::SetCompare),
+            "Clicking frames pins them instead of selecting them, two pinned frames are shown side by side on your image",
+            Position::Bottom,
+        )
+        .style(Style::Frame);
+
+        let mut view = col![row![
+            col![
+                button("Cancel").on_press(FrameMessage::CancelFrame),
+                vertical_space(10)
+            ],
+            col![compare, vertical_space(10)],
+            filter,
         ]
-        .width(Length::Fill)
-        .into()
+        .align_items(Alignment::Center)
+        .spacing(10)
+        .padding(4)
+        .height(Length::Shrink)];
+
+        if self.compare {
+            view = view.push(self.comparison_view(pdata));
+        }
+
+        view.push(scrollable(images).height(Length::Fill))
+            .width(Length::Fill)
+            .into()
     }
 }
 
 impl Frame {
     /// Sets the frame image to be used within the frame. It returns a task to resize the frame image to the same size as expected export size
-    fn set_frame(&mut self, frame: &FrameImage, wdata: &WorkspaceData) -> Command<FrameMessage> {
+    ///
+    /// The resize filter last chosen for the frame is restored, frames without one use the default filter
+    fn set_frame(
+        &mut self,
+        frame: &FrameImage,
+        pdata: &ProgramData,
+        wdata: &WorkspaceData,
+    ) -> Command<FrameMessage> {
+        self.resize_filter = cached_resize_filter(pdata, frame.id());
+        self.use_frame(frame);
+        self.update_tint(wdata)
+    }
+
+    /// Makes the frame the source of the modifier without rendering it or changing the resize filter
+    fn use_frame(&mut self, frame: &FrameImage) {
         self.select_frame = false;
+        self.end_comparison();
+        self.frame_id = Some(frame.id().to_string());
         self.source = Some(frame.image());
         self.source_mask = frame.mask();
-        let mask = frame.mask();
-        let frame = frame.image();
+    }
+
+    /// Tests whatever the tint leaves the frame colors unchanged
+    fn is_tint_neutral(&self) -> bool {
+        self.tint == Color::WHITE && self.tint_mode == TintMode::Multiply
+    }
+
+    /// Returns a task producing tinted thumbnails for frame selection if the current ones don't match the tint
+    fn request_previews(&self, pdata: &ProgramData) -> Command<FrameMessage> {
+        if !self.select_frame
+            || self.is_tint_neutral()
+            || self.previews_tint == Some((self.tint, self.tint_mode))
+        {
+            return Command::none();
+        }
+        let frames: Vec<_> = pdata
+            .available_frames
+            .iter()
+            .map(|x| (x.id().to_string(), x.image()))
+            .collect();
+        let tint = self.tint;
+        let mode = self.tint_mode;
+        Command::perform(tint_previews(frames, tint, mode), move |x| {
+            FrameMessage::TintedPreviews(tint, mode, x)
+        })
+    }
+
+    /// Returns a task rendering the image with each of the pinned frames once enough frames are pinned
+    ///
+    /// Only the source image and the frames are rendered, other modifiers aren't applied in the comparison
+    fn request_comparison(
+        &self,
+        pdata: &ProgramData,
+        wdata: &WorkspaceData,
+    ) -> Command<FrameMessage> {
+        if self.pinned.len() < COMPARE_COUNT {
+            return Command::none();
+        }
+        let frames = self
+            .pinned
+            .iter()
+            .filter_map(|id| pdata.available_frames.iter().find(|x| x.id() == id))
+            .map(|x| (x.clone(), cached_resize_filter(pdata, x.id())))
+            .collect();
+        let art = ImageOperation::Begin {
+            image: wdata.source.clone(),
+            resolution: fit_size(
+                wdata.export_size,
+                Size {
+                    width: COMPARE_SIZE,
+                    height: COMPARE_SIZE,
+                },
+            ),
+            focus_point: wdata.focus_point(),
+            size: wdata.zoom,
+            mirror: wdata.mirror,
+        };
+        let tint = self.tint;
+        let mode = self.tint_mode;
+        let workers = pdata.render_workers();
+        Command::perform(compare_frames(art, frames, tint, mode, workers), move |x| {
+            FrameMessage::Compared(tint, mode, x)
+        })
+    }
+
+    /// Unpins all frames and leaves the comparison
+    fn end_comparison(&mut self) {
+        self.compare = false;
+        self.pinned.clear();
+        self.comparison.clear();
+    }
+
+    /// Shows the image rendered with each pinned frame side by side, with buttons to select either of them
+    fn comparison_view(&self, pdata: &ProgramData) -> Element<'_, FrameMessage, Renderer> {
+        if self.pinned.len() < COMPARE_COUNT {
+            return container(text(format!(
+                "Click {} frames to compare them on your image",
+                COMPARE_COUNT - self.pinned.len()
+            )))
+            .center_x()
+            .width(Length::Fill)
+            .padding(4)
+            .into();
+        }
+        self.pinned
+            .iter()
+            .filter_map(|id| {
+                pdata
+                    .available_frames
+                    .iter()
+                    .position(|x| x.id() == id)
+                    .map(|i| (i, &pdata.available_frames[i]))
+            })
+            .fold(
+                row![]
+                    .spacing(10)
+                    .padding(4)
+                    .height(Length::Fixed(COMPARE_SIZE as f32)),
+                |r, (index, frame)| {
+                    let preview: Element<FrameMessage, Renderer> =
+                        match self.comparison.iter().find(|x| x.0 == frame.id()) {
+                            Some((_, render)) => iced::widget::image(render.clone())
+                                .content_fit(iced::ContentFit::Contain)
+                                .width(Length::Fill)
+                                .height(Length::Fill)
+                                .into(),
+                            None => container(text("Rendering..."))
+                                .center_x()
+                                .center_y()
+                                .width(Length::Fill)
+                                .height(Length::Fill)
+                                .into(),
+                        };
+                    r.push(
+                        col![
+                            preview,
+                            text(frame.name()),
+                            button("Use This Frame").on_press(FrameMessage::FrameSelected(index)),
+                        ]
+                        .align_items(Alignment::Center)
+                        .spacing(4)
+                        .width(Length::Fill)
+                        .height(Length::Fill),
+                    )
+                },
+            )
+            .into()
+    }
+
+    /// Size the frame is scaled to, which is the export size unless it is limited by the frame resolution
+    ///
+    /// Workspace clamps its export size on its own but the frame may be resized before that happens
+    fn frame_size(&self, wdata: &WorkspaceData) -> Size<u32> {
+        match (self.keep_resolution, &self.source) {
+            (true, Some(source)) => fit_size(
+                wdata.export_size,
+                Size {
+                    width: source.width(),
+                    height: source.height(),
+                },
+            ),
+            _ => wdata.export_size,
+        }
+    }
+
+    /// Tests whatever the selected frame has lower resolution than the export size which would make it blocky once scaled up
+    ///
+    /// Frames that keep their resolution are never scaled up
+    fn is_low_resolution(&self, wdata: &WorkspaceData) -> bool {
+        let Some(frame) = &self.source else {
+            return false;
+        };
+        if self.keep_resolution {
+            return false;
+        }
+        frame.width() < wdata.export_size.width || frame.height() < wdata.export_size.height
+    }
+
+    /// Reapplies the tint and resizing to the source frame. Returns a task producing the tinted frame if there is a frame selected
+    fn update_tint(&self, wdata: &WorkspaceData) -> Command<FrameMessage> {
+        let Some(frame) = &self.source else {
+            return Command::none();
+        };
         Command::perform(
-            update_frame(frame, mask, self.tint, wdata.export_size),
-            |x| FrameMessage::NewFrame(x.0, x.1),
+            update_frame(
+                frame.clone(),
+                self.source_mask.clone(),
+                self.tint,
+                self.tint_mode,
+                self.resize_filter,
+                self.frame_size(wdata),
+            ),
+            |x| FrameMessage::NewFrame(x.0, x.1, x.2),
+        )
+    }
+}
+
+/// Determines how the tint color is combined with the frame pixels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TintMode {
+    /// Multiplies frame colors by the tint, can only darken the frame
+    #[default]
+    Multiply,
+    /// Darkens dark parts and brightens bright parts of the frame towards the tint
+    Overlay,
+    /// Replaces hue of the frame with hue of the tint, keeping saturation and brightness of the frame
+    ReplaceHue,
+    /// Uses hue and saturation of the tint and brightness of the frame
+    Colorize,
+}
+
+impl TintMode {
+    pub const ALL: [TintMode; 4] = [
+        TintMode::Multiply,
+        TintMode::Overlay,
+        TintMode::ReplaceHue,
+        TintMode::Colorize,
+    ];
+
+    /// Applies the tint to the color according to the mode
+    fn apply(&self, color: Color, tint: Color) -> Color {
+        match self {
+            TintMode::Multiply => Color {
+                r: color.r * tint.r,
+                g: color.g * tint.g,
+                b: color.b * tint.b,
+                a: color.a,
+            },
+            TintMode::Overlay => {
+                let overlay = |base: f32, blend: f32| {
+                    if base < 0.5 {
+                        2.0 * base * blend
+                    } else {
+                        1.0 - 2.0 * (1.0 - base) * (1.0 - blend)
+                    }
+                };
+                Color {
+                    r: overlay(color.r, tint.r),
+                    g: overlay(color.g, tint.g),
+                    b: overlay(color.b, tint.b),
+                    a: color.a,
+                }
+            }
+            TintMode::ReplaceHue => {
+                let (_, saturation, value) = color_to_hsv(color);
+                let (hue, ..) = color_to_hsv(tint);
+                Color {
+                    a: color.a,
+                    ..hsv_to_color(hue, saturation, value)
+                }
+            }
+            TintMode::Colorize => {
+                let (hue, saturation, _) = color_to_hsv(tint);
+                let luminance = color.r * 0.299 + color.g * 0.587 + color.b * 0.114;
+                Color {
+                    a: color.a,
+                    ..hsv_to_color(hue, saturation, luminance)
+                }
+            }
+        }
+    }
+}
+
+impl Display for TintMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                TintMode::Multiply => "Multiply",
+                TintMode::Overlay => "Overlay",
+                TintMode::ReplaceHue => "Replace Hue",
+                TintMode::Colorize => "Colorize",
+            }
+        )
+    }
+}
+
+/// Interpolation used to scale the frame and its mask to the export size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResizeFilter {
+    /// Keeps hard pixel edges, meant for pixel art frames
+    Nearest,
+    /// Smooth linear interpolation
+    #[default]
+    Triangle,
+    /// Sharper smooth interpolation, best for detailed photographic frames
+    Lanczos3,
+}
+
+impl ResizeFilter {
+    pub const ALL: [ResizeFilter; 3] = [
+        ResizeFilter::Nearest,
+        ResizeFilter::Triangle,
+        ResizeFilter::Lanczos3,
+    ];
+
+    /// Finds the filter by the name it is displayed and stored under
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.to_string() == name)
+    }
+
+    fn filter_type(&self) -> FilterType {
+        match self {
+            ResizeFilter::Nearest => FilterType::Nearest,
+            ResizeFilter::Triangle => FilterType::Triangle,
+            ResizeFilter::Lanczos3 => FilterType::Lanczos3,
+        }
+    }
+}
+
+impl Display for ResizeFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ResizeFilter::Nearest => "Nearest",
+                ResizeFilter::Triangle => "Triangle",
+                ResizeFilter::Lanczos3 => "Lanczos3",
+            }
         )
     }
 }
@@ -361,45 +928,240 @@ async fn update_frame(
     frame: Arc<RgbaImage>,
     mask: Option<Arc<GrayscaleImage>>,
     tint: Color,
+    mode: TintMode,
+    filter: ResizeFilter,
     size: Size<u32>,
-) -> (Arc<RgbaImage>, Option<Arc<GrayscaleImage>>) {
+) -> (Arc<RgbaImage>, Option<Arc<GrayscaleImage>>, Handle) {
     let mut frame = resize(
         frame.as_ref(),
         size.width,
         size.height,
-        image::imageops::FilterType::Nearest,
+        filter.filter_type(),
     );
 
-    frame.pixels_mut().filter(|x| x[3] > 0).for_each(|x| {
-        let r = (x[0] as f32 / u8::MAX as f32) * tint.r;
-        let g = (x[1] as f32 / u8::MAX as f32) * tint.g;
-        let b = (x[2] as f32 / u8::MAX as f32) * tint.b;
-        x[0] = (r * u8::MAX as f32) as u8;
-        x[1] = (g * u8::MAX as f32) as u8;
-        x[2] = (b * u8::MAX as f32) as u8;
-    });
+    tint_image(&mut frame, tint, mode);
 
-    if let Some(mask) = mask {
-        let mask = resize(
+    let mask = mask.map(|mask| {
+        Arc::new(resize(
             mask.as_ref(),
             size.width,
             size.height,
-            image::imageops::FilterType::Nearest,
-        );
-        (Arc::new(frame), Some(Arc::new(mask)))
-    } else {
-        (Arc::new(frame), None)
+            filter.filter_type(),
+        ))
+    });
+    let safe_area = image_to_handle(safe_area(&frame, mask.as_deref()));
+    (Arc::new(frame), mask, safe_area)
+}
+
+/// Resize filter last chosen for the frame, or the default one if there was none
+fn cached_resize_filter(pdata: &ProgramData, id: &str) -> ResizeFilter {
+    pdata
+        .cache
+        .get(PersistentData::ResizeFilter, id)
+        .and_then(|x| x.check_string())
+        .and_then(ResizeFilter::from_name)
+        .unwrap_or_default()
+}
+
+/// Renders the image with each of the frames applied, tinted and scaled the same way the frame modifier does
+async fn compare_frames(
+    art: ImageOperation,
+    frames: Vec<(FrameImage, ResizeFilter)>,
+    tint: Color,
+    mode: TintMode,
+    workers: usize,
+) -> Vec<(String, Handle)> {
+    let art = art.begin(workers).await;
+    let size = Size {
+        width: art.width(),
+        height: art.height(),
+    };
+    let mut renders = Vec::with_capacity(frames.len());
+    for (frame, filter) in frames {
+        let id = frame.id().to_string();
+        let (frame, mask, _) =
+            update_frame(frame.image(), frame.mask(), tint, mode, filter, size).await;
+        let mut image = art.clone();
+        if let Some(mask) = mask {
+            image = ImageOperation::Mask { mask }.perform(image, workers).await;
+        }
+        image = ImageOperation::Blend { overlay: frame }
+            .perform(image, workers)
+            .await;
+        renders.push((id, image_to_handle(image)));
     }
+    renders
+}
+
+/// Applies the tint to all visible pixels of the image
+fn tint_image(image: &mut RgbaImage, tint: Color, mode: TintMode) {
+    image.pixels_mut().filter(|x| x[3] > 0).for_each(|x| {
+        let c = mode.apply(pixel_to_color(x), tint);
+        x[0] = (c.r * u8::MAX as f32) as u8;
+        x[1] = (c.g * u8::MAX as f32) as u8;
+        x[2] = (c.b * u8::MAX as f32) as u8;
+    });
+}
+
+/// Creates small tinted versions of the frames for display in frame selection
+async fn tint_previews(
+    frames: Vec<(String, Arc<RgbaImage>)>,
+    tint: Color,
+    mode: TintMode,
+) -> Vec<(String, Handle)> {
+    const PREVIEW_SIZE: f32 = 160.0;
+    frames
+        .into_iter()
+        .map(|(id, frame)| {
+            let scale = (PREVIEW_SIZE / frame.width().max(frame.height()).max(1) as f32).min(1.0);
+            let mut preview = thumbnail(
+                frame.as_ref(),
+                ((frame.width() as f32 * scale) as u32).max(1),
+                ((frame.height() as f32 * scale) as u32).max(1),
+            );
+            tint_image(&mut preview, tint, mode);
+            (id, image_to_handle(preview))
+        })
+        .collect()
+}
+
+/// Creates an overlay outlining the part of the image that remains visible through the frame
+///
+/// Visible area is where the frame is transparent and the mask, if any, doesn't hide the image
+fn safe_area(frame: &RgbaImage, mask: Option<&GrayscaleImage>) -> RgbaImage {
+    const THRESHOLD: u8 = 128;
+    let (width, height) = frame.dimensions();
+    let visible = |x: i64, y: i64| -> bool {
+        if x < 0 || y < 0 || x >= width as i64 || y >= height as i64 {
+            return false;
+        }
+        let (x, y) = (x as u32, y as u32);
+        frame.get_pixel(x, y)[3] < THRESHOLD
+            && mask.is_none_or(|m| m.get_pixel(x, y)[0] >= THRESHOLD)
+    };
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let (x, y) = (x as i64, y as i64);
+        let inside = visible(x, y);
+        let edge = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+            .iter()
+            .any(|(x, y)| visible(*x, *y) != inside);
+        if !edge {
+            [0, 0, 0, 0].into()
+        } else if inside {
+            [0, 255, 255, 255].into()
+        } else {
+            [0, 0, 0, 255].into()
+        }
+    })
 }
 
 enum PersistentData {
     ID,
+    ResizeFilter,
+    KeepResolution,
 }
 
 impl PersistentKey for PersistentData {
     fn get_id(&self) -> &'static str {
         match self {
             PersistentData::ID => "modifier-frame",
+            PersistentData::ResizeFilter => "modifier-frame-resize-filter",
+            PersistentData::KeepResolution => "modifier-frame-keep-resolution",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Orange tint, its hue is 1/15 of the color wheel at full saturation
+    const TINT: Color = Color {
+        r: 1.0,
+        g: 0.4,
+        b: 0.0,
+        a: 1.0,
+    };
+
+    /// Frame with a warm pixel, a transparent one, a half transparent white one and a cool one
+    fn frame() -> RgbaImage {
+        RgbaImage::from_raw(
+            2,
+            2,
+            vec![
+                204, 102, 51, 255, //
+                0, 0, 0, 0, //
+                255, 255, 255, 128, //
+                51, 153, 102, 255,
+            ],
+        )
+        .unwrap()
+    }
+
+    /// Tints the test frame and compares it to the expected pixels, allowing for rounding of the channels
+    fn assert_tinted(mode: TintMode, expected: [[u8; 4]; 4]) {
+        let mut image = frame();
+        tint_image(&mut image, TINT, mode);
+        for (pixel, expected) in image.pixels().zip(expected) {
+            let close = pixel
+                .0
+                .iter()
+                .zip(expected)
+                .all(|(a, b)| a.abs_diff(b) <= 1);
+            assert!(close, "{mode}: got {:?}, expected {:?}", pixel.0, expected);
         }
     }
+
+    #[test]
+    fn multiply() {
+        assert_tinted(
+            TintMode::Multiply,
+            [
+                [204, 40, 0, 255],
+                [0, 0, 0, 0],
+                [255, 102, 0, 128],
+                [51, 61, 0, 255],
+            ],
+        );
+    }
+
+    #[test]
+    fn overlay() {
+        assert_tinted(
+            TintMode::Overlay,
+            [
+                [255, 81, 0, 255],
+                [0, 0, 0, 0],
+                [255, 255, 255, 128],
+                [102, 132, 0, 255],
+            ],
+        );
+    }
+
+    #[test]
+    fn replace_hue() {
+        assert_tinted(
+            TintMode::ReplaceHue,
+            [
+                [204, 112, 51, 255],
+                [0, 0, 0, 0],
+                [255, 255, 255, 128],
+                [153, 91, 51, 255],
+            ],
+        );
+    }
+
+    #[test]
+    fn colorize() {
+        assert_tinted(
+            TintMode::Colorize,
+            [
+                [126, 50, 0, 255],
+                [0, 0, 0, 0],
+                [255, 102, 0, 128],
+                [116, 46, 0, 255],
+            ],
+        );
+    }
 }
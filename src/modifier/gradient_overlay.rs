@@ -0,0 +1,237 @@
+use iced::{
+    widget::{button, column as col, horizontal_space, radio, row, slider, text, tooltip},
+    Alignment, Color, Command, Length,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{ProgramData, WorkspaceData},
+    image::{
+        convert::color_to_hex,
+        operations::{BlendMode, GradientShape},
+        ImageOperation,
+    },
+    style::Style,
+    widgets::{swatches, ColorPicker},
+};
+
+use super::{read_state, write_state, Modifier, ModifierOperation, OrderHint};
+
+/// Blends a gradient over the image, useful for lighting effects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradientOverlay {
+    shape: GradientShape,
+    /// Color at the start of a linear gradient or in the center of a radial one
+    #[serde(with = "super::hex_color")]
+    start: Color,
+    /// Color at the end of a linear gradient or in the corners of a radial one
+    #[serde(with = "super::hex_color")]
+    end: Color,
+    /// Direction of a linear gradient in degrees, at 0 it goes from the top to the bottom
+    angle: f32,
+    mode: BlendMode,
+    opacity: f32,
+
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum GradientOverlayMessage {
+    SetShape(GradientShape),
+    SetStart(Color),
+    SetEnd(Color),
+    SetAngle(f32),
+    SetMode(BlendMode),
+    SetOpacity(f32),
+    /// Exchanges the start and end colors
+    SwapColors,
+}
+
+impl<'a> Modifier<'a> for GradientOverlay {
+    type Message = GradientOverlayMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &ProgramData,
+        _wdata: &WorkspaceData,
+    ) -> ModifierOperation {
+        ImageOperation::GradientOverlay {
+            shape: self.shape,
+            start: self.start,
+            end: self.end,
+            angle: self.angle,
+            mode: self.mode,
+            opacity: self.opacity,
+        }
+        .into()
+    }
+
+    fn create(_pdata: &ProgramData, _wdata: &WorkspaceData) -> (Command<Self::Message>, Self) {
+        (
+            Command::none(),
+            Self {
+                shape: GradientShape::Linear,
+                start: Color::WHITE,
+                end: Color::BLACK,
+                angle: 0.0,
+                mode: BlendMode::SoftLight,
+                opacity: 0.3,
+                dirty: true,
+            },
+        )
+    }
+
+    fn label() -> &'static str {
+        "Gradient Overlay"
+    }
+
+    fn tooltip() -> &'static str {
+        "Blends a gradient over the image, like light falling on it from one side"
+    }
+
+    fn order_hint() -> OrderHint {
+        OrderHint::Any
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("shape", self.shape.to_string()),
+            ("start", color_to_hex(self.start)),
+            ("end", color_to_hex(self.end)),
+            ("angle", self.angle.to_string()),
+            ("mode", self.mode.to_string()),
+            ("opacity", self.opacity.to_string()),
+        ]
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        _pdata: &mut ProgramData,
+        _wdata: &mut WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            GradientOverlayMessage::SetShape(shape) => self.shape = shape,
+            GradientOverlayMessage::SetStart(color) => self.start = color,
+            GradientOverlayMessage::SetEnd(color) => self.end = color,
+            GradientOverlayMessage::SetAngle(angle) => self.angle = angle,
+            GradientOverlayMessage::SetMode(mode) => self.mode = mode,
+            GradientOverlayMessage::SetOpacity(opacity) => self.opacity = opacity,
+            GradientOverlayMessage::SwapColors => std::mem::swap(&mut self.start, &mut self.end),
+        }
+        self.dirty = true;
+        Command::none()
+    }
+
+    fn properties_view(
+        &'a self,
+        pdata: &'a ProgramData,
+        wdata: &'a WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        let shape = GradientShape::ALL.iter().fold(
+            row![text("Shape: ")]
+                .spacing(8)
+                .align_items(Alignment::Center),
+            |r, s| {
+                r.push(radio(
+                    s.to_string(),
+                    *s,
+                    Some(self.shape),
+                    GradientOverlayMessage::SetShape,
+                ))
+            },
+        );
+
+        let color = |label, color, message: fn(Color) -> GradientOverlayMessage| {
+            row![
+                text(label).width(Length::Fill),
+                ColorPicker::new(color, message)
+                    .recent(&pdata.recent_colors)
+                    .width(Length::Fixed(32.0))
+                    .height(Length::Fixed(32.0)),
+                swatches(&wdata.palette, message),
+                horizontal_space(Length::FillPortion(4)),
+            ]
+            .spacing(4)
+            .align_items(Alignment::Center)
+        };
+
+        let mut ui = col![
+            shape,
+            color("Start: ", self.start, GradientOverlayMessage::SetStart),
+            color("End: ", self.end, GradientOverlayMessage::SetEnd),
+            button("Swap Colors").on_press(GradientOverlayMessage::SwapColors),
+        ]
+        .spacing(6);
+
+        if self.shape == GradientShape::Linear {
+            ui = ui.push(
+                row![
+                    tooltip(
+                        text(format!("Angle: {:.0}", self.angle)).width(Length::Fill),
+                        "Direction of the gradient, at 0 the start color is at the top and the end color at the bottom",
+                        tooltip::Position::Bottom,
+                    )
+                    .style(Style::Frame),
+                    slider(0.0..=360.0, self.angle, GradientOverlayMessage::SetAngle)
+                        .step(1.0)
+                        .width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+            );
+        }
+
+        let mode = BlendMode::ALL.iter().fold(
+            col![tooltip(
+                text("Blend Mode:"),
+                "How the gradient colors are combined with the image. Multiply darkens, Screen brightens, Overlay and Soft Light do both with middle gray leaving the image unchanged",
+                tooltip::Position::Bottom,
+            )
+            .style(Style::Frame)]
+            .spacing(4),
+            |c, m| c.push(radio(m.to_string(), *m, Some(self.mode), GradientOverlayMessage::SetMode)),
+        );
+
+        Some(
+            ui.push(
+                row![
+                    text("Opacity: ").width(Length::Fill),
+                    slider(0.0..=1.0, self.opacity, GradientOverlayMessage::SetOpacity)
+                        .step(0.01)
+                        .width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+            )
+            .push(mode)
+            .into(),
+        )
+    }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(self)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &ProgramData,
+        _wdata: &WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        *self = Self {
+            dirty: true,
+            ..read_state(state)?
+        };
+        Ok(Command::none())
+    }
+}
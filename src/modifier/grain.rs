@@ -0,0 +1,188 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use iced::{
+    widget::{
+        button, checkbox, column as col, horizontal_space, row, slider, text, text_input, tooltip,
+    },
+    Alignment, Command, Length,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{image::ImageOperation, style::Style};
+
+use super::{read_state, write_state, Modifier, OrderHint};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Grain {
+    intensity: f32,
+    monochrome: bool,
+    seed: u64,
+    /// Carrier for the seed, allowing the user to type in text which isn't a valid number yet
+    #[serde(skip)]
+    seed_carrier: String,
+
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum GrainMessage {
+    SetIntensity(f32),
+    SetMonochrome(bool),
+    SetSeed(String),
+    RandomizeSeed,
+}
+
+impl<'a> Modifier<'a> for Grain {
+    type Message = GrainMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> super::ModifierOperation {
+        ImageOperation::Noise {
+            intensity: self.intensity,
+            monochrome: self.monochrome,
+            seed: self.seed,
+        }
+        .into()
+    }
+
+    fn create(
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> (iced::Command<Self::Message>, Self) {
+        (
+            Command::none(),
+            Self {
+                intensity: 0.1,
+                monochrome: true,
+                seed: 1,
+                seed_carrier: String::from("1"),
+                dirty: true,
+            },
+        )
+    }
+
+    fn label() -> &'static str {
+        "Grain"
+    }
+
+    fn tooltip() -> &'static str {
+        "Adds film grain noise to the image"
+    }
+
+    fn order_hint() -> OrderHint {
+        OrderHint::Any
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("intensity", self.intensity.to_string()),
+            ("monochrome", self.monochrome.to_string()),
+            ("seed", self.seed.to_string()),
+        ]
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        _pdata: &mut crate::data::ProgramData,
+        _wdata: &mut crate::data::WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            GrainMessage::SetIntensity(intensity) => {
+                self.intensity = intensity;
+                self.dirty = true;
+            }
+            GrainMessage::SetMonochrome(monochrome) => {
+                self.monochrome = monochrome;
+                self.dirty = true;
+            }
+            GrainMessage::SetSeed(seed) => {
+                if let Ok(x) = seed.parse() {
+                    self.seed = x;
+                    self.dirty = true;
+                }
+                self.seed_carrier = seed;
+            }
+            GrainMessage::RandomizeSeed => {
+                self.seed = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|x| x.as_nanos() as u64)
+                    .unwrap_or_default()
+                    % 1_000_000;
+                self.seed_carrier = self.seed.to_string();
+                self.dirty = true;
+            }
+        }
+        Command::none()
+    }
+
+    fn properties_view(
+        &'a self,
+        pdata: &'a crate::data::ProgramData,
+        _wdata: &'a crate::data::WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        let slider_intensity =
+            slider(0.0..=1.0, self.intensity, GrainMessage::SetIntensity).step(0.01);
+
+        let seed = tooltip(
+            text("Seed: "),
+            "The same seed always produces the same grain, change it to get a different pattern.",
+            tooltip::Position::Bottom,
+        )
+        .style(Style::Frame);
+
+        Some(
+            col![
+                row![
+                    text("Intensity: ").width(Length::Fill),
+                    slider_intensity.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+                checkbox("Monochrome", self.monochrome, GrainMessage::SetMonochrome),
+                row![
+                    seed,
+                    text_input("Seed", &self.seed_carrier, GrainMessage::SetSeed)
+                        .style(pdata.input_style())
+                        .width(100),
+                    button("Randomize").on_press(GrainMessage::RandomizeSeed),
+                ]
+                .spacing(4)
+                .align_items(Alignment::Center),
+            ]
+            .spacing(6)
+            .into(),
+        )
+    }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(self)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        let saved: Self = read_state(state)?;
+        *self = Self {
+            seed_carrier: saved.seed.to_string(),
+            dirty: true,
+            ..saved
+        };
+        Ok(Command::none())
+    }
+}
@@ -2,22 +2,26 @@ use iced::{
     widget::{button, column as col, horizontal_space, row, slider, text, tooltip},
     Color, Command, Length, Vector,
 };
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    image::ImageOperation,
+    image::{convert::color_to_hex, ImageOperation},
     style::Style,
     widgets::{ColorPicker, PixelSampler},
 };
 
-use super::Modifier;
+use super::{read_state, write_state, Modifier, OrderHint};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Greenscreen {
+    #[serde(with = "super::hex_color")]
     color: Color,
     range: f32,
     blending: f32,
 
+    #[serde(skip)]
     dirty: bool,
+    #[serde(skip)]
     sampling_pixel: bool,
 }
 
@@ -71,6 +75,18 @@ impl<'a> Modifier<'a> for Greenscreen {
         "Hides parts of the image that match selected color"
     }
 
+    fn order_hint() -> OrderHint {
+        OrderHint::Source
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("color", color_to_hex(self.color)),
+            ("range", self.range.to_string()),
+            ("blending", self.blending.to_string()),
+        ]
+    }
+
     fn is_dirty(&self) -> bool {
         self.dirty
     }
@@ -134,10 +150,11 @@ impl<'a> Modifier<'a> for Greenscreen {
 
     fn properties_view(
         &'a self,
-        _pdata: &'a crate::data::ProgramData,
+        pdata: &'a crate::data::ProgramData,
         _wdata: &'a crate::data::WorkspaceData,
     ) -> Option<iced::Element<Self::Message, iced::Renderer>> {
         let picker = ColorPicker::new(self.color, |x| GreenscreenMessage::SetColor(x))
+            .recent(&pdata.recent_colors)
             .width(26)
             .height(26);
         let butt = if self.sampling_pixel {
@@ -202,4 +219,21 @@ impl<'a> Modifier<'a> for Greenscreen {
         });
         picker.into()
     }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(self)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        *self = Self {
+            dirty: true,
+            ..read_state(state)?
+        };
+        Ok(Command::none())
+    }
 }
@@ -0,0 +1,210 @@
+use iced::{
+    widget::{column as col, horizontal_space, row, slider, text, tooltip},
+    Command, Length,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{image::ImageOperation, style::Style, widgets::collapsible};
+
+use super::{read_state, write_state, Modifier, OrderHint};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroundShadow {
+    /// Vertical distance of the shadow center from the center of the image, as a fraction of image height
+    offset: f32,
+    /// Width of the shadow as a fraction of image width
+    width: f32,
+    /// Height of the shadow as a fraction of image height
+    height: f32,
+    /// Blur radius as a fraction of the smaller side of the image
+    blur: f32,
+    opacity: f32,
+
+    #[serde(skip)]
+    dirty: bool,
+    /// Whatever the section with size of the shadow is expanded
+    #[serde(skip)]
+    show_shape: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum GroundShadowMessage {
+    SetOffset(f32),
+    SetWidth(f32),
+    SetHeight(f32),
+    SetBlur(f32),
+    SetOpacity(f32),
+    ShowShape(bool),
+}
+
+impl<'a> Modifier<'a> for GroundShadow {
+    type Message = GroundShadowMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> super::ModifierOperation {
+        ImageOperation::GroundShadow {
+            offset: self.offset,
+            width: self.width,
+            height: self.height,
+            blur: self.blur,
+            opacity: self.opacity,
+        }
+        .into()
+    }
+
+    fn create(
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> (iced::Command<Self::Message>, Self) {
+        (
+            Command::none(),
+            Self {
+                offset: 0.35,
+                width: 0.7,
+                height: 0.15,
+                blur: 0.03,
+                opacity: 0.6,
+                dirty: true,
+                show_shape: false,
+            },
+        )
+    }
+
+    fn label() -> &'static str {
+        "Ground Shadow"
+    }
+
+    fn tooltip() -> &'static str {
+        "Adds a soft shadow under the subject so it looks like it stands on the map"
+    }
+
+    fn order_hint() -> OrderHint {
+        OrderHint::Underlay
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("offset", self.offset.to_string()),
+            ("width", self.width.to_string()),
+            ("height", self.height.to_string()),
+            ("blur", self.blur.to_string()),
+            ("opacity", self.opacity.to_string()),
+        ]
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        _pdata: &mut crate::data::ProgramData,
+        _wdata: &mut crate::data::WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            GroundShadowMessage::SetOffset(offset) => self.offset = offset,
+            GroundShadowMessage::SetWidth(width) => self.width = width,
+            GroundShadowMessage::SetHeight(height) => self.height = height,
+            GroundShadowMessage::SetBlur(blur) => self.blur = blur,
+            GroundShadowMessage::SetOpacity(opacity) => self.opacity = opacity,
+            GroundShadowMessage::ShowShape(show) => {
+                self.show_shape = show;
+                return Command::none();
+            }
+        }
+        self.dirty = true;
+        Command::none()
+    }
+
+    fn properties_view(
+        &'a self,
+        _pdata: &'a crate::data::ProgramData,
+        _wdata: &'a crate::data::WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        let offset = tooltip(
+            text("Offset: ").width(Length::Fill),
+            "Moves the shadow up or down from the center of the image.",
+            tooltip::Position::Bottom,
+        )
+        .style(Style::Frame);
+
+        let slider_offset =
+            slider(-0.5..=0.5, self.offset, GroundShadowMessage::SetOffset).step(0.01);
+        let slider_width = slider(0.05..=1.0, self.width, GroundShadowMessage::SetWidth).step(0.01);
+        let slider_height =
+            slider(0.02..=0.5, self.height, GroundShadowMessage::SetHeight).step(0.01);
+        let slider_blur = slider(0.0..=0.1, self.blur, GroundShadowMessage::SetBlur).step(0.001);
+        let slider_opacity =
+            slider(0.0..=1.0, self.opacity, GroundShadowMessage::SetOpacity).step(0.01);
+
+        Some(
+            col![
+                row![
+                    offset,
+                    slider_offset.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+                row![
+                    text("Blur: ").width(Length::Fill),
+                    slider_blur.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+                row![
+                    text("Opacity: ").width(Length::Fill),
+                    slider_opacity.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+                collapsible(
+                    "Shape",
+                    self.show_shape,
+                    GroundShadowMessage::ShowShape,
+                    col![
+                        row![
+                            text("Width: ").width(Length::Fill),
+                            slider_width.width(Length::FillPortion(4)),
+                            horizontal_space(Length::FillPortion(2))
+                        ]
+                        .spacing(4),
+                        row![
+                            text("Height: ").width(Length::Fill),
+                            slider_height.width(Length::FillPortion(4)),
+                            horizontal_space(Length::FillPortion(2))
+                        ]
+                        .spacing(4),
+                    ]
+                    .spacing(6)
+                ),
+            ]
+            .spacing(6)
+            .into(),
+        )
+    }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(self)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        *self = Self {
+            dirty: true,
+            ..read_state(state)?
+        };
+        Ok(Command::none())
+    }
+}
@@ -0,0 +1,159 @@
+use iced::{
+    widget::{column as col, horizontal_space, row, slider, text, tooltip},
+    Command, Length,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{image::ImageOperation, style::Style};
+
+use super::{read_state, write_state, Modifier, OrderHint};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Kaleidoscope {
+    /// How many mirrored segments the pattern has around the center
+    segments: u32,
+    /// Angle in degrees of the wedge the segments are made from
+    rotation: f32,
+
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum KaleidoscopeMessage {
+    SetSegments(u32),
+    SetRotation(f32),
+}
+
+impl<'a> Modifier<'a> for Kaleidoscope {
+    type Message = KaleidoscopeMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> super::ModifierOperation {
+        ImageOperation::Kaleidoscope {
+            segments: self.segments,
+            rotation: self.rotation,
+        }
+        .into()
+    }
+
+    fn create(
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> (iced::Command<Self::Message>, Self) {
+        (
+            Command::none(),
+            Self {
+                segments: 6,
+                rotation: 0.0,
+                dirty: true,
+            },
+        )
+    }
+
+    fn label() -> &'static str {
+        "Kaleidoscope"
+    }
+
+    fn tooltip() -> &'static str {
+        "Mirrors a slice of the image around its center into a symmetric pattern"
+    }
+
+    fn order_hint() -> OrderHint {
+        OrderHint::Source
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("segments", self.segments.to_string()),
+            ("rotation", self.rotation.to_string()),
+        ]
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        _pdata: &mut crate::data::ProgramData,
+        _wdata: &mut crate::data::WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            KaleidoscopeMessage::SetSegments(segments) => self.segments = segments,
+            KaleidoscopeMessage::SetRotation(rotation) => self.rotation = rotation,
+        }
+        self.dirty = true;
+        Command::none()
+    }
+
+    fn properties_view(
+        &'a self,
+        _pdata: &'a crate::data::ProgramData,
+        _wdata: &'a crate::data::WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        let segments = tooltip(
+            text(format!("Segments: {}", self.segments)).width(Length::Fill),
+            "How many times the slice of the image repeats around the center.",
+            tooltip::Position::Bottom,
+        )
+        .style(Style::Frame);
+        let rotation = tooltip(
+            text(format!("Rotation: {:.0}", self.rotation)).width(Length::Fill),
+            "Chooses which slice of the image the pattern is made from, turning the pattern.",
+            tooltip::Position::Bottom,
+        )
+        .style(Style::Frame);
+
+        let slider_segments = slider(2.0..=24.0, self.segments as f32, |x| {
+            KaleidoscopeMessage::SetSegments(x as u32)
+        })
+        .step(1.0);
+        let slider_rotation =
+            slider(0.0..=360.0, self.rotation, KaleidoscopeMessage::SetRotation).step(1.0);
+
+        Some(
+            col![
+                row![
+                    segments,
+                    slider_segments.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+                row![
+                    rotation,
+                    slider_rotation.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+            ]
+            .spacing(6)
+            .into(),
+        )
+    }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(self)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        *self = Self {
+            dirty: true,
+            ..read_state(state)?
+        };
+        Ok(Command::none())
+    }
+}
@@ -0,0 +1,305 @@
+use iced::{
+    widget::{button, column as col, row, slider, text, tooltip, Slider},
+    Command, Length,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    image::{operations::histogram_percentiles, ImageOperation},
+    style::Style,
+    widgets::collapsible,
+};
+
+use super::{read_state, write_state, Modifier, OrderHint};
+
+/// Fraction of the darkest and brightest values automatic levels clip away
+const AUTO_CLIP: f32 = 0.01;
+/// Smallest distance allowed between black and white points
+const MIN_RANGE: f32 = 0.01;
+/// Names of the color channels with their own points, in the order they are stored
+const CHANNEL_NAMES: [&str; 3] = ["Red", "Green", "Blue"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Levels {
+    black: f32,
+    white: f32,
+    /// Darkest value the image is compressed to after stretching
+    #[serde(default)]
+    output_black: f32,
+    /// Brightest value the image is compressed to after stretching
+    #[serde(default = "default_output_white")]
+    output_white: f32,
+    /// Black and white points of red, green and blue channels applied after the common ones
+    #[serde(default = "default_channels")]
+    channels: [(f32, f32); 3],
+
+    #[serde(skip)]
+    dirty: bool,
+    /// Whatever the section with black and white points is expanded
+    #[serde(skip)]
+    show_input: bool,
+    /// Whatever the section with output range is expanded
+    #[serde(skip)]
+    show_output: bool,
+    /// Whatever the section with points of separate channels is expanded
+    #[serde(skip)]
+    show_channels: bool,
+}
+
+fn default_output_white() -> f32 {
+    1.0
+}
+
+fn default_channels() -> [(f32, f32); 3] {
+    [(0.0, 1.0); 3]
+}
+
+#[derive(Debug, Clone)]
+pub enum LevelsMessage {
+    SetBlack(f32),
+    SetWhite(f32),
+    SetOutputBlack(f32),
+    SetOutputWhite(f32),
+    SetChannelBlack(usize, f32),
+    SetChannelWhite(usize, f32),
+    Auto,
+    ShowInput(bool),
+    ShowOutput(bool),
+    ShowChannels(bool),
+}
+
+impl<'a> Modifier<'a> for Levels {
+    type Message = LevelsMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> super::ModifierOperation {
+        ImageOperation::Levels {
+            black: self.black,
+            white: self.white,
+            channels: self.channels,
+            output: (self.output_black, self.output_white),
+        }
+        .into()
+    }
+
+    fn create(
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> (iced::Command<Self::Message>, Self) {
+        (
+            Command::none(),
+            Self {
+                black: 0.0,
+                white: 1.0,
+                output_black: 0.0,
+                output_white: 1.0,
+                channels: default_channels(),
+                dirty: true,
+                show_input: true,
+                show_output: false,
+                show_channels: false,
+            },
+        )
+    }
+
+    fn label() -> &'static str {
+        "Levels"
+    }
+
+    fn tooltip() -> &'static str {
+        "Stretches the tonal range of the image between black and white points"
+    }
+
+    fn order_hint() -> OrderHint {
+        OrderHint::Source
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        let [red, green, blue] = self.channels;
+        vec![
+            ("black", self.black.to_string()),
+            ("white", self.white.to_string()),
+            ("output_black", self.output_black.to_string()),
+            ("output_white", self.output_white.to_string()),
+            ("red", format!("{} {}", red.0, red.1)),
+            ("green", format!("{} {}", green.0, green.1)),
+            ("blue", format!("{} {}", blue.0, blue.1)),
+        ]
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        _pdata: &mut crate::data::ProgramData,
+        wdata: &mut crate::data::WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            LevelsMessage::SetBlack(black) => {
+                self.black = black.min(self.white - MIN_RANGE).max(0.0);
+                self.dirty = true;
+            }
+            LevelsMessage::SetWhite(white) => {
+                self.white = white.max(self.black + MIN_RANGE).min(1.0);
+                self.dirty = true;
+            }
+            LevelsMessage::SetOutputBlack(black) => {
+                self.output_black = black.clamp(0.0, 1.0);
+                self.dirty = true;
+            }
+            LevelsMessage::SetOutputWhite(white) => {
+                self.output_white = white.clamp(0.0, 1.0);
+                self.dirty = true;
+            }
+            LevelsMessage::SetChannelBlack(channel, black) => {
+                let (channel_black, channel_white) = &mut self.channels[channel];
+                *channel_black = black.min(*channel_white - MIN_RANGE).max(0.0);
+                self.dirty = true;
+            }
+            LevelsMessage::SetChannelWhite(channel, white) => {
+                let (channel_black, channel_white) = &mut self.channels[channel];
+                *channel_white = white.max(*channel_black + MIN_RANGE).min(1.0);
+                self.dirty = true;
+            }
+            LevelsMessage::ShowInput(show) => self.show_input = show,
+            LevelsMessage::ShowOutput(show) => self.show_output = show,
+            LevelsMessage::ShowChannels(show) => self.show_channels = show,
+            LevelsMessage::Auto => {
+                // fully transparent images have nothing to analyze
+                if let Some((black, white)) =
+                    histogram_percentiles(&wdata.source, AUTO_CLIP, 1.0 - AUTO_CLIP)
+                {
+                    if white - black >= MIN_RANGE {
+                        self.black = black;
+                        self.white = white;
+                    } else {
+                        // flat images keep the range centered on their only tone
+                        let center = ((black + white) * 0.5).clamp(MIN_RANGE, 1.0 - MIN_RANGE);
+                        self.black = center - MIN_RANGE * 0.5;
+                        self.white = center + MIN_RANGE * 0.5;
+                    }
+                    self.dirty = true;
+                }
+            }
+        }
+        Command::none()
+    }
+
+    fn properties_view(
+        &'a self,
+        _pdata: &'a crate::data::ProgramData,
+        _wdata: &'a crate::data::WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        let auto = tooltip(
+            button("Auto").on_press(LevelsMessage::Auto),
+            "Sets the black and white points from the histogram of the source image, ignoring the darkest and brightest 1% of it.",
+            tooltip::Position::Bottom,
+        )
+        .style(Style::Frame);
+
+        let slider_black = slider(0.0..=1.0, self.black, LevelsMessage::SetBlack);
+        let slider_white = slider(0.0..=1.0, self.white, LevelsMessage::SetWhite);
+        let slider_output_black =
+            slider(0.0..=1.0, self.output_black, LevelsMessage::SetOutputBlack);
+        let slider_output_white =
+            slider(0.0..=1.0, self.output_white, LevelsMessage::SetOutputWhite);
+
+        let channels = self.channels.iter().zip(CHANNEL_NAMES).enumerate().fold(
+            col![].spacing(6),
+            |column, (channel, (&(black, white), name))| {
+                column
+                    .push(text(name))
+                    .push(level_row(
+                        "Black: ",
+                        slider(0.0..=1.0, black, move |x| {
+                            LevelsMessage::SetChannelBlack(channel, x)
+                        }),
+                        black,
+                    ))
+                    .push(level_row(
+                        "White: ",
+                        slider(0.0..=1.0, white, move |x| {
+                            LevelsMessage::SetChannelWhite(channel, x)
+                        }),
+                        white,
+                    ))
+            },
+        );
+
+        Some(
+            col![
+                collapsible(
+                    "Input",
+                    self.show_input,
+                    LevelsMessage::ShowInput,
+                    col![
+                        auto,
+                        level_row("Black: ", slider_black, self.black),
+                        level_row("White: ", slider_white, self.white),
+                    ]
+                    .spacing(6)
+                ),
+                collapsible(
+                    "Output",
+                    self.show_output,
+                    LevelsMessage::ShowOutput,
+                    col![
+                        level_row("Black: ", slider_output_black, self.output_black),
+                        level_row("White: ", slider_output_white, self.output_white),
+                    ]
+                    .spacing(6)
+                ),
+                collapsible(
+                    "Channels",
+                    self.show_channels,
+                    LevelsMessage::ShowChannels,
+                    channels
+                ),
+            ]
+            .spacing(6)
+            .into(),
+        )
+    }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(self)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        *self = Self {
+            dirty: true,
+            show_input: true,
+            ..read_state(state)?
+        };
+        Ok(Command::none())
+    }
+}
+
+/// Row with a labeled slider and the value it is set to in 0 to 255 range
+fn level_row<'a>(
+    label: &'a str,
+    slider: Slider<'a, f32, LevelsMessage, iced::Renderer>,
+    value: f32,
+) -> iced::widget::Row<'a, LevelsMessage, iced::Renderer> {
+    row![
+        text(label).width(Length::Fill),
+        slider.step(0.001).width(Length::FillPortion(4)),
+        text(format!("{:.0}", value * 255.0)).width(Length::FillPortion(2)),
+    ]
+    .spacing(4)
+}
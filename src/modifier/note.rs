@@ -0,0 +1,102 @@
+use iced::{
+    widget::{column as col, text_input},
+    Command,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{read_state, write_state, Modifier, ModifierOperation};
+
+/// Note modifier doesn't change the image, it serves as a comment or a divider in the modifier list
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Note {
+    text: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum NoteMessage {
+    SetText(String),
+}
+
+impl<'a> Modifier<'a> for Note {
+    type Message = NoteMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> ModifierOperation {
+        ModifierOperation::None
+    }
+
+    fn create(
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> (Command<Self::Message>, Self) {
+        (Command::none(), Self::default())
+    }
+
+    fn label() -> &'static str {
+        "Note"
+    }
+
+    fn tooltip() -> &'static str {
+        "Doesn't change the image, allows you to annotate sections of the modifier list"
+    }
+
+    fn list_label(&self) -> String {
+        if self.text.is_empty() {
+            Self::label().to_string()
+        } else {
+            format!("# {}", self.text)
+        }
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        vec![("text", self.text.clone())]
+    }
+
+    fn is_dirty(&self) -> bool {
+        false
+    }
+
+    fn set_clean(&mut self) {}
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        _pdata: &mut crate::data::ProgramData,
+        _wdata: &mut crate::data::WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            NoteMessage::SetText(t) => {
+                self.text = t;
+                Command::none()
+            }
+        }
+    }
+
+    fn properties_view(
+        &'a self,
+        pdata: &'a crate::data::ProgramData,
+        _wdata: &'a crate::data::WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        Some(
+            col![text_input("Note", &self.text, NoteMessage::SetText).style(pdata.input_style())]
+                .into(),
+        )
+    }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(self)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &crate::data::ProgramData,
+        _wdata: &crate::data::WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        *self = read_state(state)?;
+        Ok(Command::none())
+    }
+}
@@ -0,0 +1,159 @@
+use iced::{
+    widget::{column as col, horizontal_space, row, slider, text, tooltip},
+    Alignment, Color, Command, Length,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{ProgramData, WorkspaceData},
+    image::{convert::color_to_hex, ImageOperation},
+    style::Style,
+    widgets::{swatches, ColorPicker},
+};
+
+use super::{read_state, write_state, Modifier, ModifierOperation, OrderHint};
+
+/// Traces the silhouette of the subject with a solid colored line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Outline {
+    /// Width of the outline in pixels
+    thickness: u32,
+    #[serde(with = "super::hex_color")]
+    color: Color,
+
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum OutlineMessage {
+    SetThickness(u32),
+    SetColor(Color),
+}
+
+impl<'a> Modifier<'a> for Outline {
+    type Message = OutlineMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &ProgramData,
+        _wdata: &WorkspaceData,
+    ) -> ModifierOperation {
+        ImageOperation::Outline {
+            thickness: self.thickness,
+            color: self.color,
+        }
+        .into()
+    }
+
+    fn create(_pdata: &ProgramData, _wdata: &WorkspaceData) -> (Command<Self::Message>, Self) {
+        (
+            Command::none(),
+            Self {
+                thickness: 4,
+                color: Color::WHITE,
+                dirty: true,
+            },
+        )
+    }
+
+    fn label() -> &'static str {
+        "Outline"
+    }
+
+    fn tooltip() -> &'static str {
+        "Draws a colored outline around the visible parts of the image"
+    }
+
+    fn order_hint() -> OrderHint {
+        OrderHint::Any
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("thickness", self.thickness.to_string()),
+            ("color", color_to_hex(self.color)),
+        ]
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        _pdata: &mut ProgramData,
+        _wdata: &mut WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            OutlineMessage::SetThickness(thickness) => self.thickness = thickness,
+            OutlineMessage::SetColor(color) => self.color = color,
+        }
+        self.dirty = true;
+        Command::none()
+    }
+
+    fn properties_view(
+        &'a self,
+        pdata: &'a ProgramData,
+        wdata: &'a WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        let label = tooltip(
+            text(format!("Thickness: {}", self.thickness)).width(Length::Fill),
+            "How many pixels away from the visible parts of the image the outline reaches.",
+            tooltip::Position::Bottom,
+        )
+        .style(Style::Frame);
+
+        let slider_thickness = slider(1.0..=20.0, self.thickness as f32, |x| {
+            OutlineMessage::SetThickness(x as u32)
+        })
+        .step(1.0);
+
+        Some(
+            col![
+                row![
+                    label,
+                    slider_thickness.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+                row![
+                    text("Color: ").width(Length::Fill),
+                    ColorPicker::new(self.color, OutlineMessage::SetColor)
+                        .recent(&pdata.recent_colors)
+                        .width(Length::Fixed(32.0))
+                        .height(Length::Fixed(32.0)),
+                    swatches(&wdata.palette, OutlineMessage::SetColor),
+                    horizontal_space(Length::FillPortion(4)),
+                ]
+                .spacing(4)
+                .align_items(Alignment::Center),
+            ]
+            .spacing(6)
+            .into(),
+        )
+    }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(self)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &ProgramData,
+        _wdata: &WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        *self = Self {
+            dirty: true,
+            ..read_state(state)?
+        };
+        Ok(Command::none())
+    }
+}
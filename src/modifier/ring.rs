@@ -0,0 +1,314 @@
+use std::sync::Arc;
+
+use iced::{
+    widget::{button, column as col, row, slider, text, tooltip},
+    Alignment, Color, Command, Length, Size,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{ProgramData, WorkspaceData},
+    image::{convert::color_to_hex, ImageOperation, RgbaImage},
+    style::Style,
+    widgets::{swatches, ColorPicker},
+};
+
+use super::{read_state, write_state, Modifier, ModifierOperation, OrderHint};
+
+/// Draws concentric rings along the edge of the token, useful for marking teams or conditions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ring {
+    /// Rings ordered from the outermost to the innermost
+    bands: Vec<RingBand>,
+    /// Distance of the outermost ring from the edge of the image, as a fraction of the token radius
+    margin: f32,
+    /// Rendered rings matching the export size
+    #[serde(skip)]
+    image: Option<Arc<RgbaImage>>,
+
+    #[serde(skip)]
+    dirty: bool,
+}
+
+/// Single ring of the modifier
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RingBand {
+    #[serde(with = "super::hex_color")]
+    color: Color,
+    /// Width of the ring as a fraction of the token radius
+    thickness: f32,
+}
+
+#[derive(Debug, Clone)]
+pub enum RingMessage {
+    AddRing,
+    RemoveRing(usize),
+    SetColor(usize, Color),
+    SetThickness(usize, f32),
+    SetMargin(f32),
+    /// Rendered rings along with the settings they were rendered with
+    Rendered(Vec<RingBand>, f32, Arc<RgbaImage>),
+}
+
+impl Ring {
+    /// Creates a task rendering the rings in the export size
+    fn render(&self, wdata: &WorkspaceData) -> Command<RingMessage> {
+        let bands = self.bands.clone();
+        let margin = self.margin;
+        let size = wdata.export_size;
+        Command::perform(
+            async move {
+                let image = render_rings(&bands, margin, size);
+                (bands, margin, Arc::new(image))
+            },
+            |x| RingMessage::Rendered(x.0, x.1, x.2),
+        )
+    }
+}
+
+impl<'a> Modifier<'a> for Ring {
+    type Message = RingMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &ProgramData,
+        wdata: &WorkspaceData,
+    ) -> ModifierOperation {
+        match &self.image {
+            Some(image)
+                if image.width() == wdata.export_size.width
+                    && image.height() == wdata.export_size.height
+                    && !self.bands.is_empty() =>
+            {
+                ImageOperation::Blend {
+                    overlay: image.clone(),
+                }
+                .into()
+            }
+            _ => ModifierOperation::None,
+        }
+    }
+
+    fn create(_pdata: &ProgramData, wdata: &WorkspaceData) -> (Command<Self::Message>, Self) {
+        let ring = Self {
+            bands: vec![RingBand {
+                color: Color::from_rgb8(200, 40, 40),
+                thickness: 0.06,
+            }],
+            margin: 0.0,
+            image: None,
+            dirty: false,
+        };
+        (ring.render(wdata), ring)
+    }
+
+    fn label() -> &'static str {
+        "Ring"
+    }
+
+    fn tooltip() -> &'static str {
+        "Draws colored rings along the edge of the token"
+    }
+
+    fn order_hint() -> OrderHint {
+        OrderHint::Overlay
+    }
+
+    fn list_label(&self) -> String {
+        match self.bands.len() {
+            1 => Self::label().to_string(),
+            count => format!("{} x{}", Self::label(), count),
+        }
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        let bands = self
+            .bands
+            .iter()
+            .map(|x| format!("{} {}", color_to_hex(x.color), x.thickness))
+            .collect::<Vec<_>>()
+            .join(", ");
+        vec![("margin", self.margin.to_string()), ("rings", bands)]
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        _pdata: &mut ProgramData,
+        wdata: &mut WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            RingMessage::AddRing => {
+                let color = self.bands.last().map_or(Color::WHITE, |x| x.color);
+                self.bands.push(RingBand {
+                    color,
+                    thickness: 0.04,
+                });
+                self.render(wdata)
+            }
+            RingMessage::RemoveRing(index) => {
+                if index < self.bands.len() {
+                    self.bands.remove(index);
+                }
+                // no rings means there is nothing to draw, the image has to be rerendered without them
+                self.dirty = true;
+                self.render(wdata)
+            }
+            RingMessage::SetColor(index, color) => {
+                if let Some(band) = self.bands.get_mut(index) {
+                    band.color = color;
+                }
+                self.render(wdata)
+            }
+            RingMessage::SetThickness(index, thickness) => {
+                if let Some(band) = self.bands.get_mut(index) {
+                    band.thickness = thickness;
+                }
+                self.render(wdata)
+            }
+            RingMessage::SetMargin(margin) => {
+                self.margin = margin;
+                self.render(wdata)
+            }
+            RingMessage::Rendered(bands, margin, image) => {
+                // results of older settings arriving late would undo the latest change
+                if bands == self.bands && margin == self.margin {
+                    self.image = Some(image);
+                    self.dirty = true;
+                }
+                Command::none()
+            }
+        }
+    }
+
+    fn properties_view(
+        &'a self,
+        pdata: &'a ProgramData,
+        wdata: &'a WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        let margin = row![
+            tooltip(
+                text("Margin: ").width(Length::Fill),
+                "Distance of the outermost ring from the edge of the token",
+                tooltip::Position::Bottom,
+            )
+            .style(Style::Frame),
+            slider(0.0..=0.5, self.margin, RingMessage::SetMargin)
+                .step(0.005)
+                .width(Length::FillPortion(4)),
+        ]
+        .spacing(4)
+        .align_items(Alignment::Center);
+
+        let bands = self
+            .bands
+            .iter()
+            .enumerate()
+            .fold(col![].spacing(6), |c, (i, band)| {
+                c.push(
+                    row![
+                        ColorPicker::new(band.color, move |x| RingMessage::SetColor(i, x))
+                            .recent(&pdata.recent_colors)
+                            .width(Length::Fixed(32.0))
+                            .height(Length::Fixed(32.0)),
+                        swatches(&wdata.palette, move |x| RingMessage::SetColor(i, x)),
+                        slider(0.005..=0.3, band.thickness, move |x| {
+                            RingMessage::SetThickness(i, x)
+                        })
+                        .step(0.005)
+                        .width(Length::Fill),
+                        button("Remove").on_press(RingMessage::RemoveRing(i)),
+                    ]
+                    .spacing(4)
+                    .align_items(Alignment::Center),
+                )
+            });
+
+        let ui = col![
+            margin,
+            text(format!("Rings: {}", self.bands.len())),
+            bands,
+            button("Add Ring").on_press(RingMessage::AddRing),
+        ]
+        .spacing(6);
+
+        Some(ui.into())
+    }
+
+    fn workspace_update(
+        &mut self,
+        _pdata: &ProgramData,
+        wdata: &WorkspaceData,
+    ) -> Command<Self::Message> {
+        match &self.image {
+            Some(image)
+                if image.width() == wdata.export_size.width
+                    && image.height() == wdata.export_size.height =>
+            {
+                Command::none()
+            }
+            _ => self.render(wdata),
+        }
+    }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(self)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &ProgramData,
+        wdata: &WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        *self = read_state(state)?;
+        Ok(self.render(wdata))
+    }
+}
+
+/// Draws the rings inward from the edge of the circle inscribed in the image
+fn render_rings(bands: &[RingBand], margin: f32, size: Size<u32>) -> RgbaImage {
+    let radius = size.width.min(size.height) as f32 * 0.5;
+    let (cx, cy) = (size.width as f32 * 0.5, size.height as f32 * 0.5);
+
+    // radii of each band, from the outer edge to the inner edge
+    let mut outer = radius * (1.0 - margin);
+    let radii: Vec<(f32, f32, Color)> = bands
+        .iter()
+        .map(|band| {
+            let inner = (outer - radius * band.thickness).max(0.0);
+            let r = (outer, inner, band.color);
+            outer = inner;
+            r
+        })
+        .collect();
+
+    RgbaImage::from_fn(size.width, size.height, |x, y| {
+        let distance = ((x as f32 + 0.5 - cx).powi(2) + (y as f32 + 0.5 - cy).powi(2)).sqrt();
+        let mut pixel = [0.0f32; 4];
+        for (outer, inner, color) in &radii {
+            // coverage gives the ring edges a pixel wide smooth transition
+            let coverage =
+                (outer - distance + 0.5).clamp(0.0, 1.0) * (distance - inner + 0.5).clamp(0.0, 1.0);
+            let alpha = color.a * coverage;
+            if alpha <= 0.0 {
+                continue;
+            }
+            let rest = pixel[3] * (1.0 - alpha);
+            let total = alpha + rest;
+            pixel[0] = (color.r * alpha + pixel[0] * rest) / total;
+            pixel[1] = (color.g * alpha + pixel[1] * rest) / total;
+            pixel[2] = (color.b * alpha + pixel[2] * rest) / total;
+            pixel[3] = total;
+        }
+        image::Rgba(pixel.map(|x| (x * u8::MAX as f32) as u8))
+    })
+}
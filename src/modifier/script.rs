@@ -0,0 +1,190 @@
+use std::sync::Arc;
+
+use iced::{
+    widget::{button, column as col, container, row, text, text_input, tooltip},
+    Alignment, Command, Length,
+};
+
+use crate::{
+    data::{ProgramData, WorkspaceData},
+    image::{script::PixelScript, ImageOperation},
+    style::Style,
+};
+
+use super::{read_state, write_state, Modifier, ModifierOperation, OrderHint};
+
+/// Names of the channels in order the script expressions are stored in
+const CHANNELS: [&str; 4] = ["Red", "Green", "Blue", "Alpha"];
+
+/// Transforms each pixel of the image with expressions written by the user
+#[derive(Debug, Clone)]
+pub struct Script {
+    /// Expressions as typed in by the user, for red, green, blue and alpha channels
+    sources: [String; 4],
+    /// Latest script in which all expressions were valid
+    script: Arc<PixelScript>,
+    /// Problems found in each of the expressions as they are typed in
+    errors: [Option<String>; 4],
+
+    dirty: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum ScriptMessage {
+    /// Sets expression for the channel at the index
+    SetExpression(usize, String),
+    /// Sets all expressions back to leaving the image unchanged
+    Reset,
+}
+
+impl<'a> Modifier<'a> for Script {
+    type Message = ScriptMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &ProgramData,
+        _wdata: &WorkspaceData,
+    ) -> ModifierOperation {
+        if self.script.is_identity() {
+            return ModifierOperation::None;
+        }
+        ImageOperation::Custom(self.script.clone()).into()
+    }
+
+    fn create(_pdata: &ProgramData, _wdata: &WorkspaceData) -> (Command<Self::Message>, Self) {
+        let sources = PixelScript::IDENTITY.map(String::from);
+        let script = PixelScript::parse(&sources).expect("Identity script has to be valid");
+        (
+            Command::none(),
+            Self {
+                sources,
+                script: Arc::new(script),
+                errors: Default::default(),
+                dirty: true,
+            },
+        )
+    }
+
+    fn label() -> &'static str {
+        "Script"
+    }
+
+    fn tooltip() -> &'static str {
+        "Calculates each pixel of the image with your own expressions"
+    }
+
+    fn order_hint() -> OrderHint {
+        OrderHint::Any
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("red", self.sources[0].clone()),
+            ("green", self.sources[1].clone()),
+            ("blue", self.sources[2].clone()),
+            ("alpha", self.sources[3].clone()),
+        ]
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        _pdata: &mut ProgramData,
+        _wdata: &mut WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            ScriptMessage::SetExpression(channel, expression) => {
+                let Some(source) = self.sources.get_mut(channel) else {
+                    return Command::none();
+                };
+                *source = expression;
+            }
+            ScriptMessage::Reset => self.sources = PixelScript::IDENTITY.map(String::from),
+        }
+        // the image keeps the last valid script while any of the expressions is being fixed
+        match PixelScript::parse(&self.sources) {
+            Ok(script) => {
+                self.script = Arc::new(script);
+                self.errors = Default::default();
+                self.dirty = true;
+            }
+            Err(errors) => self.errors = errors,
+        }
+        Command::none()
+    }
+
+    fn properties_view(
+        &'a self,
+        pdata: &'a ProgramData,
+        _wdata: &'a WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        let help = tooltip(
+            text("Expressions:"),
+            "Values: r, g, b, a are channels of the pixel from 0 to 1, x, y is its position from 0 to 1, w, h is the image size in pixels, pi\n\
+             Operators: + - * / % ^ < > <= >=, comparisons give 1 when true and 0 otherwise\n\
+             Functions: abs, min, max, clamp, mix, step, smoothstep, sqrt, pow, sin, cos, floor, fract, if",
+            tooltip::Position::Bottom,
+        )
+        .style(Style::Frame);
+
+        let ui = CHANNELS
+            .iter()
+            .zip(self.sources.iter().zip(self.errors.iter()))
+            .enumerate()
+            .fold(col![help].spacing(6), |c, (i, (label, (source, error)))| {
+                let c = c.push(
+                    row![
+                        text(format!("{label}: ")).width(Length::Fill),
+                        text_input(PixelScript::IDENTITY[i], source, move |x| {
+                            ScriptMessage::SetExpression(i, x)
+                        })
+                        .style(pdata.input_style())
+                        .width(Length::FillPortion(5)),
+                    ]
+                    .spacing(4)
+                    .align_items(Alignment::Center),
+                );
+                match error {
+                    Some(error) => c.push(
+                        container(text(error))
+                            .padding(4)
+                            .width(Length::Fill)
+                            .style(Style::Danger),
+                    ),
+                    None => c,
+                }
+            });
+
+        Some(
+            ui.push(button("Reset").on_press(ScriptMessage::Reset))
+                .into(),
+        )
+    }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(&self.sources)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &ProgramData,
+        _wdata: &WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        self.sources = read_state(state)?;
+        // expressions saved while they were being fixed come back along with their errors
+        match PixelScript::parse(&self.sources) {
+            Ok(script) => self.script = Arc::new(script),
+            Err(errors) => self.errors = errors,
+        }
+        Ok(Command::none())
+    }
+}
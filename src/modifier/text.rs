@@ -0,0 +1,277 @@
+use iced::{
+    widget::{
+        button, column as col, horizontal_space, radio, row, slider, text, text_input, tooltip,
+    },
+    Alignment, Color, Command, Length, Point, Vector,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{ProgramData, WorkspaceData},
+    image::{convert::color_to_hex, text::TextAlignment, ImageOperation},
+    style::Style,
+    widgets::{swatches, ColorPicker, PixelSampler},
+};
+
+use super::{read_state, write_state, Modifier, ModifierOperation, OrderHint};
+
+/// Draws a line of text over the image, like initials or a number labeling the token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Text {
+    text: String,
+    /// Height of the line as a fraction of the image height
+    size: f32,
+    #[serde(with = "super::hex_color")]
+    color: Color,
+    /// Position of the text relative to the center of the image, as a fraction of the image size
+    #[serde(with = "super::point_pair")]
+    offset: Point,
+    alignment: TextAlignment,
+    /// Whatever the user is placing the text by clicking on the image
+    #[serde(skip)]
+    placing: bool,
+
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum TextMessage {
+    SetText(String),
+    SetSize(f32),
+    SetColor(Color),
+    SetOffsetX(f32),
+    SetOffsetY(f32),
+    SetAlignment(TextAlignment),
+    /// Switches to placing the text by clicking on the image
+    StartPlacing,
+    StopPlacing,
+    /// Moves the text to the pixel of the image
+    Placed(Vector<u32>),
+}
+
+impl<'a> Modifier<'a> for Text {
+    type Message = TextMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &ProgramData,
+        _wdata: &WorkspaceData,
+    ) -> ModifierOperation {
+        if self.text.trim().is_empty() {
+            return ModifierOperation::None;
+        }
+        ImageOperation::DrawText {
+            text: self.text.clone(),
+            offset: self.offset,
+            size: self.size,
+            color: self.color,
+            alignment: self.alignment,
+        }
+        .into()
+    }
+
+    fn create(_pdata: &ProgramData, _wdata: &WorkspaceData) -> (Command<Self::Message>, Self) {
+        (
+            Command::none(),
+            Self {
+                text: String::from("A"),
+                size: 0.2,
+                color: Color::WHITE,
+                offset: Point::new(0.0, 0.3),
+                alignment: TextAlignment::default(),
+                placing: false,
+                dirty: true,
+            },
+        )
+    }
+
+    fn label() -> &'static str {
+        "Text"
+    }
+
+    fn tooltip() -> &'static str {
+        "Draws a line of text over the token, like initials of a monster or its number"
+    }
+
+    fn order_hint() -> OrderHint {
+        OrderHint::Overlay
+    }
+
+    fn list_label(&self) -> String {
+        if self.text.is_empty() {
+            Self::label().to_string()
+        } else {
+            format!("{}: {}", Self::label(), self.text)
+        }
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("text", self.text.clone()),
+            ("size", self.size.to_string()),
+            ("color", color_to_hex(self.color)),
+            ("offset", format!("{},{}", self.offset.x, self.offset.y)),
+            ("alignment", self.alignment.to_string()),
+        ]
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        _pdata: &mut ProgramData,
+        wdata: &mut WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            TextMessage::SetText(text) => self.text = text,
+            TextMessage::SetSize(size) => self.size = size,
+            TextMessage::SetColor(color) => self.color = color,
+            TextMessage::SetOffsetX(x) => self.offset.x = x,
+            TextMessage::SetOffsetY(y) => self.offset.y = y,
+            TextMessage::SetAlignment(alignment) => self.alignment = alignment,
+            TextMessage::StartPlacing => {
+                self.placing = true;
+                return Command::none();
+            }
+            TextMessage::StopPlacing => {
+                self.placing = false;
+                return Command::none();
+            }
+            TextMessage::Placed(position) => {
+                let size = wdata.export_size;
+                if size.width == 0 || size.height == 0 {
+                    return Command::none();
+                }
+                let x = position.x.min(size.width - 1) as f32 + 0.5;
+                let y = position.y.min(size.height - 1) as f32 + 0.5;
+                self.offset = Point::new(x / size.width as f32 - 0.5, y / size.height as f32 - 0.5);
+            }
+        }
+        self.dirty = true;
+        Command::none()
+    }
+
+    fn properties_view(
+        &'a self,
+        pdata: &'a ProgramData,
+        wdata: &'a WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        let slider_row = |label, value, message: fn(f32) -> TextMessage| {
+            row![
+                text(label).width(Length::Fill),
+                slider(-0.5..=0.5, value, message)
+                    .step(0.01)
+                    .width(Length::FillPortion(4)),
+                horizontal_space(Length::FillPortion(2))
+            ]
+            .spacing(4)
+        };
+
+        let alignment = TextAlignment::ALL.iter().fold(
+            row![text("Alignment: ")]
+                .spacing(8)
+                .align_items(Alignment::Center),
+            |r, a| {
+                r.push(radio(
+                    a.to_string(),
+                    *a,
+                    Some(self.alignment),
+                    TextMessage::SetAlignment,
+                ))
+            },
+        );
+
+        let place = if self.placing {
+            button("Cancel placing")
+                .on_press(TextMessage::StopPlacing)
+                .style(Style::Highlight.into())
+        } else {
+            button("Place Text").on_press(TextMessage::StartPlacing)
+        };
+        let place = tooltip(
+            place,
+            "Click on the image to move the text there, alignment decides which side of the text lands on the clicked spot",
+            tooltip::Position::Bottom,
+        )
+        .style(Style::Frame);
+
+        let ui = col![
+            row![
+                text("Text: ").width(Length::Fill),
+                text_input("Label", &self.text, TextMessage::SetText)
+                    .style(pdata.input_style())
+                    .width(Length::FillPortion(4)),
+            ]
+            .spacing(4)
+            .align_items(Alignment::Center),
+            row![
+                tooltip(
+                    text("Size: ").width(Length::Fill),
+                    "Height of the text compared to the token",
+                    tooltip::Position::Bottom,
+                )
+                .style(Style::Frame),
+                slider(0.02..=0.5, self.size, TextMessage::SetSize)
+                    .step(0.01)
+                    .width(Length::FillPortion(4)),
+                horizontal_space(Length::FillPortion(2))
+            ]
+            .spacing(4),
+            row![
+                text("Color: ").width(Length::Fill),
+                ColorPicker::new(self.color, TextMessage::SetColor)
+                    .recent(&pdata.recent_colors)
+                    .width(Length::Fixed(32.0))
+                    .height(Length::Fixed(32.0)),
+                swatches(&wdata.palette, TextMessage::SetColor),
+                horizontal_space(Length::FillPortion(4)),
+            ]
+            .spacing(4)
+            .align_items(Alignment::Center),
+            alignment,
+            slider_row("Horizontal: ", self.offset.x, TextMessage::SetOffsetX),
+            slider_row("Vertical: ", self.offset.y, TextMessage::SetOffsetY),
+            place,
+        ]
+        .spacing(6);
+
+        Some(ui.into())
+    }
+
+    fn main_view(
+        &'a self,
+        _pdata: &'a ProgramData,
+        wdata: &'a WorkspaceData,
+    ) -> iced::Element<'a, Self::Message, iced::Renderer> {
+        PixelSampler::new(wdata.image_result.clone(), TextMessage::Placed).into()
+    }
+
+    fn wants_main_view(&self, _pdata: &ProgramData, _wdata: &WorkspaceData) -> bool {
+        self.placing
+    }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(self)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &ProgramData,
+        _wdata: &WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        *self = Self {
+            dirty: true,
+            ..read_state(state)?
+        };
+        Ok(Command::none())
+    }
+}
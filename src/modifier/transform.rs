@@ -0,0 +1,202 @@
+use iced::{
+    widget::{button, checkbox, column as col, horizontal_space, row, slider, text, tooltip},
+    Alignment, Command, Length,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{ProgramData, WorkspaceData},
+    image::ImageOperation,
+    style::Style,
+};
+
+use super::{read_state, write_state, Modifier, ModifierOperation, OrderHint};
+
+/// Rotates and flips the image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transform {
+    /// Number of clockwise quarter turns, from 0 to 3
+    turns: u32,
+    /// Clockwise rotation in degrees applied after the quarter turns
+    angle: f32,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum TransformMessage {
+    /// Turns the image by a quarter, clockwise when true
+    Turn(bool),
+    SetAngle(f32),
+    SetFlipHorizontal(bool),
+    SetFlipVertical(bool),
+    /// Sets all values back to leaving the image unchanged
+    Reset,
+}
+
+impl<'a> Modifier<'a> for Transform {
+    type Message = TransformMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &ProgramData,
+        _wdata: &WorkspaceData,
+    ) -> ModifierOperation {
+        let mut ops = Vec::new();
+        if self.flip_horizontal {
+            ops.push(ImageOperation::FlipHorizontal);
+        }
+        if self.flip_vertical {
+            ops.push(ImageOperation::FlipVertical);
+        }
+        if self.turns > 0 {
+            ops.push(ImageOperation::Rotate90 { turns: self.turns });
+        }
+        if self.angle != 0.0 {
+            ops.push(ImageOperation::Rotate { angle: self.angle });
+        }
+        if ops.is_empty() {
+            return ModifierOperation::None;
+        }
+        ops.into()
+    }
+
+    fn create(_pdata: &ProgramData, _wdata: &WorkspaceData) -> (Command<Self::Message>, Self) {
+        (
+            Command::none(),
+            Self {
+                turns: 0,
+                angle: 0.0,
+                flip_horizontal: false,
+                flip_vertical: false,
+                dirty: true,
+            },
+        )
+    }
+
+    fn label() -> &'static str {
+        "Transform"
+    }
+
+    fn tooltip() -> &'static str {
+        "Rotates and flips the image"
+    }
+
+    fn order_hint() -> OrderHint {
+        OrderHint::Any
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (
+                "rotation",
+                (self.turns as f32 * 90.0 + self.angle).to_string(),
+            ),
+            ("flip horizontal", self.flip_horizontal.to_string()),
+            ("flip vertical", self.flip_vertical.to_string()),
+        ]
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        _pdata: &mut ProgramData,
+        _wdata: &mut WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            TransformMessage::Turn(clockwise) => {
+                self.turns = if clockwise {
+                    (self.turns + 1) % 4
+                } else {
+                    (self.turns + 3) % 4
+                };
+            }
+            TransformMessage::SetAngle(angle) => self.angle = angle,
+            TransformMessage::SetFlipHorizontal(flip) => self.flip_horizontal = flip,
+            TransformMessage::SetFlipVertical(flip) => self.flip_vertical = flip,
+            TransformMessage::Reset => {
+                self.turns = 0;
+                self.angle = 0.0;
+                self.flip_horizontal = false;
+                self.flip_vertical = false;
+            }
+        }
+        self.dirty = true;
+        Command::none()
+    }
+
+    fn properties_view(
+        &'a self,
+        _pdata: &'a ProgramData,
+        _wdata: &'a WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        let angle = tooltip(
+            text(format!("Angle: {:.1}", self.angle)).width(Length::Fill),
+            "Rotates the image further by any angle, corners left uncovered become transparent.",
+            tooltip::Position::Bottom,
+        )
+        .style(Style::Frame);
+
+        let slider_angle = slider(-45.0..=45.0, self.angle, TransformMessage::SetAngle).step(0.5);
+
+        Some(
+            col![
+                row![
+                    text(format!("Rotation: {}", self.turns * 90)).width(Length::Fill),
+                    button("Rotate Left").on_press(TransformMessage::Turn(false)),
+                    button("Rotate Right").on_press(TransformMessage::Turn(true)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4)
+                .align_items(Alignment::Center),
+                row![
+                    angle,
+                    slider_angle.width(Length::FillPortion(4)),
+                    horizontal_space(Length::FillPortion(2))
+                ]
+                .spacing(4),
+                checkbox(
+                    "Flip Horizontally",
+                    self.flip_horizontal,
+                    TransformMessage::SetFlipHorizontal
+                ),
+                checkbox(
+                    "Flip Vertically",
+                    self.flip_vertical,
+                    TransformMessage::SetFlipVertical
+                ),
+                button("Reset").on_press(TransformMessage::Reset),
+            ]
+            .spacing(6)
+            .into(),
+        )
+    }
+
+    fn save_state(&self) -> Option<String> {
+        write_state(self)
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &str,
+        _pdata: &ProgramData,
+        _wdata: &WorkspaceData,
+    ) -> Result<Command<Self::Message>, String> {
+        *self = Self {
+            dirty: true,
+            ..read_state(state)?
+        };
+        Ok(Command::none())
+    }
+}
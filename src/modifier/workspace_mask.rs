@@ -0,0 +1,253 @@
+use std::{fmt::Display, sync::Arc};
+
+use iced::{
+    widget::{button, column as col, pick_list, row, text, tooltip},
+    Alignment, Command, Length, Size,
+};
+use image::imageops::{resize, FilterType};
+
+use crate::{
+    data::{ProgramData, WorkspaceData},
+    image::{convert::handle_to_alpha, GrayscaleImage, ImageOperation},
+    style::Style,
+};
+
+use super::{Modifier, ModifierOperation, OrderHint};
+
+/// Uses transparency of another workspace's result to hide parts of the image
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceMask {
+    /// Workspace the mask was taken from
+    source: Option<WorkspaceChoice>,
+    /// Transparency of the source workspace in its original size
+    alpha: Option<Arc<GrayscaleImage>>,
+    /// Mask resized to the export size of the workspace
+    mask: Option<Arc<GrayscaleImage>>,
+
+    dirty: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum WorkspaceMaskMessage {
+    SelectWorkspace(WorkspaceChoice),
+    Refresh,
+    GotMask(Arc<GrayscaleImage>),
+}
+
+/// Entry in the list of workspaces that can serve as the mask
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceChoice {
+    /// Identifier of the workspace, unlike its position it doesn't change when other workspaces are closed
+    id: u64,
+    /// Position of the workspace in the list at the time it was offered, only used for display
+    index: usize,
+    name: String,
+}
+
+impl Display for WorkspaceChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}. {}", self.index + 1, self.name)
+    }
+}
+
+impl WorkspaceMask {
+    /// Takes the latest result of the selected workspace and starts resizing it into the mask
+    fn pull_mask(
+        &mut self,
+        pdata: &mut ProgramData,
+        wdata: &WorkspaceData,
+    ) -> Command<WorkspaceMaskMessage> {
+        let Some(source) = &self.source else {
+            return Command::none();
+        };
+        let Some(alpha) = pdata
+            .workspace_results
+            .iter()
+            .find(|x| x.id == source.id)
+            .and_then(|x| handle_to_alpha(&x.image))
+        else {
+            pdata
+                .status
+                .warning("Selected workspace is no longer available");
+            return Command::none();
+        };
+        let alpha = Arc::new(alpha);
+        self.alpha = Some(alpha.clone());
+        Command::perform(resize_mask(alpha, wdata.export_size), |x| {
+            WorkspaceMaskMessage::GotMask(x)
+        })
+    }
+}
+
+impl<'a> Modifier<'a> for WorkspaceMask {
+    type Message = WorkspaceMaskMessage;
+
+    fn get_image_operation(
+        &self,
+        _pdata: &ProgramData,
+        wdata: &WorkspaceData,
+    ) -> ModifierOperation {
+        match &self.mask {
+            Some(mask)
+                if mask.width() == wdata.export_size.width
+                    && mask.height() == wdata.export_size.height =>
+            {
+                ImageOperation::Mask { mask: mask.clone() }.into()
+            }
+            _ => ModifierOperation::None,
+        }
+    }
+
+    fn create(_pdata: &ProgramData, _wdata: &WorkspaceData) -> (Command<Self::Message>, Self) {
+        (Command::none(), Self::default())
+    }
+
+    fn label() -> &'static str {
+        "Workspace Mask"
+    }
+
+    fn tooltip() -> &'static str {
+        "Hides parts of the image that are transparent in the result of another workspace"
+    }
+
+    fn order_hint() -> OrderHint {
+        OrderHint::Source
+    }
+
+    fn list_label(&self) -> String {
+        match &self.source {
+            Some(source) => format!("{}: {}", Self::label(), source.name),
+            None => Self::label().to_string(),
+        }
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        match &self.source {
+            Some(source) => vec![("workspace", source.name.clone())],
+            None => Vec::new(),
+        }
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn properties_update(
+        &mut self,
+        message: Self::Message,
+        pdata: &mut ProgramData,
+        wdata: &mut WorkspaceData,
+    ) -> Command<Self::Message> {
+        match message {
+            WorkspaceMaskMessage::SelectWorkspace(choice) => {
+                self.source = Some(choice);
+                self.pull_mask(pdata, wdata)
+            }
+            WorkspaceMaskMessage::Refresh => self.pull_mask(pdata, wdata),
+            WorkspaceMaskMessage::GotMask(mask) => {
+                self.mask = Some(mask);
+                self.dirty = true;
+                Command::none()
+            }
+        }
+    }
+
+    fn properties_view(
+        &'a self,
+        pdata: &'a ProgramData,
+        wdata: &'a WorkspaceData,
+    ) -> Option<iced::Element<'a, Self::Message, iced::Renderer>> {
+        // the workspace's own result is shared too
+        let choices: Vec<WorkspaceChoice> = pdata
+            .workspace_results
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.id != wdata.id)
+            .map(|(index, x)| WorkspaceChoice {
+                id: x.id,
+                index,
+                name: x.name.clone(),
+            })
+            .collect();
+        // positions shift when workspaces are closed, the selection is shown with the current one
+        let selected = self
+            .source
+            .as_ref()
+            .and_then(|source| choices.iter().find(|x| x.id == source.id))
+            .cloned();
+
+        let refresh = if self.source.is_some() {
+            button("Refresh").on_press(WorkspaceMaskMessage::Refresh)
+        } else {
+            button("Refresh")
+        };
+        let refresh = tooltip(
+            refresh,
+            "Takes the current result of the selected workspace again",
+            tooltip::Position::Bottom,
+        )
+        .style(Style::Frame);
+
+        let ui = col![
+            row![
+                text("Workspace:").width(Length::Shrink),
+                pick_list(choices, selected, |x| {
+                    WorkspaceMaskMessage::SelectWorkspace(x)
+                })
+                .placeholder("Select workspace")
+                .width(Length::Fill),
+                refresh,
+            ]
+            .spacing(4)
+            .align_items(Alignment::Center),
+            text("Changes in the selected workspace are used after pressing Refresh"),
+        ]
+        .spacing(6);
+
+        Some(ui.into())
+    }
+
+    fn workspace_update(
+        &mut self,
+        pdata: &ProgramData,
+        wdata: &WorkspaceData,
+    ) -> Command<Self::Message> {
+        if let Some(source) = &self.source {
+            if !pdata.workspace_results.iter().any(|x| x.id == source.id) {
+                // the workspace was closed, the mask it left behind would be impossible to refresh
+                self.source = None;
+                self.alpha = None;
+                self.mask = None;
+                self.dirty = true;
+                return Command::none();
+            }
+        }
+        let (Some(alpha), Some(mask)) = (&self.alpha, &self.mask) else {
+            return Command::none();
+        };
+        if mask.width() != wdata.export_size.width || mask.height() != wdata.export_size.height {
+            Command::perform(resize_mask(alpha.clone(), wdata.export_size), |x| {
+                WorkspaceMaskMessage::GotMask(x)
+            })
+        } else {
+            Command::none()
+        }
+    }
+}
+
+/// Stretches the mask to match the export size
+async fn resize_mask(alpha: Arc<GrayscaleImage>, size: Size<u32>) -> Arc<GrayscaleImage> {
+    if alpha.width() == size.width && alpha.height() == size.height {
+        return alpha;
+    }
+    Arc::new(resize(
+        alpha.as_ref(),
+        size.width,
+        size.height,
+        FilterType::Triangle,
+    ))
+}
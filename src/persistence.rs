@@ -1,8 +1,9 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, fmt::Display, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    data::working_dir,
     image::ImageFormat,
     style::{Layout, Theme},
     workspace::WorkspaceTemplate,
@@ -15,6 +16,9 @@ pub enum PersistentValue {
     Layout(Layout),
     WorkspaceTemplate(WorkspaceTemplate),
     ImageFormat(ImageFormat),
+    Bool(bool),
+    Float(f32),
+    Integer(u32),
 }
 
 impl PersistentValue {
@@ -25,8 +29,29 @@ impl PersistentValue {
             _ => None,
         }
     }
+    /// Checks the boolean value if the type of `CacheValue` is a boolean.
+    pub fn check_bool(&self) -> Option<bool> {
+        match self {
+            PersistentValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+    /// Checks the number value if the type of `CacheValue` is a floating point number.
+    pub fn check_float(&self) -> Option<f32> {
+        match self {
+            PersistentValue::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+    /// Checks the number value if the type of `CacheValue` is an integer.
+    pub fn check_integer(&self) -> Option<u32> {
+        match self {
+            PersistentValue::Integer(x) => Some(*x),
+            _ => None,
+        }
+    }
     /// Consumes the value and turns it into a string. If the value was not a string, it will return an empty string.
-    pub fn to_string(self) -> String {
+    pub fn into_string(self) -> String {
         match self {
             Self::String(x) => x,
             _ => String::new(),
@@ -46,6 +71,37 @@ impl PersistentValue {
             _ => Layout::default(),
         }
     }
+    /// Parses the text into a value of the same type as this one, the text is expected in the form the value is displayed in
+    ///
+    /// Returns none if the text doesn't describe a valid value of the type
+    pub fn parse_like(&self, text: &str) -> Option<Self> {
+        let trimmed = text.trim();
+        match self {
+            Self::String(_) => Some(Self::String(text.to_string())),
+            Self::Bool(_) => trimmed.parse().ok().map(Self::Bool),
+            Self::Float(_) => trimmed.parse().ok().map(Self::Float),
+            Self::Integer(_) => trimmed.parse().ok().map(Self::Integer),
+            Self::Theme(_) => ron::from_str(trimmed).ok().map(Self::Theme),
+            Self::Layout(_) => ron::from_str(trimmed).ok().map(Self::Layout),
+            Self::WorkspaceTemplate(_) => ron::from_str(trimmed).ok().map(Self::WorkspaceTemplate),
+            Self::ImageFormat(_) => ron::from_str(trimmed).ok().map(Self::ImageFormat),
+        }
+    }
+}
+
+impl Display for PersistentValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String(x) => write!(f, "{}", x),
+            Self::Bool(x) => write!(f, "{}", x),
+            Self::Float(x) => write!(f, "{}", x),
+            Self::Integer(x) => write!(f, "{}", x),
+            Self::Theme(x) => write!(f, "{:?}", x),
+            Self::Layout(x) => write!(f, "{:?}", x),
+            Self::WorkspaceTemplate(x) => write!(f, "{:?}", x),
+            Self::ImageFormat(x) => write!(f, "{:?}", x),
+        }
+    }
 }
 
 impl From<String> for PersistentValue {
@@ -78,6 +134,21 @@ impl From<WorkspaceTemplate> for PersistentValue {
         Self::WorkspaceTemplate(value)
     }
 }
+impl From<bool> for PersistentValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+impl From<f32> for PersistentValue {
+    fn from(value: f32) -> Self {
+        Self::Float(value)
+    }
+}
+impl From<u32> for PersistentValue {
+    fn from(value: u32) -> Self {
+        Self::Integer(value)
+    }
+}
 impl From<ImageFormat> for PersistentValue {
     fn from(value: ImageFormat) -> Self {
         Self::ImageFormat(value)
@@ -150,6 +221,33 @@ impl Persistence {
             }
         }
     }
+    /// Removes the value from cache, the id is removed as well once it has no values left
+    pub fn remove(
+        &mut self,
+        id: impl PersistentKey,
+        key: impl PersistentKey,
+    ) -> Option<PersistentValue> {
+        let values = self.db.get_mut(id.get_id())?;
+        let value = values.remove(key.get_id());
+        if values.is_empty() {
+            self.db.remove(id.get_id());
+        }
+        value
+    }
+    /// Lists all values in the cache along with their ids and keys, sorted by id and key
+    pub fn entries(&self) -> Vec<(&str, &str, &PersistentValue)> {
+        let mut entries: Vec<_> = self
+            .db
+            .iter()
+            .flat_map(|(id, values)| {
+                values
+                    .iter()
+                    .map(move |(key, value)| (id.as_str(), key.as_str(), value))
+            })
+            .collect();
+        entries.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+        entries
+    }
     /// Loads cache from drive
     pub fn load() -> Self {
         let path = Persistence::cache_file();
@@ -170,8 +268,10 @@ impl Persistence {
         std::fs::write(path, s).unwrap();
     }
     /// Gets path to cache file, it also makes sure the folder leading to the file is present
+    ///
+    /// The cache is kept in the working directory if the system doesn't provide a cache directory
     pub fn cache_file() -> PathBuf {
-        let mut path = dirs::cache_dir().unwrap();
+        let mut path = dirs::cache_dir().unwrap_or_else(working_dir);
         path.push("token-maker");
         if path.exists() == false {
             std::fs::create_dir_all(&path).unwrap();
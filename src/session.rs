@@ -0,0 +1,121 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::{save_data_path, PrintSize, PROJECT_DATA_FOLDER, PROJECT_NAME};
+use crate::image::{ImageFormat, RgbaImage};
+use crate::modifier::ModifierTag;
+use crate::workspace::WorkspaceTemplate;
+
+/// Folder in the data path where the autosaved session is kept
+const RECOVERY_FOLDER: &str = "recovery";
+/// Name of the file describing the autosaved session
+const SESSION_FILE: &str = "session.ron";
+
+/// Snapshot of all open workspaces, saved periodically to recover work after a crash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub project_name: String,
+    pub workspaces: Vec<WorkspaceSession>,
+}
+
+/// Settings of a single workspace in the saved session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceSession {
+    /// Name of the file in the recovery folder holding the source image
+    pub source_file: String,
+    /// Path or address the source image was originally loaded from
+    pub source_path: Option<String>,
+    pub output: String,
+    pub template: WorkspaceTemplate,
+    pub format: ImageFormat,
+    pub export_size: (u32, u32),
+    pub offset: (f32, f32),
+    pub zoom: f32,
+    /// Missing in sessions saved by older versions
+    #[serde(default)]
+    pub mirror: bool,
+    pub export_art: bool,
+    /// Physical size the workspace calculates its export size from, missing in sessions saved by older versions
+    #[serde(default)]
+    pub print_size: Option<PrintSize>,
+    #[serde(default)]
+    pub locked: bool,
+    /// Notes about what the workspace is for, missing in sessions saved by older versions
+    #[serde(default)]
+    pub notes: String,
+    /// Types of modifiers in the order they were in the workspace
+    pub modifiers: Vec<ModifierTag>,
+    /// Settings of the modifiers in the same order as their types, missing in sessions saved by older versions
+    #[serde(default)]
+    pub modifier_states: Vec<Option<String>>,
+}
+
+/// Tests whatever the previous run of the program left an autosaved session behind
+pub fn has_recovery() -> bool {
+    save_data_path!(RECOVERY_FOLDER, SESSION_FILE).exists()
+}
+
+/// Writes the session into the recovery folder
+///
+/// # Parameters
+/// `session` - description of the workspaces
+/// `sources` - source images to write along with the file name from the session, images which didn't change since the last autosave can be left out
+pub async fn save_session(
+    session: Session,
+    sources: Vec<(String, Arc<RgbaImage>)>,
+) -> Result<(), String> {
+    let folder = save_data_path!(RECOVERY_FOLDER);
+    std::fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+
+    for (name, image) in sources {
+        let mut data = Vec::new();
+        image
+            .write_to(
+                &mut std::io::Cursor::new(&mut data),
+                image::ImageOutputFormat::Png,
+            )
+            .map_err(|e| e.to_string())?;
+        write_atomic(&folder.join(name), &data)?;
+    }
+
+    // session is written last so it never refers to images that haven't been written yet
+    let description = ron::to_string(&session).map_err(|e| e.to_string())?;
+    write_atomic(&folder.join(SESSION_FILE), description.as_bytes())
+}
+
+/// Reads the autosaved session along with source images of its workspaces
+pub async fn load_session() -> Result<(Session, Vec<RgbaImage>), String> {
+    let folder = save_data_path!(RECOVERY_FOLDER);
+    let description =
+        std::fs::read_to_string(folder.join(SESSION_FILE)).map_err(|e| e.to_string())?;
+    let session: Session = ron::from_str(&description).map_err(|e| e.to_string())?;
+
+    let images = session
+        .workspaces
+        .iter()
+        .map(|w| {
+            image::open(folder.join(&w.source_file))
+                .map(|x| x.into_rgba8())
+                .map_err(|e| format!("{}: {}", w.source_file, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((session, images))
+}
+
+/// Removes the autosaved session, meant for when the program exits cleanly or the session is no longer wanted
+pub fn clear_recovery() {
+    let folder = save_data_path!(RECOVERY_FOLDER);
+    if folder.exists() {
+        let _ = std::fs::remove_dir_all(folder);
+    }
+}
+
+/// Writes the file under a temporary name first so a crash in the middle of writing doesn't leave a broken file behind
+fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    let mut temporary = PathBuf::from(path);
+    temporary.set_extension("tmp");
+    std::fs::write(&temporary, data).map_err(|e| e.to_string())?;
+    std::fs::rename(&temporary, path).map_err(|e| e.to_string())
+}
@@ -1,4 +1,4 @@
-use iced::{theme, widget, Theme as IcedTheme, Vector};
+use iced::{theme, theme::Palette, widget, Color, Theme as IcedTheme, Vector};
 use serde::{Deserialize, Serialize};
 
 /// Tags for program color theme
@@ -7,6 +7,8 @@ pub enum Theme {
     #[default]
     Light,
     Dark,
+    /// White text on black background with saturated accents, meant for low vision users
+    HighContrast,
 }
 
 impl From<Theme> for iced::Theme {
@@ -14,6 +16,13 @@ impl From<Theme> for iced::Theme {
         match value {
             Theme::Light => Self::Light,
             Theme::Dark => Self::Dark,
+            Theme::HighContrast => Self::custom(Palette {
+                background: Color::BLACK,
+                text: Color::WHITE,
+                primary: Color::from_rgb(1.0, 0.85, 0.0),
+                success: Color::from_rgb(0.0, 1.0, 0.5),
+                danger: Color::from_rgb(1.0, 0.3, 0.3),
+            }),
         }
     }
 }
@@ -36,6 +45,12 @@ pub enum Style {
     Danger,
     Action,
     Highlight,
+    /// Text input, outlined with a thick ring in the primary color of the theme while focused if `focus_ring` is set
+    Input {
+        focus_ring: bool,
+    },
+    /// Displays a sample of the color
+    Swatch(Color),
 }
 
 impl widget::container::StyleSheet for Style {
@@ -59,7 +74,7 @@ impl widget::container::StyleSheet for Style {
                     border_color: color,
                 }
             }
-            Style::Frame => {
+            Style::Frame | Style::Input { .. } => {
                 let border = ext.background.base.color;
                 let color = ext.background.weak.color;
                 let text = ext.background.weak.text;
@@ -109,6 +124,13 @@ impl widget::container::StyleSheet for Style {
                 }
             }
             Style::Highlight => todo!(),
+            Style::Swatch(color) => Appearance {
+                text_color: None,
+                background: Some((*color).into()),
+                border_radius: 2.0,
+                border_width: 1.0,
+                border_color: ext.background.strong.color,
+            },
         }
     }
 }
@@ -136,7 +158,15 @@ impl widget::button::StyleSheet for Style {
                     shadow_offset: Vector { x: 2.0, y: 2.0 },
                 }
             }
-            Style::Highlight | Style::Danger | Style::Action | Style::Header | Style::Margins => unreachable!(), // unused?
+            Style::Swatch(color) => Appearance {
+                background: (*color).into(),
+                text_color: ext.background.base.text,
+                border_color: ext.background.strong.color,
+                border_radius: 2.0,
+                border_width: 1.0,
+                shadow_offset: Vector { x: 1.0, y: 1.0 },
+            },
+            Style::Highlight | Style::Danger | Style::Action | Style::Header | Style::Margins | Style::Input { .. } => unreachable!(), // unused?
         }
     }
 
@@ -160,7 +190,15 @@ impl widget::button::StyleSheet for Style {
                     shadow_offset: Vector { x: 2.0, y: 2.0 },
                 }
             }
-            Style::Highlight | Style::Danger | Style::Action | Style::Header | Style::Margins => unreachable!(), // unused?
+            Style::Swatch(color) => Appearance {
+                background: (*color).into(),
+                text_color: ext.background.base.text,
+                border_color: ext.background.base.text,
+                border_radius: 2.0,
+                border_width: 2.0,
+                shadow_offset: Vector { x: 1.0, y: 1.0 },
+            },
+            Style::Highlight | Style::Danger | Style::Action | Style::Header | Style::Margins | Style::Input { .. } => unreachable!(), // unused?
         }
     }
 
@@ -184,7 +222,15 @@ impl widget::button::StyleSheet for Style {
                     shadow_offset: Vector { x: -2.0, y: -2.0 },
                 }
             }
-            Style::Highlight | Style::Danger | Style::Action | Style::Header | Style::Margins => unreachable!(), // unused?
+            Style::Swatch(color) => Appearance {
+                background: (*color).into(),
+                text_color: ext.background.base.text,
+                border_color: ext.background.base.text,
+                border_radius: 2.0,
+                border_width: 2.0,
+                shadow_offset: Vector { x: -1.0, y: -1.0 },
+            },
+            Style::Highlight | Style::Danger | Style::Action | Style::Header | Style::Margins | Style::Input { .. } => unreachable!(), // unused?
         }
     }
 
@@ -208,8 +254,53 @@ impl widget::button::StyleSheet for Style {
                     shadow_offset: Vector { x: 1.0, y: 1.0 },
                 }
             }
-            Style::Highlight | Style::Danger | Style::Action | Style::Header | Style::Margins => unreachable!(), // unused?
+            Style::Swatch(color) => Appearance {
+                background: (*color).into(),
+                text_color: ext.secondary.weak.text,
+                border_color: ext.secondary.base.color,
+                border_radius: 2.0,
+                border_width: 1.0,
+                shadow_offset: Vector::default(),
+            },
+            Style::Highlight | Style::Danger | Style::Action | Style::Header | Style::Margins | Style::Input { .. } => unreachable!(), // unused?
+        }
+    }
+}
+
+/// Text inputs look the same as the theme draws them except for the focus ring
+impl widget::text_input::StyleSheet for Style {
+    type Style = IcedTheme;
+
+    fn active(&self, style: &Self::Style) -> widget::text_input::Appearance {
+        IcedTheme::active(style, &theme::TextInput::Default)
+    }
+
+    fn hovered(&self, style: &Self::Style) -> widget::text_input::Appearance {
+        IcedTheme::hovered(style, &theme::TextInput::Default)
+    }
+
+    fn focused(&self, style: &Self::Style) -> widget::text_input::Appearance {
+        let appearance = IcedTheme::focused(style, &theme::TextInput::Default);
+        if !matches!(self, Style::Input { focus_ring: true }) {
+            return appearance;
         }
+        widget::text_input::Appearance {
+            border_width: 3.0,
+            border_color: style.extended_palette().primary.base.color,
+            ..appearance
+        }
+    }
+
+    fn placeholder_color(&self, style: &Self::Style) -> Color {
+        IcedTheme::placeholder_color(style, &theme::TextInput::Default)
+    }
+
+    fn value_color(&self, style: &Self::Style) -> Color {
+        IcedTheme::value_color(style, &theme::TextInput::Default)
+    }
+
+    fn selection_color(&self, style: &Self::Style) -> Color {
+        IcedTheme::selection_color(style, &theme::TextInput::Default)
     }
 }
 
@@ -225,11 +316,18 @@ impl From<Style> for theme::Button {
 
         match value {
             Style::Header => Button::Text,
-            Style::Frame => Button::Custom(Box::new(value)),
+            Style::Frame | Style::Swatch(_) => Button::Custom(Box::new(value)),
             Style::Margins => Button::Secondary,
             Style::Danger => Button::Destructive,
             Style::Action => Button::Primary,
             Style::Highlight => Button::Positive,
+            Style::Input { .. } => Button::Secondary,
         }
     }
 }
+
+impl From<Style> for theme::TextInput {
+    fn from(value: Style) -> Self {
+        theme::TextInput::Custom(Box::new(value))
+    }
+}
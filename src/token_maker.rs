@@ -1,21 +1,33 @@
 use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
+use iced::widget::image::Handle;
 use iced::widget::tooltip::Position;
 use iced::widget::{
-    button, column as col, container, horizontal_space, image as picture, radio, row, text,
-    text_input, tooltip, vertical_space, Row,
+    button, column as col, container, horizontal_space, image as picture, radio, row, scrollable,
+    text, text_input, tooltip, vertical_space, PickList, Row,
 };
 use iced::{
-    executor, Alignment, Application, Command, ContentFit, Element, Length, Renderer, Subscription,
-    Theme,
+    executor, Alignment, Application, Color, Command, ContentFit, Element, Length, Point, Renderer,
+    Subscription, Theme,
 };
 
-use crate::data::{load_frames, FrameImage, ProgramData, ProgramDataMessage};
+use crate::data::{
+    import_frames, is_writable, load_frames, sanitize_file_name, sanitize_file_name_allow_path,
+    sanitize_file_name_ends, write_starter_frames, FrameImage, ProgramData, ProgramDataMessage,
+    WorkspaceResult,
+};
 use crate::frame_maker::{FrameMaker, FrameMakerMessage};
-use crate::image::{download_image, image_filter, RgbaImage};
+use crate::image::{
+    convert::{handle_to_image, image_arc_to_handle, image_to_handle},
+    download_image, icc, image_filter, open_image, open_image_16, psd, ImageFormat, RgbaImage,
+};
+use crate::naming_convention::NamingConvention;
+use crate::session::{self, Session};
 use crate::style::{Layout, Style};
-use crate::widgets::{BrowserOperation, BrowsingResult, Target};
+use crate::widgets::{BrowserOperation, BrowsingResult, ColorPicker, Target};
 use crate::workspace::{Workspace, WorkspaceMessage, WorkspaceTemplate};
 
 /// Main application, manages general aspects of the application
@@ -24,8 +36,36 @@ pub struct TokenMaker {
     data: ProgramData,
     workspaces: Vec<Workspace>,
     frame_maker: FrameMaker,
+    /// Workspace which was interacted with most recently, keyboard shortcuts are sent to it
+    focused_workspace: usize,
+    /// Color last used to tint frames in all workspaces
+    batch_tint: Color,
+    /// Carrier for the width to set as export size of all workspaces
+    batch_width: String,
+    /// Carrier for the height to set as export size of all workspaces
+    batch_height: String,
+    /// Layers of the PSD document offered to replace the flattened image, each with its preview
+    psd_layers: Vec<(psd::PsdLayer, Handle)>,
+    /// Size of the PSD document, the chosen layer is placed on a canvas of this size
+    psd_size: (u32, u32),
+    /// Workspace created from the PSD document whose layers are offered
+    psd_workspace: usize,
+    /// Folder from which frames are to be imported
+    import_folder: PathBuf,
+    /// Category under which the frames are to be imported
+    import_category: String,
+    /// Address of the image being downloaded, remembered as the source of the workspace
+    download_url: String,
+    /// Source images written by the latest autosave, images which didn't change aren't written again
+    autosaved_sources: Vec<Arc<RgbaImage>>,
+    /// Workspace which was last given a chance to render in the background, they take turns
+    background_render: usize,
+    /// Files found by the last dry run of the export, each noting whatever it would be overwritten or can't be written
+    dry_run: Vec<String>,
 
     download_in_progress: bool,
+    /// Whatever the clipboard should be checked for an image address or path once the frames load, it only happens at start
+    check_clipboard: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -36,6 +76,8 @@ pub enum Message {
     LookForImageFromUrl,
     /// Starts a download of an image
     DownloadImage(String),
+    /// Text in the clipboard checked for an image address or path when the program starts
+    StartupClipboardLink(Option<String>),
     /// Result of the image download
     ImageDownloadResult(Result<RgbaImage, String>),
     /// Opens file browser to look for a folder to which workspaces will export their images
@@ -63,6 +105,16 @@ pub enum Message {
     WorkspaceSelect(usize),
     /// Request to create a new workspace and copy image used by other workspace as the base for it
     WorkspaceNewFromSource(usize),
+    /// Sets tint of frames in all workspaces
+    TintAllFrames(Color),
+    /// Changes width to be set as export size of all workspaces
+    SetBatchWidth(String),
+    /// Changes height to be set as export size of all workspaces
+    SetBatchHeight(String),
+    /// Sets export size of all workspaces to the chosen width and height
+    ResizeAllWorkspaces,
+    /// Sets export size of all workspaces to a square as large as the largest side among them and centers their images
+    SquareAllWorkspaces,
     /// Sets default workspace template to use for new workspaces
     WorkspaceTemplate(WorkspaceTemplate),
     /// Message related to program settings
@@ -73,11 +125,130 @@ pub enum Message {
     FrameMakerMessage(FrameMakerMessage),
     /// Request to export frame in frame editor
     FrameMakerExport,
+    /// Saves the frame from frame editor in place of an existing frame with the same category and name
+    FrameMakerOverwrite,
+    /// Saves the frame from frame editor with a number added to its name so it doesn't replace an existing frame
+    FrameMakerRename,
+    /// Returns to frame editor without saving the frame
+    FrameMakerCancelSave,
+    /// Opens file browser to look for a folder with frames to import
+    LookForFrameFolder,
+    /// Sets category under which the frames are imported
+    SetImportCategory(String),
+    /// Copies frames from the chosen folder into the frames folder
+    ImportFrames,
+    /// Result of importing frames, names of imported frames and descriptions of skipped files
+    FramesImported(Result<(Vec<String>, Vec<String>), String>),
     /// Error message
     /// TODO turn this into a proper error handling
     Error(String),
+    /// Displays summary of all the files that will be written on export
+    DisplayExportReview,
     /// Saves images from all workspaces
     Export,
+    /// Checks files the export would write and reports the result without writing anything
+    DryRunExport,
+    /// Saves results of all workspaces as layers of a single PSD document
+    ExportLayers,
+    /// Result of writing the layered document
+    LayersExported(Result<PathBuf, String>),
+    /// Replaces source of the workspace created from a PSD document with the chosen layer, none keeps the flattened image
+    PsdLayerSelected(Option<usize>),
+    /// Keyboard shortcut was pressed
+    Shortcut(Shortcut),
+    /// Lets the next workspace out of the prioritized one refresh its preview
+    RenderBackground,
+    /// Saves open workspaces for recovery after a crash
+    Autosave,
+    /// Result of the autosave with source images that were saved
+    Autosaved(Result<Vec<Arc<RgbaImage>>, String>),
+    /// Loads the session autosaved before the program last failed to exit cleanly
+    RestoreSession,
+    /// Removes the autosaved session without restoring it
+    DiscardSession,
+    /// Result of loading the autosaved session with source images of its workspaces
+    SessionLoaded(Result<(Session, Vec<RgbaImage>), String>),
+    /// File dragged onto the window from outside of the program
+    FileDropped(PathBuf),
+}
+
+/// Actions triggered by keyboard shortcuts
+#[derive(Debug, Clone, Copy)]
+pub enum Shortcut {
+    /// Opens search for adding modifiers to the focused workspace
+    ModifierSearch,
+    /// Closes whatever popup or prompt is open
+    Cancel,
+    /// Saves the result of the current editor
+    Save,
+    /// Undoes the last change in the current editor
+    Undo,
+    /// Moves the image in the focused workspace by the number of exported pixels horizontally and vertically
+    Nudge(f32, f32),
+}
+
+impl Shortcut {
+    /// Translates keyboard events into shortcuts
+    fn from_event(event: iced::Event, status: iced::event::Status) -> Option<Message> {
+        use iced::keyboard::{Event, KeyCode};
+
+        let iced::Event::Keyboard(Event::KeyPressed {
+            key_code,
+            modifiers,
+        }) = event
+        else {
+            return None;
+        };
+        // arrow keys are left to text inputs that use them
+        let captured = status == iced::event::Status::Captured;
+        let step = if modifiers.shift() { 10.0 } else { 1.0 };
+        let shortcut = match key_code {
+            KeyCode::Left if !captured => Shortcut::Nudge(-step, 0.0),
+            KeyCode::Right if !captured => Shortcut::Nudge(step, 0.0),
+            KeyCode::Up if !captured => Shortcut::Nudge(0.0, -step),
+            KeyCode::Down if !captured => Shortcut::Nudge(0.0, step),
+            KeyCode::P if modifiers.control() => Shortcut::ModifierSearch,
+            KeyCode::Escape => Shortcut::Cancel,
+            KeyCode::S if modifiers.control() => Shortcut::Save,
+            KeyCode::Z if modifiers.control() => Shortcut::Undo,
+            _ => return None,
+        };
+        Some(Message::Shortcut(shortcut))
+    }
+}
+
+/// Decodes escaped characters in a file URI, like %20 in place of spaces
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| uri.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|x| u8::from_str_radix(x, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Translates files dropped onto the window into messages
+fn file_dropped(event: iced::Event, _status: iced::event::Status) -> Option<Message> {
+    match event {
+        iced::Event::Window(iced::window::Event::FileDropped(path)) => {
+            Some(Message::FileDropped(path))
+        }
+        _ => None,
+    }
 }
 
 /// Describes which mode the program should operate in
@@ -98,6 +269,16 @@ pub enum Mode {
     FrameMaker,
     /// Screen for swapping image in all open workspaces
     SourceSwap,
+    /// Summary of all workspaces to be exported, last check before writing files
+    ExportReview,
+    /// Picker for the layer of the loaded PSD document to be used in place of the flattened image
+    PsdLayers,
+    /// Screen for importing frames from a folder
+    FrameImport,
+    /// Prompt for restoring workspaces autosaved before the program last failed to exit cleanly
+    RestoreSession,
+    /// Prompt for resolving a frame from frame editor having the same identifier as an existing frame
+    FrameOverwrite(String),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -106,6 +287,12 @@ pub enum BrowsingFor {
     ReplacementToken,
     Output,
     Frame,
+    FrameFolder,
+    FrameSearchFolder,
+    /// Replacement source image of the workspace on the index
+    WorkspaceSource(usize),
+    /// Reference image shown next to the preview of the workspace on the index
+    WorkspaceReference(usize),
 }
 
 impl Application for TokenMaker {
@@ -118,31 +305,38 @@ impl Application for TokenMaker {
     type Flags = ();
 
     fn new(_flags: Self::Flags) -> (Self, Command<Self::Message>) {
-        (
-            {
-                let mut data = ProgramData::new();
-                data.status.log("Loading frames...");
-                let s = Self {
-                    data,
-                    operation: Mode::CreateWorkspace,
-                    workspaces: Vec::new(),
-                    frame_maker: FrameMaker::new(),
-                    download_in_progress: false,
-                };
-                s
-            },
-            Command::perform(load_frames(), |x| {
-                if let Ok(x) = x {
-                    if x.len() > 0 {
-                        Message::LoadedFrames(x)
-                    } else {
-                        Message::Error("Could not find any frames".to_string())
-                    }
-                } else {
-                    Message::Error("Failed to load frames".to_string())
-                }
-            }),
-        )
+        let mut data = ProgramData::new();
+        data.status.log("Loading frames...");
+        let operation = if session::has_recovery() {
+            Mode::RestoreSession
+        } else {
+            Mode::CreateWorkspace
+        };
+        let frames = reload_frames(data.frame_folders().to_vec());
+        // waiting for the frames lets the new workspace pick its frame right away
+        let check_clipboard = data.clipboard_link_on_start() && operation == Mode::CreateWorkspace;
+        let s = Self {
+            data,
+            operation,
+            workspaces: Vec::new(),
+            frame_maker: FrameMaker::new(),
+            focused_workspace: 0,
+            batch_tint: Color::WHITE,
+            batch_width: String::from("512"),
+            batch_height: String::from("512"),
+            psd_layers: Vec::new(),
+            psd_size: (0, 0),
+            psd_workspace: 0,
+            import_folder: PathBuf::new(),
+            import_category: String::new(),
+            download_url: String::new(),
+            autosaved_sources: Vec::new(),
+            background_render: 0,
+            dry_run: Vec::new(),
+            download_in_progress: false,
+            check_clipboard,
+        };
+        (s, frames)
     }
 
     fn theme(&self) -> Self::Theme {
@@ -153,6 +347,7 @@ impl Application for TokenMaker {
     }
 
     fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
+        self.share_workspace_results();
         match message {
             Message::LookForImage => {
                 match self.operation {
@@ -163,7 +358,7 @@ impl Application for TokenMaker {
                     _ => unreachable!(),
                 }
 
-                self.data.file.set_filter(image_filter);
+                self.data.file.set_filter(self.data.image_filter());
                 self.data.file.refresh_path().unwrap();
                 Command::none()
             }
@@ -177,6 +372,7 @@ impl Application for TokenMaker {
 
             Message::DownloadImage(url) => {
                 self.download_in_progress = true;
+                self.download_url = url.clone();
                 Command::perform(
                     async move {
                         let img = download_image(url).await;
@@ -193,6 +389,9 @@ impl Application for TokenMaker {
                         Mode::CreateWorkspace => {
                             let name = String::from("image");
                             let c = self.add_workspace(name, img.into());
+                            if let Some(w) = self.workspaces.last_mut() {
+                                w.set_source_path(Some(self.download_url.clone()));
+                            }
                             self.main_screen();
                             c
                         }
@@ -202,8 +401,12 @@ impl Application for TokenMaker {
                             let cmd = self
                                 .workspaces
                                 .iter_mut()
-                                .map(|x| x.set_source(arc.clone(), &self.data))
                                 .enumerate()
+                                .filter(|(_, x)| !x.is_locked())
+                                .map(|(i, x)| {
+                                    x.set_source_path(Some(self.download_url.clone()));
+                                    (i, x.set_source(arc.clone(), &self.data))
+                                })
                                 .map(|(i, x)| x.map(move |x| Message::Workspace(i, x)))
                                 .fold(vec![], |mut v, c| {
                                     v.push(c);
@@ -225,10 +428,11 @@ impl Application for TokenMaker {
             Message::LookForOutputFolder => {
                 self.operation = Mode::FileBrowser(BrowsingFor::Output);
                 self.data.file.set_target(Target::Directory);
-                if self.data.get_output_folder().exists() {
-                    self.data
-                        .file
-                        .set_path(self.data.get_output_folder().clone());
+                let output = self
+                    .data
+                    .get_output_folder(self.data.get_workspace_template());
+                if output.exists() {
+                    self.data.file.set_path(output.clone());
                 }
                 self.data.file.refresh_path().unwrap();
                 Command::none()
@@ -236,7 +440,7 @@ impl Application for TokenMaker {
 
             Message::LookForFrame => {
                 self.operation = Mode::FileBrowser(BrowsingFor::Frame);
-                self.data.file.set_filter(image_filter);
+                self.data.file.set_filter(self.data.image_filter());
                 self.data.file.refresh_path().unwrap();
                 Command::none()
             }
@@ -255,31 +459,33 @@ impl Application for TokenMaker {
                                 panic!("How did we get here...");
                             };
                             match reason {
-                                BrowsingFor::Token => {
-                                    if let Ok(img) = image::open(&path) {
-                                        let img = img.into_rgba8();
-                                        let name =
-                                            path.file_stem().unwrap().to_string_lossy().to_string();
-                                        let c = self.add_workspace(name, img.into());
-                                        self.main_screen();
-                                        c
-                                    } else {
-                                        Command::none()
-                                    }
-                                }
+                                BrowsingFor::Token => self.open_workspace(&path),
 
                                 BrowsingFor::ReplacementToken => {
-                                    if let Ok(img) = image::open(&path) {
-                                        let img = img.into_rgba8();
+                                    if let Ok(img) = self.data.images.load(&path) {
                                         let name =
                                             path.file_stem().unwrap().to_string_lossy().to_string();
                                         self.data.naming.project_name = name;
-                                        let img = Arc::new(img);
+                                        let profile = icc::read_profile(&path).map(Arc::new);
+                                        let precise = if self.data.keep_high_precision() {
+                                            open_image_16(&path).map(Arc::new)
+                                        } else {
+                                            None
+                                        };
                                         let cmd = self
                                             .workspaces
                                             .iter_mut()
-                                            .map(|x| x.set_source(img.clone(), &self.data))
                                             .enumerate()
+                                            .filter(|(_, x)| !x.is_locked())
+                                            .map(|(i, x)| {
+                                                let c = x.set_source(img.clone(), &self.data);
+                                                x.set_color_profile(profile.clone());
+                                                x.set_precise_source(precise.clone());
+                                                x.set_source_path(Some(
+                                                    path.to_string_lossy().to_string(),
+                                                ));
+                                                (i, c)
+                                            })
                                             .map(|(i, x)| x.map(move |x| Message::Workspace(i, x)))
                                             .fold(vec![], |mut v, c| {
                                                 v.push(c);
@@ -292,24 +498,92 @@ impl Application for TokenMaker {
                                     }
                                 }
 
+                                BrowsingFor::WorkspaceSource(index) => {
+                                    let index = *index;
+                                    self.main_screen();
+                                    let Some(workspace) = self.workspaces.get_mut(index) else {
+                                        return Command::none();
+                                    };
+                                    match self.data.images.load(&path) {
+                                        Ok(img) => {
+                                            let c = workspace.set_source(img, &self.data);
+                                            workspace.set_color_profile(
+                                                icc::read_profile(&path).map(Arc::new),
+                                            );
+                                            workspace.set_precise_source(
+                                                if self.data.keep_high_precision() {
+                                                    open_image_16(&path).map(Arc::new)
+                                                } else {
+                                                    None
+                                                },
+                                            );
+                                            workspace.set_source_path(Some(
+                                                path.to_string_lossy().to_string(),
+                                            ));
+                                            c.map(move |x| Message::Workspace(index, x))
+                                        }
+                                        Err(e) => {
+                                            self.data
+                                                .status
+                                                .error(&format!("Failed to open the image: {}", e));
+                                            Command::none()
+                                        }
+                                    }
+                                }
+
+                                BrowsingFor::WorkspaceReference(index) => {
+                                    let index = *index;
+                                    self.main_screen();
+                                    let Some(workspace) = self.workspaces.get_mut(index) else {
+                                        return Command::none();
+                                    };
+                                    match self.data.images.load(&path) {
+                                        Ok(img) => {
+                                            workspace.set_reference(image_arc_to_handle(&img))
+                                        }
+                                        Err(e) => self.data.status.error(&format!(
+                                            "Failed to open the reference image: {}",
+                                            e
+                                        )),
+                                    }
+                                    Command::none()
+                                }
+
+                                BrowsingFor::FrameFolder => {
+                                    self.import_category = path
+                                        .file_name()
+                                        .map(|x| {
+                                            sanitize_file_name_allow_path(
+                                                x.to_string_lossy().to_string(),
+                                            )
+                                        })
+                                        .unwrap_or_default();
+                                    self.import_folder = path;
+                                    self.operation = Mode::FrameImport;
+                                    Command::none()
+                                }
+
+                                BrowsingFor::FrameSearchFolder => {
+                                    self.operation = Mode::Settings;
+                                    if self.data.add_frame_folder(path) {
+                                        self.data.status.log("Loading frames...");
+                                        reload_frames(self.data.frame_folders().to_vec())
+                                    } else {
+                                        self.data.status.warning("The folder was already added");
+                                        Command::none()
+                                    }
+                                }
+
                                 BrowsingFor::Output => {
-                                    self.data.set_output_folder(path);
+                                    let template = self.data.get_workspace_template();
+                                    self.data.set_output_folder(path, template);
+                                    self.invalidate_exports();
                                     self.main_screen();
                                     Command::none()
                                 }
 
                                 BrowsingFor::Frame => {
-                                    if let Ok(img) = image::open(&path) {
-                                        let img = img.into_rgba8();
-                                        self.frame_maker.load(img);
-                                        self.frame_maker.set_name(
-                                            path.file_stem()
-                                                .and_then(|x| x.to_str())
-                                                .and_then(|x| Some(x.to_string()))
-                                                .unwrap(),
-                                        );
-                                        self.operation = Mode::FrameMaker;
-                                    } else {
+                                    if !self.load_frame_maker(&path) {
                                         self.main_screen();
                                     }
                                     Command::none()
@@ -325,8 +599,17 @@ impl Application for TokenMaker {
             Message::WorkspaceNewFromSource(index) => {
                 let command = if let Some(w) = self.workspaces.get(index) {
                     let img = w.get_source().clone();
+                    let profile = w.get_color_profile();
+                    let source_path = w.get_source_path().map(|x| x.to_string());
+                    let precise = w.get_precise_source();
                     let name = w.get_output_name().to_string();
-                    self.add_workspace(name, img)
+                    let c = self.add_workspace(name, img);
+                    if let Some(w) = self.workspaces.last_mut() {
+                        w.set_color_profile(profile);
+                        w.set_source_path(source_path);
+                        w.set_precise_source(precise);
+                    }
+                    c
                 } else {
                     Command::none()
                 };
@@ -359,7 +642,25 @@ impl Application for TokenMaker {
                 Command::none()
             }
 
-            Message::SettingsMessage(x) => self.data.update(x).map(|x| Message::SettingsMessage(x)),
+            Message::SettingsMessage(ProgramDataMessage::BrowseFrameFolder) => {
+                self.operation = Mode::FileBrowser(BrowsingFor::FrameSearchFolder);
+                self.data.file.set_target(Target::Directory);
+                self.data.file.refresh_path().unwrap();
+                Command::none()
+            }
+
+            Message::SettingsMessage(x) => {
+                let reload = matches!(x, ProgramDataMessage::RemoveFrameFolder(_));
+                if x.changes_export() {
+                    self.invalidate_exports();
+                }
+                let command = self.data.update(x).map(Message::SettingsMessage);
+                if reload {
+                    Command::batch([command, reload_frames(self.data.frame_folders().to_vec())])
+                } else {
+                    command
+                }
+            }
 
             Message::WorkspaceClose(index) => {
                 if self.workspaces.len() > index {
@@ -370,7 +671,18 @@ impl Application for TokenMaker {
                     }
                 }
                 self.main_screen();
-                Command::none()
+                // modifiers using the closed workspace need to let go of it
+                self.share_workspace_results();
+                let commands: Vec<_> = self
+                    .workspaces
+                    .iter_mut()
+                    .enumerate()
+                    .map(|(i, x)| {
+                        x.workspaces_changed(&self.data)
+                            .map(move |x| Message::Workspace(i, x))
+                    })
+                    .collect();
+                Command::batch(commands)
             }
 
             Message::WorkspaceSelect(i) => {
@@ -378,7 +690,60 @@ impl Application for TokenMaker {
                 Command::none()
             }
 
+            Message::Workspace(index, WorkspaceMessage::ReplaceSource) => {
+                self.focused_workspace = index;
+                self.operation = Mode::FileBrowser(BrowsingFor::WorkspaceSource(index));
+                self.data.file.set_filter(self.data.image_filter());
+                self.data.file.refresh_path().unwrap();
+                Command::none()
+            }
+
+            Message::Workspace(index, WorkspaceMessage::PinReference) => {
+                self.focused_workspace = index;
+                self.operation = Mode::FileBrowser(BrowsingFor::WorkspaceReference(index));
+                self.data.file.set_filter(self.data.image_filter());
+                self.data.file.refresh_path().unwrap();
+                Command::none()
+            }
+
+            Message::Workspace(index, WorkspaceMessage::MakeFrame) => {
+                let Some(image) = self
+                    .workspaces
+                    .get(index)
+                    .and_then(|x| handle_to_image(&x.get_output()))
+                else {
+                    return Command::none();
+                };
+                self.frame_maker.load(image);
+                let name = sanitize_file_name(self.data.workspace_results[index].name.clone());
+                self.frame_maker.set_name(name);
+                self.operation = Mode::FrameMaker;
+                Command::none()
+            }
+
+            Message::Workspace(index, WorkspaceMessage::CopySettingsFrom(source)) => {
+                if index == source
+                    || index >= self.workspaces.len()
+                    || source >= self.workspaces.len()
+                {
+                    return Command::none();
+                }
+                self.focused_workspace = index;
+                let (first, second) = self.workspaces.split_at_mut(index.max(source));
+                let (target, source) = if index < source {
+                    (&mut first[index], &second[0])
+                } else {
+                    (&mut second[0], &first[source])
+                };
+                target
+                    .copy_settings(source, &mut self.data)
+                    .map(move |x| Message::Workspace(index, x))
+            }
+
             Message::Workspace(index, message) => {
+                if !message.is_background_job() {
+                    self.focused_workspace = index;
+                }
                 if let Some(workspace) = self.workspaces.get_mut(index) {
                     workspace
                         .update(message, &mut self.data)
@@ -396,7 +761,36 @@ impl Application for TokenMaker {
             Message::LoadedFrames(frames) => {
                 self.data.available_frames = frames;
                 self.data.status.log("Frames loaded");
-                Command::none()
+                if std::mem::take(&mut self.check_clipboard) {
+                    iced::clipboard::read(Message::StartupClipboardLink)
+                } else {
+                    Command::none()
+                }
+            }
+
+            Message::StartupClipboardLink(clip) => {
+                // the user might have started working on something else already
+                if self.operation != Mode::CreateWorkspace || self.download_in_progress {
+                    return Command::none();
+                }
+                let Some(clip) = clip else {
+                    return Command::none();
+                };
+                let clip = clip.lines().next().unwrap_or_default().trim();
+                if clip.starts_with("http://") || clip.starts_with("https://") {
+                    self.data.status.log("Downloading image from clipboard...");
+                    return self.update(Message::DownloadImage(clip.to_string()));
+                }
+                let path = match clip.strip_prefix("file://") {
+                    Some(uri) => PathBuf::from(percent_decode(uri)),
+                    None => PathBuf::from(clip),
+                };
+                if path.is_file() && image_filter(&path, &self.data.recognized_extensions()) {
+                    self.data.status.log("Opened image from clipboard");
+                    self.open_workspace(&path)
+                } else {
+                    Command::none()
+                }
             }
 
             Message::Error(e) => {
@@ -405,23 +799,548 @@ impl Application for TokenMaker {
                 Command::none()
             }
 
+            Message::DisplayExportReview => {
+                self.dry_run.clear();
+                self.operation = Mode::ExportReview;
+                Command::none()
+            }
+
             Message::Export => {
-                self.workspaces.iter().for_each(|x| x.export(&self.data));
-                self.data.status.log("Export successful");
+                if let Err(e) = self.can_save() {
+                    self.data.status.error(&e);
+                    return Command::none();
+                }
+                if !self.formats_chosen() {
+                    self.data
+                        .status
+                        .warning("Choose export format for the new workspaces first");
+                    self.operation = Mode::ExportReview;
+                    return Command::none();
+                }
+                let exported: Vec<_> = (0..self.workspaces.len())
+                    .filter(|x| !self.skips_export(&self.workspaces[*x]))
+                    .collect();
+                if exported.is_empty() {
+                    self.data
+                        .status
+                        .log("Nothing changed since the last export");
+                    self.main_screen();
+                    return Command::none();
+                }
+                let commands: Vec<_> = exported
+                    .iter()
+                    .map(|&i| {
+                        self.workspaces[i]
+                            .export(&self.data)
+                            .map(move |x| Message::Workspace(i, x))
+                    })
+                    .collect();
+                let skipped = self.workspaces.len() - exported.len();
+                if skipped > 0 {
+                    self.data.status.log(&format!(
+                        "Export successful, skipped {} unchanged workspaces",
+                        skipped
+                    ));
+                } else {
+                    self.data.status.log("Export successful");
+                }
+                self.main_screen();
+                Command::batch(commands)
+            }
+
+            Message::DryRunExport => {
+                self.dry_run.clear();
+                if let Err(e) = self.can_save() {
+                    self.data.status.error(&format!("Dry run: {e}"));
+                    return Command::none();
+                }
+                let files: Vec<PathBuf> = self
+                    .workspaces
+                    .iter()
+                    .filter(|x| !self.skips_export(x))
+                    .flat_map(|x| x.export_files(&self.data))
+                    .collect();
+                self.dry_run = files
+                    .iter()
+                    .map(|x| {
+                        let note = if !is_writable(x) {
+                            " - can't be written"
+                        } else if x.exists() {
+                            " - would be overwritten"
+                        } else {
+                            ""
+                        };
+                        format!("{}{}", x.to_string_lossy(), note)
+                    })
+                    .collect();
+                let blocked: Vec<_> = files
+                    .iter()
+                    .filter(|x| !is_writable(x))
+                    .map(|x| x.to_string_lossy())
+                    .collect();
+                if blocked.is_empty() {
+                    let overwritten = files.iter().filter(|x| x.exists()).count();
+                    self.data.status.log(&format!(
+                        "Dry run: {} files would be written, {} of them overwritten",
+                        files.len(),
+                        overwritten
+                    ));
+                } else {
+                    self.data
+                        .status
+                        .error(&format!("Dry run: can't write to {}", blocked.join(", ")));
+                }
+                Command::none()
+            }
+
+            Message::ExportLayers => {
+                if let Err(e) = self.can_save() {
+                    self.data.status.error(&e);
+                    return Command::none();
+                }
+                self.share_workspace_results();
+                let layers: Option<Vec<_>> = self
+                    .data
+                    .workspace_results
+                    .iter()
+                    .map(|x| {
+                        handle_to_image(&x.image).map(|image| psd::PsdLayer {
+                            name: x.name.clone(),
+                            image,
+                            left: 0,
+                            top: 0,
+                        })
+                    })
+                    .collect();
+                let Some(layers) = layers else {
+                    self.data.status.error("Workspaces didn't finish rendering");
+                    return Command::none();
+                };
+                let name = match sanitize_file_name(self.data.naming.project_name.clone()) {
+                    x if x.is_empty() => String::from("workspaces"),
+                    x => x,
+                };
+                let path = self
+                    .data
+                    .get_output_folder(self.data.get_workspace_template())
+                    .join(format!("{}.psd", name));
+                self.main_screen();
+                Command::perform(
+                    async move {
+                        let data = psd::write_psd(&layers)?;
+                        std::fs::write(&path, data).map_err(|e| e.to_string())?;
+                        Ok(path)
+                    },
+                    Message::LayersExported,
+                )
+            }
+
+            Message::LayersExported(result) => {
+                match result {
+                    Ok(path) => self
+                        .data
+                        .status
+                        .log(&format!("Exported layers to {}", path.display())),
+                    Err(e) => self
+                        .data
+                        .status
+                        .error(&format!("Failed to export layers: {}", e)),
+                }
+                Command::none()
+            }
+
+            Message::PsdLayerSelected(layer) => {
+                let layers = std::mem::take(&mut self.psd_layers);
+                let i = self.psd_workspace;
+                let layer = layer.and_then(|x| layers.into_iter().nth(x));
+                let command = match (self.workspaces.get_mut(i), layer) {
+                    (Some(w), Some((layer, _))) => {
+                        let (width, height) = self.psd_size;
+                        w.set_source(Arc::new(layer.place(width, height)), &self.data)
+                            .map(move |x| Message::Workspace(i, x))
+                    }
+                    _ => Command::none(),
+                };
+                self.main_screen();
+                command
+            }
+
+            Message::TintAllFrames(color) => {
+                self.batch_tint = color;
+                let commands: Vec<_> = self
+                    .workspaces
+                    .iter_mut()
+                    .enumerate()
+                    .filter(|(_, x)| !x.is_locked())
+                    .filter_map(|(i, x)| {
+                        x.set_tint(color, &mut self.data)
+                            .map(|c| c.map(move |x| Message::Workspace(i, x)))
+                    })
+                    .collect();
+                if commands.is_empty() {
+                    self.data
+                        .status
+                        .warning("None of the unlocked workspaces has a Frame modifier to tint");
+                }
+                Command::batch(commands)
+            }
+
+            Message::SetBatchWidth(width) => {
+                if width.is_empty() || width.parse::<u32>().is_ok() {
+                    self.batch_width = width;
+                }
                 Command::none()
             }
 
+            Message::SetBatchHeight(height) => {
+                if height.is_empty() || height.parse::<u32>().is_ok() {
+                    self.batch_height = height;
+                }
+                Command::none()
+            }
+
+            Message::SquareAllWorkspaces => {
+                let Some(side) = self
+                    .workspaces
+                    .iter()
+                    .filter(|x| !x.is_locked())
+                    .map(|x| {
+                        let size = x.get_export_size();
+                        size.width.max(size.height)
+                    })
+                    .max()
+                else {
+                    return Command::none();
+                };
+                let side = side.to_string();
+                let commands: Vec<_> = self
+                    .workspaces
+                    .iter_mut()
+                    .enumerate()
+                    .filter(|(_, x)| !x.is_locked())
+                    .flat_map(|(i, x)| {
+                        [
+                            WorkspaceMessage::SetOutputWidth(side.clone()),
+                            WorkspaceMessage::SetOutputHeight(side.clone()),
+                            WorkspaceMessage::Slide(Point::ORIGIN),
+                        ]
+                        .map(|m| {
+                            x.update(m, &mut self.data)
+                                .map(move |x| Message::Workspace(i, x))
+                        })
+                    })
+                    .collect();
+                self.data.status.log(&format!(
+                    "Set export size of all unlocked workspaces to {}x{} and centered their images",
+                    side, side
+                ));
+                Command::batch(commands)
+            }
+
+            Message::ResizeAllWorkspaces => {
+                let Some((width, height)) = self.batch_size() else {
+                    return Command::none();
+                };
+                let (width, height) = (width.to_string(), height.to_string());
+                let commands: Vec<_> = self
+                    .workspaces
+                    .iter_mut()
+                    .enumerate()
+                    .filter(|(_, x)| !x.is_locked())
+                    .flat_map(|(i, x)| {
+                        [
+                            x.update(
+                                WorkspaceMessage::SetOutputWidth(width.clone()),
+                                &mut self.data,
+                            )
+                            .map(move |x| Message::Workspace(i, x)),
+                            x.update(
+                                WorkspaceMessage::SetOutputHeight(height.clone()),
+                                &mut self.data,
+                            )
+                            .map(move |x| Message::Workspace(i, x)),
+                        ]
+                    })
+                    .collect();
+                self.data.status.log(&format!(
+                    "Set export size of all unlocked workspaces to {}x{}",
+                    width, height
+                ));
+                Command::batch(commands)
+            }
+
+            Message::Shortcut(shortcut) => match (shortcut, &self.operation) {
+                (Shortcut::ModifierSearch, Mode::Workspace) => {
+                    let focused = match self.data.get_layout() {
+                        Layout::Stacking(i) => i,
+                        Layout::Parallel => self.focused_workspace,
+                    };
+                    let cmd = self
+                        .workspaces
+                        .iter_mut()
+                        .enumerate()
+                        .map(|(i, x)| {
+                            let message = if i == focused {
+                                WorkspaceMessage::OpenModifierSearch
+                            } else {
+                                WorkspaceMessage::CloseModifierSearch
+                            };
+                            x.update(message, &mut self.data)
+                                .map(move |x| Message::Workspace(i, x))
+                        })
+                        .collect::<Vec<_>>();
+                    Command::batch(cmd)
+                }
+                (Shortcut::Nudge(x, y), Mode::Workspace) => {
+                    let focused = match self.data.get_layout() {
+                        Layout::Stacking(i) => i,
+                        Layout::Parallel => self.focused_workspace,
+                    };
+                    let Some(offset) = self
+                        .workspaces
+                        .get(focused)
+                        .and_then(|w| w.nudged_offset(&self.data, x, y))
+                    else {
+                        return Command::none();
+                    };
+                    self.update(Message::Workspace(focused, WorkspaceMessage::Slide(offset)))
+                }
+                (Shortcut::Cancel, Mode::Workspace) => {
+                    self.workspaces.iter_mut().for_each(|x| {
+                        let _ = x.update(WorkspaceMessage::CloseModifierSearch, &mut self.data);
+                    });
+                    Command::none()
+                }
+                (Shortcut::Cancel, Mode::FrameMaker) => self.update(Message::DisplayWorkspaces),
+                (Shortcut::Cancel, Mode::FrameOverwrite(_)) => {
+                    self.update(Message::FrameMakerCancelSave)
+                }
+                (Shortcut::Save, Mode::FrameMaker) if self.frame_maker.can_save() => {
+                    self.update(Message::FrameMakerExport)
+                }
+                (Shortcut::Undo, Mode::FrameMaker) if self.frame_maker.can_undo() => self
+                    .frame_maker
+                    .update(FrameMakerMessage::UndoMask, &mut self.data)
+                    .map(Message::FrameMakerMessage),
+                _ => Command::none(),
+            },
+
             Message::FrameMakerMessage(x) => self
                 .frame_maker
                 .update(x, &mut self.data)
                 .map(|x| Message::FrameMakerMessage(x)),
 
             Message::FrameMakerExport => {
-                self.main_screen();
                 let frame = self.frame_maker.create_frame();
-                frame.save_frame();
-                self.data.status.log("Frame saved successfully");
-                self.data.available_frames.push(frame);
+                if self.frame_exists(frame.id()) {
+                    self.operation = Mode::FrameOverwrite(frame.id().to_string());
+                    return Command::none();
+                }
+                self.save_frame(frame);
+                Command::none()
+            }
+
+            Message::FrameMakerOverwrite => {
+                self.save_frame(self.frame_maker.create_frame());
+                Command::none()
+            }
+
+            Message::FrameMakerRename => {
+                let frame = self.frame_maker.create_frame();
+                let Some(name) = (2..)
+                    .map(|i| format!("{}-{}", frame.name(), i))
+                    .find(|x| !self.frame_exists(&format!("{}/{}", frame.category(), x)))
+                else {
+                    return Command::none();
+                };
+                self.frame_maker.set_name(name);
+                self.save_frame(self.frame_maker.create_frame());
+                Command::none()
+            }
+
+            Message::FrameMakerCancelSave => {
+                self.operation = Mode::FrameMaker;
+                Command::none()
+            }
+
+            Message::LookForFrameFolder => {
+                self.operation = Mode::FileBrowser(BrowsingFor::FrameFolder);
+                self.data.file.set_target(Target::Directory);
+                self.data.file.refresh_path().unwrap();
+                Command::none()
+            }
+
+            Message::SetImportCategory(category) => {
+                self.import_category = sanitize_file_name_allow_path(category);
+                Command::none()
+            }
+
+            Message::ImportFrames => {
+                let category = sanitize_file_name_ends(&self.import_category);
+                if category.is_empty() {
+                    self.data
+                        .status
+                        .error("Category for imported frames can't be empty");
+                    return Command::none();
+                }
+                self.data.status.log("Importing frames...");
+                self.main_screen();
+                let extensions = self.data.recognized_extensions();
+                Command::perform(
+                    import_frames(self.import_folder.clone(), category, extensions),
+                    |x| Message::FramesImported(x.map_err(|e| e.to_string())),
+                )
+            }
+
+            Message::FramesImported(result) => match result {
+                Ok((imported, skipped)) => {
+                    if skipped.is_empty() {
+                        self.data
+                            .status
+                            .log(&format!("Imported {} frames", imported.len()));
+                    } else {
+                        self.data.status.warning(&format!(
+                            "Imported {} frames, skipped {}: {}",
+                            imported.len(),
+                            skipped.len(),
+                            skipped.join("; ")
+                        ));
+                    }
+                    if imported.is_empty() {
+                        Command::none()
+                    } else {
+                        reload_frames(self.data.frame_folders().to_vec())
+                    }
+                }
+                Err(e) => {
+                    self.data
+                        .status
+                        .error(&format!("Failed to import frames: {}", e));
+                    Command::none()
+                }
+            },
+
+            Message::RenderBackground => {
+                let prioritized = self.prioritized_workspace();
+                let count = self.workspaces.len();
+                // workspaces take turns so a single one with slow rendering doesn't hold back the rest
+                let next = (1..=count)
+                    .map(|x| (self.background_render + x) % count)
+                    .find(|x| *x != prioritized && self.workspaces[*x].needs_render());
+                let Some(index) = next else {
+                    return Command::none();
+                };
+                self.background_render = index;
+                self.workspaces[index]
+                    .update(WorkspaceMessage::Render, &mut self.data)
+                    .map(move |x| Message::Workspace(index, x))
+            }
+
+            Message::Autosave => {
+                if self.workspaces.is_empty() {
+                    session::clear_recovery();
+                    self.autosaved_sources.clear();
+                    return Command::none();
+                }
+                // settings are otherwise only saved when the program closes
+                self.data.store_recent_colors();
+                self.data.cache.save();
+
+                let mut sources = Vec::new();
+                let workspaces = self
+                    .workspaces
+                    .iter()
+                    .enumerate()
+                    .map(|(i, w)| {
+                        let file = format!("workspace-{}.png", i);
+                        let source = w.get_source();
+                        let saved = self
+                            .autosaved_sources
+                            .get(i)
+                            .is_some_and(|x| Arc::ptr_eq(x, source));
+                        if !saved {
+                            sources.push((file.clone(), source.clone()));
+                        }
+                        w.session_state(file)
+                    })
+                    .collect();
+                let session = Session {
+                    project_name: self.data.naming.project_name.clone(),
+                    workspaces,
+                };
+                let saved: Vec<_> = self
+                    .workspaces
+                    .iter()
+                    .map(|x| x.get_source().clone())
+                    .collect();
+                Command::perform(session::save_session(session, sources), move |x| {
+                    Message::Autosaved(x.map(|_| saved))
+                })
+            }
+
+            Message::Autosaved(result) => {
+                match result {
+                    Ok(saved) => self.autosaved_sources = saved,
+                    Err(e) => {
+                        // images may have been written only partially, all of them are written again next time
+                        self.autosaved_sources.clear();
+                        self.data.status.error(&format!("Autosave failed: {}", e));
+                    }
+                }
+                Command::none()
+            }
+
+            Message::RestoreSession => {
+                self.data.status.log("Restoring previous session...");
+                Command::perform(session::load_session(), Message::SessionLoaded)
+            }
+
+            Message::DiscardSession => {
+                session::clear_recovery();
+                self.operation = Mode::CreateWorkspace;
+                Command::none()
+            }
+
+            Message::SessionLoaded(result) => {
+                let (session, images) = match result {
+                    Ok(x) => x,
+                    Err(e) => {
+                        self.data
+                            .status
+                            .error(&format!("Failed to restore previous session: {}", e));
+                        self.operation = Mode::CreateWorkspace;
+                        return Command::none();
+                    }
+                };
+                self.data.naming.project_name = session.project_name;
+                let mut commands = Vec::new();
+                for (state, image) in session.workspaces.into_iter().zip(images) {
+                    let i = self.workspaces.len();
+                    // default modifiers of the new workspace are replaced by the restored ones so their setup isn't needed
+                    let _ = self.add_workspace(state.output.clone(), Arc::new(image));
+                    let command = self.workspaces[i]
+                        .restore_session(state, &mut self.data)
+                        .map(move |x| Message::Workspace(i, x));
+                    commands.push(command);
+                }
+                self.data.status.log(&format!(
+                    "Restored {} workspaces from previous session",
+                    commands.len()
+                ));
+                self.main_screen();
+                Command::batch(commands)
+            }
+
+            Message::FileDropped(path) => {
+                let frame_maker = matches!(
+                    self.operation,
+                    Mode::FrameMaker | Mode::FileBrowser(BrowsingFor::Frame)
+                );
+                if frame_maker && !self.load_frame_maker(&path) {
+                    self.data
+                        .status
+                        .error(&format!("Failed to open {} as a frame", path.display()));
+                }
                 Command::none()
             }
         }
@@ -438,7 +1357,10 @@ impl Application for TokenMaker {
 
         let ui = match self.operation {
             Mode::FileBrowser(_) => col![
-                self.data.file.view().map(|x| Message::FileBrowser(x)),
+                self.data
+                    .file
+                    .view(self.data.input_style())
+                    .map(Message::FileBrowser),
                 status
             ],
             Mode::SourceSwap => col![top_bar, self.swap_source_image_view(), status,],
@@ -453,6 +1375,11 @@ impl Application for TokenMaker {
                 status
             ],
             Mode::CloseWorkspace => col![top_bar, self.workspace_close_view(), status],
+            Mode::ExportReview => col![top_bar, self.export_review_view(), status],
+            Mode::PsdLayers => col![top_bar, self.psd_layers_view(), status],
+            Mode::FrameImport => col![top_bar, self.frame_import_view(), status],
+            Mode::RestoreSession => col![self.restore_session_view(), status],
+            Mode::FrameOverwrite(ref id) => col![self.frame_overwrite_view(id), status],
         };
 
         container(ui)
@@ -466,22 +1393,77 @@ impl Application for TokenMaker {
     fn subscription(&self) -> Subscription<Self::Message> {
         // collects subscribtions from workspaces and sends them to the framework
         // Everything is worked into regular workspace update cycle
-        let mut subs = Vec::new();
-        self.workspaces.iter().enumerate().for_each(|(i, x)| {
-            let s = x
-                .subscribtion()
-                .with(i)
-                .map(|(i, m)| Message::Workspace(i, m));
-            subs.push(s)
-        });
-        if subs.len() > 0 {
-            Subscription::batch(subs)
-        } else {
-            Subscription::none()
+        // Keyboard shortcuts are listened to regardless of workspaces
+        let mut subs = vec![iced::subscription::events_with(Shortcut::from_event)];
+        // frame images can be dropped into the frame maker, or in place of browsing for one
+        if matches!(
+            self.operation,
+            Mode::FrameMaker | Mode::FileBrowser(BrowsingFor::Frame)
+        ) {
+            subs.push(iced::subscription::events_with(file_dropped));
+        }
+        // only the workspace the user works with renders right away, the rest refresh one at a time
+        let prioritized = self.prioritized_workspace();
+        if let Some(workspace) = self.workspaces.get(prioritized) {
+            subs.push(
+                workspace
+                    .subscribtion()
+                    .with(prioritized)
+                    .map(|(i, m)| Message::Workspace(i, m)),
+            );
         }
+        if self.workspaces.len() > 1 {
+            subs.push(
+                iced::time::every(BACKGROUND_RENDER_INTERVAL).map(|_| Message::RenderBackground),
+            );
+        }
+        if let Some(interval) = self.data.autosave_interval() {
+            if !self.workspaces.is_empty() {
+                subs.push(
+                    iced::time::every(Duration::from_secs_f32(interval * 60.0))
+                        .map(|_| Message::Autosave),
+                );
+            }
+        }
+        Subscription::batch(subs)
     }
 }
 
+impl Drop for TokenMaker {
+    fn drop(&mut self) {
+        // the autosaved session is only kept when the program crashed or the user didn't decide what to do with it yet
+        if !std::thread::panicking() && self.operation != Mode::RestoreSession {
+            session::clear_recovery();
+        }
+    }
+}
+
+/// How often workspaces other than the prioritized one get a turn to refresh their preview
+const BACKGROUND_RENDER_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Creates a task which loads all the frames from drive, including the additional folders
+///
+/// When no frames are found, frames built into the program are written into the frames folder first
+fn reload_frames(folders: Vec<PathBuf>) -> Command<Message> {
+    let load = async move {
+        let frames = load_frames(folders.clone())
+            .await
+            .map_err(|_| "Failed to load frames".to_string())?;
+        if !frames.is_empty() {
+            return Ok(frames);
+        }
+        write_starter_frames().map_err(|e| format!("Failed to write starter frames: {}", e))?;
+        load_frames(folders)
+            .await
+            .map_err(|_| "Failed to load frames".to_string())
+    };
+    Command::perform(load, |x| match x {
+        Ok(x) if !x.is_empty() => Message::LoadedFrames(x),
+        Ok(_) => Message::Error("Could not find any frames".to_string()),
+        Err(e) => Message::Error(e),
+    })
+}
+
 impl TokenMaker {
     fn main_screen(&mut self) {
         if self.workspaces.len() > 0 {
@@ -500,7 +1482,8 @@ impl TokenMaker {
         }
         let name = self.data.naming.get(&self.data.get_workspace_template());
 
-        let (command, new_workspace) = Workspace::new(name, image, &self.data);
+        let id = self.data.next_workspace_id();
+        let (command, new_workspace) = Workspace::new(id, name, image, &self.data);
         let command = command.map(move |x| Message::Workspace(i, x));
 
         // Switching to a new tab if the layout is stacking
@@ -511,9 +1494,61 @@ impl TokenMaker {
         command
     }
 
+    /// Updates the program data with current results of all workspaces so modifiers can use them
+    fn share_workspace_results(&mut self) {
+        self.data.workspace_results = self
+            .workspaces
+            .iter()
+            .map(|x| {
+                let name = x
+                    .get_output_name()
+                    .replace(
+                        NamingConvention::KEYWORD_PROJECT,
+                        &self.data.naming.project_name,
+                    )
+                    .replace('$', "");
+                WorkspaceResult {
+                    id: x.id(),
+                    name,
+                    image: x.get_output(),
+                }
+            })
+            .collect();
+    }
+
+    /// Reads layers of the PSD document and lets the user pick one for the latest workspace if there is more than one
+    fn offer_psd_layers(&mut self, path: &Path) {
+        let psd = match psd::open_psd(path) {
+            Ok(psd) => psd,
+            Err(e) => {
+                self.data.status.error(&e);
+                return;
+            }
+        };
+        if psd.layers.len() < 2 {
+            return;
+        }
+        // layers are only placed on the document canvas once chosen, previews show just the layer
+        self.psd_size = psd.composite.dimensions();
+        self.psd_layers = psd
+            .layers
+            .into_iter()
+            .map(|x| {
+                let preview = image_to_handle(x.image.clone());
+                (x, preview)
+            })
+            .collect();
+        self.psd_workspace = self.workspaces.len() - 1;
+        self.operation = Mode::PsdLayers;
+    }
+
     /// Checks if it is save to save images
     fn can_save(&self) -> Result<(), String> {
-        if self.data.get_output_folder().exists() == false {
+        if self
+            .workspaces
+            .iter()
+            .any(|x| !self.data.get_output_folder(x.get_template()).exists())
+        {
             return Err(String::from("Export folder not set"));
         }
         if self.workspaces.len() == 0 {
@@ -523,6 +1558,9 @@ impl TokenMaker {
             if x.can_save() == false {
                 return Err(String::from("Waitning for workspaces"));
             }
+            if x.is_solo() {
+                return Err(String::from("Turn off solo preview before exporting"));
+            }
             if self
                 .workspaces
                 .iter()
@@ -538,6 +1576,95 @@ impl TokenMaker {
         Ok(())
     }
 
+    /// Returns index of the workspace whose preview is kept up to date first, the shown tab or the last one worked with
+    fn prioritized_workspace(&self) -> usize {
+        match self.data.get_layout() {
+            Layout::Stacking(index) => index,
+            Layout::Parallel => self.focused_workspace,
+        }
+    }
+
+    /// Tests whatever a frame with the identifier is already loaded
+    fn frame_exists(&self, id: &str) -> bool {
+        self.data.available_frames.iter().any(|x| x.id() == id)
+    }
+
+    /// Writes the frame to the frames folder and makes it available, replacing a loaded frame with the same identifier
+    fn save_frame(&mut self, frame: FrameImage) {
+        self.main_screen();
+        if let Err(e) = frame.save_frame() {
+            self.data
+                .status
+                .error(&format!("Failed to save the frame: {}", e));
+            return;
+        }
+        self.data.status.log("Frame saved successfully");
+        match self
+            .data
+            .available_frames
+            .iter_mut()
+            .find(|x| x.id() == frame.id())
+        {
+            Some(existing) => *existing = frame,
+            None => self.data.available_frames.push(frame),
+        }
+    }
+
+    /// Creates a new workspace from the image file, named after the file
+    fn open_workspace(&mut self, path: &Path) -> Command<Message> {
+        let Ok(img) = self.data.images.load(path) else {
+            return Command::none();
+        };
+        let name = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let c = self.add_workspace(name, img);
+        if let Some(w) = self.workspaces.last_mut() {
+            w.set_color_profile(icc::read_profile(path).map(Arc::new));
+            w.set_source_path(Some(path.to_string_lossy().to_string()));
+            if self.data.keep_high_precision() {
+                w.set_precise_source(open_image_16(path).map(Arc::new));
+            }
+        }
+        self.main_screen();
+        if psd::is_psd(path) {
+            self.offer_psd_layers(path);
+        }
+        c
+    }
+
+    /// Opens the image in the frame maker, named after the file. Returns false if the image couldn't be opened
+    fn load_frame_maker(&mut self, path: &Path) -> bool {
+        let Ok(img) = open_image(path) else {
+            return false;
+        };
+        self.frame_maker.load(img);
+        if let Some(name) = path.file_stem().and_then(|x| x.to_str()) {
+            self.frame_maker.set_name(name.to_string());
+        }
+        self.operation = Mode::FrameMaker;
+        true
+    }
+
+    /// Tests whatever all workspaces have their export format chosen, new workspaces wait for it while the program is set to ask for the format
+    fn formats_chosen(&self) -> bool {
+        self.workspaces.iter().all(|x| x.is_format_chosen())
+    }
+
+    /// Makes the next export include all workspaces, files written by earlier exports no longer match the settings
+    fn invalidate_exports(&mut self) {
+        self.workspaces
+            .iter_mut()
+            .for_each(|x| x.invalidate_export());
+    }
+
+    /// Tests whatever export leaves the workspace out because it didn't change since its last export
+    fn skips_export(&self, workspace: &Workspace) -> bool {
+        self.data.export_changed_only() && workspace.is_exported_clean()
+    }
+
     /// Main program UI located at the top of the window
     fn top_bar(&self) -> Element<Message, Renderer> {
         let left = match self.operation {
@@ -550,7 +1677,17 @@ impl TokenMaker {
                     "Replace images in all open workspaces",
                     Position::Bottom
                 )
-                .style(Style::Frame)
+                .style(Style::Frame),
+                tooltip(
+                    ColorPicker::new(self.batch_tint, Message::TintAllFrames)
+                        .recent(&self.data.recent_colors)
+                        .width(28)
+                        .height(28),
+                    "Tint frames in all open workspaces",
+                    Position::Bottom
+                )
+                .style(Style::Frame),
+                self.resize_all_view(),
             ]
             .align_items(Alignment::Center)
             .spacing(4),
@@ -566,6 +1703,15 @@ impl TokenMaker {
             Mode::SourceSwap => {
                 row![button("Cancel").on_press(Message::DisplayWorkspaces)]
             }
+            Mode::ExportReview => {
+                row![button("Cancel").on_press(Message::DisplayWorkspaces)]
+            }
+            Mode::PsdLayers => {
+                row![button("Keep Flattened").on_press(Message::PsdLayerSelected(None))]
+            }
+            Mode::FrameImport => {
+                row![button("Cancel").on_press(Message::DisplayWorkspaces)]
+            }
             _ => {
                 row![]
             }
@@ -587,12 +1733,67 @@ impl TokenMaker {
             Mode::Settings => {
                 row![button("Close").on_press(Message::DisplayWorkspaces)]
             }
-            Mode::FileBrowser(_) => {
+            Mode::ExportReview => {
+                let dry_run = tooltip(
+                    button("Dry Run").on_press(Message::DryRunExport),
+                    "Checks whatever all files can be written without writing anything",
+                    Position::Left,
+                )
+                .style(Style::Frame);
+                if let Err(e) = self.can_save() {
+                    row![
+                        dry_run,
+                        tooltip(button("Export Layers"), e.clone(), Position::Left)
+                            .style(Style::Frame),
+                        tooltip(button("Export"), e, Position::Left).style(Style::Frame)
+                    ]
+                    .spacing(5)
+                } else {
+                    row![
+                        dry_run,
+                        tooltip(
+                            button("Export Layers").on_press(Message::ExportLayers),
+                            "Saves all workspaces as layers of a single PSD document named after the project",
+                            Position::Left,
+                        )
+                        .style(Style::Frame),
+                        if self.formats_chosen() {
+                            tooltip(
+                                button("Export").on_press(Message::Export),
+                                "Export to selected folder",
+                                Position::Left,
+                            )
+                        } else {
+                            tooltip(
+                                button("Export"),
+                                "Choose export format for the new workspaces first",
+                                Position::Left,
+                            )
+                        }
+                        .style(Style::Frame)
+                    ]
+                    .spacing(5)
+                }
+            }
+            Mode::FrameImport => {
+                if self.import_category.is_empty() {
+                    row![button("Import")]
+                } else {
+                    row![button("Import").on_press(Message::ImportFrames)]
+                }
+            }
+            Mode::FileBrowser(_) | Mode::PsdLayers => {
                 row![]
             }
             _ => {
                 row![
                     button("Frame Maker").on_press(Message::LookForFrame),
+                    tooltip(
+                        button("Import Frames").on_press(Message::LookForFrameFolder),
+                        "Import frames with their masks from a folder",
+                        Position::Bottom
+                    )
+                    .style(Style::Frame),
                     button("Settings").on_press(Message::DisplaySettings)
                 ]
             }
@@ -604,12 +1805,16 @@ impl TokenMaker {
                 text("Project Name: "),
                 text_input("Project Name", &self.data.naming.project_name, |x| {
                     Message::SettingsMessage(ProgramDataMessage::SetProjectName(x))
-                }),
+                })
+                .style(self.data.input_style()),
                 tooltip(
                     button("Set Export Path").on_press(Message::LookForOutputFolder),
                     format!(
-                        "Current Path: {}",
-                        self.data.get_output_folder().to_string_lossy()
+                        "Current Path for {} template: {}",
+                        self.data.get_workspace_template(),
+                        self.data
+                            .get_output_folder(self.data.get_workspace_template())
+                            .to_string_lossy()
                     ),
                     Position::Bottom
                 )
@@ -620,11 +1825,11 @@ impl TokenMaker {
                     if self
                         .workspaces
                         .iter()
-                        .any(|x| x.is_destructive_export(&self.data))
+                        .any(|x| !self.skips_export(x) && x.is_destructive_export(&self.data))
                     {
                         tooltip(
                             button("Export")
-                                .on_press(Message::Export)
+                                .on_press(Message::DisplayExportReview)
                                 .style(Style::Danger.into()),
                             "One or more workspaces will override existing file",
                             Position::Bottom,
@@ -632,7 +1837,7 @@ impl TokenMaker {
                         .style(Style::Frame)
                     } else {
                         tooltip(
-                            button("Export").on_press(Message::Export),
+                            button("Export").on_press(Message::DisplayExportReview),
                             "Export to selected folder",
                             tooltip::Position::Bottom,
                         )
@@ -692,7 +1897,12 @@ impl TokenMaker {
                             .padding(5)
                             .align_items(Alignment::Center),
                         |r, i| r.push(
-                            button(text(i.to_string())).on_press(Message::WorkspaceSelect(i))
+                            button(text(if self.workspaces[i].is_locked() {
+                                format!("{} (locked)", i)
+                            } else {
+                                i.to_string()
+                            }))
+                            .on_press(Message::WorkspaceSelect(i))
                         )
                     ),
                     ui
@@ -750,6 +1960,265 @@ impl TokenMaker {
         .into()
     }
 
+    /// Constructs UI for choosing category of frames imported from a folder
+    fn frame_import_view(&self) -> Element<'_, Message, Renderer> {
+        let ui = col![
+            text(format!(
+                "Importing frames from: {}",
+                self.import_folder.to_string_lossy()
+            )),
+            text("Each frame needs a mask named after it with -mask suffix, like frame.webp and frame-mask.webp"),
+            row![
+                text("Category: "),
+                text_input("Category", &self.import_category, Message::SetImportCategory).style(self.data.input_style())
+                    .on_submit(Message::ImportFrames),
+            ]
+            .spacing(5)
+            .align_items(Alignment::Center),
+        ]
+        .spacing(10);
+        let ui = container(ui).style(Style::Frame).padding(20).width(600);
+
+        container(ui)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(Style::Margins)
+            .into()
+    }
+
+    /// Returns the size chosen for all workspaces if both dimensions are valid
+    fn batch_size(&self) -> Option<(u32, u32)> {
+        let width = self.batch_width.parse::<u32>().ok().filter(|x| *x > 0)?;
+        let height = self.batch_height.parse::<u32>().ok().filter(|x| *x > 0)?;
+        Some((width, height))
+    }
+
+    /// Constructs controls for setting export size of all workspaces at once
+    fn resize_all_view(&self) -> Element<'_, Message, Renderer> {
+        let apply = if self.batch_size().is_some() {
+            button("Size All").on_press(Message::ResizeAllWorkspaces)
+        } else {
+            button("Size All")
+        };
+        row![
+            text_input("Width", &self.batch_width, Message::SetBatchWidth).style(self.data.input_style())
+                .on_submit(Message::ResizeAllWorkspaces)
+                .width(60),
+            text("x"),
+            text_input("Height", &self.batch_height, Message::SetBatchHeight).style(self.data.input_style())
+                .on_submit(Message::ResizeAllWorkspaces)
+                .width(60),
+            tooltip(
+                apply,
+                "Set export size of all open workspaces",
+                Position::Bottom
+            )
+            .style(Style::Frame),
+            tooltip(
+                button("Square All").on_press(Message::SquareAllWorkspaces),
+                "Set export size of all open workspaces to a square as large as the largest of them and center their images",
+                Position::Bottom
+            )
+            .style(Style::Frame),
+        ]
+        .spacing(2)
+        .align_items(Alignment::Center)
+        .into()
+    }
+
+    /// Constructs UI asking whatever workspaces autosaved before the program last failed to exit cleanly should be restored
+    fn restore_session_view(&self) -> Element<'_, Message, Renderer> {
+        let ui = col![
+            text("The program didn't close properly last time, do you want to restore the workspaces it was working on?"),
+            row![
+                button("Restore").on_press(Message::RestoreSession),
+                button("Discard").on_press(Message::DiscardSession),
+            ]
+            .spacing(5),
+        ]
+        .spacing(10);
+        let ui = container(ui).style(Style::Frame).padding(20).width(600);
+
+        container(ui)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(Style::Margins)
+            .into()
+    }
+
+    /// Constructs UI asking what to do with a frame which would replace an existing one
+    fn frame_overwrite_view(&self, id: &str) -> Element<'_, Message, Renderer> {
+        let ui = col![
+            text(format!(
+                "Frame {} already exists, do you want to replace it or save the new frame under a different name?",
+                id
+            )),
+            row![
+                button("Overwrite")
+                    .on_press(Message::FrameMakerOverwrite)
+                    .style(Style::Danger.into()),
+                button("Rename").on_press(Message::FrameMakerRename),
+                button("Cancel").on_press(Message::FrameMakerCancelSave),
+            ]
+            .spacing(5),
+        ]
+        .spacing(10);
+        let ui = container(ui).style(Style::Frame).padding(20).width(600);
+
+        container(ui)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(Style::Margins)
+            .into()
+    }
+
+    /// Constructs UI for choosing which layer of the loaded PSD document to use as the source image
+    fn psd_layers_view(&self) -> Element<'_, Message, Renderer> {
+        let entries = self
+            .psd_layers
+            .iter()
+            .enumerate()
+            .map(|(i, (layer, preview))| {
+                let entry = row![
+                    picture(preview.clone())
+                        .content_fit(ContentFit::Contain)
+                        .width(64)
+                        .height(64),
+                    text(&layer.name),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center);
+                button(entry)
+                    .on_press(Message::PsdLayerSelected(Some(i)))
+                    .width(Length::Fill)
+            })
+            .fold(
+                col![text("Choose a layer to use as the image:")]
+                    .spacing(4)
+                    .padding(10),
+                |c, e| c.push(e),
+            );
+
+        container(scrollable(entries))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(Style::Margins)
+            .into()
+    }
+
+    /// Constructs UI listing what each workspace will write on export
+    fn export_review_view(&self) -> Element<'_, Message, Renderer> {
+        let entries = self
+            .workspaces
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                let size = w.get_export_size();
+                let skipped = self.skips_export(w);
+                let destructive = !skipped && w.is_destructive_export(&self.data);
+                let format: Element<_, _> = if w.is_format_chosen() {
+                    text(format!(
+                        "Format: {}, Size: {}x{}",
+                        w.get_export_format(),
+                        size.width,
+                        size.height
+                    ))
+                    .into()
+                } else {
+                    row![
+                        text("Format:"),
+                        PickList::new(&ImageFormat::EXPORTABLE[..], None, move |x| {
+                            Message::Workspace(i, WorkspaceMessage::SetFormat(x))
+                        })
+                        .placeholder("Choose format"),
+                        text(format!("Size: {}x{}", size.width, size.height)),
+                    ]
+                    .spacing(5)
+                    .align_items(Alignment::Center)
+                    .into()
+                };
+                let info = col![
+                    text(w.construct_export_path(&self.data).to_string_lossy()),
+                    format,
+                ]
+                .spacing(2)
+                .width(Length::Fill);
+                let info = if self.data.export_outline() {
+                    info.push(text("SVG outline will be exported alongside the image"))
+                } else {
+                    info
+                };
+                let info = if w.get_notes().is_empty() {
+                    info
+                } else {
+                    info.push(text(format!("Notes: {}", w.get_notes())))
+                };
+                let info = if w.exports_art() {
+                    info.push(text(format!(
+                        "Art without frame will be exported to {}",
+                        w.construct_art_export_path(&self.data).to_string_lossy()
+                    )))
+                } else {
+                    info
+                };
+                let info = if destructive {
+                    info.push(text("File already exists and will be overwritten"))
+                } else {
+                    info
+                };
+                let info = if skipped {
+                    info.push(text("Unchanged since the last export, it will be skipped"))
+                } else {
+                    info
+                };
+                let entry = row![
+                    picture(w.get_preview())
+                        .content_fit(ContentFit::Contain)
+                        .width(64)
+                        .height(64),
+                    info,
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center);
+
+                container(entry)
+                    .padding(5)
+                    .width(Length::Fill)
+                    .style(if destructive {
+                        Style::Danger
+                    } else {
+                        Style::Frame
+                    })
+            })
+            .fold(col![].spacing(4).padding(10), |c, e| c.push(e));
+        let entries = if self.dry_run.is_empty() {
+            entries
+        } else {
+            let plan = self.dry_run.iter().fold(
+                col![text("Dry run, files the export would write:")].spacing(2),
+                |c, x| c.push(text(x)),
+            );
+            entries.push(
+                container(plan)
+                    .padding(5)
+                    .width(Length::Fill)
+                    .style(Style::Frame),
+            )
+        };
+
+        container(scrollable(entries))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(Style::Margins)
+            .into()
+    }
+
     fn workspace_close_view(&self) -> Element<Message, Renderer> {
         let views = self
             .workspaces
@@ -893,3 +2362,31 @@ impl TokenMaker {
             .into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_decode_escapes() {
+        assert_eq!(
+            percent_decode("/home/user/My%20Tokens/orc.png"),
+            "/home/user/My Tokens/orc.png"
+        );
+        assert_eq!(
+            percent_decode("/tmp/%c5%bc%C3%B3%C5%82w.png"),
+            "/tmp/żółw.png"
+        );
+        assert_eq!(percent_decode("/tmp/100%25.png"), "/tmp/100%.png");
+    }
+
+    #[test]
+    fn percent_decode_leaves_invalid_escapes() {
+        assert_eq!(percent_decode("/tmp/50%off.png"), "/tmp/50%off.png");
+        assert_eq!(percent_decode("/tmp/end%2"), "/tmp/end%2");
+        assert_eq!(percent_decode("/tmp/end%"), "/tmp/end%");
+        assert_eq!(percent_decode("/tmp/%zz%4"), "/tmp/%zz%4");
+        assert_eq!(percent_decode("/tmp/żółw%20.png"), "/tmp/żółw .png");
+        assert_eq!(percent_decode("/tmp/%żółw"), "/tmp/%żółw");
+    }
+}
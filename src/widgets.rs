@@ -1,10 +1,18 @@
+mod collapsible;
 mod color_picker;
+mod drag_list;
 mod file_browser;
 mod pixel_sampler;
+mod splitter;
+mod swatches;
 mod text_box;
 mod trackpad;
 
-pub use color_picker::ColorPicker;
+pub use collapsible::collapsible;
+pub use color_picker::{ColorPicker, RecentColors};
+pub use drag_list::DragList;
 pub use file_browser::{Browser, BrowserOperation, BrowsingResult, Target};
 pub use pixel_sampler::PixelSampler;
+pub use splitter::Splitter;
+pub use swatches::swatches;
 pub use trackpad::Trackpad;
@@ -0,0 +1,31 @@
+use iced::{
+    widget::{button, column as col, text},
+    Element, Renderer,
+};
+
+use crate::style::Style;
+
+/// Creates a section with a clickable title which shows or hides the content under it
+///
+/// The widget doesn't remember whatever it is expanded, the owner keeps the state and updates it with the message from `on_toggle`
+pub fn collapsible<'a, Message, F>(
+    title: &str,
+    expanded: bool,
+    on_toggle: F,
+    content: impl Into<Element<'a, Message, Renderer>>,
+) -> Element<'a, Message, Renderer>
+where
+    Message: Clone + 'a,
+    F: Fn(bool) -> Message,
+{
+    let marker = if expanded { "v" } else { ">" };
+    let header = button(text(format!("{} {}", marker, title)))
+        .padding(2)
+        .on_press(on_toggle(!expanded))
+        .style(Style::Header.into());
+    if expanded {
+        col![header, content.into()].spacing(6).into()
+    } else {
+        header.into()
+    }
+}
@@ -1,3 +1,5 @@
+use std::{cell::RefCell, rc::Rc};
+
 use iced::{
     alignment::{Horizontal, Vertical},
     event::Status,
@@ -13,7 +15,7 @@ use iced_native::{
     Renderer as _, Widget,
 };
 
-use crate::image::convert::{color_to_hsv, hsv_to_color};
+use crate::image::convert::{color_to_hex, color_to_hsv, hex_to_color, hsv_to_color};
 
 use super::text_box::{self, TextBox, TextBoxStyle};
 
@@ -25,16 +27,84 @@ where
 {
     color: Color,
     on_submit: Box<dyn 'c + Fn(Color) -> M>,
+    recent: Option<RecentColors>,
     width: Length,
     height: Length,
     style: <R::Theme as StyleSheet>::Style,
 }
 
+/// Text support of the renderer the color picker is drawn with
+///
+/// Renderers with text backends get it implemented automatically. Renderers of backends without text support
+/// can opt in with an empty implementation, the picker then leaves out its text inputs and labels while hue and value picking,
+/// the sliders and recent colors keep working, and hex codes can be pasted with Ctrl+V in place of typing them.
+pub trait PickerText<Theme> {
+    /// Whatever the renderer can draw text
+    const TEXT: bool = false;
+
+    /// Draws the text if the renderer supports it
+    fn picker_text(&mut self, _text: Text<'_, iced::Font>) {}
+
+    /// Draws the text box if the renderer supports text
+    fn picker_text_box(
+        &mut self,
+        _text_box: &TextBox,
+        _area: Rectangle,
+        _theme: &Theme,
+        _cursor_position: Point,
+    ) {
+    }
+
+    /// Passes the event to the text box, without text support text boxes ignore all events
+    fn picker_text_box_event(
+        &self,
+        _text_box: &mut TextBox,
+        _area: Rectangle,
+        _event: &iced::Event,
+        _cursor_position: Point,
+    ) -> text_box::TextBoxStatus {
+        text_box::TextBoxStatus::Ignored
+    }
+}
+
+impl<B, T> PickerText<T> for Renderer<B, T>
+where
+    B: Backend + iced_graphics::backend::Text,
+    T: text_box::StyleSheet<Style = TextBoxStyle>,
+{
+    const TEXT: bool = true;
+
+    fn picker_text(&mut self, text: Text<'_, iced::Font>) {
+        self.fill_text(text);
+    }
+
+    fn picker_text_box(
+        &mut self,
+        text_box: &TextBox,
+        area: Rectangle,
+        theme: &T,
+        cursor_position: Point,
+    ) {
+        text_box.draw(area, theme, self, cursor_position);
+    }
+
+    fn picker_text_box_event(
+        &self,
+        text_box: &mut TextBox,
+        area: Rectangle,
+        event: &iced::Event,
+        cursor_position: Point,
+    ) -> text_box::TextBoxStatus {
+        text_box.on_event(area, event, self, cursor_position)
+    }
+}
+
 impl<'c, M, B, T> Widget<M, Renderer<B, T>> for ColorPicker<'c, M, Renderer<B, T>>
 where
     M: Clone,
-    B: Backend + iced_graphics::backend::Text,
-    T: StyleSheet + text_box::StyleSheet<Style = TextBoxStyle>,
+    B: Backend,
+    T: StyleSheet,
+    Renderer<B, T>: PickerText<T>,
 {
     fn width(&self) -> iced::Length {
         self.width
@@ -55,11 +125,14 @@ where
         state.r_input.set_input(u8_numbers_only_text_input);
         state.g_input.set_input(u8_numbers_only_text_input);
         state.b_input.set_input(u8_numbers_only_text_input);
+        state.a_input.set_input(u8_numbers_only_text_input);
+        state.hex_input.set_input(hex_only_text_input);
 
         let (hue, sat, val) = color_to_hsv(self.color);
         state.hue = hue;
         state.saturation = sat;
         state.value = val;
+        state.alpha = self.color.a;
 
         state.regenerate_ui();
 
@@ -95,6 +168,7 @@ where
         let border_width = if is_mouse_over { 2.0 } else { 1.0 };
         let border_radius = border_width.into();
 
+        draw_checkerboard(renderer, bounds);
         renderer.fill_quad(
             iced_native::renderer::Quad {
                 bounds,
@@ -155,7 +229,16 @@ where
         };
 
         if local_state.open {
-            Some(Overlay::new(local_state, pos, &self.on_submit, &self.style).into())
+            Some(
+                Overlay::new(
+                    local_state,
+                    pos,
+                    &self.on_submit,
+                    self.recent.as_ref(),
+                    &self.style,
+                )
+                .into(),
+            )
         } else {
             None
         }
@@ -172,6 +255,7 @@ where
         Self {
             color,
             on_submit: Box::new(on_submit),
+            recent: None,
             height: Length::Shrink,
             width: Length::Shrink,
             style: <R::Theme as StyleSheet>::Style::default(),
@@ -187,6 +271,39 @@ where
         self.height = height.into();
         self
     }
+
+    /// Shows the recently chosen colors in the picker and adds the chosen color to them
+    pub fn recent(mut self, recent: &RecentColors) -> Self {
+        self.recent = Some(recent.clone());
+        self
+    }
+}
+
+/// Colors most recently chosen in color pickers, the list is shared by all pickers it was given to
+#[derive(Debug, Clone, Default)]
+pub struct RecentColors(Rc<RefCell<Vec<Color>>>);
+
+impl RecentColors {
+    /// Maximum number of remembered colors
+    pub const LIMIT: usize = 10;
+
+    pub fn new(mut colors: Vec<Color>) -> Self {
+        colors.truncate(Self::LIMIT);
+        Self(Rc::new(RefCell::new(colors)))
+    }
+
+    /// Moves the color to the front of the list, dropping the oldest color if the list is full
+    pub fn push(&self, color: Color) {
+        let mut colors = self.0.borrow_mut();
+        colors.retain(|x| *x != color);
+        colors.insert(0, color);
+        colors.truncate(Self::LIMIT);
+    }
+
+    /// Returns the colors from the most recent one
+    pub fn colors(&self) -> Vec<Color> {
+        self.0.borrow().clone()
+    }
 }
 
 #[derive(Default)]
@@ -199,11 +316,15 @@ struct State {
     r_input: TextBox,
     g_input: TextBox,
     b_input: TextBox,
+    a_input: TextBox,
+    /// Hex code of the color, kept in sync with the other inputs
+    hex_input: TextBox,
     color_input_type: ColorInputType,
 
     hue: f32,
     saturation: f32,
     value: f32,
+    alpha: f32,
 
     mouseover_hue: bool,
     mouseover_color: bool,
@@ -215,28 +336,71 @@ impl State {
         self.color_widget.clear();
 
         let Color { r, g, b, a: _ } = hsv_to_color(self.hue, self.saturation, self.value);
-        let (r, g, b) = match self.color_input_type {
+        let a = self.alpha;
+        let (r, g, b, a) = match self.color_input_type {
             ColorInputType::Float => {
                 let r = format!("{:.4}", r);
                 let g = format!("{:.4}", g);
                 let b = format!("{:.4}", b);
-                (r, g, b)
+                let a = format!("{:.4}", a);
+                (r, g, b, a)
             }
             ColorInputType::Int => {
                 let r = format!("{}", (r * 255.0) as u8);
                 let g = format!("{}", (g * 255.0) as u8);
                 let b = format!("{}", (b * 255.0) as u8);
-                (r, g, b)
+                let a = format!("{}", (a * 255.0) as u8);
+                (r, g, b, a)
             }
         };
 
         self.r_input.set_content(r);
         self.g_input.set_content(g);
         self.b_input.set_content(b);
+        self.a_input.set_content(a);
+        self.refresh_hex_input();
+    }
+
+    /// Sets the hex code to the chosen color, alpha is left out for opaque colors
+    fn refresh_hex_input(&mut self) {
+        let mut hex = color_to_hex(self.color());
+        if self.alpha >= 1.0 {
+            hex.truncate(7);
+        }
+        self.hex_input.set_content(hex);
+    }
+
+    /// Sets the color from the hex code if the code is complete
+    fn update_color_from_hex(&mut self) {
+        let Some(color) = hex_to_color(self.hex_input.get_content()) else {
+            return;
+        };
+        // the code is kept as typed in, in case the user is about to add alpha to it
+        let hex = self.hex_input.get_content().clone();
+        self.set_color(color);
+        self.regenerate_ui();
+        self.hex_input.set_content(hex);
+    }
+
+    /// Color currently chosen in the picker, including its alpha
+    fn color(&self) -> Color {
+        Color {
+            a: self.alpha,
+            ..hsv_to_color(self.hue, self.saturation, self.value)
+        }
+    }
+
+    /// Sets the chosen color, including its alpha
+    fn set_color(&mut self, color: Color) {
+        let (h, s, v) = color_to_hsv(color);
+        self.hue = h;
+        self.saturation = s;
+        self.value = v;
+        self.alpha = color.a;
     }
 
     fn update_color_from_input(&mut self) {
-        let (r, g, b) = match self.color_input_type {
+        let (r, g, b, a) = match self.color_input_type {
             ColorInputType::Float => {
                 let Ok(r) = self.r_input.get_content().parse() else {
                     return;
@@ -247,7 +411,19 @@ impl State {
                 let Ok(b) = self.b_input.get_content().parse() else {
                     return;
                 };
-                (r, g, b)
+                let Ok(a) = self.a_input.get_content().parse::<f32>() else {
+                    return;
+                };
+                // typed values can go past the range of the color or not be numbers at all
+                if [r, g, b, a].iter().any(|x: &f32| x.is_nan()) {
+                    return;
+                }
+                (
+                    r.clamp(0.0, 1.0),
+                    g.clamp(0.0, 1.0),
+                    b.clamp(0.0, 1.0),
+                    a.clamp(0.0, 1.0),
+                )
             }
             ColorInputType::Int => {
                 let Ok(r) = self.r_input.get_content().parse::<u8>() else {
@@ -259,18 +435,20 @@ impl State {
                 let Ok(b) = self.b_input.get_content().parse::<u8>() else {
                     return;
                 };
+                let Ok(a) = self.a_input.get_content().parse::<u8>() else {
+                    return;
+                };
                 let r = r as f32 / 255.0;
                 let g = g as f32 / 255.0;
                 let b = b as f32 / 255.0;
+                let a = a as f32 / 255.0;
 
-                (r, g, b)
+                (r, g, b, a)
             }
         };
 
-        let (hue, sat, val) = color_to_hsv(Color { r, g, b, a: 1.0 });
-        self.hue = hue;
-        self.saturation = sat;
-        self.value = val;
+        self.set_color(Color { r, g, b, a });
+        self.refresh_hex_input();
 
         self.hue_widget.clear();
         self.color_widget.clear();
@@ -287,6 +465,7 @@ where
     margin: f32,
     spacing: f32,
     on_submit: &'a Box<dyn 'a + Fn(Color) -> M>,
+    recent: Option<&'a RecentColors>,
     style: &'a <R::Theme as StyleSheet>::Style,
 }
 
@@ -299,6 +478,7 @@ where
         state: &'a mut State,
         pos: Point,
         on_submit: &'a Box<dyn 'a + Fn(Color) -> M>,
+        recent: Option<&'a RecentColors>,
         style: &'a T::Style,
     ) -> Self {
         Self {
@@ -307,21 +487,37 @@ where
                 x: pos.x,
                 y: pos.y,
                 width: 400.0,
-                height: 200.0,
+                height: 240.0,
             },
             margin: 10.0,
             spacing: 10.0,
             on_submit,
+            recent,
             style,
         }
     }
+
+    /// Colors to be shown as recently chosen
+    fn recent_colors(&self) -> Vec<Color> {
+        self.recent.map(|x| x.colors()).unwrap_or_default()
+    }
+
+    /// Sends the chosen color and closes the picker
+    fn submit(&mut self, color: Color, shell: &mut iced_native::Shell<'_, M>) {
+        if let Some(recent) = self.recent {
+            recent.push(color);
+        }
+        self.state.open = false;
+        shell.publish((self.on_submit)(color));
+    }
 }
 
 impl<'a, M, B, T> iced_native::Overlay<M, Renderer<B, T>> for Overlay<'a, M, Renderer<B, T>>
 where
     M: Clone,
-    B: Backend + iced_graphics::backend::Text,
-    T: StyleSheet + text_box::StyleSheet<Style = TextBoxStyle>,
+    B: Backend,
+    T: StyleSheet,
+    Renderer<B, T>: PickerText<T>,
 {
     fn layout(&self, _renderer: &Renderer<B, T>, bounds: Size, _position: iced::Point) -> Node {
         let mut n = Node::new(self.area.size());
@@ -539,15 +735,19 @@ where
         let r_area = slider_widget_rect(&bounds, self.margin, self.spacing, 0.0);
         let g_area = slider_widget_rect(&bounds, self.margin, self.spacing, 1.0);
         let b_area = slider_widget_rect(&bounds, self.margin, self.spacing, 2.0);
+        let a_area = slider_widget_rect(&bounds, self.margin, self.spacing, 3.0);
         let r_label_area = slider_label_rect(&bounds, self.margin, self.spacing, 0.0);
         let g_label_area = slider_label_rect(&bounds, self.margin, self.spacing, 1.0);
         let b_label_area = slider_label_rect(&bounds, self.margin, self.spacing, 2.0);
-        let p_area = slider_label_rect(&bounds, self.margin, self.spacing, 3.0);
+        let a_label_area = slider_label_rect(&bounds, self.margin, self.spacing, 3.0);
+        let p_area = slider_label_rect(&bounds, self.margin, self.spacing, 4.0);
         let r_input = slider_text_box_rect(&bounds, self.margin, self.spacing, 0.0);
         let g_input = slider_text_box_rect(&bounds, self.margin, self.spacing, 1.0);
         let b_input = slider_text_box_rect(&bounds, self.margin, self.spacing, 2.0);
-        let toggle_input = slider_text_box_rect(&bounds, self.margin, self.spacing, 3.0);
-        let col = hsv_to_color(self.state.hue, self.state.saturation, self.state.value);
+        let a_input = slider_text_box_rect(&bounds, self.margin, self.spacing, 3.0);
+        let toggle_input = slider_text_box_rect(&bounds, self.margin, self.spacing, 4.0);
+        let hex_input = slider_widget_rect(&bounds, self.margin, self.spacing, 4.0);
+        let col = self.state.color();
 
         let mut r_border = if r_area.contains(cursor_position) {
             Quad {
@@ -594,6 +794,21 @@ where
                 bounds: b_area,
             }
         };
+        let mut a_border = if a_area.contains(cursor_position) {
+            Quad {
+                border_color: style.hover_border_color,
+                border_radius: style.hover_border_radius.into(),
+                border_width: style.hover_border_width,
+                bounds: a_area,
+            }
+        } else {
+            Quad {
+                border_color: style.border_color,
+                border_radius: style.border_radius.into(),
+                border_width: style.border_width,
+                bounds: a_area,
+            }
+        };
         let toggle_border = if toggle_input.contains(cursor_position) {
             Quad {
                 border_color: style.hover_border_color,
@@ -614,30 +829,31 @@ where
         renderer.fill_quad(r_border, style.background);
         renderer.fill_quad(g_border, style.background);
         renderer.fill_quad(b_border, style.background);
+        renderer.fill_quad(a_border, style.background);
 
         r_border.bounds.width *= col.r;
         g_border.bounds.width *= col.g;
         b_border.bounds.width *= col.b;
+        a_border.bounds.width *= col.a;
 
         // Drawing fills for the sliders
         renderer.fill_quad(r_border, Color::from_rgb(col.r, 0.0, 0.0));
         renderer.fill_quad(g_border, Color::from_rgb(0.0, col.g, 0.0));
         renderer.fill_quad(b_border, Color::from_rgb(0.0, 0.0, col.b));
+        renderer.fill_quad(a_border, Color::from_rgb(col.a, col.a, col.a));
 
         // draw the text input boxes
-        self.state
-            .r_input
-            .draw(r_input, theme, renderer, cursor_position);
-        self.state
-            .g_input
-            .draw(g_input, theme, renderer, cursor_position);
-        self.state
-            .b_input
-            .draw(b_input, theme, renderer, cursor_position);
-
-        // drawing toggle for text input type
-        renderer.fill_quad(toggle_border, style.button_color);
-        renderer.fill_text(Text {
+        renderer.picker_text_box(&self.state.r_input, r_input, theme, cursor_position);
+        renderer.picker_text_box(&self.state.g_input, g_input, theme, cursor_position);
+        renderer.picker_text_box(&self.state.b_input, b_input, theme, cursor_position);
+        renderer.picker_text_box(&self.state.a_input, a_input, theme, cursor_position);
+        renderer.picker_text_box(&self.state.hex_input, hex_input, theme, cursor_position);
+
+        // drawing toggle for text input type, it is useless without text inputs
+        if <Renderer<B, T> as PickerText<T>>::TEXT {
+            renderer.fill_quad(toggle_border, style.button_color);
+        }
+        renderer.picker_text(Text {
             content: match self.state.color_input_type {
                 ColorInputType::Float => "Float",
                 ColorInputType::Int => "Int",
@@ -655,7 +871,7 @@ where
         });
 
         // labels for sliders
-        renderer.fill_text(Text {
+        renderer.picker_text(Text {
             content: "R",
             bounds: Rectangle {
                 x: r_label_area.x + r_label_area.width * 0.5,
@@ -668,7 +884,7 @@ where
             horizontal_alignment: Horizontal::Center,
             vertical_alignment: Vertical::Center,
         });
-        renderer.fill_text(Text {
+        renderer.picker_text(Text {
             content: "G",
             bounds: Rectangle {
                 x: g_label_area.x + g_label_area.width * 0.5,
@@ -681,7 +897,7 @@ where
             horizontal_alignment: Horizontal::Center,
             vertical_alignment: Vertical::Center,
         });
-        renderer.fill_text(Text {
+        renderer.picker_text(Text {
             content: "B",
             bounds: Rectangle {
                 x: b_label_area.x + b_label_area.width * 0.5,
@@ -694,8 +910,22 @@ where
             horizontal_alignment: Horizontal::Center,
             vertical_alignment: Vertical::Center,
         });
+        renderer.picker_text(Text {
+            content: "A",
+            bounds: Rectangle {
+                x: a_label_area.x + a_label_area.width * 0.5,
+                y: a_label_area.y + a_label_area.height * 0.5,
+                ..a_label_area
+            },
+            size: a_label_area.height - 4.0,
+            color: style.text_color,
+            font: Default::default(),
+            horizontal_alignment: Horizontal::Center,
+            vertical_alignment: Vertical::Center,
+        });
 
-        // preview square
+        // preview square, drawn over a checkerboard so the transparency is visible
+        draw_checkerboard(renderer, p_area);
         renderer.fill_quad(
             Quad {
                 border_color: style.border_color,
@@ -726,7 +956,26 @@ where
 
         renderer.fill_quad(accept_quad, style.button_color);
 
-        renderer.fill_text(Text {
+        // recently chosen colors
+        for (i, recent) in self.recent_colors().into_iter().enumerate() {
+            let area = recent_rect(&bounds, self.margin, self.spacing, i);
+            let (border_color, border_width) = if area.contains(cursor_position) {
+                (style.hover_border_color, style.hover_border_width)
+            } else {
+                (style.border_color, style.border_width)
+            };
+            renderer.fill_quad(
+                Quad {
+                    bounds: area,
+                    border_color,
+                    border_width,
+                    border_radius: 0.0.into(),
+                },
+                recent,
+            );
+        }
+
+        renderer.picker_text(Text {
             bounds: butt,
             color: style.text_color,
             content: " >",
@@ -743,17 +992,18 @@ where
         layout: iced_native::Layout<'_>,
         cursor_position: iced::Point,
         renderer: &Renderer<B, T>,
-        _clipboard: &mut dyn iced_native::Clipboard,
+        clipboard: &mut dyn iced_native::Clipboard,
         shell: &mut iced_native::Shell<'_, M>,
     ) -> Status {
         let bounds = layout.bounds();
 
         let r_input = slider_text_box_rect(&bounds, self.margin, self.spacing, 0.0);
-        match self
-            .state
-            .r_input
-            .on_event(r_input, &event, renderer, cursor_position)
-        {
+        match renderer.picker_text_box_event(
+            &mut self.state.r_input,
+            r_input,
+            &event,
+            cursor_position,
+        ) {
             text_box::TextBoxStatus::Ignored => {}
             text_box::TextBoxStatus::Captured => return Status::Captured,
             text_box::TextBoxStatus::ContentChanged => {
@@ -763,11 +1013,12 @@ where
         }
 
         let g_input = slider_text_box_rect(&bounds, self.margin, self.spacing, 1.0);
-        match self
-            .state
-            .g_input
-            .on_event(g_input, &event, renderer, cursor_position)
-        {
+        match renderer.picker_text_box_event(
+            &mut self.state.g_input,
+            g_input,
+            &event,
+            cursor_position,
+        ) {
             text_box::TextBoxStatus::Ignored => {}
             text_box::TextBoxStatus::Captured => return Status::Captured,
             text_box::TextBoxStatus::ContentChanged => {
@@ -777,11 +1028,27 @@ where
         }
 
         let b_input = slider_text_box_rect(&bounds, self.margin, self.spacing, 2.0);
-        match self
-            .state
-            .b_input
-            .on_event(b_input, &event, renderer, cursor_position)
-        {
+        match renderer.picker_text_box_event(
+            &mut self.state.b_input,
+            b_input,
+            &event,
+            cursor_position,
+        ) {
+            text_box::TextBoxStatus::Ignored => {}
+            text_box::TextBoxStatus::Captured => return Status::Captured,
+            text_box::TextBoxStatus::ContentChanged => {
+                self.state.update_color_from_input();
+                return Status::Captured;
+            }
+        }
+
+        let a_input = slider_text_box_rect(&bounds, self.margin, self.spacing, 3.0);
+        match renderer.picker_text_box_event(
+            &mut self.state.a_input,
+            a_input,
+            &event,
+            cursor_position,
+        ) {
             text_box::TextBoxStatus::Ignored => {}
             text_box::TextBoxStatus::Captured => return Status::Captured,
             text_box::TextBoxStatus::ContentChanged => {
@@ -790,7 +1057,34 @@ where
             }
         }
 
+        let hex_input = slider_widget_rect(&bounds, self.margin, self.spacing, 4.0);
+        match renderer.picker_text_box_event(
+            &mut self.state.hex_input,
+            hex_input,
+            &event,
+            cursor_position,
+        ) {
+            text_box::TextBoxStatus::Ignored => {}
+            text_box::TextBoxStatus::Captured => return Status::Captured,
+            text_box::TextBoxStatus::ContentChanged => {
+                self.state.update_color_from_hex();
+                return Status::Captured;
+            }
+        }
+
         match event {
+            // hex codes can be pasted in, which is the only way to enter exact colors without text support
+            iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                key_code: iced::keyboard::KeyCode::V,
+                modifiers,
+            }) if modifiers.command() => {
+                let Some(col) = clipboard.read().and_then(|x| hex_to_color(x.trim())) else {
+                    return Status::Ignored;
+                };
+                self.state.set_color(col);
+                self.state.regenerate_ui();
+                Status::Captured
+            }
             iced::Event::Mouse(event) => match event {
                 iced::mouse::Event::ButtonPressed(_) if self.area.contains(cursor_position) => {
                     if let Some(p) = rect_local_point_normalized(
@@ -847,7 +1141,14 @@ where
                         self.state.value = v;
                         self.state.regenerate_ui();
                         Status::Captured
-                    } else if slider_text_box_rect(&bounds, self.margin, self.spacing, 3.0)
+                    } else if let Some(p) = rect_local_point_normalized(
+                        slider_widget_rect(&bounds, self.margin, self.spacing, 3.0),
+                        cursor_position,
+                    ) {
+                        self.state.alpha = p.x;
+                        self.state.regenerate_ui();
+                        Status::Captured
+                    } else if slider_text_box_rect(&bounds, self.margin, self.spacing, 4.0)
                         .contains(cursor_position)
                     {
                         // toggle for slider input type
@@ -857,22 +1158,35 @@ where
                                 self.state.r_input.set_input(u8_numbers_only_text_input);
                                 self.state.g_input.set_input(u8_numbers_only_text_input);
                                 self.state.b_input.set_input(u8_numbers_only_text_input);
+                                self.state.a_input.set_input(u8_numbers_only_text_input);
                             }
                             ColorInputType::Int => {
                                 self.state.color_input_type = ColorInputType::Float;
                                 self.state.r_input.set_input(float_numbers_only_text_input);
                                 self.state.g_input.set_input(float_numbers_only_text_input);
                                 self.state.b_input.set_input(float_numbers_only_text_input);
+                                self.state.a_input.set_input(float_numbers_only_text_input);
                             }
                         }
                         self.state.regenerate_ui();
                         Status::Captured
                     } else if accept_rect(&bounds, self.margin).contains(cursor_position) {
-                        let col =
-                            hsv_to_color(self.state.hue, self.state.saturation, self.state.value);
-                        let m = (self.on_submit)(col);
-                        self.state.open = false;
-                        shell.publish(m);
+                        let col = self.state.color();
+                        self.submit(col, shell);
+                        Status::Captured
+                    } else if let Some(col) =
+                        self.recent_colors()
+                            .into_iter()
+                            .enumerate()
+                            .find_map(|(i, x)| {
+                                recent_rect(&bounds, self.margin, self.spacing, i)
+                                    .contains(cursor_position)
+                                    .then_some(x)
+                            })
+                    {
+                        self.state.set_color(col);
+                        self.state.regenerate_ui();
+                        self.submit(col, shell);
                         Status::Captured
                     } else {
                         Status::Ignored
@@ -977,6 +1291,47 @@ fn accept_rect(area: &Rectangle, margin: f32) -> Rectangle {
     }
 }
 
+/// Draws a checkerboard pattern in the area, making transparency of colors drawn over it visible
+fn draw_checkerboard<R: iced_native::Renderer>(renderer: &mut R, area: Rectangle) {
+    const CHECK: f32 = 5.0;
+    let quad = |bounds| Quad {
+        bounds,
+        border_radius: 0.0.into(),
+        border_width: 0.0,
+        border_color: Color::TRANSPARENT,
+    };
+    renderer.fill_quad(quad(area), Color::WHITE);
+
+    let columns = (area.width / CHECK).ceil() as u32;
+    let rows = (area.height / CHECK).ceil() as u32;
+    for y in 0..rows {
+        for x in (y % 2..columns).step_by(2) {
+            let (x, y) = (x as f32 * CHECK, y as f32 * CHECK);
+            let check = Rectangle {
+                x: area.x + x,
+                y: area.y + y,
+                width: CHECK.min(area.width - x),
+                height: CHECK.min(area.height - y),
+            };
+            renderer.fill_quad(quad(check), Color::from_rgb(0.75, 0.75, 0.75));
+        }
+    }
+}
+
+/// Area of a recently chosen color, they're laid out in two rows left of the accept button
+fn recent_rect(area: &Rectangle, margin: f32, spacing: f32, index: usize) -> Rectangle {
+    let size = area.height * 0.08;
+    let gap = 4.0;
+    let columns = RecentColors::LIMIT.div_ceil(2);
+    let (column, row) = ((index % columns) as f32, (index / columns) as f32);
+    Rectangle {
+        x: area.x + area.width * 0.5 + spacing * 0.5 + column * (size + gap),
+        y: area.y + area.height * 0.8 - margin + row * (size + gap),
+        width: size,
+        height: size,
+    }
+}
+
 #[derive(Default)]
 enum ColorInputType {
     #[default]
@@ -1096,6 +1451,25 @@ fn float_numbers_only_text_input(content: &mut String, cursor: &mut usize, c: ch
     }
 }
 
+/// Accepts hex digits and a leading #, up to the length of a hex code with alpha
+fn hex_only_text_input(content: &mut String, cursor: &mut usize, c: char) -> Status {
+    let hash = content.starts_with('#');
+    let accepted = match c {
+        '#' => *cursor == 0 && !hash,
+        c => {
+            c.is_ascii_hexdigit()
+                && content.trim_start_matches('#').len() < 8
+                && (*cursor > 0 || !hash)
+        }
+    };
+    if !accepted {
+        return Status::Ignored;
+    }
+    content.insert(*cursor, c);
+    *cursor += 1;
+    Status::Captured
+}
+
 // TODO make different functions to get different states for normal, hover, and pressed instead of having one massive appearance
 
 /// Dictates the look of the `ColorPicker` widget
@@ -1148,8 +1522,9 @@ impl StyleSheet for Theme {
 impl<'a, M, B, T> From<ColorPicker<'a, M, Renderer<B, T>>> for Element<'a, M, Renderer<B, T>>
 where
     M: Clone + 'a,
-    B: Backend + iced_graphics::backend::Text + 'a,
-    T: StyleSheet + 'a + Default + text_box::StyleSheet<Style = TextBoxStyle>,
+    B: Backend + 'a,
+    T: StyleSheet + 'a + Default,
+    Renderer<B, T>: PickerText<T>,
 {
     fn from(value: ColorPicker<'a, M, Renderer<B, T>>) -> Self {
         Self::new(value)
@@ -1160,8 +1535,9 @@ impl<'a, M, B, T> From<Overlay<'a, M, Renderer<B, T>>>
     for iced_native::overlay::Element<'a, M, Renderer<B, T>>
 where
     M: Clone,
-    B: Backend + iced_graphics::backend::Text + 'a,
-    T: StyleSheet + 'a + text_box::StyleSheet<Style = TextBoxStyle>,
+    B: Backend + 'a,
+    T: StyleSheet + 'a,
+    Renderer<B, T>: PickerText<T>,
 {
     fn from(value: Overlay<'a, M, Renderer<B, T>>) -> Self {
         Self::new(iced::Point { x: 0.0, y: 0.0 }, Box::new(value))
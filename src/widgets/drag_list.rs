@@ -0,0 +1,268 @@
+use iced::{event::Status, mouse::Button, Element, Length, Point, Rectangle};
+use iced_native::{
+    layout::{flex, Limits, Node},
+    renderer::Quad,
+    widget::{tree, Operation, Tree},
+    Alignment, Padding, Widget,
+};
+
+/// Widget laying out its items in a column which can be reordered by dragging them with left mouse button
+///
+/// Items still receive all the events, the widget only observes where the mouse button was pressed and released
+/// and reports the move once the cursor is released over a different item than the one it was pressed on
+pub struct DragList<'a, Message, Renderer> {
+    items: Vec<Element<'a, Message, Renderer>>,
+    on_move: Box<dyn Fn(usize, usize) -> Message + 'a>,
+    spacing: f32,
+    width: Length,
+}
+
+impl<'a, Message, Renderer> DragList<'a, Message, Renderer> {
+    /// Creates a new `DragList`
+    ///
+    /// `on_move` is provided with the index of the dragged item and index of the item it was dropped onto
+    pub fn new<F>(items: Vec<Element<'a, Message, Renderer>>, on_move: F) -> Self
+    where
+        F: Fn(usize, usize) -> Message + 'a,
+    {
+        Self {
+            items,
+            on_move: Box::new(on_move),
+            spacing: 0.0,
+            width: Length::Shrink,
+        }
+    }
+
+    /// Sets the space between items
+    pub fn spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Sets the width for the widget
+    pub fn width<L: Into<Length>>(mut self, width: L) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    /// Finds index of the item under the cursor
+    fn item_at(layout: iced_native::Layout<'_>, cursor_position: Point) -> Option<usize> {
+        layout
+            .children()
+            .position(|x| x.bounds().contains(cursor_position))
+    }
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer> for DragList<'a, Message, Renderer>
+where
+    Renderer: iced_native::Renderer,
+{
+    fn width(&self) -> Length {
+        self.width
+    }
+
+    fn height(&self) -> Length {
+        Length::Shrink
+    }
+
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        self.items.iter().map(Tree::new).collect()
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(&self.items);
+    }
+
+    fn layout(&self, renderer: &Renderer, limits: &Limits) -> Node {
+        let limits = limits.width(self.width).height(Length::Shrink);
+        flex::resolve(
+            flex::Axis::Vertical,
+            renderer,
+            &limits,
+            Padding::ZERO,
+            self.spacing,
+            Alignment::Start,
+            &self.items,
+        )
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: iced_native::Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation<Message>,
+    ) {
+        operation.container(None, &mut |operation| {
+            self.items
+                .iter()
+                .zip(&mut tree.children)
+                .zip(layout.children())
+                .for_each(|((item, state), layout)| {
+                    item.as_widget().operate(state, layout, renderer, operation);
+                })
+        });
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: iced::Event,
+        layout: iced_native::Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer,
+        clipboard: &mut dyn iced_native::Clipboard,
+        shell: &mut iced_native::Shell<'_, Message>,
+    ) -> Status {
+        let status = self
+            .items
+            .iter_mut()
+            .zip(&mut tree.children)
+            .zip(layout.children())
+            .map(|((item, state), layout)| {
+                item.as_widget_mut().on_event(
+                    state,
+                    event.clone(),
+                    layout,
+                    cursor_position,
+                    renderer,
+                    clipboard,
+                    shell,
+                )
+            })
+            .fold(Status::Ignored, Status::merge);
+
+        let local_state = tree.state.downcast_mut::<State>();
+        match event {
+            iced::Event::Mouse(iced::mouse::Event::ButtonPressed(Button::Left)) => {
+                local_state.dragged = Self::item_at(layout, cursor_position);
+            }
+            iced::Event::Mouse(iced::mouse::Event::ButtonReleased(Button::Left)) => {
+                let Some(from) = local_state.dragged.take() else {
+                    return status;
+                };
+                match Self::item_at(layout, cursor_position) {
+                    Some(to) if to != from => {
+                        shell.publish((self.on_move)(from, to));
+                        return Status::Captured;
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+        status
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: iced_native::Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> iced::mouse::Interaction {
+        let local_state = tree.state.downcast_ref::<State>();
+        if local_state.dragged.is_some() && layout.bounds().contains(cursor_position) {
+            return iced::mouse::Interaction::Grabbing;
+        }
+        self.items
+            .iter()
+            .zip(&tree.children)
+            .zip(layout.children())
+            .map(|((item, state), layout)| {
+                item.as_widget().mouse_interaction(
+                    state,
+                    layout,
+                    cursor_position,
+                    viewport,
+                    renderer,
+                )
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Renderer::Theme,
+        style: &iced_native::renderer::Style,
+        layout: iced_native::Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) {
+        for ((item, state), layout) in self.items.iter().zip(&tree.children).zip(layout.children())
+        {
+            item.as_widget().draw(
+                state,
+                renderer,
+                theme,
+                style,
+                layout,
+                cursor_position,
+                viewport,
+            );
+        }
+
+        // marking the place where the dragged item is going to land
+        let local_state = tree.state.downcast_ref::<State>();
+        let Some(from) = local_state.dragged else {
+            return;
+        };
+        let Some(to) = Self::item_at(layout, cursor_position) else {
+            return;
+        };
+        if from == to {
+            return;
+        }
+        let Some(target) = layout.children().nth(to) else {
+            return;
+        };
+        let target = target.bounds();
+        let y = if to < from {
+            target.y
+        } else {
+            target.y + target.height - 2.0
+        };
+        renderer.fill_quad(
+            Quad {
+                bounds: Rectangle {
+                    x: target.x,
+                    y,
+                    width: target.width,
+                    height: 2.0,
+                },
+                border_radius: 0.0.into(),
+                border_width: 0.0,
+                border_color: style.text_color,
+            },
+            style.text_color,
+        );
+    }
+}
+
+impl<'a, Message: 'a, Renderer> From<DragList<'a, Message, Renderer>>
+    for Element<'a, Message, Renderer>
+where
+    Renderer: iced_native::Renderer + 'a,
+{
+    fn from(value: DragList<'a, Message, Renderer>) -> Element<'a, Message, Renderer> {
+        Self::new(value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct State {
+    /// Index of the item the mouse button was pressed on
+    dragged: Option<usize>,
+}
@@ -4,15 +4,18 @@ use std::path::PathBuf;
 
 use iced::widget::{
     button, column as col, container, horizontal_space, row, scrollable, text, vertical_space, text_input, image as image_view,
+    tooltip, tooltip::Position,
 };
 use iced::{Alignment, Element, Length, Renderer, Command};
 use iced_native::image::Handle;
 use image::ImageFormat;
 
-use crate::data::{sanitize_file_name_ends, sanitize_dir_name};
+use crate::data::{sanitize_file_name_ends, sanitize_dir_name, working_dir};
 use crate::status_bar::StatusBar;
 use crate::style::Style;
 
+use super::DragList;
+
 pub struct Browser {
     path: PathBuf,
     selected: Option<PathBuf>,
@@ -20,6 +23,8 @@ pub struct Browser {
     target: Target,
     roots: Vec<PathBuf>,
     favorites: Vec<PathBuf>,
+    /// Whatever each of the favorites exists, checked when favorites change or the browser moves to another directory
+    favorites_exist: Vec<bool>,
     new_dir_name: Option<String>,
     image_preview: Option<Handle>,
 }
@@ -34,6 +39,8 @@ pub enum BrowserOperation {
     UpdateDirectoryName(String),
     SetPreviewImage(Option<Handle>),
     Favorite,
+    MoveFavorite(usize, usize),
+    PruneFavorites,
     Cancel,
     Accept,
 }
@@ -64,14 +71,18 @@ impl Browser {
             target: Target::File,
             roots: Browser::get_roots(),
             favorites: Self::get_favorites(),
+            favorites_exist: Vec::new(),
             new_dir_name: None,
             image_preview: None,
         }
+        .with_checked_favorites()
     }
 
     /// Creates a browser and sets browser path to home directory
+    ///
+    /// Browser starts in the working directory if the system doesn't provide a home directory
     pub fn start_at_home() -> Self {
-        let path = dirs::home_dir().unwrap();
+        let path = dirs::home_dir().unwrap_or_else(working_dir);
         Self {
             path,
             selected: None,
@@ -79,13 +90,17 @@ impl Browser {
             target: Target::File,
             roots: Browser::get_roots(),
             favorites: Self::get_favorites(),
+            favorites_exist: Vec::new(),
             new_dir_name: None,
             image_preview: None,
         }
+        .with_checked_favorites()
     }
 
     fn get_favorites() -> Vec<PathBuf> {
-        let favorites_path = dirs::config_dir().unwrap();
+        let Some(favorites_path) = dirs::config_dir() else {
+            return Vec::new();
+        };
         let favorites_path = favorites_path.join(crate::data::PROJECT_NAME).join("favorites.list");
         if (!favorites_path.exists() || !favorites_path.is_file()) {
             return Vec::new();
@@ -103,7 +118,10 @@ impl Browser {
     }
 
     fn save_favorite(&self) {
-        let config_dir = dirs::config_dir().unwrap().join(crate::data::PROJECT_NAME);
+        let Some(config_dir) = dirs::config_dir() else {
+            return;
+        };
+        let config_dir = config_dir.join(crate::data::PROJECT_NAME);
         if !config_dir.exists() {
             std::fs::create_dir_all(config_dir.clone());
         }
@@ -119,7 +137,26 @@ impl Browser {
         }
     }
 
+    /// Checks which favorites still exist so the view doesn't need to touch the drive
+    fn check_favorites(&mut self) {
+        self.favorites_exist = self.favorites.iter().map(|x| x.exists()).collect();
+    }
+
+    fn with_checked_favorites(mut self) -> Self {
+        self.check_favorites();
+        self
+    }
+
     fn get_roots() -> Vec<PathBuf> {
+        Self::roots_from(dirs::home_dir(), dirs::picture_dir(), dirs::download_dir())
+    }
+
+    /// Lists drives and mount points of the system along with the user directories, leaving out the ones which don't exist
+    fn roots_from(
+        home: Option<PathBuf>,
+        pictures: Option<PathBuf>,
+        downloads: Option<PathBuf>,
+    ) -> Vec<PathBuf> {
         let mut roots = Vec::new();
         if cfg!(windows) {
             for letter in 'A'..'Z' {
@@ -128,7 +165,6 @@ impl Browser {
                     roots.push(path);
                 }
             }
-            roots.push(dirs::home_dir().unwrap());
         }
         else if cfg!(unix) {
             roots.push(PathBuf::from("/")) ;
@@ -153,9 +189,11 @@ impl Browser {
                 }
             }
         }
-        roots.push(dirs::home_dir().unwrap());
-        roots.push(dirs::picture_dir().unwrap());
-        roots.push(dirs::download_dir().unwrap());
+        // user directories may be missing on minimal systems, those are simply not listed
+        roots.extend(home);
+        roots.extend(pictures);
+        roots.extend(downloads);
+        roots.retain(|x| x.exists());
         roots
     }
 
@@ -182,6 +220,8 @@ impl Browser {
     /// Updates browser cache with files and directories from current path
     pub fn refresh_path(&mut self) -> Result<(), std::io::Error> {
         self.dir.clear();
+        // drives with favorites may have been plugged in or out since the last check
+        self.check_favorites();
         let dir = std::fs::read_dir(&self.path)?;
         for f in dir {
             if let Ok(f) = f {
@@ -220,8 +260,9 @@ impl Browser {
                     self.image_preview = None;
                     Ok(BrowsingResult::Pending)
                 } else {
-                    // this should never happen
-                    unreachable!()
+                    // a favorite may have disappeared since it was last checked
+                    self.check_favorites();
+                    Ok(BrowsingResult::Pending)
                 }
             }
             BrowserOperation::Select(path) => {
@@ -261,14 +302,32 @@ impl Browser {
             }
             BrowserOperation::Favorite => if let Some(idx) = self.favorites.iter().position(|x| self.path.eq(x)) {
                 self.favorites.remove(idx);
+                self.check_favorites();
                 self.save_favorite();
                 Ok(BrowsingResult::Pending)
             }
             else {
                 self.favorites.push(self.path.clone());
+                self.check_favorites();
                 self.save_favorite();
                 Ok(BrowsingResult::Pending)
             },
+            BrowserOperation::MoveFavorite(from, to) => {
+                if from < self.favorites.len() && to < self.favorites.len() {
+                    let fav = self.favorites.remove(from);
+                    self.favorites.insert(to, fav);
+                    let exists = self.favorites_exist.remove(from);
+                    self.favorites_exist.insert(to, exists);
+                    self.save_favorite();
+                }
+                Ok(BrowsingResult::Pending)
+            }
+            BrowserOperation::PruneFavorites => {
+                self.favorites.retain(|x| x.exists());
+                self.check_favorites();
+                self.save_favorite();
+                Ok(BrowsingResult::Pending)
+            }
             BrowserOperation::ToggleAddDirectory => if self.new_dir_name.is_none() {
                 self.new_dir_name = Some("".into());
                 Ok(BrowsingResult::Pending)
@@ -296,7 +355,7 @@ impl Browser {
             }
         }
     }
-    pub fn view(&self) -> Element<BrowserOperation, Renderer> {
+    pub fn view(&self, input_style: Style) -> Element<'_, BrowserOperation, Renderer> {
         // calculating file list widgets
         let file_list = self
             .dir
@@ -379,25 +438,42 @@ impl Browser {
         let favorites_list = self
             .favorites
             .iter()
-            .map(|dir| {
+            .zip(self.favorites_exist.iter())
+            .map(|(dir, exists)| {
                 if let Some(name) = dir.file_name().and_then(|n| n.to_str()) {
-                    (dir, name)
+                    (dir, name, exists)
                 }
                 else {
                     if cfg!(windows) {
-                        (dir, dir.to_str().unwrap())
+                        (dir, dir.to_str().unwrap(), exists)
                     }
                     else {
-                        (dir, "root")
+                        (dir, "root", exists)
                     }
                 }
             })
-            .map(|( dir, name )| {
-                button(text(name)).on_press(BrowserOperation::MoveInto(dir.clone()))
-                    .width(Length::Fill)
+            .map(|( dir, name, exists )| {
+                // favorites pointing at folders that no longer exist, like on unplugged drives, are left disabled
+                if *exists {
+                    button(text(name)).on_press(BrowserOperation::MoveInto(dir.clone()))
+                        .width(Length::Fill)
+                        .into()
+                } else {
+                    tooltip(
+                        button(text(name)).width(Length::Fill),
+                        "Folder no longer exists",
+                        Position::Bottom,
+                    )
+                    .style(Style::Frame)
+                    .into()
+                }
             })
-            .fold(col![].spacing(1), |col, butt| col.push(butt));
+            .collect();
 
+        // favorites can be reordered by dragging them onto each other
+        let favorites_list = DragList::new(favorites_list, BrowserOperation::MoveFavorite)
+            .spacing(1.0)
+            .width(Length::Fill);
 
         let favorites_list = container(favorites_list)
             .style(Style::Margins)
@@ -410,13 +486,25 @@ impl Browser {
             vertical_space(6),
             row![
                 text("Favorites").width(Length::Fill),
+                if self.favorites_exist.contains(&false) {
+                    tooltip(
+                        button("Prune").on_press(BrowserOperation::PruneFavorites),
+                        "Removes favorites which no longer exist",
+                        Position::Bottom,
+                    )
+                    .style(Style::Frame)
+                    .into()
+                }
+                else {
+                    Element::from(horizontal_space(Length::Shrink))
+                },
                 if self.favorites.contains(&self.path) {
                     button("-").on_press(BrowserOperation::Favorite)
                 }
                 else {
                     button("+").on_press(BrowserOperation::Favorite)
                 }
-            ].align_items(Alignment::End),
+            ].align_items(Alignment::End).spacing(2),
             vertical_space(2),
             favorites_list
         ]
@@ -449,7 +537,7 @@ impl Browser {
             Some(folder_name) => ( row![
                 button("Cancel").on_press(BrowserOperation::ToggleAddDirectory),
                 button("Make").on_press(BrowserOperation::CreateDirectory),
-                text_input("Directory Name", folder_name, |x| BrowserOperation::UpdateDirectoryName(x))
+                text_input("Directory Name", folder_name, BrowserOperation::UpdateDirectoryName).style(input_style)
             ], true),
             None => (row![button("Make Directory").on_press(BrowserOperation::ToggleAddDirectory)], false)
         };
@@ -483,3 +571,39 @@ impl Browser {
         col![top, bottom].into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roots_without_user_directories() {
+        let roots = Browser::roots_from(None, None, None);
+        assert!(roots.iter().all(|x| x.exists()));
+        if cfg!(unix) {
+            assert_eq!(roots.first(), Some(&PathBuf::from("/")));
+        }
+    }
+
+    #[test]
+    fn roots_skip_missing_user_directories() {
+        let existing = std::env::temp_dir();
+        let missing = existing.join("token-maker-missing-directory");
+        let roots = Browser::roots_from(None, Some(existing.clone()), Some(missing.clone()));
+        assert!(roots.contains(&existing));
+        assert!(!roots.contains(&missing));
+    }
+
+    #[test]
+    fn favorites_existence_is_cached() {
+        let existing = std::env::temp_dir();
+        let missing = existing.join("token-maker-missing-favorite");
+        let mut browser = Browser::new(existing.clone());
+        browser.favorites = vec![existing.clone(), missing];
+        browser.check_favorites();
+        assert_eq!(browser.favorites_exist, vec![true, false]);
+
+        browser.refresh_path().unwrap();
+        assert_eq!(browser.favorites_exist, vec![true, false]);
+    }
+}
@@ -1,12 +1,16 @@
-use iced::{event::Status, ContentFit, Element, Length, Point, Rectangle, Size, Vector};
+use iced::{event::Status, Color, ContentFit, Element, Length, Point, Rectangle, Size, Vector};
 use iced_native::{
     image::Handle,
     layout::{Limits, Node},
-    renderer::Style,
+    mouse::Interaction,
+    renderer::{Quad, Style},
     widget::Tree,
     Layout, Widget,
 };
 
+/// Length of each arm of the crosshair drawn over the hovered pixel
+const CROSSHAIR_ARM: f32 = 8.0;
+
 pub struct PixelSampler<'a, Message> {
     handle: Handle,
     on_click: Box<dyn Fn(Vector<u32>) -> Message + 'a>,
@@ -53,15 +57,50 @@ where
         _theme: &Renderer::Theme,
         _style: &Style,
         layout: Layout<'_>,
-        _cursor_position: Point,
+        cursor_position: Point,
         _viewport: &Rectangle,
     ) {
         let image = self.handle.clone();
         let bounds = layout.bounds();
 
         let bounds = preview_rect(&image, renderer, &bounds);
+        let source = renderer.dimensions(&image);
 
         renderer.draw(image, bounds);
+
+        let Some(pixel) = source_pixel(cursor_position, bounds, source) else {
+            return;
+        };
+        let center = pixel_center(pixel, bounds, source);
+        // dark outline under a light line keeps the crosshair visible on any image
+        for (thickness, color) in [
+            (3.0, Color::from_rgba(0.0, 0.0, 0.0, 0.8)),
+            (1.0, Color::WHITE),
+        ] {
+            let horizontal = Rectangle {
+                x: center.x - CROSSHAIR_ARM,
+                y: center.y - thickness * 0.5,
+                width: CROSSHAIR_ARM * 2.0,
+                height: thickness,
+            };
+            let vertical = Rectangle {
+                x: center.x - thickness * 0.5,
+                y: center.y - CROSSHAIR_ARM,
+                width: thickness,
+                height: CROSSHAIR_ARM * 2.0,
+            };
+            for bounds in [horizontal, vertical] {
+                renderer.fill_quad(
+                    Quad {
+                        bounds,
+                        border_radius: 0.0.into(),
+                        border_width: 0.0,
+                        border_color: Color::TRANSPARENT,
+                    },
+                    color,
+                );
+            }
+        }
     }
     fn on_event(
         &mut self,
@@ -78,31 +117,13 @@ where
                 iced::mouse::Event::ButtonPressed(_) => {
                     let bounds = layout.bounds();
                     let bounds = preview_rect(&self.handle, renderer, &bounds);
-                    if bounds.contains(cursor_position) {
-                        let mut pos = Vector {
-                            x: cursor_position.x - bounds.x,
-                            y: cursor_position.y - bounds.y,
-                        };
-                        let pic_size = renderer.dimensions(&self.handle);
-                        if pic_size.width != bounds.width as u32
-                            || pic_size.height != bounds.height as u32
-                        {
-                            let x = pic_size.width as f32 / bounds.width;
-                            let y = pic_size.height as f32 / bounds.height;
-                            pos = Vector {
-                                x: pos.x * x,
-                                y: pos.y * y,
-                            };
+                    let source = renderer.dimensions(&self.handle);
+                    match source_pixel(cursor_position, bounds, source) {
+                        Some(pos) => {
+                            shell.publish((self.on_click)(pos));
+                            Status::Captured
                         }
-                        let pos = Vector {
-                            x: pos.x as u32,
-                            y: pos.y as u32,
-                        };
-                        let m = (self.on_click)(pos);
-                        shell.publish(m);
-                        Status::Captured
-                    } else {
-                        Status::Ignored
+                        None => Status::Ignored,
                     }
                 }
                 _ => Status::Ignored,
@@ -110,6 +131,22 @@ where
             _ => Status::Ignored,
         }
     }
+
+    fn mouse_interaction(
+        &self,
+        _state: &Tree,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> Interaction {
+        let bounds = preview_rect(&self.handle, renderer, &layout.bounds());
+        if bounds.contains(cursor_position) {
+            Interaction::Crosshair
+        } else {
+            Interaction::default()
+        }
+    }
 }
 
 impl<'a, Message: 'a, Renderer> From<PixelSampler<'a, Message>> for Element<'a, Message, Renderer>
@@ -125,10 +162,14 @@ fn preview_rect<Renderer>(image: &Handle, renderer: &Renderer, bounds: &Rectangl
 where
     Renderer: iced_native::image::Renderer<Handle = Handle>,
 {
-    let size = renderer.dimensions(image);
+    fit_rect(renderer.dimensions(image), bounds)
+}
+
+/// Area the image of `source` size takes when it is fitted into the center of `bounds` without cropping
+fn fit_rect(source: Size<u32>, bounds: &Rectangle) -> Rectangle {
     let size = Size {
-        width: size.width as f32,
-        height: size.height as f32,
+        width: source.width as f32,
+        height: source.height as f32,
     };
 
     let c = ContentFit::Contain.fit(size, bounds.size());
@@ -140,3 +181,110 @@ where
         y: bounds.y + (bounds.height - c.height).max(0.0) / 2.0,
     }
 }
+
+/// Translates a cursor position into coordinates of the source pixel under it
+///
+/// `display` is the area the image is drawn in and `source` is the size of the image itself,
+/// returns None when the cursor is outside of the displayed image
+fn source_pixel(cursor: Point, display: Rectangle, source: Size<u32>) -> Option<Vector<u32>> {
+    if source.width == 0 || source.height == 0 || display.width <= 0.0 || display.height <= 0.0 {
+        return None;
+    }
+    if !display.contains(cursor) {
+        return None;
+    }
+    let x = (cursor.x - display.x) / display.width * source.width as f32;
+    let y = (cursor.y - display.y) / display.height * source.height as f32;
+    // the far edges of the display area still belong to the last pixel
+    Some(Vector {
+        x: (x.floor().max(0.0) as u32).min(source.width - 1),
+        y: (y.floor().max(0.0) as u32).min(source.height - 1),
+    })
+}
+
+/// Position on the screen of the center of a source pixel, reverse of [source_pixel]
+fn pixel_center(pixel: Vector<u32>, display: Rectangle, source: Size<u32>) -> Point {
+    Point {
+        x: display.x + (pixel.x as f32 + 0.5) * display.width / source.width as f32,
+        y: display.y + (pixel.y as f32 + 0.5) * display.height / source.height as f32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: Rectangle = Rectangle {
+        x: 10.0,
+        y: 20.0,
+        width: 200.0,
+        height: 100.0,
+    };
+
+    #[test]
+    fn fit_scales_down_large_image() {
+        let source = Size::new(800, 800);
+        let display = fit_rect(source, &BOUNDS);
+        assert_eq!(
+            display,
+            Rectangle::new(Point::new(60.0, 20.0), Size::new(100.0, 100.0))
+        );
+
+        // each displayed point covers 8 source pixels
+        let pixel = source_pixel(Point::new(61.0, 21.0), display, source);
+        assert_eq!(pixel, Some(Vector::new(8, 8)));
+        let pixel = source_pixel(Point::new(110.0, 70.0), display, source);
+        assert_eq!(pixel, Some(Vector::new(400, 400)));
+    }
+
+    #[test]
+    fn fit_scales_up_small_image() {
+        let source = Size::new(4, 2);
+        let display = fit_rect(source, &BOUNDS);
+        assert_eq!(display, BOUNDS);
+
+        // each source pixel covers 50 displayed points
+        let pixel = source_pixel(Point::new(10.0, 20.0), display, source);
+        assert_eq!(pixel, Some(Vector::new(0, 0)));
+        let pixel = source_pixel(Point::new(59.9, 69.9), display, source);
+        assert_eq!(pixel, Some(Vector::new(0, 0)));
+        let pixel = source_pixel(Point::new(60.0, 70.0), display, source);
+        assert_eq!(pixel, Some(Vector::new(1, 1)));
+    }
+
+    #[test]
+    fn far_edge_clamps_to_last_pixel() {
+        let source = Size::new(4, 2);
+        let pixel = source_pixel(Point::new(210.0, 120.0), BOUNDS, source);
+        assert_eq!(pixel, Some(Vector::new(3, 1)));
+    }
+
+    #[test]
+    fn outside_of_display_is_none() {
+        let source = Size::new(4, 2);
+        for cursor in [
+            Point::new(9.0, 50.0),
+            Point::new(211.0, 50.0),
+            Point::new(100.0, 19.0),
+            Point::new(100.0, 121.0),
+        ] {
+            assert_eq!(source_pixel(cursor, BOUNDS, source), None);
+        }
+        let empty = Size::new(0, 0);
+        assert_eq!(source_pixel(Point::new(100.0, 50.0), BOUNDS, empty), None);
+    }
+
+    #[test]
+    fn pixel_center_round_trips() {
+        for source in [Size::new(4, 2), Size::new(800, 800), Size::new(33, 7)] {
+            let display = fit_rect(source, &BOUNDS);
+            for y in 0..source.height.min(10) {
+                for x in 0..source.width.min(10) {
+                    let pixel = Vector::new(x, y);
+                    let center = pixel_center(pixel, display, source);
+                    assert_eq!(source_pixel(center, display, source), Some(pixel));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use iced::{event::Status, mouse::Button, Color, Element, Length, Point, Rectangle, Size};
+use iced_native::{
+    layout::{Limits, Node},
+    mouse::Interaction,
+    renderer::Quad,
+    widget::Tree,
+    Layout, Widget,
+};
+
+/// Width of the bar drawn in the middle of the splitter
+const BAR_WIDTH: f32 = 2.0;
+
+/// Vertical bar placed between two panels, dragging it sends messages with the horizontal distance the cursor moved
+pub struct Splitter<'a, Message> {
+    on_drag: Box<dyn Fn(f32) -> Message + 'a>,
+    width: f32,
+}
+
+impl<'a, Message> Splitter<'a, Message> {
+    pub fn new<F: Fn(f32) -> Message + 'a>(on_drag: F) -> Self {
+        Self {
+            on_drag: Box::new(on_drag),
+            width: 8.0,
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    /// Last position of the cursor while the splitter is being dragged
+    dragging: Option<f32>,
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer> for Splitter<'a, Message>
+where
+    Renderer: iced_native::Renderer,
+{
+    fn width(&self) -> Length {
+        Length::Fixed(self.width)
+    }
+
+    fn height(&self) -> Length {
+        Length::Fill
+    }
+
+    fn tag(&self) -> iced_native::widget::tree::Tag {
+        iced_native::widget::tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> iced_native::widget::tree::State {
+        iced_native::widget::tree::State::new(State::default())
+    }
+
+    fn layout(&self, _renderer: &Renderer, limits: &Limits) -> Node {
+        let limits = limits.width(Length::Fixed(self.width)).height(Length::Fill);
+        Node::new(limits.resolve(Size::ZERO))
+    }
+
+    fn draw(
+        &self,
+        state: &Tree,
+        renderer: &mut Renderer,
+        _theme: &Renderer::Theme,
+        _style: &iced_native::renderer::Style,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _viewport: &Rectangle,
+    ) {
+        let bounds = layout.bounds();
+        let active = state.state.downcast_ref::<State>().dragging.is_some()
+            || bounds.contains(cursor_position);
+        let color = if active {
+            Color::from_rgba(0.5, 0.5, 0.5, 0.9)
+        } else {
+            Color::from_rgba(0.5, 0.5, 0.5, 0.4)
+        };
+        renderer.fill_quad(
+            Quad {
+                bounds: Rectangle {
+                    x: bounds.center_x() - BAR_WIDTH * 0.5,
+                    width: BAR_WIDTH,
+                    ..bounds
+                },
+                border_radius: 0.0.into(),
+                border_width: 0.0,
+                border_color: Color::TRANSPARENT,
+            },
+            color,
+        );
+    }
+
+    fn on_event(
+        &mut self,
+        state: &mut Tree,
+        event: iced::Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _renderer: &Renderer,
+        _clipboard: &mut dyn iced_native::Clipboard,
+        shell: &mut iced_native::Shell<'_, Message>,
+    ) -> Status {
+        let local_state = state.state.downcast_mut::<State>();
+        match event {
+            iced::Event::Mouse(iced::mouse::Event::ButtonPressed(Button::Left))
+                if layout.bounds().contains(cursor_position) =>
+            {
+                local_state.dragging = Some(cursor_position.x);
+                Status::Captured
+            }
+            iced::Event::Mouse(iced::mouse::Event::CursorMoved { position }) => {
+                let Some(last) = local_state.dragging else {
+                    return Status::Ignored;
+                };
+                local_state.dragging = Some(position.x);
+                shell.publish((self.on_drag)(position.x - last));
+                Status::Captured
+            }
+            iced::Event::Mouse(iced::mouse::Event::ButtonReleased(Button::Left))
+                if local_state.dragging.is_some() =>
+            {
+                local_state.dragging = None;
+                Status::Captured
+            }
+            _ => Status::Ignored,
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Tree,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _viewport: &Rectangle,
+        _renderer: &Renderer,
+    ) -> Interaction {
+        if state.state.downcast_ref::<State>().dragging.is_some()
+            || layout.bounds().contains(cursor_position)
+        {
+            Interaction::ResizingHorizontally
+        } else {
+            Interaction::default()
+        }
+    }
+}
+
+impl<'a, Message: 'a, Renderer> From<Splitter<'a, Message>> for Element<'a, Message, Renderer>
+where
+    Renderer: iced_native::Renderer,
+{
+    fn from(value: Splitter<'a, Message>) -> Self {
+        Self::new(value)
+    }
+}
@@ -0,0 +1,25 @@
+use iced::{
+    widget::{button, row, Space},
+    Color, Element, Length, Renderer,
+};
+
+use crate::style::Style;
+
+/// Creates a row of clickable color samples, pressing a sample sends a message with its color
+pub fn swatches<'a, Message, F>(colors: &[Color], on_press: F) -> Element<'a, Message, Renderer>
+where
+    Message: Clone + 'a,
+    F: Fn(Color) -> Message,
+{
+    colors
+        .iter()
+        .fold(row![].spacing(2), |r, c| {
+            r.push(
+                button(Space::new(Length::Fixed(12.0), Length::Fixed(12.0)))
+                    .padding(2)
+                    .on_press(on_press(*c))
+                    .style(Style::Swatch(*c).into()),
+            )
+        })
+        .into()
+}
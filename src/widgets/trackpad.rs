@@ -2,11 +2,12 @@ use iced::{
     event::Status,
     keyboard::Modifiers,
     mouse::{Button, ScrollDelta},
-    ContentFit, Element, Length, Point, Size, Vector,
+    Color, ContentFit, Element, Length, Point, Rectangle, Size, Vector,
 };
 use iced_native::{
     image::Handle,
     layout::{Limits, Node},
+    renderer::Quad,
     widget::Tree,
     Widget,
 };
@@ -23,9 +24,12 @@ pub struct Trackpad<'a, Message> {
     on_drag: Option<Box<dyn Fn(Modifiers, Button, Point, Vector) -> Option<Message> + 'a>>,
     on_click: Option<Box<dyn Fn(Modifiers, Button, Point) -> Option<Message> + 'a>>,
     on_scroll: Option<Box<dyn Fn(Modifiers, ScrollDelta) -> Option<Message> + 'a>>,
+    comparison: Option<Comparison<'a, Message>>,
     width: Length,
     height: Length,
     content_fit: ContentFit,
+    checkerboard: Option<f32>,
+    overlay: Option<Handle>,
 }
 
 impl<'a, Message> Trackpad<'a, Message> {
@@ -38,9 +42,12 @@ impl<'a, Message> Trackpad<'a, Message> {
             on_drag: None,
             on_click: None,
             on_scroll: None,
+            comparison: None,
             width: Length::Fill,
             height: Length::Fill,
             content_fit: ContentFit::ScaleDown,
+            checkerboard: None,
+            overlay: None,
         }
     }
 
@@ -88,6 +95,36 @@ impl<'a, Message> Trackpad<'a, Message> {
         self
     }
 
+    /// Draws a checkerboard pattern behind the image to make transparent areas visible
+    ///
+    /// `size` is the length of a side of a single check
+    pub fn with_checkerboard(mut self, size: f32) -> Self {
+        self.checkerboard = Some(size.max(1.0));
+        self
+    }
+
+    /// Draws the overlay image stretched over the main image
+    pub fn with_overlay(mut self, overlay: Handle) -> Self {
+        self.overlay = Some(overlay);
+        self
+    }
+
+    /// Draws the other image over the left part of the main image, split by a divider that can be dragged with the mouse
+    ///
+    /// `split` is the position of the divider as a fraction of the width,
+    /// `on_split` function is provided with the new position of the divider when it's dragged
+    pub fn with_comparison<F>(mut self, image: Handle, split: f32, on_split: F) -> Self
+    where
+        F: Fn(f32) -> Message + 'a,
+    {
+        self.comparison = Some(Comparison {
+            image,
+            split: split.clamp(0.0, 1.0),
+            on_split: Box::new(on_split),
+        });
+        self
+    }
+
     /// Sets the width for the widget
     pub fn width<L: Into<Length>>(mut self, width: L) -> Self {
         self.width = width.into();
@@ -147,7 +184,42 @@ where
     ) {
         let image = self.handle.clone();
         let bounds = layout.bounds();
+        if let Some(size) = self.checkerboard {
+            draw_checkerboard(renderer, bounds, size);
+        }
         renderer.draw(image, bounds);
+        if let Some(comparison) = &self.comparison {
+            let divider = comparison.divider(bounds);
+            let clip = Rectangle {
+                width: divider - bounds.x,
+                ..bounds
+            };
+            renderer.with_layer(clip, |renderer| {
+                if let Some(size) = self.checkerboard {
+                    draw_checkerboard(renderer, bounds, size);
+                }
+                renderer.draw(comparison.image.clone(), bounds);
+            });
+        }
+        if let Some(overlay) = &self.overlay {
+            renderer.draw(overlay.clone(), bounds);
+        }
+        if let Some(comparison) = &self.comparison {
+            let divider = comparison.divider(bounds);
+            renderer.fill_quad(
+                Quad {
+                    bounds: Rectangle {
+                        x: divider - DIVIDER_WIDTH * 0.5,
+                        width: DIVIDER_WIDTH,
+                        ..bounds
+                    },
+                    border_radius: 0.0.into(),
+                    border_width: 1.0,
+                    border_color: Color::BLACK,
+                },
+                Color::WHITE,
+            );
+        }
     }
     fn on_event(
         &mut self,
@@ -173,6 +245,15 @@ where
 
             iced::Event::Mouse(mouse) => match mouse {
                 iced::mouse::Event::CursorMoved { position } => match &self.on_drag {
+                    _ if local_state.splitting => {
+                        local_state.cursor = position;
+                        let Some(comparison) = &self.comparison else {
+                            return Status::Ignored;
+                        };
+                        let split = (position.x - bounds.x) / bounds.width.max(1.0);
+                        shell.publish((comparison.on_split)(split.clamp(0.0, 1.0)));
+                        Status::Captured
+                    }
                     Some(on_drag) if local_state.tracking => {
                         let delta = position - local_state.cursor;
                         let new_point = self.position + delta;
@@ -193,6 +274,16 @@ where
 
                 iced::mouse::Event::ButtonPressed(button) => {
                     if bounds.contains(cursor_position) {
+                        // grabbing the divider takes precedence over dragging the image
+                        if let Some(comparison) = &self.comparison {
+                            let divider = comparison.divider(bounds);
+                            if button == Button::Left
+                                && (cursor_position.x - divider).abs() <= DIVIDER_GRAB_DISTANCE
+                            {
+                                local_state.splitting = true;
+                                return Status::Captured;
+                            }
+                        }
                         local_state.tracking = true;
                         local_state.button = button;
 
@@ -214,7 +305,10 @@ where
                 }
 
                 iced::mouse::Event::ButtonReleased(_button) => {
-                    if local_state.tracking {
+                    if local_state.splitting {
+                        local_state.splitting = false;
+                        Status::Captured
+                    } else if local_state.tracking {
                         local_state.tracking = false;
                         Status::Captured
                     } else {
@@ -253,9 +347,59 @@ where
     }
 }
 
+/// Fills the area with a pattern of light and dark checks
+fn draw_checkerboard<Renderer>(renderer: &mut Renderer, bounds: Rectangle, size: f32)
+where
+    Renderer: iced_native::Renderer,
+{
+    let quad = |bounds| Quad {
+        bounds,
+        border_radius: 0.0.into(),
+        border_width: 0.0,
+        border_color: Color::TRANSPARENT,
+    };
+    renderer.fill_quad(quad(bounds), Color::from_rgb8(204, 204, 204));
+
+    let columns = (bounds.width / size).ceil() as u32;
+    let rows = (bounds.height / size).ceil() as u32;
+    for y in 0..rows {
+        for x in (y % 2..columns).step_by(2) {
+            let check = Rectangle {
+                x: bounds.x + x as f32 * size,
+                y: bounds.y + y as f32 * size,
+                width: size.min(bounds.width - x as f32 * size),
+                height: size.min(bounds.height - y as f32 * size),
+            };
+            renderer.fill_quad(quad(check), Color::from_rgb8(153, 153, 153));
+        }
+    }
+}
+
+/// Width of the line dividing compared images
+const DIVIDER_WIDTH: f32 = 3.0;
+/// How far from the divider the cursor can be to grab it
+const DIVIDER_GRAB_DISTANCE: f32 = 6.0;
+
+/// Second image shown next to the main one for comparison
+struct Comparison<'a, Message> {
+    image: Handle,
+    /// Position of the divider as a fraction of the width
+    split: f32,
+    on_split: Box<dyn Fn(f32) -> Message + 'a>,
+}
+
+impl<'a, Message> Comparison<'a, Message> {
+    /// Horizontal position of the divider within the bounds
+    fn divider(&self, bounds: Rectangle) -> f32 {
+        bounds.x + bounds.width * self.split
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct State {
     tracking: bool,
+    /// Whatever the divider of compared images is being dragged
+    splitting: bool,
     cursor: Point,
     mods: Modifiers,
     button: Button,
@@ -265,6 +409,7 @@ impl Default for State {
     fn default() -> Self {
         Self {
             tracking: Default::default(),
+            splitting: Default::default(),
             cursor: Default::default(),
             mods: Default::default(),
             button: Button::Left,
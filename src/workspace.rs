@@ -1,35 +1,64 @@
+use std::collections::BTreeMap;
+use std::io::Cursor;
 use std::sync::Arc;
-use std::time::Duration;
-use std::{fmt::Display, path::PathBuf};
+use std::time::{Duration, Instant};
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
 
 use iced::widget::tooltip::Position;
 use iced::widget::{radio, tooltip};
 use iced::{
     widget::{
-        button, column as col, container, horizontal_space, image::Handle, row, scrollable, text,
-        text_input,
+        button, checkbox, column as col, container, horizontal_space, image as picture,
+        image::Handle, row, scrollable, scrollable::Properties, text, text_input, vertical_space,
     },
-    Alignment, Command, ContentFit, Element, Length, Point, Renderer, Size, Subscription,
+    Alignment, Color, Command, ContentFit, Element, Length, Point, Rectangle, Renderer, Size,
+    Subscription,
 };
 
 use iced_native::{image::Data, widget::PickList};
+use image::{
+    imageops::{crop_imm, resize, FilterType},
+    DynamicImage, Rgba,
+};
 use serde::{Deserialize, Serialize};
 
-use crate::modifier::{ModifierBox, ModifierMessage, ModifierOperation, ModifierTag};
-use crate::widgets::Trackpad;
+use crate::modifier::{ModifierBox, ModifierMessage, ModifierTag, OrderHint};
+use crate::widgets::{Splitter, Trackpad};
 use crate::{
-    data::{has_invalid_characters, sanitize_file_name, ProgramData, WorkspaceData},
+    data::{
+        fit_size, has_invalid_characters, sanitize_file_name, working_dir, PrintSize, ProgramData,
+        WorkspaceData,
+    },
     naming_convention::NamingConvention,
     persistence::PersistentKey,
+    session::WorkspaceSession,
 };
 use crate::{
     image::{
-        convert::{image_arc_to_handle, image_to_handle},
-        ImageFormat, ImageOperation, RgbaImage,
+        convert::{handle_to_image, image_arc_to_handle, image_to_handle},
+        dpi::{self, MM_PER_INCH},
+        icc,
+        operations::{resample_image, visible_bounds},
+        outline,
+        palette::extract_palette,
+        ImageFormat, ImageOperation, Rgba16Image, RgbaImage,
     },
     style::Style,
 };
 
+/// Amount of colors extracted from the image for the palette
+const PALETTE_SIZE: usize = 8;
+/// Named aspect ratios offered for the export size as label, width and height proportion
+const ASPECT_PRESETS: [(&str, f32, f32); 4] = [
+    ("1:1", 1.0, 1.0),
+    ("4:3", 4.0, 3.0),
+    ("16:9", 16.0, 9.0),
+    ("Card", 2.5, 3.5),
+];
+
 /// Workspace serves purpose of providing tools to take the source image through series of operations to final result
 pub struct Workspace {
     /// List of modifiers in order which they should be applied to the image
@@ -41,26 +70,78 @@ pub struct Workspace {
     data: WorkspaceData,
     /// Flag specifies whatever there is active rendering job in process
     rendering: bool,
+    /// Sequence number of the latest requested rendering job, results of older jobs are discarded
+    render_sequence: u64,
+    /// How long the latest displayed rendering job took
+    render_time: Option<Duration>,
     /// Carrier for the width of the exported image, when it is a valid number, it is transformed into actual value
     width_carrier: String,
     /// Carrier for the height of the exported image, when it is a valid number, it is transformed into actual value
     height_carrier: String,
+    /// Carrier for the physical width of the printed image in millimeters
+    print_width_carrier: String,
+    /// Carrier for the resolution the image is printed with
+    print_dpi_carrier: String,
+    /// Whatever the area of the image visible through the frame should be outlined in the preview
+    show_safe_area: bool,
+    /// Position of the divider comparing the image before and after modifiers as a fraction of the preview width, comparison is shown when it has a value
+    compare_split: Option<f32>,
+    /// Image with no modifiers applied, shown on one side of the comparison
+    before_preview: Option<Handle>,
+    /// Whatever the preview should only render modifiers up to and including the selected one
+    solo: bool,
+    /// Descriptions of image operations the latest render was made of, in the order they were applied
+    operation_log: Vec<String>,
+    /// Whatever the list of image operations of the latest render is shown next to the preview
+    show_operations: bool,
+    /// Image shown next to the preview for comparison, it is never used in rendering
+    reference: Option<Handle>,
+    /// Locked workspaces are left out of operations affecting all workspaces
+    locked: bool,
+    /// Filter for searching modifiers to add, the search is open when it has a value
+    modifier_search: Option<String>,
+    /// Whatever nothing changed since the last export was started, any edit clears it
+    exported_clean: bool,
+    /// Number of files of the last export that are still being written
+    exports_pending: usize,
+    /// Whatever writing any of the files of the last export failed
+    export_failed: bool,
 }
 
 #[derive(Debug, Clone)]
 pub enum WorkspaceMessage {
     /// Change to the name of the file the image is to be writen to
     OutputNameChange(String),
+    /// Sets the notes about what the workspace is for
+    SetNotes(String),
     /// Sets desired image format for the exported file
     SetFormat(ImageFormat),
     /// Sets width for the exported image. It uses string carrier to allow user input invalid input without breaking the input
     SetOutputWidth(String),
     /// Sets height for the exported image. It uses string carrier to allow user input invalid input without breaking the input
     SetOutputHeight(String),
+    /// Changes export height to match the aspect ratio preset on the index, keeping the width
+    SetAspect(usize),
+    /// Toggles calculating the export size from physical size of the printed image
+    SetPrintSize(bool),
+    /// Sets width of the printed image in millimeters
+    SetPrintWidth(String),
+    /// Sets resolution the image is printed with
+    SetPrintDpi(String),
     /// Request to add a specific modifier type
     AddModifier(ModifierTag),
     /// Request to remove a modifier on specified index
     RemoveModifier(usize),
+    /// Inserts a copy of modifier on specified index right after it
+    DuplicateModifier(usize),
+    /// Restores default settings of modifier on specified index
+    ResetModifier(usize),
+    /// Toggles rendering only modifiers up to and including the selected one
+    SetSolo(bool),
+    /// Applies placement, size and modifiers of the workspace on the index to this one, intercepted by the program as it needs access to other workspaces
+    CopySettingsFrom(usize),
+    /// Changes width of the modifier properties panel by the distance the splitter was dragged
+    ResizeProperties(f32),
     /// Modifier has received a message (index, message)
     ModifierMessage(usize, ModifierMessage),
     /// Changes which modifier is selected
@@ -71,37 +152,105 @@ pub enum WorkspaceMessage {
     MoveModifierBackward(usize),
     /// Prompt new render job
     Render,
-    /// Rendering has completed with a result
-    RenderResult(Handle),
+    /// Toggles outline of the area visible through the frame in the preview
+    ShowSafeArea(bool),
+    /// Toggles comparison of the image before and after applying modifiers in the preview
+    SetCompare(bool),
+    /// Moves the divider between compared images
+    SetCompareSplit(f32),
+    /// Toggles the list of image operations the latest render was made of
+    ShowOperations(bool),
+    /// Image without modifiers for comparison with the sequence number of the rendering job it belongs to
+    BeforeResult(u64, Handle),
+    /// Sets whatever a copy of the image without the frame should be exported too
+    SetExportArt(bool),
+    /// Sets whatever the workspace is left out of operations affecting all workspaces
+    SetLocked(bool),
+    /// Exporting the image without the frame has finished
+    ArtExported(Result<PathBuf, String>),
+    /// Exporting the image with 16 bit precision has finished
+    PreciseExported(Result<PathBuf, String>),
+    /// Exporting the SVG outline of the visible area has finished
+    OutlineExported(Result<PathBuf, String>),
+    /// Writing the JSON file with settings the image was made with has finished
+    MetadataExported(Result<PathBuf, String>),
+    /// Saves the latest preview at the size it is displayed in, next to the exported image
+    SavePreview,
+    /// Saving the preview has finished
+    PreviewSaved(Result<PathBuf, String>),
+    /// Requests the result to be opened in Frame Maker, the workspace itself doesn't handle it
+    MakeFrame,
+    /// Requests a different image to be chosen as the source of this workspace, the workspace itself doesn't handle it as it requires the file browser
+    ReplaceSource,
+    /// Requests an image to be chosen as the reference shown next to the preview, the workspace itself doesn't handle it as it requires the file browser
+    PinReference,
+    /// Removes the reference image shown next to the preview
+    UnpinReference,
+    /// Rendering job with the sequence number has completed with a result and the time it took
+    RenderResult(u64, Handle, Duration),
     /// Change to image offset
     Slide(Point),
+    /// Flips the image horizontally
+    SetMirror(bool),
     /// Change to image size and how zoomed it is
     Zoom(f32),
     /// Change to size of the widget rendering the image
     View(f32),
     /// Resets the view zoom level
     ResetViewZoom,
+    /// Extracts dominant colors from the source image
+    ExtractSourcePalette,
+    /// Extracts dominant colors from the rendered image
+    ExtractResultPalette,
+    /// Palette extraction has completed with a result
+    PaletteResult(Vec<Color>),
+    /// Opens the search for adding modifiers
+    OpenModifierSearch,
+    /// Changes the filter for the modifier search
+    SetModifierSearch(String),
+    /// Adds the first modifier matching the search
+    SubmitModifierSearch,
+    /// Closes the search for adding modifiers
+    CloseModifierSearch,
+}
+
+impl WorkspaceMessage {
+    /// Tests whatever the message comes from a scheduled or asynchronous job rather than user interaction
+    pub fn is_background_job(&self) -> bool {
+        matches!(
+            self,
+            WorkspaceMessage::Render
+                | WorkspaceMessage::RenderResult(..)
+                | WorkspaceMessage::BeforeResult(..)
+                | WorkspaceMessage::PaletteResult(_)
+        )
+    }
 }
 
 impl Workspace {
     /// Creates a new workspace from provided image
     ///
     /// # Parameters
+    /// `id`       - identifier of the workspace, other workspaces refer to it by this
     /// `name`     - the name that should be used as default export name
     /// `source`   - the image to be used as a base
     /// `pdata`    - program data used for loading parameters for workspace and its modifiers
     /// `template` - setting to set up the workspace with defaults for specific template
     pub fn new(
+        id: u64,
         name: String,
         source: Arc<RgbaImage>,
         pdata: &ProgramData,
     ) -> (Command<WorkspaceMessage>, Self) {
-        let data = WorkspaceData::new(source, name, pdata);
+        let mut data = WorkspaceData::new(id, source, name, pdata);
         let mut modifiers = Vec::new();
 
         let command = match pdata.get_workspace_template() {
             WorkspaceTemplate::None | WorkspaceTemplate::Portrait => Command::none(),
             WorkspaceTemplate::Token => {
+                if pdata.token_auto_square() {
+                    data.export_size = square_size(&data.source);
+                }
                 let (command, frame) = ModifierTag::Frame.make_box(pdata, &data);
                 modifiers.push(frame);
                 command.map(|x| WorkspaceMessage::ModifierMessage(0, x))
@@ -116,6 +265,22 @@ impl Workspace {
 
             selected_modifier: 0,
             rendering: false,
+            render_sequence: 0,
+            render_time: None,
+            print_width_carrier: String::new(),
+            print_dpi_carrier: String::new(),
+            show_safe_area: false,
+            compare_split: None,
+            before_preview: None,
+            solo: false,
+            operation_log: Vec::new(),
+            show_operations: false,
+            reference: None,
+            locked: false,
+            modifier_search: None,
+            exported_clean: false,
+            exports_pending: 0,
+            export_failed: false,
         };
         (command, s)
     }
@@ -134,13 +299,25 @@ impl Workspace {
                         .warning("Removed invalid characters from workspace name")
                 }
                 self.data.output = sanitize_file_name(s);
+                self.exported_clean = false;
                 self.update_modifiers(pdata)
             }
+            WorkspaceMessage::SetNotes(notes) => {
+                self.data.notes = notes;
+                // notes are written into the exported metadata
+                self.exported_clean = false;
+                Command::none()
+            }
             WorkspaceMessage::SetOutputWidth(w) => {
                 if let Ok(p) = w.parse::<u32>() {
                     self.data.export_size.width = p;
                     self.width_carrier = w;
                     self.data.dirty = true;
+                    // physical size follows the pixel size when it is changed directly
+                    if let Some(print) = &mut self.data.print_size {
+                        print.width_mm = p as f32 / print.dpi * MM_PER_INCH;
+                        self.print_width_carrier = format!("{:.1}", print.width_mm);
+                    }
                     self.update_modifiers(pdata)
                 } else {
                     if w.len() == 0 {
@@ -162,11 +339,66 @@ impl Workspace {
                     Command::none()
                 }
             }
+            WorkspaceMessage::SetAspect(index) => {
+                let Some((_, w, h)) = ASPECT_PRESETS.get(index) else {
+                    return Command::none();
+                };
+                let width = self.data.export_size.width.max(1);
+                let height = ((width as f32 * h / w).round() as u32).max(1);
+                self.data.export_size = Size { width, height };
+                self.width_carrier = width.to_string();
+                self.height_carrier = height.to_string();
+                self.data.dirty = true;
+                self.update_modifiers(pdata)
+            }
+            WorkspaceMessage::SetPrintSize(enabled) => {
+                // physical size is written into the exported image
+                self.exported_clean = false;
+                if !enabled {
+                    self.data.print_size = None;
+                    return Command::none();
+                }
+                let print = PrintSize {
+                    width_mm: self.data.export_size.width as f32 / 300.0 * MM_PER_INCH,
+                    dpi: 300.0,
+                };
+                self.print_width_carrier = format!("{:.1}", print.width_mm);
+                self.print_dpi_carrier = print.dpi.to_string();
+                self.data.print_size = Some(print);
+                Command::none()
+            }
+            WorkspaceMessage::SetPrintWidth(w) => {
+                let value = w.parse::<f32>().ok().filter(|x| *x > 0.0);
+                self.print_width_carrier = w;
+                match (value, &mut self.data.print_size) {
+                    (Some(value), Some(print)) => {
+                        print.width_mm = value;
+                        self.apply_print_size(pdata)
+                    }
+                    _ => Command::none(),
+                }
+            }
+            WorkspaceMessage::SetPrintDpi(d) => {
+                let value = d.parse::<f32>().ok().filter(|x| *x > 0.0);
+                self.print_dpi_carrier = d;
+                match (value, &mut self.data.print_size) {
+                    (Some(value), Some(print)) => {
+                        print.dpi = value;
+                        self.apply_print_size(pdata)
+                    }
+                    _ => Command::none(),
+                }
+            }
             WorkspaceMessage::Slide(x) => {
                 self.data.offset = x;
                 self.data.dirty = true;
                 self.update_modifiers(pdata)
             }
+            WorkspaceMessage::SetMirror(mirror) => {
+                self.data.mirror = mirror;
+                self.data.dirty = true;
+                self.update_modifiers(pdata)
+            }
             WorkspaceMessage::Zoom(x) => {
                 self.data.zoom -= x;
                 self.data.dirty = true;
@@ -180,12 +412,165 @@ impl Workspace {
                 self.data.view = 1.0;
                 self.update_modifiers(pdata)
             }
-            WorkspaceMessage::RenderResult(r) => {
+            WorkspaceMessage::RenderResult(sequence, r, time) => {
+                // result of a job that was superseded by a newer one, it would only flash a stale image
+                if sequence < self.render_sequence {
+                    return Command::none();
+                }
                 self.data.image_result = r;
+                self.render_time = Some(time);
                 self.rendering = false;
                 Command::none()
             }
-            WorkspaceMessage::Render => self.produce_render(pdata),
+            WorkspaceMessage::Render => {
+                if self.fit_size_limit(pdata) {
+                    pdata
+                        .status
+                        .log("Export size was reduced to the largest size the modifiers support");
+                    return self.update_modifiers(pdata);
+                }
+                self.produce_render(pdata)
+            }
+            WorkspaceMessage::ShowSafeArea(show) => {
+                self.show_safe_area = show;
+                Command::none()
+            }
+            WorkspaceMessage::ShowOperations(show) => {
+                self.show_operations = show;
+                Command::none()
+            }
+            WorkspaceMessage::SetCompare(compare) => {
+                if compare {
+                    self.compare_split = Some(0.5);
+                    // the image without modifiers is only rendered while comparing
+                    self.data.dirty = true;
+                } else {
+                    self.compare_split = None;
+                    self.before_preview = None;
+                }
+                Command::none()
+            }
+            WorkspaceMessage::SetCompareSplit(split) => {
+                if self.compare_split.is_some() {
+                    self.compare_split = Some(split);
+                }
+                Command::none()
+            }
+            WorkspaceMessage::BeforeResult(sequence, image) => {
+                if sequence >= self.render_sequence && self.compare_split.is_some() {
+                    self.before_preview = Some(image);
+                }
+                Command::none()
+            }
+            WorkspaceMessage::SetExportArt(export) => {
+                self.data.export_art = export;
+                self.exported_clean = false;
+                Command::none()
+            }
+            WorkspaceMessage::SetLocked(locked) => {
+                self.locked = locked;
+                Command::none()
+            }
+            WorkspaceMessage::ArtExported(result) => {
+                self.export_finished(result.is_ok());
+                match result {
+                    Ok(path) => pdata
+                        .status
+                        .log(&format!("Exported art without frame to {}", path.display())),
+                    Err(e) => pdata
+                        .status
+                        .error(&format!("Failed to export art without frame: {}", e)),
+                }
+                Command::none()
+            }
+            WorkspaceMessage::SavePreview => {
+                if self.is_stale() {
+                    pdata
+                        .status
+                        .warning("Preview is still rendering, save it once it's up to date");
+                    return Command::none();
+                }
+                let Some(preview) = handle_to_image(&self.data.image_result) else {
+                    return Command::none();
+                };
+                // the preview widget is sized after the export size, scaled by the view
+                let width = (self.data.export_size.width as f32 * self.data.view).round() as u32;
+                let height = (self.data.export_size.height as f32 * self.data.view).round() as u32;
+                let path = self.construct_preview_path(pdata);
+                Command::perform(
+                    async move {
+                        if let Some(folder) = path.parent() {
+                            std::fs::create_dir_all(folder).map_err(|e| e.to_string())?;
+                        }
+                        let preview =
+                            resize(&preview, width.max(1), height.max(1), FilterType::Triangle);
+                        preview
+                            .save_with_format(&path, image::ImageFormat::Png)
+                            .map(|_| path)
+                            .map_err(|e| e.to_string())
+                    },
+                    WorkspaceMessage::PreviewSaved,
+                )
+            }
+            WorkspaceMessage::PreviewSaved(result) => match result {
+                Ok(path) => {
+                    pdata.status.log(&format!(
+                        "Saved preview to {}, the path is copied to the clipboard",
+                        path.display()
+                    ));
+                    iced::clipboard::write(path.display().to_string())
+                }
+                Err(e) => {
+                    pdata
+                        .status
+                        .error(&format!("Failed to save preview: {}", e));
+                    Command::none()
+                }
+            },
+            WorkspaceMessage::PreciseExported(result) => {
+                self.export_finished(result.is_ok());
+                match result {
+                    Ok(path) => pdata
+                        .status
+                        .log(&format!("Exported 16 bit image to {}", path.display())),
+                    Err(e) => pdata
+                        .status
+                        .error(&format!("Failed to export 16 bit image: {}", e)),
+                }
+                Command::none()
+            }
+            WorkspaceMessage::OutlineExported(result) => {
+                self.export_finished(result.is_ok());
+                match result {
+                    Ok(path) => pdata
+                        .status
+                        .log(&format!("Exported outline to {}", path.display())),
+                    Err(e) => pdata
+                        .status
+                        .error(&format!("Failed to export outline: {}", e)),
+                }
+                Command::none()
+            }
+            WorkspaceMessage::MetadataExported(result) => {
+                self.export_finished(result.is_ok());
+                match result {
+                    Ok(path) => pdata
+                        .status
+                        .log(&format!("Exported metadata to {}", path.display())),
+                    Err(e) => pdata
+                        .status
+                        .error(&format!("Failed to export metadata: {}", e)),
+                }
+                Command::none()
+            }
+            WorkspaceMessage::MakeFrame => Command::none(),
+            WorkspaceMessage::ReplaceSource => Command::none(),
+            WorkspaceMessage::PinReference => Command::none(),
+            WorkspaceMessage::UnpinReference => {
+                self.reference = None;
+                Command::none()
+            }
+            WorkspaceMessage::CopySettingsFrom(_) => Command::none(),
             WorkspaceMessage::ModifierMessage(index, message) => {
                 if let Some(m) = self.modifiers.get_mut(index) {
                     m.properties_update(message, pdata, &mut self.data)
@@ -201,17 +586,50 @@ impl Workspace {
                 }
                 Command::none()
             }
+            WorkspaceMessage::DuplicateModifier(i) => {
+                if let Some(m) = self.modifiers.get(i) {
+                    self.modifiers.insert(i + 1, m.clone());
+                    self.selected_modifier = i + 1;
+                    self.data.dirty = true;
+                    self.check_modifier_order(pdata);
+                }
+                Command::none()
+            }
+            WorkspaceMessage::ResetModifier(index) => {
+                if let Some(m) = self.modifiers.get_mut(index) {
+                    let command = m.reset(pdata, &self.data);
+                    self.data.dirty = true;
+                    command.map(move |x| WorkspaceMessage::ModifierMessage(index, x))
+                } else {
+                    Command::none()
+                }
+            }
             WorkspaceMessage::AddModifier(m) => {
+                self.modifier_search = None;
                 let (command, modifier) = m.make_box(pdata, &self.data);
                 let index = self.modifiers.len();
                 self.modifiers.push(modifier);
                 self.selected_modifier = index;
+                self.check_modifier_order(pdata);
                 command.map(move |x| WorkspaceMessage::ModifierMessage(index, x))
             }
             WorkspaceMessage::SelectModifier(index) => {
+                if self.solo && self.selected_modifier != index {
+                    self.data.dirty = true;
+                }
                 self.selected_modifier = index;
                 Command::none()
             }
+            WorkspaceMessage::SetSolo(solo) => {
+                self.solo = solo;
+                self.data.dirty = true;
+                Command::none()
+            }
+            WorkspaceMessage::ResizeProperties(delta) => {
+                // the panel is on the right side so dragging the splitter left makes it wider
+                pdata.set_properties_width(pdata.properties_width() - delta);
+                Command::none()
+            }
             WorkspaceMessage::MoveModifierBackward(index) => {
                 if index > 0 {
                     if index == self.selected_modifier {
@@ -221,6 +639,7 @@ impl Workspace {
                     }
                     self.modifiers.swap(index, index - 1);
                     self.data.dirty = true;
+                    self.check_modifier_order(pdata);
                 }
                 Command::none()
             }
@@ -233,16 +652,106 @@ impl Workspace {
                     }
                     self.modifiers.swap(index, index + 1);
                     self.data.dirty = true;
+                    self.check_modifier_order(pdata);
                 }
                 Command::none()
             }
+            WorkspaceMessage::ExtractSourcePalette => {
+                let source = self.data.source.clone();
+                Command::perform(
+                    async move { extract_palette(source.as_raw(), PALETTE_SIZE) },
+                    WorkspaceMessage::PaletteResult,
+                )
+            }
+            WorkspaceMessage::ExtractResultPalette => {
+                let Data::Rgba { pixels, .. } = self.data.image_result.data() else {
+                    return Command::none();
+                };
+                let pixels = pixels.clone();
+                Command::perform(
+                    async move { extract_palette(&pixels, PALETTE_SIZE) },
+                    WorkspaceMessage::PaletteResult,
+                )
+            }
+            WorkspaceMessage::PaletteResult(palette) => {
+                self.data.palette = palette;
+                Command::none()
+            }
+            WorkspaceMessage::OpenModifierSearch => {
+                self.modifier_search = Some(String::new());
+                text_input::focus(modifier_search_id())
+            }
+            WorkspaceMessage::SetModifierSearch(filter) => {
+                self.modifier_search = Some(filter);
+                Command::none()
+            }
+            WorkspaceMessage::SubmitModifierSearch => {
+                let Some(tag) = self.modifier_search_results().next() else {
+                    return Command::none();
+                };
+                self.update(WorkspaceMessage::AddModifier(tag), pdata)
+            }
+            WorkspaceMessage::CloseModifierSearch => {
+                self.modifier_search = None;
+                Command::none()
+            }
             WorkspaceMessage::SetFormat(format) => {
                 self.data.set_export_format(format, pdata);
+                self.exported_clean = false;
                 Command::none()
             }
         }
     }
 
+    /// Calculates export size from the physical size of the printed image, keeping the aspect ratio of the export size
+    fn apply_print_size(&mut self, pdata: &ProgramData) -> Command<WorkspaceMessage> {
+        let Some(print) = self.data.print_size else {
+            return Command::none();
+        };
+        let size = self.data.export_size;
+        let width = print.pixels();
+        let height = if size.width > 0 {
+            ((width as f32 * size.height as f32 / size.width as f32).round() as u32).max(1)
+        } else {
+            width
+        };
+        self.data.export_size = Size { width, height };
+        self.width_carrier = width.to_string();
+        self.height_carrier = height.to_string();
+        self.data.dirty = true;
+        self.update_modifiers(pdata)
+    }
+
+    /// Shrinks the export size to fit within the size limits of the modifiers, returns true if the size changed
+    ///
+    /// Physical print size follows the pixel size the same way it does when the width is changed directly
+    fn fit_size_limit(&mut self, pdata: &ProgramData) -> bool {
+        let limit = self
+            .modifiers
+            .iter()
+            .filter_map(|x| x.size_limit(pdata, &self.data))
+            .reduce(|a, b| Size {
+                width: a.width.min(b.width),
+                height: a.height.min(b.height),
+            });
+        let Some(limit) = limit else {
+            return false;
+        };
+        let size = fit_size(self.data.export_size, limit);
+        if size == self.data.export_size {
+            return false;
+        }
+        self.data.export_size = size;
+        self.width_carrier = size.width.to_string();
+        self.height_carrier = size.height.to_string();
+        if let Some(print) = &mut self.data.print_size {
+            print.width_mm = size.width as f32 / print.dpi * MM_PER_INCH;
+            self.print_width_carrier = format!("{:.1}", print.width_mm);
+        }
+        self.data.dirty = true;
+        true
+    }
+
     /// Sends update signal to the modifiers
     ///
     /// Purpose of this function is to let modifiers update their internal state or schedule jobs when workspace data has changed if they depend on it
@@ -261,56 +770,109 @@ impl Workspace {
         Command::batch(coms)
     }
 
+    /// Lets the modifiers know the list of open workspaces has changed, so they can drop references to closed ones
+    pub fn workspaces_changed(&mut self, pdata: &ProgramData) -> Command<WorkspaceMessage> {
+        self.update_modifiers(pdata)
+    }
+
+    /// Tests whatever modifiers are applied in the order they hint at and warns the user if they're not
+    ///
+    /// Modifiers are applied from the bottom of the list to the top
+    fn check_modifier_order(&self, pdata: &mut ProgramData) {
+        let mut latest: Option<&ModifierBox> = None;
+        for m in self.modifiers.iter().rev() {
+            let hint = m.order_hint();
+            if hint == OrderHint::Any {
+                continue;
+            }
+            match latest {
+                Some(l) if l.order_hint() > hint => {
+                    pdata.status.warning(&format!(
+                        "{} is applied after {}, consider moving it below {} in the modifier list",
+                        m.label(),
+                        l.label(),
+                        l.label()
+                    ));
+                    return;
+                }
+                Some(l) if l.order_hint() == hint => {}
+                _ => latest = Some(m),
+            }
+        }
+    }
+
     /// Main rendering job builder
     ///
     /// The function constructs and schedules a rendering job for the image
-    /// It will do so only if there is no rendering job in progress and either workspace data or modifiers have dirty flag enabled
+    /// It will do so only if either workspace data or modifiers have dirty flag enabled.
+    /// New job supersedes any job still in progress, the result of the older job is discarded when it arrives
     fn produce_render(&mut self, pdata: &ProgramData) -> Command<WorkspaceMessage> {
-        if self.rendering {
-            return Command::none();
-        }
-        if self.data.dirty || self.modifiers.iter().any(|x| x.is_dirty()) {
+        if self.needs_render() {
             self.data.dirty = false;
+            self.exported_clean = false;
             self.rendering = true;
+            self.render_sequence += 1;
+            let sequence = self.render_sequence;
 
-            let mut ops = vec![ImageOperation::Begin {
-                image: self.data.source.clone(),
-                resolution: self.data.export_size,
-                focus_point: Point {
-                    x: self.data.source.width() as f32 * 0.5 - self.data.offset.x,
-                    y: self.data.source.height() as f32 * 0.5 - self.data.offset.y,
-                },
-                size: self.data.zoom,
-            }];
-
-            self.modifiers.iter_mut().rev().for_each(|x| {
-                match x.get_image_operation(pdata, &self.data) {
-                    ModifierOperation::None => {}
-                    ModifierOperation::Single(o) => ops.push(o),
-                    ModifierOperation::Double(first, second) => {
-                        ops.push(first);
-                        ops.push(second);
+            let mut ops = vec![self.begin_operation()];
+            // modifiers are applied from the bottom, solo preview stops at the selected one
+            let first = if self.solo { self.selected_modifier } else { 0 };
+            self.modifiers
+                .iter_mut()
+                .enumerate()
+                .rev()
+                .for_each(|(i, x)| {
+                    let op = x.get_image_operation(pdata, &self.data);
+                    if i >= first {
+                        op.push_into(&mut ops);
                     }
-                    ModifierOperation::Multiple(mut o) => ops.append(&mut o),
-                }
-            });
+                });
+            self.operation_log = ops.iter().map(|x| x.to_string()).collect();
 
-            Command::perform(
+            let workers = pdata.render_workers();
+            let result = Command::perform(
                 async move {
-                    let start = ops.remove(0);
-                    let mut img = start.begin().await;
-                    for op in ops {
-                        img = op.perform(img).await;
-                    }
-                    image_to_handle(img)
+                    let start = Instant::now();
+                    let image = image_to_handle(render(ops, workers).await);
+                    (image, start.elapsed())
                 },
-                |x| WorkspaceMessage::RenderResult(x),
-            )
+                move |x| WorkspaceMessage::RenderResult(sequence, x.0, x.1),
+            );
+            if self.compare_split.is_none() {
+                return result;
+            }
+            let begin = self.begin_operation();
+            let before = Command::perform(
+                async move { image_to_handle(begin.begin(workers).await) },
+                move |x| WorkspaceMessage::BeforeResult(sequence, x),
+            );
+            Command::batch([result, before])
         } else {
             Command::none()
         }
     }
 
+    /// Tests whatever the shown preview doesn't reflect the latest changes yet, either waiting for a render or rendering
+    pub fn is_stale(&self) -> bool {
+        self.rendering || self.needs_render()
+    }
+
+    /// Tests whatever the preview is out of date and a new render should be started
+    pub fn needs_render(&self) -> bool {
+        self.data.dirty || self.modifiers.iter().any(|x| x.is_dirty())
+    }
+
+    /// Creates the first operation of the rendering process
+    fn begin_operation(&self) -> ImageOperation {
+        ImageOperation::Begin {
+            image: self.data.source.clone(),
+            resolution: self.data.export_size,
+            focus_point: self.data.focus_point(),
+            size: self.data.zoom,
+            mirror: self.data.mirror,
+        }
+    }
+
     /// Creates a schedule for rendering jobs
     pub fn subscribtion(&self) -> Subscription<WorkspaceMessage> {
         iced::time::every(Duration::from_secs_f32(0.05)).map(|_| WorkspaceMessage::Render)
@@ -329,16 +891,201 @@ impl Workspace {
                     height: source.height(),
                 }
             }
+            WorkspaceTemplate::Token if pdata.token_auto_square() => {
+                self.data.export_size = square_size(&source);
+            }
             _ => {}
         }
         self.width_carrier = self.data.export_size.width.to_string();
         self.height_carrier = self.data.export_size.height.to_string();
         self.data.source_preview = image_arc_to_handle(&source);
         self.data.source = source;
+        self.data.color_profile = None;
+        self.data.precise_source = None;
         self.data.dirty = true;
         self.update_modifiers(pdata)
     }
 
+    /// Sets the color profile of the source image to be embedded in the exported image
+    pub fn set_color_profile(&mut self, profile: Option<Arc<Vec<u8>>>) {
+        self.data.color_profile = profile;
+    }
+
+    /// Sets the image shown next to the preview for comparison
+    pub fn set_reference(&mut self, reference: Handle) {
+        self.reference = Some(reference);
+    }
+
+    /// Remembers where the source image was loaded from
+    pub fn set_source_path(&mut self, path: Option<String>) {
+        self.data.source_path = path;
+    }
+
+    /// Returns path or address the source image was loaded from
+    pub fn get_source_path(&self) -> Option<&str> {
+        self.data.source_path.as_deref()
+    }
+
+    /// Constructs controls for calculating export size from the physical size of the printed image
+    fn print_size_view(&self, pdata: &ProgramData) -> Element<'_, WorkspaceMessage, Renderer> {
+        let toggle = tooltip(
+            checkbox(
+                "Print size",
+                self.data.print_size.is_some(),
+                WorkspaceMessage::SetPrintSize,
+            ),
+            "Calculates export size from physical width and resolution, the resolution is saved in PNG and JPEG files",
+            Position::Bottom,
+        )
+        .style(Style::Frame);
+        if self.data.print_size.is_none() {
+            return row![toggle].into();
+        }
+        row![
+            toggle,
+            text_input(
+                "Width",
+                &self.print_width_carrier,
+                WorkspaceMessage::SetPrintWidth
+            )
+            .style(pdata.input_style())
+            .width(Length::FillPortion(2)),
+            text("mm at"),
+            text_input(
+                "DPI",
+                &self.print_dpi_carrier,
+                WorkspaceMessage::SetPrintDpi
+            )
+            .style(pdata.input_style())
+            .width(Length::FillPortion(2)),
+            text("DPI"),
+        ]
+        .spacing(5)
+        .align_items(Alignment::Center)
+        .into()
+    }
+
+    /// Describes settings of the workspace for saving them in a session
+    ///
+    /// # Parameters
+    /// `source_file` - name of the file the source image is saved to along with the session
+    pub fn session_state(&self, source_file: String) -> WorkspaceSession {
+        WorkspaceSession {
+            source_file,
+            source_path: self.data.source_path.clone(),
+            output: self.data.output.clone(),
+            template: self.data.template,
+            format: self.data.get_export_format(),
+            export_size: (self.data.export_size.width, self.data.export_size.height),
+            offset: (self.data.offset.x, self.data.offset.y),
+            zoom: self.data.zoom,
+            mirror: self.data.mirror,
+            export_art: self.data.export_art,
+            print_size: self.data.print_size,
+            locked: self.locked,
+            notes: self.data.notes.clone(),
+            modifiers: self.modifiers.iter().map(|x| x.tag()).collect(),
+            modifier_states: self.modifiers.iter().map(|x| x.save_state()).collect(),
+        }
+    }
+
+    /// Applies settings saved in a session, modifiers whose settings can't be read are recreated with their default settings
+    pub fn restore_session(
+        &mut self,
+        state: WorkspaceSession,
+        pdata: &mut ProgramData,
+    ) -> Command<WorkspaceMessage> {
+        self.data.source_path = state.source_path;
+        self.data.output = state.output;
+        self.data.template = state.template;
+        self.data.set_export_format(state.format, pdata);
+        self.data.export_size = Size {
+            width: state.export_size.0,
+            height: state.export_size.1,
+        };
+        self.width_carrier = self.data.export_size.width.to_string();
+        self.height_carrier = self.data.export_size.height.to_string();
+        self.data.offset = Point {
+            x: state.offset.0,
+            y: state.offset.1,
+        };
+        self.data.zoom = state.zoom;
+        self.data.mirror = state.mirror;
+        self.data.export_art = state.export_art;
+        self.data.print_size = state.print_size;
+        self.locked = state.locked;
+        self.data.notes = state.notes;
+        if let Some(print) = state.print_size {
+            self.print_width_carrier = format!("{:.1}", print.width_mm);
+            self.print_dpi_carrier = print.dpi.to_string();
+        }
+        self.data.dirty = true;
+
+        self.selected_modifier = 0;
+        self.modifiers.clear();
+        let commands = state
+            .modifiers
+            .iter()
+            .enumerate()
+            .map(|(i, tag)| {
+                let (mut command, mut modifier) = tag.make_box(pdata, &self.data);
+                if let Some(Some(saved)) = state.modifier_states.get(i) {
+                    match modifier.restore_state(saved, pdata, &self.data) {
+                        Ok(c) => command = c,
+                        Err(e) => pdata.status.error(&format!(
+                            "Failed to restore settings of {} modifier: {}",
+                            tag, e
+                        )),
+                    }
+                }
+                self.modifiers.push(modifier);
+                command.map(move |x| WorkspaceMessage::ModifierMessage(i, x))
+            })
+            .collect::<Vec<_>>();
+        Command::batch(commands)
+    }
+
+    /// Applies placement, export settings and a copy of modifiers from the other workspace while keeping own source image and name
+    pub fn copy_settings(
+        &mut self,
+        other: &Workspace,
+        pdata: &mut ProgramData,
+    ) -> Command<WorkspaceMessage> {
+        self.data
+            .set_export_format(other.data.get_export_format(), pdata);
+        self.data.export_size = other.data.export_size;
+        self.data.offset = other.data.offset;
+        self.data.zoom = other.data.zoom;
+        self.data.mirror = other.data.mirror;
+        self.data.export_art = other.data.export_art;
+        self.data.print_size = other.data.print_size;
+        self.width_carrier = other.width_carrier.clone();
+        self.height_carrier = other.height_carrier.clone();
+        self.print_width_carrier = other.print_width_carrier.clone();
+        self.print_dpi_carrier = other.print_dpi_carrier.clone();
+
+        self.modifiers = other.modifiers.clone();
+        self.selected_modifier = other.selected_modifier;
+        self.data.dirty = true;
+        // modifiers which depend on the source image need to recalculate themselves for this one
+        self.update_modifiers(pdata)
+    }
+
+    /// Sets the 16 bit version of the source image used to keep precision in PNG exports
+    pub fn set_precise_source(&mut self, source: Option<Arc<Rgba16Image>>) {
+        self.data.precise_source = source;
+    }
+
+    /// Returns the 16 bit version of the source image, if it has one
+    pub fn get_precise_source(&self) -> Option<Arc<Rgba16Image>> {
+        self.data.precise_source.clone()
+    }
+
+    /// Returns color profile of the source image
+    pub fn get_color_profile(&self) -> Option<Arc<Vec<u8>>> {
+        self.data.color_profile.clone()
+    }
+
     /// Returns the source image this workspace uses
     pub fn get_source(&self) -> &Arc<RgbaImage> {
         &self.data.source
@@ -359,11 +1106,21 @@ impl Workspace {
         &self.data.output
     }
 
+    /// Returns the notes about what the workspace is for
+    pub fn get_notes(&self) -> &str {
+        &self.data.notes
+    }
+
     /// Returns a clone of the latest rendering result
     pub fn get_output(&self) -> Handle {
         self.data.image_result.clone()
     }
 
+    /// Identifier of the workspace, it doesn't change when other workspaces are closed
+    pub fn id(&self) -> u64 {
+        self.data.id
+    }
+
     /// Workspace UI
     pub fn view<'a>(&'a self, pdata: &'a ProgramData) -> Element<'a, WorkspaceMessage, Renderer> {
         let selected_mod = self.selected_modifier;
@@ -382,6 +1139,7 @@ impl Workspace {
             )
         } else {
             let img = self.get_output();
+            let step = pdata.scroll_zoom_step();
             let img = Trackpad::new(img)
                 .with_drag(self.data.offset, |mods, butt, point, delta| match butt {
                     iced::mouse::Button::Left => Some(WorkspaceMessage::Slide(if mods.shift() {
@@ -401,11 +1159,11 @@ impl Workspace {
                     }
                     _ => None,
                 })
-                .with_scroll(|mods, delta| {
+                .with_scroll(move |mods, delta| {
                     let change = match delta {
                         iced::mouse::ScrollDelta::Lines { x: _, y } => y,
                         iced::mouse::ScrollDelta::Pixels { x: _, y } => y,
-                    } * 0.1;
+                    } * step;
                     let change = if mods.shift() { change * 0.1 } else { change };
                     if mods.alt() {
                         Some(WorkspaceMessage::View(change))
@@ -416,8 +1174,42 @@ impl Workspace {
                 .width(self.data.export_size.width as f32 * self.data.view)
                 .height(self.data.export_size.height as f32 * self.data.view)
                 .with_content_fit(ContentFit::Contain);
+            let img = match pdata.checkerboard() {
+                Some(size) => img.with_checkerboard(size),
+                None => img,
+            };
+            let img = match self.safe_area_overlay(pdata) {
+                Some(overlay) => img.with_overlay(overlay),
+                None => img,
+            };
+            let img = match (self.compare_split, &self.before_preview) {
+                (Some(split), Some(before)) => {
+                    img.with_comparison(before.clone(), split, WorkspaceMessage::SetCompareSplit)
+                }
+                _ => img,
+            };
+            // the line is kept even when empty so the preview doesn't jump around as renders come and go
+            let status = if self.is_stale() { "Updating..." } else { "" };
+            let status = text(status).size(14).height(18);
 
-            container(img)
+            let img = col![status, img].spacing(2).align_items(Alignment::Center);
+            let img = match &self.reference {
+                Some(reference) => row![
+                    img,
+                    picture(reference.clone())
+                        .width(self.data.export_size.width as f32 * self.data.view)
+                        .height(self.data.export_size.height as f32 * self.data.view)
+                        .content_fit(ContentFit::Contain)
+                ]
+                .spacing(10)
+                .align_items(Alignment::End),
+                None => row![img],
+            };
+            if self.show_operations {
+                container(img.push(self.operations_view()).spacing(10))
+            } else {
+                container(img)
+            }
         }
         .style(Style::Margins)
         .center_x()
@@ -431,6 +1223,39 @@ impl Workspace {
             .into()
     }
 
+    /// Lists image operations the latest render was made of, for finding out why the image looks the way it does
+    fn operations_view(&self) -> Element<'_, WorkspaceMessage, Renderer> {
+        let list = self
+            .operation_log
+            .iter()
+            .enumerate()
+            .fold(col![text("Operations:")].spacing(4), |c, (i, x)| {
+                c.push(text(format!("{}. {}", i + 1, x)).size(14))
+            });
+        container(scrollable(list.padding(5)))
+            .style(Style::Frame)
+            .width(320)
+            .max_height(400)
+            .into()
+    }
+
+    /// Returns overlay outlining area visible through the frame if it is enabled
+    ///
+    /// Selected modifier is preferred when it provides the overlay, otherwise the top most modifier that has one is used
+    fn safe_area_overlay(&self, pdata: &ProgramData) -> Option<Handle> {
+        if !self.show_safe_area {
+            return None;
+        }
+        self.modifiers
+            .get(self.selected_modifier)
+            .and_then(|x| x.overlay(pdata, &self.data))
+            .or_else(|| {
+                self.modifiers
+                    .iter()
+                    .find_map(|x| x.overlay(pdata, &self.data))
+            })
+    }
+
     /// Constructs the toolbar portion of the workspace UI
     fn toolbar<'a>(&'a self, pdata: &'a ProgramData) -> Element<'a, WorkspaceMessage, Renderer> {
         // main controls are mostly for customizing the workspace
@@ -438,22 +1263,95 @@ impl Workspace {
             row![
                 text_input("File name", &self.data.output, |x| {
                     WorkspaceMessage::OutputNameChange(x)
-                }),
+                })
+                .style(pdata.input_style()),
                 PickList::new(
                     &ImageFormat::EXPORTABLE[..],
                     Some(self.data.get_export_format()),
                     |x| { WorkspaceMessage::SetFormat(x) }
                 ),
+                tooltip(
+                    checkbox("Art", self.data.export_art, WorkspaceMessage::SetExportArt),
+                    "Also exports the image without the frame, with -art added to the file name",
+                    Position::Bottom
+                ).style(Style::Frame),
+                tooltip(
+                    button("Replace Image").on_press(WorkspaceMessage::ReplaceSource),
+                    "Opens a different image in this workspace only, keeping its modifiers and settings",
+                    Position::Bottom
+                ).style(Style::Frame),
+                tooltip(
+                    button("Make Frame").on_press(WorkspaceMessage::MakeFrame),
+                    "Opens the result in Frame Maker to turn it into a frame",
+                    Position::Bottom
+                ).style(Style::Frame),
+                tooltip(
+                    checkbox("Lock", self.locked, WorkspaceMessage::SetLocked),
+                    "Keeps the workspace unchanged by replacing the image, tinting frames or setting size of all workspaces",
+                    Position::Bottom
+                ).style(Style::Frame),
             ]
+            .spacing(5)
             .height(Length::Shrink)
             .align_items(Alignment::Center),
 
+            row![
+                text("Notes:"),
+                text_input("Who or what the image is for", &self.data.notes, |x| {
+                    WorkspaceMessage::SetNotes(x)
+                })
+                .style(pdata.input_style()),
+            ]
+            .spacing(5)
+            .align_items(Alignment::Center),
+
             row![
                 text(&format!(
                     "Image size: {}x{}",
                     self.data.source.width(),
                     self.data.source.height()
                 )),
+                tooltip(
+                    text(match self.render_time {
+                        Some(time) => format!("Render: {} ms", time.as_millis()),
+                        None => String::from("Render: -"),
+                    }),
+                    "How long rendering of the latest image took",
+                    Position::Bottom
+                ).style(Style::Frame),
+                tooltip(
+                    checkbox("Safe area", self.show_safe_area, WorkspaceMessage::ShowSafeArea),
+                    "Outlines the part of the image that stays visible through the frame",
+                    Position::Bottom
+                ).style(Style::Frame),
+                tooltip(
+                    checkbox("Compare", self.compare_split.is_some(), WorkspaceMessage::SetCompare),
+                    "Shows the image without modifiers on the left side of the preview, drag the divider to move it",
+                    Position::Bottom
+                ).style(Style::Frame),
+                tooltip(
+                    checkbox("Operations", self.show_operations, WorkspaceMessage::ShowOperations),
+                    "Lists image operations the preview was rendered with, in the order they were applied",
+                    Position::Bottom
+                ).style(Style::Frame),
+                if self.reference.is_some() {
+                    tooltip(
+                        button("Unpin Reference").on_press(WorkspaceMessage::UnpinReference),
+                        "Removes the reference image from the side of the preview",
+                        Position::Bottom
+                    )
+                } else {
+                    tooltip(
+                        button("Pin Reference").on_press(WorkspaceMessage::PinReference),
+                        "Shows an image from your drive next to the preview for comparison, it isn't used in the token",
+                        Position::Bottom
+                    )
+                }.style(Style::Frame),
+                tooltip(
+                    button("Save Preview").on_press(WorkspaceMessage::SavePreview),
+                    "Saves the rendered image at the size it is shown in as PNG with -preview added to the file name, guides and checkerboard are left out",
+                    Position::Bottom
+                ).style(Style::Frame),
                 horizontal_space(Length::FillPortion(1)),
                 tooltip(
                     text("Zoom: "),
@@ -467,6 +1365,7 @@ impl Workspace {
                         WorkspaceMessage::Zoom(self.data.zoom)
                     }
                 })
+                .style(pdata.input_style())
                 .width(Length::FillPortion(2)),
             ]
             .height(Length::Shrink)
@@ -492,6 +1391,7 @@ impl Workspace {
                         WorkspaceMessage::Slide(self.data.offset)
                     }
                 })
+                .style(pdata.input_style())
                 .width(Length::FillPortion(2)),
                 text("x"),
                 text_input("y", &format!("{:.2}", self.data.offset.y), |y| {
@@ -504,7 +1404,38 @@ impl Workspace {
                         WorkspaceMessage::Slide(self.data.offset)
                     }
                 })
+                .style(pdata.input_style())
                 .width(Length::FillPortion(2)),
+                tooltip(
+                    checkbox("Mirror", self.data.mirror, WorkspaceMessage::SetMirror),
+                    "Flips the image horizontally, use it to make characters face the same way across a set",
+                    Position::Bottom
+                ).style(Style::Frame),
+            ]
+            .height(Length::Shrink)
+            .spacing(5)
+            .align_items(Alignment::Center),
+
+            row![
+                tooltip(
+                    text("Palette: "),
+                    "Extracts dominant colors from the image, modifiers with color settings can use them",
+                    Position::Bottom
+                ).style(Style::Frame),
+                button("Source").on_press(WorkspaceMessage::ExtractSourcePalette),
+                button("Result").on_press(WorkspaceMessage::ExtractResultPalette),
+                self.data.palette.iter().fold(row![].spacing(2), |r, c| {
+                    r.push(container(horizontal_space(12)).height(12).style(Style::Swatch(*c)))
+                }),
+                horizontal_space(Length::Fill),
+                tooltip(
+                    PickList::new(self.settings_sources(pdata), None, |x| {
+                        WorkspaceMessage::CopySettingsFrom(x.index)
+                    })
+                    .placeholder("Copy settings from"),
+                    "Applies offset, zoom, mirroring, size, format and modifiers of another workspace to this one, the image and name stay unchanged",
+                    Position::Bottom
+                ).style(Style::Frame),
             ]
             .height(Length::Shrink)
             .spacing(5)
@@ -522,16 +1453,31 @@ impl Workspace {
                 text_input("Width", &self.width_carrier, |x| {
                     WorkspaceMessage::SetOutputWidth(x)
                 })
+                .style(pdata.input_style())
                 .width(Length::FillPortion(2)),
                 text("x"),
                 text_input("Height", &self.height_carrier, |x| {
                     WorkspaceMessage::SetOutputHeight(x)
                 })
+                .style(pdata.input_style())
                 .width(Length::FillPortion(2)),
             ]
             .height(Length::Shrink)
             .spacing(5)
             .align_items(Alignment::Center),
+
+            ASPECT_PRESETS.iter().enumerate().fold(
+                row![tooltip(
+                    text("Aspect: "),
+                    "Changes the export height to match the aspect ratio, keeping the width. Card is the 2.5:3.5 proportion of playing cards.",
+                    Position::Bottom
+                ).style(Style::Frame)]
+                .spacing(5)
+                .align_items(Alignment::Center),
+                |r, (i, (label, _, _))| r.push(button(*label).on_press(WorkspaceMessage::SetAspect(i)))
+            ),
+
+            self.print_size_view(pdata),
         ]
         .width(Length::Fill)
         .height(Length::Shrink)
@@ -551,6 +1497,12 @@ impl Workspace {
                         .on_press(WorkspaceMessage::RemoveModifier(i))
                         .style(Style::Danger.into()),
                     horizontal_space(2),
+                    tooltip(
+                        button("+").on_press(WorkspaceMessage::DuplicateModifier(i)),
+                        "Duplicate",
+                        Position::Bottom,
+                    )
+                    .style(Style::Frame),
                     if i > 0 {
                         button("^").on_press(WorkspaceMessage::MoveModifierBackward(i))
                     } else {
@@ -564,9 +1516,9 @@ impl Workspace {
                 ]
                 .spacing(2);
                 r = row![
-                    r.width(64),
+                    r.width(88),
                     tooltip(
-                        radio(m.label(), i, Some(self.selected_modifier), |x| {
+                        radio(m.list_label(), i, Some(self.selected_modifier), |x| {
                             WorkspaceMessage::SelectModifier(x)
                         }),
                         m.tooltip(),
@@ -584,7 +1536,33 @@ impl Workspace {
         let modifiers = PickList::new(&ModifierTag::ALL[..], None, WorkspaceMessage::AddModifier)
             .placeholder("Add new");
 
-        let modifier_list = col![text("Active Modifiers:"), modifiers, modifier_list,].spacing(5);
+        let modifier_list = if let Some(filter) = &self.modifier_search {
+            let search = text_input(
+                "Search modifier",
+                filter,
+                WorkspaceMessage::SetModifierSearch,
+            )
+            .style(pdata.input_style())
+            .id(modifier_search_id())
+            .on_submit(WorkspaceMessage::SubmitModifierSearch);
+            let results = self
+                .modifier_search_results()
+                .fold(row![].spacing(2), |r, tag| {
+                    r.push(
+                        button(text(tag.to_string())).on_press(WorkspaceMessage::AddModifier(tag)),
+                    )
+                });
+            col![
+                text("Active Modifiers:"),
+                search,
+                scrollable(col![results, vertical_space(10)])
+                    .horizontal_scroll(Properties::default()),
+                modifier_list,
+            ]
+        } else {
+            col![text("Active Modifiers:"), modifiers, modifier_list,]
+        }
+        .spacing(5);
 
         let main_controls = container(main_controls)
             .width(Length::Fill)
@@ -603,18 +1581,35 @@ impl Workspace {
         {
             let modifier_properties =
                 selected.map(move |x| WorkspaceMessage::ModifierMessage(self.selected_modifier, x));
+            let reset = tooltip(
+                button("Reset").on_press(WorkspaceMessage::ResetModifier(self.selected_modifier)),
+                "Restores default settings of the modifier",
+                Position::Left,
+            )
+            .style(Style::Frame);
+            let solo = tooltip(
+                checkbox("Solo", self.solo, WorkspaceMessage::SetSolo),
+                "Previews the image with only this modifier and the ones below it applied",
+                Position::Left,
+            )
+            .style(Style::Frame);
+            let modifier_properties = row![
+                container(modifier_properties).width(Length::Fill),
+                col![reset, solo].spacing(4)
+            ]
+            .spacing(4);
 
             let modifier_properties = container(modifier_properties)
                 .padding(5)
                 .style(Style::Frame)
-                .width(Length::Fill)
+                .width(Length::Fixed(pdata.properties_width()))
                 .height(Length::Fill);
 
             row![
                 modifier_list,
-                col![main_controls, modifier_properties]
-                    .spacing(2)
-                    .width(Length::Fill)
+                main_controls,
+                Splitter::new(WorkspaceMessage::ResizeProperties),
+                modifier_properties
             ]
         } else {
             row![modifier_list, main_controls]
@@ -626,9 +1621,56 @@ impl Workspace {
         container(top).style(Style::Margins).height(246).into()
     }
 
+    /// Lists other open workspaces settings can be copied from
+    fn settings_sources(&self, pdata: &ProgramData) -> Vec<SettingsSource> {
+        // the workspace's own result is shared too
+        pdata
+            .workspace_results
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.id != self.data.id)
+            .map(|(index, x)| SettingsSource {
+                index,
+                name: x.name.clone(),
+            })
+            .collect()
+    }
+
+    /// Lists modifiers matching the current modifier search
+    fn modifier_search_results(&self) -> impl Iterator<Item = ModifierTag> + '_ {
+        let filter = self
+            .modifier_search
+            .as_deref()
+            .unwrap_or_default()
+            .to_lowercase();
+        ModifierTag::ALL
+            .into_iter()
+            .filter(move |x| x.to_string().to_lowercase().contains(&filter))
+    }
+
+    /// Returns the template the workspace was created with
+    pub fn get_template(&self) -> WorkspaceTemplate {
+        self.data.template
+    }
+
+    /// Returns the format the workspace will export its image in
+    pub fn get_export_format(&self) -> ImageFormat {
+        self.data.get_export_format()
+    }
+
+    /// Tests whatever the export format was chosen, it isn't when the program is set to ask for it on first export
+    pub fn is_format_chosen(&self) -> bool {
+        self.data.is_format_chosen()
+    }
+
+    /// Returns the resolution the workspace will export its image in
+    pub fn get_export_size(&self) -> Size<u32> {
+        self.data.export_size
+    }
+
     /// Constructs the path buffer pointing to the desired export path for the image
-    fn construct_export_path(&self, pdata: &ProgramData) -> PathBuf {
-        let mut path = pdata.get_output_folder().clone();
+    pub fn construct_export_path(&self, pdata: &ProgramData) -> PathBuf {
+        let mut path = pdata.get_output_folder(self.data.template).clone();
         // Constructing the final name for the export
         let name = self
             .data
@@ -643,25 +1685,288 @@ impl Workspace {
         path
     }
 
+    /// Creates path to which the image without the frame is exported
+    pub fn construct_art_export_path(&self, pdata: &ProgramData) -> PathBuf {
+        let path = self.construct_export_path(pdata);
+        let name = format!(
+            "{}{}",
+            path.file_stem().unwrap_or_default().to_string_lossy(),
+            ART_SUFFIX
+        );
+        path.with_file_name(name)
+            .with_extension(self.data.get_export_format().to_string())
+    }
+
+    /// Creates path to which the preview is saved, previews are always saved as PNG
+    fn construct_preview_path(&self, pdata: &ProgramData) -> PathBuf {
+        let path = self.construct_export_path(pdata);
+        let name = format!(
+            "{}{}",
+            path.file_stem().unwrap_or_default().to_string_lossy(),
+            PREVIEW_SUFFIX
+        );
+        // previews are kept apart from the exports so they don't get mixed up with finished tokens
+        let mut preview = dirs::cache_dir().unwrap_or_else(working_dir);
+        preview.push("token-maker");
+        preview.push("previews");
+        preview.push(name);
+        preview.set_extension("png");
+        preview
+    }
+
+    /// Tests whatever a copy of the image without the frame is exported along the result
+    pub fn exports_art(&self) -> bool {
+        self.data.export_art
+    }
+
+    /// Lists all files the export of this workspace would write
+    pub fn export_files(&self, pdata: &ProgramData) -> Vec<PathBuf> {
+        let path = self.construct_export_path(pdata);
+        let mut files = Vec::new();
+        if pdata.export_outline() {
+            files.push(path.with_extension("svg"));
+        }
+        if pdata.export_metadata() {
+            files.push(path.with_extension("json"));
+        }
+        if self.data.export_art {
+            files.push(self.construct_art_export_path(pdata));
+        }
+        files.insert(0, path);
+        files
+    }
+
+    /// Tests whatever nothing changed in the workspace since it was last exported and all files of the export were written
+    pub fn is_exported_clean(&self) -> bool {
+        self.exported_clean && self.exports_pending == 0 && !self.export_failed
+    }
+
+    /// Makes the next export include the workspace, used when export settings change
+    pub fn invalidate_export(&mut self) {
+        self.exported_clean = false;
+    }
+
+    /// Records that one of the files of the last export was written or failed to be
+    fn export_finished(&mut self, success: bool) {
+        self.exports_pending = self.exports_pending.saturating_sub(1);
+        if !success {
+            self.export_failed = true;
+        }
+    }
+
     /// Tests if the path set as export in this workspace already contains a file
     pub fn is_destructive_export(&self, pdata: &ProgramData) -> bool {
         self.construct_export_path(pdata).exists()
     }
 
     /// Exports latest preview image to drive
-    pub fn export(&self, pdata: &ProgramData) {
+    ///
+    /// Returned command renders and exports the image without the frame if the workspace is set to do so,
+    /// PNG images of workspaces with 16 bit source are exported by the command as well
+    ///
+    /// Fully transparent margins are trimmed from all exported images if the program is set to do so
+    pub fn export(&mut self, pdata: &ProgramData) -> Command<WorkspaceMessage> {
         let path = self.construct_export_path(pdata);
         // Produce the image
         let Data::Rgba { width, height, pixels } = self.data.image_result.data() else {
             panic!("doesn't work!");
         };
-        image::save_buffer(path, pixels, *width, *height, image::ColorType::Rgba8).unwrap();
+        let result = RgbaImage::from_raw(*width, *height, pixels.to_vec()).unwrap();
+        let trim = pdata
+            .trim_export()
+            .and_then(|padding| visible_bounds(&result, padding));
+        let trimmed =
+            trim.map(|area| crop_imm(&result, area.x, area.y, area.width, area.height).to_image());
+        let output = trimmed.as_ref().unwrap_or(&result);
+        let (width, height, pixels) = (output.width(), output.height(), output.as_raw());
+        let format = self.data.get_export_format();
+        let options = ExportOptions {
+            path: path.clone(),
+            trim,
+            profile: if pdata.keep_color_profile() {
+                self.data.color_profile.clone()
+            } else {
+                None
+            },
+            dpi: self.data.print_size.map(|x| x.dpi),
+            workers: pdata.render_workers(),
+        };
+        let mut commands = Vec::new();
+        if pdata.export_outline() {
+            let svg_path = path.with_extension("svg");
+            let pixels = pixels.to_vec();
+            commands.push(Command::perform(
+                async move {
+                    let svg = outline::outline_svg(width, height, &pixels);
+                    std::fs::write(&svg_path, svg)
+                        .map(|_| svg_path)
+                        .map_err(|e| e.to_string())
+                },
+                WorkspaceMessage::OutlineExported,
+            ));
+        }
+        match (&self.data.precise_source, format) {
+            (Some(precise), ImageFormat::Png) => {
+                commands.push(Command::perform(
+                    export_precise(
+                        precise.clone(),
+                        self.begin_operation(),
+                        result,
+                        options.clone(),
+                    ),
+                    WorkspaceMessage::PreciseExported,
+                ));
+            }
+            _ => {
+                let profile = options.profile.as_ref().map(|x| x.as_slice());
+                write_image(&path, width, height, pixels, format, profile, options.dpi).unwrap();
+            }
+        }
+        if pdata.export_metadata() {
+            let json_path = path.with_extension("json");
+            let metadata = self.metadata();
+            commands.push(Command::perform(
+                async move {
+                    std::fs::write(&json_path, metadata)
+                        .map(|_| json_path)
+                        .map_err(|e| e.to_string())
+                },
+                WorkspaceMessage::MetadataExported,
+            ));
+        }
+
+        if self.data.export_art {
+            // rendering the image again with frames skipped so the preview stays untouched
+            let mut ops = vec![self.begin_operation()];
+            self.modifiers
+                .iter()
+                .rev()
+                .filter(|x| x.tag() != ModifierTag::Frame)
+                .for_each(|x| {
+                    x.peek_image_operation(pdata, &self.data)
+                        .push_into(&mut ops)
+                });
+            let art = ExportOptions {
+                path: self.construct_art_export_path(pdata),
+                // the art has no frame so its visible area can differ from the main export
+                trim: None,
+                ..options
+            };
+            let padding = pdata.trim_export();
+            commands.push(Command::perform(
+                async move {
+                    let mut img = render(ops, art.workers).await;
+                    if let Some(area) = padding.and_then(|padding| visible_bounds(&img, padding)) {
+                        img = crop_imm(&img, area.x, area.y, area.width, area.height).to_image();
+                    }
+                    let (width, height) = img.dimensions();
+                    let profile = art.profile.as_ref().map(|x| x.as_slice());
+                    write_image(&art.path, width, height, &img, format, profile, art.dpi)
+                        .map(|_| art.path)
+                        .map_err(|e| e.to_string())
+                },
+                WorkspaceMessage::ArtExported,
+            ));
+        }
+
+        // the workspace only counts as exported once all the files are written, an export made while a render is still pending doesn't count
+        self.exported_clean = !self.is_stale();
+        self.exports_pending = commands.len();
+        self.export_failed = false;
+        Command::batch(commands)
+    }
+
+    /// Serializes workspace settings and modifiers into JSON describing how the export was made
+    fn metadata(&self) -> String {
+        let modifiers: Vec<ModifierMetadata> = self
+            .modifiers
+            .iter()
+            .map(|x| ModifierMetadata {
+                kind: x.tag().to_string(),
+                label: x.list_label(),
+                settings: x.metadata().into_iter().collect(),
+            })
+            .collect();
+        let frame = modifiers
+            .iter()
+            .find_map(|x| x.settings.get("frame").cloned());
+        let metadata = ExportMetadata {
+            source: self.data.source_path.as_deref(),
+            template: self.data.template,
+            format: self.data.get_export_format().to_string(),
+            width: self.data.export_size.width,
+            height: self.data.export_size.height,
+            offset: [self.data.offset.x, self.data.offset.y],
+            zoom: self.data.zoom,
+            mirror: self.data.mirror,
+            print_size: self.data.print_size,
+            frame,
+            notes: &self.data.notes,
+            modifiers,
+        };
+        serde_json::to_string_pretty(&metadata).unwrap_or_default()
+    }
+
+    /// Sets tint of all modifiers supporting it, returns none if there are no such modifiers
+    pub fn set_tint(
+        &mut self,
+        color: Color,
+        pdata: &mut ProgramData,
+    ) -> Option<Command<WorkspaceMessage>> {
+        let messages: Vec<_> = self
+            .modifiers
+            .iter()
+            .enumerate()
+            .filter_map(|(i, x)| x.tint_message(color).map(|m| (i, m)))
+            .collect();
+        if messages.is_empty() {
+            return None;
+        }
+        let commands = messages
+            .into_iter()
+            .map(|(i, m)| self.update(WorkspaceMessage::ModifierMessage(i, m), pdata))
+            .collect::<Vec<_>>();
+        Some(Command::batch(commands))
+    }
+
+    /// Calculates the image offset moved by the number of exported pixels
+    ///
+    /// Returns none when the preview isn't shown because a modifier or the modifier search takes its place
+    pub fn nudged_offset(&self, pdata: &ProgramData, x: f32, y: f32) -> Option<Point> {
+        if self.modifier_search.is_some()
+            || self
+                .modifiers
+                .get(self.selected_modifier)
+                .is_some_and(|m| m.wants_main_view(pdata, &self.data))
+        {
+            return None;
+        }
+        // matches how the source image is scaled into the export size when rendering
+        let source = self.data.source.dimensions();
+        let size = self.data.export_size;
+        let scale = (source.0 as f32 / size.width.max(1) as f32)
+            .min(source.1 as f32 / size.height.max(1) as f32)
+            * self.data.zoom;
+        Some(Point {
+            x: self.data.offset.x + x * scale,
+            y: self.data.offset.y + y * scale,
+        })
+    }
+
+    /// Tests whatever the workspace is left out of operations affecting all workspaces
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Tests whatever the preview only shows some of the modifiers
+    pub fn is_solo(&self) -> bool {
+        self.solo
     }
 
     /// Tests whatever the workspace can save its result to drive
     pub fn can_save(&self) -> bool {
-        // Can't save while the image is rendering
-        if self.rendering {
+        // Can't save while the image is rendering or waiting for its turn to render
+        if self.is_stale() {
             return false;
         }
         // To be valid, the name must have at least one alphanumeric character
@@ -669,6 +1974,184 @@ impl Workspace {
     }
 }
 
+/// Settings of the workspace written next to exported images
+#[derive(Serialize)]
+struct ExportMetadata<'a> {
+    source: Option<&'a str>,
+    template: WorkspaceTemplate,
+    format: String,
+    width: u32,
+    height: u32,
+    offset: [f32; 2],
+    zoom: f32,
+    /// Whatever the image was flipped horizontally
+    mirror: bool,
+    /// Physical size the image is meant to be printed at
+    print_size: Option<PrintSize>,
+    /// Identifier of the frame used by the first Frame modifier
+    frame: Option<String>,
+    /// Notes about what the workspace is for, left out when there are none
+    #[serde(skip_serializing_if = "str::is_empty")]
+    notes: &'a str,
+    /// Modifiers in the order they are listed in the workspace
+    modifiers: Vec<ModifierMetadata>,
+}
+
+/// Summary of a single modifier in exported metadata
+#[derive(Serialize)]
+struct ModifierMetadata {
+    kind: String,
+    label: String,
+    settings: BTreeMap<&'static str, String>,
+}
+
+/// Entry in the list of workspaces settings can be copied from
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SettingsSource {
+    index: usize,
+    name: String,
+}
+
+impl Display for SettingsSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}. {}", self.index + 1, self.name)
+    }
+}
+
+/// Where and how an exported image is written
+#[derive(Debug, Clone)]
+struct ExportOptions {
+    path: PathBuf,
+    /// Area the image is cropped to, the whole image is written when there's none
+    trim: Option<Rectangle<u32>>,
+    /// Color profile embedded in the file
+    profile: Option<Arc<Vec<u8>>>,
+    /// Resolution stored in the file for printing
+    dpi: Option<f32>,
+    /// Number of tasks rendering is split into
+    workers: usize,
+}
+
+/// Suffix added to the file name of images exported without the frame
+const ART_SUFFIX: &str = "-art";
+/// Suffix added to the file name of saved previews
+const PREVIEW_SUFFIX: &str = "-preview";
+
+/// Applies the operations in order, the first operation has to be `ImageOperation::Begin`
+///
+/// Work on each operation is split into the number of worker tasks, zero picks it based on the processor
+async fn render(mut ops: Vec<ImageOperation>, workers: usize) -> RgbaImage {
+    let start = ops.remove(0);
+    let mut img = start.begin(workers).await;
+    for op in ops {
+        img = op.perform(img, workers).await;
+    }
+    img
+}
+
+/// Writes RGBA pixels to the path in specified format
+///
+/// The color profile is embedded in the file if the format supports it, otherwise the image is saved without it
+fn write_image(
+    path: &Path,
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+    format: ImageFormat,
+    profile: Option<&[u8]>,
+    dpi: Option<f32>,
+) -> image::ImageResult<()> {
+    if profile.is_none() && dpi.is_none() {
+        return image::save_buffer(path, pixels, width, height, image::ColorType::Rgba8);
+    }
+    let mut encoded = Cursor::new(Vec::new());
+    image::write_buffer_with_format(
+        &mut encoded,
+        pixels,
+        width,
+        height,
+        image::ColorType::Rgba8,
+        image::ImageFormat::from_path(path)?,
+    )?;
+    // formats without color profile or resolution support are saved as they are
+    let mut data = encoded.into_inner();
+    if let Some(profile) = profile {
+        data = icc::embed_profile(&data, format, profile).unwrap_or(data);
+    }
+    if let Some(dpi) = dpi {
+        data = dpi::embed_dpi(&data, format, dpi).unwrap_or(data);
+    }
+    std::fs::write(path, data)?;
+    Ok(())
+}
+
+/// Exports the rendered image as 16 bit PNG
+///
+/// Pixels the modifiers left untouched are taken from the 16 bit source, the rest is converted from the 8 bit result.
+/// The finished image is cropped to the trim area if there is one
+async fn export_precise(
+    precise: Arc<Rgba16Image>,
+    begin: ImageOperation,
+    result: RgbaImage,
+    options: ExportOptions,
+) -> Result<PathBuf, String> {
+    let workers = options.workers;
+    let ImageOperation::Begin {
+        resolution,
+        focus_point,
+        size,
+        mirror,
+        ..
+    } = &begin
+    else {
+        return Err("Rendering has to start with the source image".to_string());
+    };
+    let mut image =
+        resample_image(precise, *resolution, *focus_point, *size, *mirror, workers).await;
+    let base = begin.begin(workers).await;
+
+    for ((out, base), result) in image.pixels_mut().zip(base.pixels()).zip(result.pixels()) {
+        if base.0[..3] == result.0[..3] {
+            if base[3] != result[3] {
+                out[3] = result[3] as u16 * 257;
+            }
+        } else {
+            *out = Rgba(result.0.map(|x| x as u16 * 257));
+        }
+    }
+    if let Some(area) = options.trim {
+        image = crop_imm(&image, area.x, area.y, area.width, area.height).to_image();
+    }
+
+    let mut encoded = Cursor::new(Vec::new());
+    DynamicImage::ImageRgba16(image)
+        .write_to(&mut encoded, image::ImageOutputFormat::Png)
+        .map_err(|e| e.to_string())?;
+    let mut data = encoded.into_inner();
+    if let Some(profile) = options.profile {
+        data = icc::embed_profile(&data, ImageFormat::Png, &profile).unwrap_or(data);
+    }
+    if let Some(dpi) = options.dpi {
+        data = dpi::embed_dpi(&data, ImageFormat::Png, dpi).unwrap_or(data);
+    }
+    std::fs::write(&options.path, data).map_err(|e| e.to_string())?;
+    Ok(options.path)
+}
+
+/// Identifier of the modifier search input, used to focus it when the search opens
+fn modifier_search_id() -> text_input::Id {
+    text_input::Id::new("modifier-search")
+}
+
+/// Calculates the biggest square size that fits within the image
+fn square_size(image: &RgbaImage) -> Size<u32> {
+    let side = image.width().min(image.height());
+    Size {
+        width: side,
+        height: side,
+    }
+}
+
 /// Allows the program to define which default values should be used for the workspace and its modifiers
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum WorkspaceTemplate {